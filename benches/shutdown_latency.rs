@@ -0,0 +1,91 @@
+//! Measures how long it takes to shut down huge subsystem trees.
+//!
+//! Run with `cargo bench --bench shutdown_latency`.
+//!
+//! The shutdown latency is expected to scale roughly linearly with the
+//! number of subsystems, independent of the shape of the tree.
+
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+
+type BoxedError = Box<dyn std::error::Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+/// Spawns a tree with `count` subsystems in total, where every
+/// subsystem has at most `fan_out` children.
+fn spawn_tree(subsys: &SubsystemHandle, count: usize, fan_out: usize) {
+    let remaining = count - 1;
+    let per_child = remaining / fan_out;
+    let extra = remaining % fan_out;
+
+    for i in 0..fan_out.min(remaining) {
+        let child_count = per_child + usize::from(i < extra);
+        if child_count == 0 {
+            continue;
+        }
+        subsys.start(SubsystemBuilder::new(
+            i.to_string(),
+            move |s: SubsystemHandle| async move {
+                spawn_tree(&s, child_count, fan_out);
+                s.on_shutdown_requested().await;
+                BoxedResult::Ok(())
+            },
+        ));
+    }
+}
+
+/// Spawns the tree, then measures only its shutdown.
+async fn measure_shutdown(count: usize, fan_out: usize) -> Duration {
+    let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+
+    let toplevel = Toplevel::<BoxedError>::new(move |s| async move {
+        spawn_tree(&s, count + 1, fan_out);
+        // Give all subsystems the chance to spawn their children
+        tokio::task::yield_now().await;
+        while s.shutdown_statistics().waiter_count < count {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        let _ = started_tx.send(Instant::now());
+        s.request_shutdown();
+    });
+
+    let shutdown_start = tokio::spawn(started_rx);
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_secs(60))
+        .await
+        .unwrap();
+    let shutdown_end = Instant::now();
+
+    shutdown_end - shutdown_start.await.unwrap().unwrap()
+}
+
+fn shutdown_latency(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    for (shape, fan_out) in [("flat", usize::MAX), ("fan_out_10", 10)] {
+        let mut group = c.benchmark_group(format!("shutdown_latency/{shape}"));
+        // Every iteration spawns an entire tree first.
+        group.sample_size(10);
+
+        for count in [1_000, 10_000, 100_000] {
+            group.throughput(Throughput::Elements(count as u64));
+            group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, &count| {
+                b.to_async(&runtime).iter_custom(|iterations| async move {
+                    let mut total = Duration::ZERO;
+                    for _ in 0..iterations {
+                        total += measure_shutdown(count, fan_out).await;
+                    }
+                    total
+                });
+            });
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, shutdown_latency);
+criterion_main!(benches);
@@ -0,0 +1,86 @@
+//! Measures how long it takes to wake up huge numbers of tasks that
+//! wait for a shutdown of the same subsystem, and how much memory they occupy.
+//!
+//! Run with `cargo bench --bench shutdown_waiters`.
+//!
+//! The trigger latency is the time between the shutdown request and the
+//! moment the last waiting task resumed.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tokio::task::JoinSet;
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+
+type BoxedError = Box<dyn std::error::Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+/// Returns the current resident set size of this process, if available.
+fn rss_kib() -> Option<u64> {
+    std::fs::read_to_string("/proc/self/status")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()
+}
+
+async fn measure(count: usize) -> (Duration, Option<u64>) {
+    let (result_tx, result_rx) = tokio::sync::oneshot::channel();
+
+    Toplevel::<BoxedError>::new(move |s| async move {
+        s.start(SubsystemBuilder::new(
+            "connections",
+            move |subsys: SubsystemHandle| async move {
+                let subsys = Arc::new(subsys);
+                let rss_before = rss_kib();
+
+                let mut waiters = JoinSet::new();
+                for _ in 0..count {
+                    let subsys = Arc::clone(&subsys);
+                    waiters.spawn(async move { subsys.on_shutdown_requested().await });
+                }
+                while subsys.shutdown_statistics().waiter_count < count {
+                    tokio::time::sleep(Duration::from_millis(1)).await;
+                }
+                let rss_after = rss_kib();
+
+                let start = Instant::now();
+                subsys.request_shutdown();
+                while waiters.join_next().await.is_some() {}
+                let latency = start.elapsed();
+
+                let per_waiter = rss_before
+                    .zip(rss_after)
+                    .map(|(before, after)| after.saturating_sub(before) * 1024 / count as u64);
+                let _ = result_tx.send((latency, per_waiter));
+
+                BoxedResult::Ok(())
+            },
+        ));
+    })
+    .handle_shutdown_requests(Duration::from_secs(60))
+    .await
+    .unwrap();
+
+    result_rx.await.unwrap()
+}
+
+fn main() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    for count in [1_000, 10_000, 100_000] {
+        let (latency, per_waiter) = runtime.block_on(measure(count));
+        let per_waiter = per_waiter
+            .map(|bytes| format!("{bytes} bytes"))
+            .unwrap_or_else(|| "unknown".to_string());
+        println!(
+            "{count:>7} waiters: trigger latency {latency:>10.2?}, memory per waiting task {per_waiter}"
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! Measures the throughput of spawning and dropping subsystems.
+//!
+//! Run with `cargo bench --bench subsystem_spawn`.
+
+use std::time::{Duration, Instant};
+
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+
+type BoxedError = Box<dyn std::error::Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+const ITERATIONS: u32 = 100;
+
+async fn spawn_and_drop(count: usize) {
+    Toplevel::<BoxedError>::new(move |s| async move {
+        for _ in 0..count {
+            s.start(SubsystemBuilder::new(
+                "subsys",
+                |_: SubsystemHandle| async { BoxedResult::Ok(()) },
+            ));
+        }
+    })
+    .handle_shutdown_requests(Duration::from_secs(10))
+    .await
+    .unwrap();
+}
+
+fn main() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    for count in [1, 10, 100, 1000] {
+        // Warm up
+        runtime.block_on(spawn_and_drop(count));
+
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            runtime.block_on(spawn_and_drop(count));
+        }
+        let elapsed = start.elapsed();
+
+        let subsystems = count as f64 * f64::from(ITERATIONS);
+        println!(
+            "spawn_and_drop/{count:<5} {:>10.2?} per iteration, {:>12.0} subsystems/s",
+            elapsed / ITERATIONS,
+            subsystems / elapsed.as_secs_f64()
+        );
+    }
+}
@@ -122,6 +122,18 @@ async fn main() -> Result<(), miette::Report> {
                 SubsystemError::Panicked(name) => {
                     tracing::warn!("   Subsystem '{}' panicked.", name)
                 }
+                SubsystemError::Internal(name, e) => {
+                    tracing::warn!("   Subsystem '{}' could not be managed: {}", name, e)
+                }
+                SubsystemError::Aborted(name) => {
+                    tracing::warn!("   Subsystem '{}' got aborted.", name)
+                }
+                SubsystemError::UncleanExit(name, exit_status) => {
+                    tracing::warn!("   Subsystem '{}' {}.", name, exit_status)
+                }
+                _ => {
+                    tracing::warn!("   Subsystem '{}' failed.", subsystem_error.name())
+                }
             }
         }
     };
@@ -0,0 +1,33 @@
+//! This example demonstrates how to use this crate from a synchronous
+//! `main()` function, without `#[tokio::main]`.
+//!
+//! The runtime gets created internally and is shut down
+//! after all subsystems have finished.
+
+use miette::Result;
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+
+async fn subsys1(subsys: SubsystemHandle) -> Result<()> {
+    tracing::info!("Subsystem1 started.");
+    subsys.on_shutdown_requested().await;
+    tracing::info!("Shutting down Subsystem1 ...");
+    sleep(Duration::from_millis(400)).await;
+    tracing::info!("Subsystem1 stopped.");
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    // Init logging
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::TRACE)
+        .init();
+
+    // Setup and execute subsystem tree
+    Toplevel::builder()
+        .shutdown_timeout(Duration::from_millis(1000))
+        .run_blocking(|s| async move {
+            s.start(SubsystemBuilder::new("Subsys1", subsys1));
+        })
+        .map_err(Into::into)
+}
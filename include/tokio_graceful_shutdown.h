@@ -0,0 +1,56 @@
+/*
+ * C interface of the `ffi` feature of tokio-graceful-shutdown.
+ *
+ * The Rust side creates a TgsHandle through `TgsHandle::new(..).into_raw()`
+ * and passes the pointer to the C code. All functions ignore null pointers.
+ *
+ * Keep in sync with src/ffi.rs.
+ */
+
+#ifndef TOKIO_GRACEFUL_SHUTDOWN_H
+#define TOKIO_GRACEFUL_SHUTDOWN_H
+
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* A handle to a subsystem tree. Opaque; only ever used through pointers. */
+typedef struct TgsHandle TgsHandle;
+
+/* Triggers a shutdown of the entire subsystem tree. */
+void tgs_request_shutdown(const TgsHandle *handle);
+
+/* Returns whether a shutdown of the subsystem tree was requested. */
+bool tgs_is_shutting_down(const TgsHandle *handle);
+
+/*
+ * Registers a callback that gets called at most once, from a runtime thread, with
+ * `user_data` as its argument, after a shutdown of the subsystem tree was requested.
+ *
+ * If the shutdown was already requested, the callback gets called soon. If the runtime
+ * shuts down before a shutdown was requested, it is never called. It should return
+ * quickly, as it blocks the runtime thread.
+ * Nothing gets registered if `handle` or `callback` is null.
+ *
+ * # Safety
+ *
+ * - `handle` must be a valid pointer obtained from `TgsHandle::into_raw`
+ *   that was not freed yet, or null.
+ * - `callback` must be safe to call from any thread with `user_data`,
+ *   as long as the runtime is alive.
+ */
+void tgs_on_shutdown(const TgsHandle *handle, void (*callback)(void *), void *user_data);
+
+/*
+ * Releases a handle. It must not be used afterwards.
+ * Callbacks that were registered through it stay registered.
+ */
+void tgs_handle_free(TgsHandle *handle);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TOKIO_GRACEFUL_SHUTDOWN_H */
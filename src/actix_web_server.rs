@@ -0,0 +1,85 @@
+use std::io;
+
+use actix_web::dev::Server;
+use async_trait::async_trait;
+
+use crate::{ErrTypeTraits, IntoSubsystem, SubsystemHandle};
+
+/// Runs an [`actix-web`](https://docs.rs/actix-web) server as a subsystem.
+///
+/// When a shutdown is requested, the server gets stopped gracefully:
+/// it stops accepting new connections and waits for the in-flight requests
+/// to finish, limited by its own
+/// [`shutdown_timeout`](https://docs.rs/actix-web/latest/actix_web/struct.HttpServer.html#method.shutdown_timeout).
+///
+/// If the server stops with an error, the subsystem fails with that error.
+///
+/// # Examples
+///
+/// ```no_run
+/// use actix_web::{web, App, HttpServer};
+/// use miette::{IntoDiagnostic, Result};
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{
+///     ActixWebServer, IntoSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel,
+/// };
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let server = HttpServer::new(|| App::new().route("/", web::get().to(|| async { "Hello!" })))
+///         .disable_signals()
+///         .bind("127.0.0.1:8080")
+///         .into_diagnostic()?
+///         .run();
+///
+///     Toplevel::new(|s: SubsystemHandle| async move {
+///         s.start(SubsystemBuilder::new(
+///             "http",
+///             ActixWebServer::new(server).into_subsystem(),
+///         ));
+///     })
+///     .catch_signals()
+///     .handle_shutdown_requests(Duration::from_secs(30))
+///     .await?;
+///
+///     Ok(())
+/// }
+/// ```
+pub struct ActixWebServer {
+    server: Server,
+}
+
+impl ActixWebServer {
+    /// Wraps the given server.
+    ///
+    /// # Arguments
+    ///
+    /// * `server` - The running server, as returned by
+    ///   [`HttpServer::run`](https://docs.rs/actix-web/latest/actix_web/struct.HttpServer.html#method.run).
+    ///   It should be created with `disable_signals()`, so that signals get handled by the [`Toplevel`](crate::Toplevel).
+    pub fn new(server: Server) -> Self {
+        Self { server }
+    }
+}
+
+#[async_trait]
+impl<ErrWrapper> IntoSubsystem<io::Error, ErrWrapper> for ActixWebServer
+where
+    io::Error: Into<ErrWrapper>,
+    ErrWrapper: ErrTypeTraits,
+{
+    async fn run(self, subsys: SubsystemHandle<ErrWrapper>) -> io::Result<()> {
+        let mut server = self.server;
+        let handle = server.handle();
+
+        tokio::select! {
+            result = &mut server => return result,
+            _ = subsys.on_shutdown_requested() => {}
+        }
+
+        // The server future has to be polled for the stop command to get processed.
+        tracing::debug!("Stopping actix-web server ...");
+        let ((), result) = tokio::join!(handle.stop(true), server);
+        result
+    }
+}
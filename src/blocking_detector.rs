@@ -0,0 +1,35 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::{middleware::SubsystemFuture, ErrTypeTraits};
+
+/// Warns about polls of the given subsystem that block the executor for longer than
+/// the threshold, while the subsystem is shutting down.
+///
+/// Only measures the subsystem's own task; tasks it spawned are not covered.
+pub(crate) fn detect_blocking<ErrType: ErrTypeTraits>(
+    name: Arc<str>,
+    threshold: Duration,
+    cancellation_token: CancellationToken,
+    mut subsystem: SubsystemFuture<ErrType>,
+) -> SubsystemFuture<ErrType> {
+    Box::pin(std::future::poll_fn(move |cx| {
+        let shutting_down = cancellation_token.is_cancelled();
+
+        // Measures real time on purpose, as blocking calls are not affected by a paused tokio clock.
+        let started = std::time::Instant::now();
+        let result = subsystem.as_mut().poll(cx);
+        let blocked = started.elapsed();
+
+        if blocked >= threshold && (shutting_down || cancellation_token.is_cancelled()) {
+            tracing::warn!(
+                "Subsystem '{name}' blocked the executor for {blocked:.2?} while shutting down. \
+                 This is usually caused by a synchronous call like `JoinHandle::join` or `block_on`; \
+                 consider `tokio::task::spawn_blocking` instead."
+            );
+        }
+
+        result
+    }))
+}
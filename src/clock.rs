@@ -0,0 +1,119 @@
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
+
+use tokio::time::Instant;
+
+/// The source of time for all timeouts, delays and timestamps of this crate.
+///
+/// Allows embedders with a custom time source, like a simulation or a deterministic
+/// replay, to drive the shutdown deterministically.
+/// Configured through [`ToplevelBuilder::clock`](crate::ToplevelBuilder::clock);
+/// defaults to [`TokioClock`].
+///
+/// The [`shutdown_watchdog`](crate::ToplevelBuilder::shutdown_watchdog), the
+/// [`runtime_shutdown_timeout`](crate::ToplevelBuilder::runtime_shutdown_timeout) and the
+/// time limit for capturing a task dump of a stalled shutdown are not
+/// affected, as they have to work even if the runtime is stuck. Neither is the
+/// [blocking detection](crate::ToplevelBuilder::detect_blocking), which
+/// measures how long a poll blocked its thread.
+///
+/// # Examples
+///
+/// ```
+/// use std::{future::Future, pin::Pin};
+///
+/// use miette::Result;
+/// use tokio::time::{Duration, Instant};
+/// use tokio_graceful_shutdown::{Clock, SubsystemBuilder, SubsystemHandle, Toplevel};
+///
+/// /// A clock that runs at double speed.
+/// struct FastClock {
+///     start: Instant,
+/// }
+///
+/// impl Clock for FastClock {
+///     fn now(&self) -> Instant {
+///         self.start + self.start.elapsed() * 2
+///     }
+///
+///     fn sleep_until(&self, deadline: Instant) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+///         let remaining = deadline.saturating_duration_since(self.now());
+///         Box::pin(tokio::time::sleep(remaining / 2))
+///     }
+/// }
+///
+/// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+///     subsys.request_shutdown();
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     Toplevel::builder()
+///         .clock(FastClock {
+///             start: Instant::now(),
+///         })
+///         .shutdown_timeout(Duration::from_millis(1000))
+///         .build(|s| async move {
+///             s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+///         })
+///         .run()
+///         .await
+///         .map_err(Into::into)
+/// }
+/// ```
+pub trait Clock: Send + Sync + 'static {
+    /// Returns the current time.
+    fn now(&self) -> Instant;
+
+    /// Creates a future that resolves once the given deadline is reached.
+    ///
+    /// # Arguments
+    ///
+    /// * `deadline` - The time at which the future should resolve.
+    fn sleep_until(&self, deadline: Instant) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+}
+
+/// The default [`Clock`], based on [`tokio::time`].
+///
+/// Respects a paused tokio clock, like in tests with `start_paused = true`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioClock;
+
+impl Clock for TokioClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(tokio::time::sleep_until(deadline))
+    }
+}
+
+/// The clock of a subsystem tree.
+pub(crate) type SharedClock = Arc<dyn Clock>;
+
+impl dyn Clock {
+    /// Waits for the given duration.
+    pub(crate) fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        match self.now().checked_add(duration) {
+            Some(deadline) => self.sleep_until(deadline),
+            // Too far in the future to ever be reached.
+            None => Box::pin(std::future::pending()),
+        }
+    }
+
+    /// Runs the given future until it completes or the given duration elapsed.
+    ///
+    /// Returns `None` if the duration elapsed first.
+    pub(crate) async fn timeout<F: Future>(
+        &self,
+        duration: Duration,
+        future: F,
+    ) -> Option<F::Output> {
+        tokio::select! {
+            biased;
+            output = future => Some(output),
+            _ = self.sleep(duration) => None,
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! Coordinates the shutdown of multiple processes.
+//!
+//! Applications that are split into multiple local processes can link their
+//! [`Toplevel`] objects through a [`ShutdownCoordinator`]: a shutdown of one
+//! process gets propagated to all of its peers, and processes can wait until
+//! their peers reported that their shutdown is complete.
+//!
+//! The messages get exchanged through a [`CoordinationTransport`]; this crate
+//! provides [`TcpTransport`] and, on Unix, [`UnixTransport`].
+//! Neither of them authenticates its peers, so they must only be reachable
+//! by the coordinated processes.
+//!
+//! # Examples
+//!
+//! ```
+//! use miette::{IntoDiagnostic, Result};
+//! use tokio::time::Duration;
+//! use tokio_graceful_shutdown::{
+//!     coordination::{ShutdownCoordinator, TcpTransport},
+//!     Toplevel,
+//! };
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<()> {
+//!     // Stand-ins for the other processes, which would listen on their own addresses
+//!     let worker1 = TcpTransport::bind("127.0.0.1:0").await.into_diagnostic()?;
+//!     let worker2 = TcpTransport::bind("127.0.0.1:0").await.into_diagnostic()?;
+//!
+//!     let transport = TcpTransport::bind("127.0.0.1:0")
+//!         .await
+//!         .into_diagnostic()?
+//!         .peer(worker1.local_addr().into_diagnostic()?)
+//!         .peer(worker2.local_addr().into_diagnostic()?);
+//!
+//!     let coordinator = ShutdownCoordinator::new("api", transport);
+//!
+//!     let toplevel = coordinator.attach(Toplevel::<miette::Report>::new(|s| async move {
+//!         s.request_shutdown();
+//!     }));
+//!     let result = toplevel
+//!         .handle_shutdown_requests(Duration::from_millis(1000))
+//!         .await;
+//!
+//!     // Wait for the other processes, but not forever
+//!     let _ = tokio::time::timeout(
+//!         Duration::from_millis(100),
+//!         coordinator.wait_for_peers(["worker1", "worker2"]),
+//!     )
+//!     .await;
+//!
+//!     result.map_err(Into::into)
+//! }
+//! ```
+
+mod protocol;
+mod tcp_transport;
+#[cfg(unix)]
+mod unix_transport;
+
+use std::{collections::HashSet, io, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use tokio::sync::watch;
+use tokio_util::sync::{CancellationToken, DropGuard};
+
+use crate::{clock::SharedClock, utils::Mutex, Clock, ErrTypeTraits, TokioClock, Toplevel};
+
+pub use tcp_transport::TcpTransport;
+#[cfg(unix)]
+pub use unix_transport::UnixTransport;
+
+// Keeps transports that fail permanently, like one with a closed broker connection,
+// from turning into a busy loop.
+const MIN_RECEIVE_BACKOFF: Duration = Duration::from_millis(10);
+const MAX_RECEIVE_BACKOFF: Duration = Duration::from_secs(1);
+
+/// A message that gets exchanged between coordinated processes.
+///
+/// Every message carries the name of the process that sent it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CoordinationMessage {
+    /// The sender started its shutdown; the receiver should shut down as well.
+    ShutdownRequested(String),
+    /// The sender finished its shutdown.
+    ShutdownCompleted(String),
+}
+
+/// Delivers [`CoordinationMessage`]s between processes.
+///
+/// Implement this trait to coordinate shutdowns through other channels,
+/// like a message broker.
+#[async_trait]
+pub trait CoordinationTransport: Send + Sync + 'static {
+    /// Sends the message to all peers.
+    ///
+    /// Peers that are not reachable should be skipped; an error should only
+    /// be returned if the message could not be delivered to any peer.
+    async fn broadcast(&self, message: &CoordinationMessage) -> io::Result<()>;
+
+    /// Receives the next message from any peer.
+    ///
+    /// After an error, the [`ShutdownCoordinator`] delays the next call,
+    /// by up to one second if the errors persist.
+    async fn receive(&self) -> io::Result<CoordinationMessage>;
+}
+
+/// Propagates shutdowns between processes.
+///
+/// For more information, see the [module level documentation](self).
+pub struct ShutdownCoordinator<T: CoordinationTransport> {
+    name: Arc<str>,
+    transport: Arc<T>,
+    remote_shutdown: CancellationToken,
+    completed_peers: watch::Receiver<HashSet<String>>,
+    // Shared with the receiving task, which already runs when the clock gets set.
+    clock: Arc<Mutex<SharedClock>>,
+    _receiver_guard: DropGuard,
+}
+
+impl<T: CoordinationTransport> ShutdownCoordinator<T> {
+    /// Creates a new coordinator and starts receiving messages from the peers.
+    ///
+    /// Must be called from within a tokio runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of this process, as seen by its peers.
+    /// * `transport` - The transport that connects this process to its peers.
+    pub fn new(name: impl Into<String>, transport: T) -> Self {
+        let name: Arc<str> = Arc::from(name.into());
+        let transport = Arc::new(transport);
+        let remote_shutdown = CancellationToken::new();
+        let (completed_sender, completed_peers) = watch::channel(HashSet::new());
+        let clock: Arc<Mutex<SharedClock>> = Arc::new(Mutex::new(Arc::new(TokioClock)));
+        let receiver_token = CancellationToken::new();
+
+        tokio::spawn({
+            let transport = Arc::clone(&transport);
+            let remote_shutdown = remote_shutdown.clone();
+            let clock = Arc::clone(&clock);
+            let receiver_token = receiver_token.clone();
+            async move {
+                let mut backoff = MIN_RECEIVE_BACKOFF;
+                loop {
+                    let message = tokio::select! {
+                        _ = receiver_token.cancelled() => break,
+                        message = transport.receive() => message,
+                    };
+
+                    if message.is_ok() {
+                        backoff = MIN_RECEIVE_BACKOFF;
+                    }
+                    match message {
+                        Ok(CoordinationMessage::ShutdownRequested(peer)) => {
+                            tracing::info!("Peer '{peer}' requested a shutdown.");
+                            remote_shutdown.cancel();
+                        }
+                        Ok(CoordinationMessage::ShutdownCompleted(peer)) => {
+                            tracing::debug!("Peer '{peer}' finished its shutdown.");
+                            completed_sender.send_modify(|completed| {
+                                completed.insert(peer);
+                            });
+                        }
+                        Err(e) => {
+                            tracing::warn!("Failed to receive coordination message: {e}");
+                            let sleep = clock.lock().sleep(backoff);
+                            tokio::select! {
+                                _ = receiver_token.cancelled() => break,
+                                _ = sleep => (),
+                            }
+                            backoff = (backoff * 2).min(MAX_RECEIVE_BACKOFF);
+                        }
+                    }
+                }
+            }
+        });
+
+        Self {
+            name,
+            transport,
+            remote_shutdown,
+            completed_peers,
+            clock,
+            _receiver_guard: receiver_token.drop_guard(),
+        }
+    }
+
+    /// Sets the source of time for the backoff after the transport failed to receive a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The clock to use. Defaults to [`TokioClock`].
+    pub fn clock(self, clock: impl Clock) -> Self {
+        *self.clock.lock() = Arc::new(clock);
+        self
+    }
+
+    /// Links the coordinator with the given [`Toplevel`].
+    ///
+    /// Shutdown requests of peers initiate a shutdown of the Toplevel,
+    /// and a shutdown of the Toplevel gets announced to the peers,
+    /// unless it was caused by a peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `toplevel` - The Toplevel that should be linked.
+    ///
+    /// # Returns
+    ///
+    /// The linked Toplevel.
+    pub fn attach<ErrType: ErrTypeTraits>(&self, toplevel: Toplevel<ErrType>) -> Toplevel<ErrType> {
+        let toplevel = toplevel.shutdown_on(self.remote_shutdown.clone());
+        let local_shutdown = toplevel.create_cancellation_token();
+
+        tokio::spawn({
+            let name = Arc::clone(&self.name);
+            let transport = Arc::clone(&self.transport);
+            let remote_shutdown = self.remote_shutdown.clone();
+            async move {
+                local_shutdown.cancelled().await;
+                if !remote_shutdown.is_cancelled() {
+                    broadcast(
+                        &*transport,
+                        CoordinationMessage::ShutdownRequested(name.to_string()),
+                    )
+                    .await;
+                }
+            }
+        });
+
+        toplevel
+    }
+
+    /// Returns whether a peer requested a shutdown.
+    pub fn is_shutdown_requested_by_peer(&self) -> bool {
+        self.remote_shutdown.is_cancelled()
+    }
+
+    /// Announces to all peers that this process finished its shutdown.
+    ///
+    /// Should be called after the shutdown of the linked [`Toplevel`] finished.
+    pub async fn report_completion(&self) {
+        broadcast(
+            &*self.transport,
+            CoordinationMessage::ShutdownCompleted(self.name.to_string()),
+        )
+        .await;
+    }
+
+    /// Waits until all of the given peers reported that their shutdown is complete.
+    ///
+    /// Completions that were received earlier are taken into account as well.
+    /// Wrap this in [`tokio::time::timeout`] to avoid waiting forever for peers that crashed.
+    ///
+    /// # Arguments
+    ///
+    /// * `peers` - The names of the peers to wait for.
+    pub async fn wait_for_peers<'a>(&self, peers: impl IntoIterator<Item = &'a str>) {
+        let peers: Vec<&str> = peers.into_iter().collect();
+        // Ignore errors; the sender only gets dropped together with `self`.
+        let _ = self
+            .completed_peers
+            .clone()
+            .wait_for(|completed| peers.iter().all(|peer| completed.contains(*peer)))
+            .await;
+    }
+}
+
+async fn broadcast<T: CoordinationTransport>(transport: &T, message: CoordinationMessage) {
+    if let Err(e) = transport.broadcast(&message).await {
+        tracing::warn!("Failed to send coordination message {message:?}: {e}");
+    }
+}
@@ -0,0 +1,205 @@
+//! The line based wire format of the built-in transports.
+
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    sync::Mutex,
+};
+
+use crate::{clock::SharedClock, TokioClock};
+
+use super::CoordinationMessage;
+
+// Frees the slots of peers that connect but never send anything.
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_MESSAGE_LENGTH: u64 = 4096;
+// Further connections wait in the backlog of the listener until a slot is free.
+const MAX_CONCURRENT_CONNECTIONS: usize = 16;
+
+const SHUTDOWN_REQUESTED: &str = "shutdown_requested";
+const SHUTDOWN_COMPLETED: &str = "shutdown_completed";
+
+pub(super) async fn write_message<W: AsyncWrite + Unpin>(
+    mut writer: W,
+    message: &CoordinationMessage,
+) -> io::Result<()> {
+    let (kind, name) = match message {
+        CoordinationMessage::ShutdownRequested(name) => (SHUTDOWN_REQUESTED, name),
+        CoordinationMessage::ShutdownCompleted(name) => (SHUTDOWN_COMPLETED, name),
+    };
+    if name.contains('\n') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "process names must not contain line breaks",
+        ));
+    }
+
+    writer
+        .write_all(format!("{kind} {name}\n").as_bytes())
+        .await?;
+    writer.shutdown().await
+}
+
+async fn read_message<R: AsyncRead + Unpin>(
+    reader: R,
+    clock: SharedClock,
+) -> io::Result<CoordinationMessage> {
+    let mut line = String::new();
+    let mut reader = BufReader::new(reader.take(MAX_MESSAGE_LENGTH));
+    clock
+        .timeout(RECEIVE_TIMEOUT, reader.read_line(&mut line))
+        .await
+        .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "peer did not send a message"))??;
+
+    match line.trim_end().split_once(' ') {
+        Some((SHUTDOWN_REQUESTED, name)) => {
+            Ok(CoordinationMessage::ShutdownRequested(name.to_string()))
+        }
+        Some((SHUTDOWN_COMPLETED, name)) => {
+            Ok(CoordinationMessage::ShutdownCompleted(name.to_string()))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("invalid coordination message: {line:?}"),
+        )),
+    }
+}
+
+/// Sends the message to every peer, skipping peers that are not reachable.
+///
+/// Only fails if the message could not be delivered to any peer.
+pub(super) async fn broadcast_to<'a, P, S, Fut>(
+    peers: &'a [P],
+    connect: impl Fn(&'a P) -> Fut,
+    message: &CoordinationMessage,
+) -> io::Result<()>
+where
+    P: std::fmt::Debug,
+    S: AsyncWrite + Unpin,
+    Fut: Future<Output = io::Result<S>>,
+{
+    let mut last_error = None;
+    let mut delivered = false;
+
+    for peer in peers {
+        match async { write_message(connect(peer).await?, message).await }.await {
+            Ok(()) => delivered = true,
+            Err(e) => {
+                tracing::debug!("Failed to send coordination message to {peer:?}: {e}");
+                last_error = Some(e);
+            }
+        }
+    }
+
+    match last_error {
+        Some(e) if !delivered => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// A listening socket of one of the built-in transports.
+pub(super) trait Listener: Send + Sync + 'static {
+    type Stream: AsyncRead + Unpin + Send + 'static;
+
+    fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<io::Result<Self::Stream>>;
+}
+
+impl Listener for tokio::net::TcpListener {
+    type Stream = tokio::net::TcpStream;
+
+    fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<io::Result<Self::Stream>> {
+        tokio::net::TcpListener::poll_accept(self, cx).map_ok(|(stream, _)| stream)
+    }
+}
+
+#[cfg(unix)]
+impl Listener for tokio::net::UnixListener {
+    type Stream = tokio::net::UnixStream;
+
+    fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<io::Result<Self::Stream>> {
+        tokio::net::UnixListener::poll_accept(self, cx).map_ok(|(stream, _)| stream)
+    }
+}
+
+/// The messages that arrive at a listener.
+///
+/// Connections only get accepted and read while waiting for the next message,
+/// without spawning any tasks. Up to [`MAX_CONCURRENT_CONNECTIONS`] connections
+/// get read at the same time, so a slow peer does not hold up the others.
+pub(super) struct IncomingMessages<L> {
+    listener: L,
+    clock: SharedClock,
+    connections: Mutex<Vec<PendingMessage>>,
+}
+
+type PendingMessage = Pin<Box<dyn Future<Output = io::Result<CoordinationMessage>> + Send>>;
+
+impl<L: Listener> IncomingMessages<L> {
+    pub(super) fn new(listener: L) -> Self {
+        Self {
+            listener,
+            clock: Arc::new(TokioClock),
+            connections: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub(super) fn listener(&self) -> &L {
+        &self.listener
+    }
+
+    pub(super) fn set_clock(&mut self, clock: SharedClock) {
+        self.clock = clock;
+    }
+
+    /// Errors of individual connections only get logged;
+    /// only errors of the listener itself get returned.
+    ///
+    /// Cancel safe; connections that are being read stay open until the next call.
+    pub(super) async fn next(&self) -> io::Result<CoordinationMessage> {
+        let mut connections = self.connections.lock().await;
+
+        std::future::poll_fn(|cx| loop {
+            while connections.len() < MAX_CONCURRENT_CONNECTIONS {
+                match self.listener.poll_accept(cx) {
+                    Poll::Ready(Ok(stream)) => {
+                        connections.push(Box::pin(read_message(stream, Arc::clone(&self.clock))))
+                    }
+                    Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                    Poll::Pending => break,
+                }
+            }
+
+            let mut freed_slot = false;
+            let mut position = 0;
+            while position < connections.len() {
+                match connections[position].as_mut().poll(cx) {
+                    Poll::Ready(result) => {
+                        drop(connections.swap_remove(position));
+                        match result {
+                            Ok(message) => return Poll::Ready(Ok(message)),
+                            Err(e) => {
+                                tracing::debug!("Failed to read coordination message: {e}");
+                                freed_slot = true;
+                            }
+                        }
+                    }
+                    Poll::Pending => position += 1,
+                }
+            }
+
+            // Accept the connections that waited for the freed slots.
+            if !freed_slot {
+                return Poll::Pending;
+            }
+        })
+        .await
+    }
+}
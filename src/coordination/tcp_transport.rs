@@ -0,0 +1,79 @@
+use std::{io, net::SocketAddr, sync::Arc};
+
+use async_trait::async_trait;
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use crate::Clock;
+
+use super::{
+    protocol::{self, IncomingMessages},
+    CoordinationMessage, CoordinationTransport,
+};
+
+/// A [`CoordinationTransport`] that exchanges messages over TCP,
+/// usually on the loopback interface.
+///
+/// Every process listens on its own address and sends its messages
+/// to the addresses of all of its peers.
+///
+/// # Security
+///
+/// Messages are neither authenticated nor encrypted. Everyone who can connect
+/// to the listening address can shut down the process, so only bind to addresses
+/// that are not reachable by untrusted parties, like `127.0.0.1`.
+pub struct TcpTransport {
+    incoming: IncomingMessages<TcpListener>,
+    peers: Vec<SocketAddr>,
+}
+
+impl TcpTransport {
+    /// Creates a new transport that listens on the given address.
+    ///
+    /// Connections get accepted while messages are being received.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address to receive messages on, like `127.0.0.1:7000`.
+    pub async fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            incoming: IncomingMessages::new(TcpListener::bind(addr).await?),
+            peers: Vec::new(),
+        })
+    }
+
+    /// Adds a peer that messages should be sent to.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - The address the peer listens on.
+    pub fn peer(mut self, addr: SocketAddr) -> Self {
+        self.peers.push(addr);
+        self
+    }
+
+    /// Sets the source of time for the timeout of connections that do not send a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The clock to use. Defaults to [`TokioClock`](crate::TokioClock).
+    pub fn clock(mut self, clock: impl Clock) -> Self {
+        self.incoming.set_clock(Arc::new(clock));
+        self
+    }
+
+    /// Returns the address this transport listens on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.incoming.listener().local_addr()
+    }
+}
+
+#[async_trait]
+impl CoordinationTransport for TcpTransport {
+    async fn broadcast(&self, message: &CoordinationMessage) -> io::Result<()> {
+        protocol::broadcast_to(&self.peers, TcpStream::connect, message).await
+    }
+
+    async fn receive(&self) -> io::Result<CoordinationMessage> {
+        self.incoming.next().await
+    }
+}
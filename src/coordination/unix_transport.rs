@@ -0,0 +1,92 @@
+use std::{
+    io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use async_trait::async_trait;
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::Clock;
+
+use super::{
+    protocol::{self, IncomingMessages},
+    CoordinationMessage, CoordinationTransport,
+};
+
+/// A [`CoordinationTransport`] that exchanges messages over Unix domain sockets.
+///
+/// Every process listens on its own socket and sends its messages
+/// to the sockets of all of its peers.
+///
+/// The socket file of this process gets removed once the transport is dropped.
+///
+/// # Security
+///
+/// Messages are not authenticated. Everyone who can connect to the socket
+/// can shut down the process, so restrict its permissions accordingly.
+pub struct UnixTransport {
+    incoming: IncomingMessages<UnixListener>,
+    path: PathBuf,
+    peers: Vec<PathBuf>,
+}
+
+impl UnixTransport {
+    /// Creates a new transport that listens on the given socket path.
+    ///
+    /// Fails if the path already exists.
+    /// Connections get accepted while messages are being received.
+    ///
+    /// Must be called from within a tokio runtime.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the socket to receive messages on.
+    pub fn bind(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        Ok(Self {
+            incoming: IncomingMessages::new(UnixListener::bind(&path)?),
+            path,
+            peers: Vec::new(),
+        })
+    }
+
+    /// Adds a peer that messages should be sent to.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the socket the peer listens on.
+    pub fn peer(mut self, path: impl AsRef<Path>) -> Self {
+        self.peers.push(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the source of time for the timeout of connections that do not send a message.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The clock to use. Defaults to [`TokioClock`](crate::TokioClock).
+    pub fn clock(mut self, clock: impl Clock) -> Self {
+        self.incoming.set_clock(Arc::new(clock));
+        self
+    }
+}
+
+impl Drop for UnixTransport {
+    fn drop(&mut self) {
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            tracing::warn!("Failed to remove socket {:?}: {e}", self.path);
+        }
+    }
+}
+
+#[async_trait]
+impl CoordinationTransport for UnixTransport {
+    async fn broadcast(&self, message: &CoordinationMessage) -> io::Result<()> {
+        protocol::broadcast_to(&self.peers, UnixStream::connect, message).await
+    }
+
+    async fn receive(&self) -> io::Result<CoordinationMessage> {
+        self.incoming.next().await
+    }
+}
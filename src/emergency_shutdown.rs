@@ -0,0 +1,95 @@
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::Arc,
+};
+
+use crate::{last_words::LastWords, utils::Mutex};
+
+pub(crate) type CriticalFinalizer = Box<dyn FnOnce() + Send>;
+
+/// The finalizers and the exit code of an emergency shutdown.
+pub(crate) struct EmergencyShutdown {
+    exit_code: i32,
+    finalizers: Mutex<Vec<(Arc<str>, CriticalFinalizer)>>,
+    last_words: Arc<LastWords>,
+}
+
+impl Default for EmergencyShutdown {
+    fn default() -> Self {
+        Self::new(1, Vec::new(), Default::default())
+    }
+}
+
+impl EmergencyShutdown {
+    pub(crate) fn new(
+        exit_code: i32,
+        finalizers: Vec<(Arc<str>, CriticalFinalizer)>,
+        last_words: Arc<LastWords>,
+    ) -> Self {
+        Self {
+            exit_code,
+            finalizers: Mutex::new(finalizers),
+            last_words,
+        }
+    }
+
+    /// Skips the graceful shutdown and terminates the process with the given exit code,
+    /// after running the critical finalizers and the last words.
+    pub(crate) fn exit(&self, reason: &str, exit_code: i32) -> ! {
+        tracing::error!("Emergency shutdown: {reason}");
+        self.run_finalizers();
+        self.last_words.run();
+        std::process::exit(exit_code)
+    }
+
+    /// Runs all finalizers that did not run yet, in the order in which they were registered.
+    fn run_finalizers(&self) {
+        let finalizers = std::mem::take(&mut *self.finalizers.lock());
+        for (name, finalizer) in finalizers {
+            tracing::info!("Running critical finalizer '{name}' ...");
+            if catch_unwind(AssertUnwindSafe(finalizer)).is_err() {
+                tracing::error!("Critical finalizer '{name}' panicked.");
+            }
+        }
+    }
+}
+
+/// Triggers an emergency shutdown of the program.
+///
+/// Meant to be wired into detectors of fatal conditions, like allocation failure
+/// handlers or custom watchdogs, where a graceful shutdown is no longer possible.
+///
+/// Can be created through [`Toplevel::emergency_handle`](crate::Toplevel::emergency_handle).
+/// Critical finalizers and the exit code get configured through
+/// [`ToplevelBuilder::critical_finalizer`](crate::ToplevelBuilder::critical_finalizer)
+/// and [`ToplevelBuilder::emergency_exit_code`](crate::ToplevelBuilder::emergency_exit_code).
+#[derive(Clone)]
+pub struct EmergencyHandle {
+    inner: Arc<EmergencyShutdown>,
+}
+
+impl EmergencyHandle {
+    pub(crate) fn new(inner: Arc<EmergencyShutdown>) -> Self {
+        Self { inner }
+    }
+
+    /// Performs an emergency shutdown.
+    ///
+    /// Skips the graceful shutdown of the subsystems, runs the critical finalizers
+    /// on the current thread, followed by the
+    /// [`last_words`](crate::ToplevelBuilder::last_words),
+    /// and then terminates the process with the configured exit code.
+    ///
+    /// Does not depend on the tokio runtime, so it can be called from any thread,
+    /// even if the runtime is unresponsive.
+    ///
+    /// # Arguments
+    ///
+    /// * `reason` - Why the emergency shutdown is necessary, used for logging.
+    pub fn trigger(&self, reason: &str) -> ! {
+        self.inner.exit(reason, self.inner.exit_code)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,40 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use tracing_test::traced_test;
+
+use super::*;
+
+#[test]
+#[traced_test]
+fn finalizers_run_once_in_order() {
+    let counter = Arc::new(AtomicU32::new(0));
+
+    let finalizer = |expected: u32| -> (Arc<str>, CriticalFinalizer) {
+        let counter = Arc::clone(&counter);
+        (
+            Arc::from(format!("finalizer{expected}")),
+            Box::new(move || {
+                assert_eq!(counter.fetch_add(1, Ordering::Relaxed), expected);
+            }),
+        )
+    };
+
+    let emergency_shutdown = EmergencyShutdown::new(
+        1,
+        vec![
+            finalizer(0),
+            (
+                Arc::from("panicking"),
+                Box::new(|| panic!("Finalizer failed")),
+            ),
+            finalizer(1),
+        ],
+        Default::default(),
+    );
+
+    emergency_shutdown.run_finalizers();
+    emergency_shutdown.run_finalizers();
+
+    assert_eq!(counter.load(Ordering::Relaxed), 2);
+    assert!(logs_contain("Critical finalizer 'panicking' panicked."));
+}
@@ -6,7 +6,7 @@ use miette::Diagnostic;
 use thiserror::Error;
 use tokio::sync::mpsc;
 
-use crate::ErrTypeTraits;
+use crate::{ErrTypeTraits, ExitStatus};
 
 /// This enum contains all the possible errors that could be returned
 /// by [`handle_shutdown_requests()`](crate::Toplevel::handle_shutdown_requests).
@@ -37,6 +37,84 @@ impl<ErrType: ErrTypeTraits> GracefulShutdownError<ErrType> {
             GracefulShutdownError::ShutdownTimeout(rel) => rel,
         }
     }
+    /// Categorizes the error, for example to choose the exit code of the program.
+    ///
+    /// If the shutdown timed out, this is [`ShutdownErrorKind::Timeout`], regardless of
+    /// the subsystem errors. Otherwise, this is [`ShutdownErrorKind::Internal`] if at least
+    /// one of the subsystems could not be managed correctly, and
+    /// [`ShutdownErrorKind::SubsystemsFailed`] if all of them failed by themselves.
+    ///
+    /// The details of the shutdown are available through
+    /// [`ToplevelHandle::shutdown_report`](crate::ToplevelHandle::shutdown_report).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::process::ExitCode;
+    ///
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{
+    ///     errors::{GracefulShutdownError, ShutdownErrorKind},
+    ///     SubsystemBuilder, SubsystemHandle, Toplevel,
+    /// };
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.request_shutdown();
+    ///     Ok(())
+    /// }
+    ///
+    /// fn exit_code(result: Result<(), GracefulShutdownError>) -> ExitCode {
+    ///     match result.map_err(|e| e.kind()) {
+    ///         Ok(()) => ExitCode::SUCCESS,
+    ///         Err(ShutdownErrorKind::SubsystemsFailed) => ExitCode::from(1),
+    ///         Err(ShutdownErrorKind::Timeout) => ExitCode::from(2),
+    ///         Err(_) => ExitCode::from(70),
+    ///     }
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> ExitCode {
+    ///     let result = Toplevel::new(|s| async move {
+    ///         s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+    ///     })
+    ///     .handle_shutdown_requests(Duration::from_millis(500))
+    ///     .await;
+    ///
+    ///     exit_code(result)
+    /// }
+    /// ```
+    pub fn kind(&self) -> ShutdownErrorKind {
+        match self {
+            GracefulShutdownError::ShutdownTimeout(_) => ShutdownErrorKind::Timeout,
+            GracefulShutdownError::SubsystemsFailed(rel) => {
+                if rel
+                    .iter()
+                    .any(|error| matches!(error, SubsystemError::Internal(..)))
+                {
+                    ShutdownErrorKind::Internal
+                } else {
+                    ShutdownErrorKind::SubsystemsFailed
+                }
+            }
+        }
+    }
+}
+
+/// The category of a [`GracefulShutdownError`].
+///
+/// Returned by [`GracefulShutdownError::kind`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ShutdownErrorKind {
+    /// At least one subsystem failed, by returning an error, panicking or
+    /// ending with an exit status that is not clean.
+    SubsystemsFailed,
+    /// The shutdown did not finish within the given timeout.
+    Timeout,
+    /// At least one subsystem could not be managed correctly,
+    /// see [`SubsystemError::Internal`].
+    Internal,
 }
 
 /// This enum contains all the possible errors that joining a subsystem
@@ -95,7 +173,11 @@ impl<ErrType> std::error::Error for SubsystemFailure<ErrType> where
 /// could cause.
 ///
 /// Every error carries the name of the subsystem as the first argument.
+///
+/// New variants may get added in minor releases, so matches on this enum
+/// need a wildcard arm.
 #[derive(Debug, Error, Diagnostic)]
+#[non_exhaustive]
 pub enum SubsystemError<ErrType: ErrTypeTraits = crate::BoxedError> {
     /// The subsystem returned an error value. Carries the actual error as the second argument.
     #[diagnostic(code(graceful_shutdown::subsystem::failed))]
@@ -105,6 +187,20 @@ pub enum SubsystemError<ErrType: ErrTypeTraits = crate::BoxedError> {
     #[diagnostic(code(graceful_shutdown::subsystem::panicked))]
     #[error("Subsystem '{0}' panicked")]
     Panicked(Arc<str>),
+    /// The subsystem could not be managed correctly. Carries the cause as the second argument.
+    #[diagnostic(code(graceful_shutdown::subsystem::internal))]
+    #[error("Internal error in subsystem '{0}'")]
+    Internal(Arc<str>, #[source] InternalError),
+    /// The subsystem did not finish within the shutdown timeout and got aborted.
+    #[diagnostic(code(graceful_shutdown::subsystem::aborted))]
+    #[error("Subsystem '{0}' got aborted")]
+    Aborted(Arc<str>),
+    /// The subsystem returned successfully, but with an exit status that is not clean,
+    /// while [`strict_exit_statuses`](crate::ToplevelBuilder::strict_exit_statuses) is enabled.
+    /// Carries the exit status as the second argument.
+    #[diagnostic(code(graceful_shutdown::subsystem::unclean_exit))]
+    #[error("Subsystem '{0}' {1}")]
+    UncleanExit(Arc<str>, ExitStatus),
 }
 
 impl<ErrType: ErrTypeTraits> SubsystemError<ErrType> {
@@ -117,10 +213,54 @@ impl<ErrType: ErrTypeTraits> SubsystemError<ErrType> {
         match self {
             SubsystemError::Failed(name, _) => name,
             SubsystemError::Panicked(name) => name,
+            SubsystemError::Internal(name, _) => name,
+            SubsystemError::Aborted(name) => name,
+            SubsystemError::UncleanExit(name, _) => name,
         }
     }
 }
 
+/// Problems that prevented this crate from managing a subsystem correctly.
+///
+/// Those are caused by misuse of the API or by bugs in this crate;
+/// instead of panicking, they get reported as [`SubsystemError::Internal`].
+#[derive(Error, Debug, Diagnostic)]
+#[non_exhaustive]
+pub enum InternalError {
+    /// The [`SubsystemHandle`](crate::SubsystemHandle) was moved out of its subsystem
+    /// and kept alive after the subsystem finished.
+    #[diagnostic(code(graceful_shutdown::internal::handle_leaked))]
+    #[error("The SubsystemHandle object was leaked out of the subsystem")]
+    SubsystemHandleLeaked,
+    /// The subsystem would have exceeded the maximum depth of the subsystem tree,
+    /// and therefore did not get started.
+    ///
+    /// For more information, see [`ToplevelBuilder::max_depth`](crate::ToplevelBuilder::max_depth).
+    #[diagnostic(code(graceful_shutdown::internal::max_depth_exceeded))]
+    #[error("The subsystem exceeds the maximum depth of {max_depth} of the subsystem tree")]
+    MaxDepthExceeded {
+        /// The configured maximum depth.
+        max_depth: usize,
+    },
+    /// The parent of the subsystem already had the maximum number of children,
+    /// and therefore the subsystem did not get started.
+    ///
+    /// For more information, see [`SubsystemBuilder::max_children`](crate::SubsystemBuilder::max_children).
+    #[diagnostic(code(graceful_shutdown::internal::child_limit_reached))]
+    #[error("The parent of the subsystem already has the maximum number of children")]
+    ChildLimitReached,
+    /// A [`SpawnHook`](crate::SpawnHook) dropped the task of the subsystem
+    /// without running it to completion.
+    #[diagnostic(code(graceful_shutdown::internal::subsystem_not_run))]
+    #[error("A spawn hook did not run the subsystem to completion")]
+    SubsystemNotRun,
+    /// The runtime of [`ToplevelBuilder::run_blocking`](crate::ToplevelBuilder::run_blocking)
+    /// could not be created, so no subsystem got started.
+    #[diagnostic(code(graceful_shutdown::internal::runtime_creation_failed))]
+    #[error("Failed to create the tokio runtime")]
+    RuntimeCreationFailed(#[source] std::io::Error),
+}
+
 /// The error that happens when a task gets cancelled through
 /// [`cancel_on_shutdown()`](crate::FutureExt::cancel_on_shutdown).
 #[derive(Error, Debug, Diagnostic)]
@@ -128,6 +268,62 @@ impl<ErrType: ErrTypeTraits> SubsystemError<ErrType> {
 #[diagnostic(code(graceful_shutdown::future::cancelled_by_shutdown))]
 pub struct CancelledByShutdown;
 
+/// The error that happens when the value of a [`ShutdownOnceCell`](crate::ShutdownOnceCell)
+/// can not be provided, because a shutdown was requested before it was initialized.
+#[derive(Error, Debug, Diagnostic)]
+#[error("The value was never initialized because a shutdown was requested")]
+#[diagnostic(code(graceful_shutdown::once_cell::never_initialized))]
+pub struct NeverInitialized;
+
+/// The error that happens when a subsystem gets started through a
+/// [`ToplevelHandle`](crate::ToplevelHandle) whose [`Toplevel`](crate::Toplevel)
+/// no longer exists.
+#[derive(Error, Debug, Diagnostic)]
+#[error("The Toplevel object no longer exists")]
+#[diagnostic(code(graceful_shutdown::toplevel::gone))]
+pub struct ToplevelGone;
+
+/// The error that happens when a subsystem gets started through
+/// [`SubsystemHandle::try_start`](crate::SubsystemHandle::try_start) while its parent
+/// already has the maximum number of children.
+///
+/// For more information, see [`SubsystemBuilder::max_children`](crate::SubsystemBuilder::max_children).
+#[derive(Error, Debug, Diagnostic)]
+#[error("The subsystem already has the maximum number of children")]
+#[diagnostic(code(graceful_shutdown::subsystem::child_limit_reached))]
+pub struct ChildLimitReached;
+
+/// The error that happens when no shutdown was requested within the duration given to
+/// [`SubsystemHandle::on_shutdown_requested_timeout`](crate::SubsystemHandle::on_shutdown_requested_timeout).
+#[derive(Error, Debug, Diagnostic)]
+#[error("No shutdown was requested within the given duration")]
+#[diagnostic(code(graceful_shutdown::subsystem::shutdown_timeout_elapsed))]
+pub struct ShutdownTimeoutElapsed;
+
+/// The error that happens when a subsystem can not be moved to a new parent through
+/// [`NestedSubsystem::transfer_to`](crate::NestedSubsystem::transfer_to).
+#[derive(Error, Debug, Diagnostic)]
+#[non_exhaustive]
+pub enum TransferError {
+    /// The subsystem was not started as [`transferable`](crate::SubsystemBuilder::transferable).
+    #[diagnostic(code(graceful_shutdown::transfer::not_transferable))]
+    #[error("The subsystem is not transferable")]
+    NotTransferable,
+    /// The subsystem is already finished.
+    #[diagnostic(code(graceful_shutdown::transfer::finished))]
+    #[error("The subsystem is already finished")]
+    Finished,
+    /// The new parent is the subsystem itself, one of its descendants,
+    /// or part of a different subsystem tree.
+    #[diagnostic(code(graceful_shutdown::transfer::invalid_parent))]
+    #[error("The subsystem can not be moved to the given parent")]
+    InvalidParent,
+    /// The new parent already has the maximum number of children.
+    #[diagnostic(code(graceful_shutdown::transfer::child_limit_reached))]
+    #[error("The new parent already has the maximum number of children")]
+    ChildLimitReached,
+}
+
 // This function contains code that stems from the principle
 // of defensive coding - meaning, handle potential errors
 // gracefully, even if they should not happen.
@@ -4,9 +4,7 @@ use crate::BoxedError;
 
 use super::*;
 
-fn examine_report(
-    error: impl miette::Diagnostic + std::error::Error + std::fmt::Debug + Sync + Send + 'static,
-) {
+fn examine_report(error: impl miette::Diagnostic + Sync + Send + 'static) {
     println!("{}", error);
     println!("{:?}", error);
     println!("{:?}", error.source());
@@ -37,7 +35,31 @@ fn errors_can_be_converted_to_diagnostic() {
         "".into(),
         SubsystemFailure("".into()),
     ));
+    examine_report(SubsystemError::Internal::<BoxedError>(
+        "".into(),
+        InternalError::SubsystemHandleLeaked,
+    ));
+    examine_report(SubsystemError::Aborted::<BoxedError>("".into()));
+    examine_report(SubsystemError::UncleanExit::<BoxedError>(
+        "".into(),
+        ExitStatus::Drained { dropped: 12 },
+    ));
+    examine_report(InternalError::SubsystemHandleLeaked);
+    examine_report(InternalError::MaxDepthExceeded { max_depth: 3 });
+    examine_report(InternalError::ChildLimitReached);
+    examine_report(InternalError::SubsystemNotRun);
+    examine_report(InternalError::RuntimeCreationFailed(std::io::Error::new(
+        std::io::ErrorKind::Other,
+        "Out of threads",
+    )));
+    examine_report(TransferError::NotTransferable);
+    examine_report(TransferError::Finished);
+    examine_report(TransferError::InvalidParent);
+    examine_report(TransferError::ChildLimitReached);
     examine_report(CancelledByShutdown);
+    examine_report(ToplevelGone);
+    examine_report(ChildLimitReached);
+    examine_report(ShutdownTimeoutElapsed);
 }
 
 #[test]
@@ -69,6 +91,29 @@ fn extract_related_from_graceful_shutdown_error() {
     matches_related(&GracefulShutdownError::SubsystemsFailed(related()).into_subsystem_errors());
 }
 
+#[test]
+fn categorize_graceful_shutdown_error() {
+    let failed = || SubsystemError::Panicked::<BoxedError>("a".into());
+    let internal = || SubsystemError::Internal("b".into(), InternalError::SubsystemHandleLeaked);
+
+    assert_eq!(
+        GracefulShutdownError::SubsystemsFailed(Box::new([failed()])).kind(),
+        ShutdownErrorKind::SubsystemsFailed
+    );
+    assert_eq!(
+        GracefulShutdownError::SubsystemsFailed(Box::new([failed(), internal()])).kind(),
+        ShutdownErrorKind::Internal
+    );
+    assert_eq!(
+        GracefulShutdownError::ShutdownTimeout(Box::new([internal()])).kind(),
+        ShutdownErrorKind::Timeout
+    );
+    assert_eq!(
+        GracefulShutdownError::<BoxedError>::ShutdownTimeout(Box::new([])).kind(),
+        ShutdownErrorKind::Timeout
+    );
+}
+
 #[test]
 fn extract_contained_error_from_convert_subsystem_failure() {
     let msg = "MyFailure".to_string();
@@ -0,0 +1,63 @@
+use std::fmt;
+
+/// How a subsystem that returned successfully ended, in terms that are meaningful to the user.
+///
+/// Set through [`SubsystemHandle::set_exit_status`](crate::SubsystemHandle::set_exit_status);
+/// subsystems that return `Ok` without setting one end with [`ExitStatus::Completed`].
+///
+/// Shows up in the [`SubsystemResult`](crate::SubsystemResult) and the
+/// [`ShutdownReport`](crate::ShutdownReport). With
+/// [`ToplevelBuilder::strict_exit_statuses`](crate::ToplevelBuilder::strict_exit_statuses),
+/// statuses that are not [clean](ExitStatus::is_clean) count as errors and therefore
+/// make the program exit with a nonzero exit code.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ExitStatus {
+    /// The subsystem did all of its work.
+    ///
+    /// This is the default.
+    #[default]
+    Completed,
+    /// The subsystem stopped because of the shutdown and left work behind.
+    Drained {
+        /// The number of work items that were not processed.
+        dropped: u64,
+    },
+    /// The subsystem had nothing to do.
+    Skipped {
+        /// Why the subsystem had nothing to do.
+        reason: String,
+    },
+    /// The subsystem ran with reduced functionality.
+    Degraded {
+        /// What was not working.
+        reason: String,
+    },
+}
+
+impl ExitStatus {
+    /// Whether the status represents a subsystem that did not lose any work.
+    ///
+    /// [`Completed`](ExitStatus::Completed), [`Skipped`](ExitStatus::Skipped) and
+    /// [`Drained`](ExitStatus::Drained) without dropped items are clean.
+    pub fn is_clean(&self) -> bool {
+        match self {
+            Self::Completed | Self::Skipped { .. } => true,
+            Self::Drained { dropped } => *dropped == 0,
+            Self::Degraded { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Completed => write!(f, "completed"),
+            Self::Drained { dropped: 0 } => write!(f, "drained"),
+            Self::Drained { dropped: 1 } => write!(f, "drained with 1 item dropped"),
+            Self::Drained { dropped } => write!(f, "drained with {dropped} items dropped"),
+            Self::Skipped { reason } => write!(f, "skipped ({reason})"),
+            Self::Degraded { reason } => write!(f, "degraded ({reason})"),
+        }
+    }
+}
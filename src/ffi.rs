@@ -0,0 +1,181 @@
+//! A C-compatible interface, through which embedded C/C++ code can take part in the shutdown.
+//!
+//! The Rust side creates a [`TgsHandle`] from a [`ToplevelHandle`] and passes the raw
+//! pointer obtained through [`TgsHandle::into_raw`] to the C code.
+//! The C code can then use the following functions, which are declared in
+//! `include/tokio_graceful_shutdown.h`:
+//!
+//! ```c
+//! typedef struct TgsHandle TgsHandle;
+//!
+//! void tgs_request_shutdown(const TgsHandle *handle);
+//! bool tgs_is_shutting_down(const TgsHandle *handle);
+//! void tgs_on_shutdown(const TgsHandle *handle, void (*callback)(void *), void *user_data);
+//! void tgs_handle_free(TgsHandle *handle);
+//! ```
+//!
+//! All functions accept null pointers and ignore them.
+//!
+//! # Examples
+//!
+//! ```
+//! use miette::Result;
+//! use tokio::time::Duration;
+//! use tokio_graceful_shutdown::{
+//!     ffi::{tgs_handle_free, tgs_is_shutting_down, tgs_request_shutdown, TgsHandle},
+//!     SubsystemHandle, Toplevel,
+//! };
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<()> {
+//!     let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+//!         s.on_shutdown_requested().await;
+//!     });
+//!
+//!     let handle = TgsHandle::new(&toplevel.handle()).into_raw();
+//!
+//!     // Usually, the pointer gets handed to C code, which calls these functions.
+//!     unsafe {
+//!         assert!(!tgs_is_shutting_down(handle));
+//!         tgs_request_shutdown(handle);
+//!         tgs_handle_free(handle);
+//!     }
+//!
+//!     toplevel
+//!         .handle_shutdown_requests(Duration::from_millis(500))
+//!         .await
+//!         .map_err(Into::into)
+//! }
+//! ```
+
+use std::ffi::c_void;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::{ErrTypeTraits, ToplevelHandle};
+
+/// A handle through which C code can request and observe the shutdown of a subsystem tree.
+///
+/// Opaque to C code; it only ever sees pointers to it.
+pub struct TgsHandle {
+    request_shutdown: Box<dyn Fn() + Send + Sync>,
+    cancellation_token: CancellationToken,
+    runtime: tokio::runtime::Handle,
+}
+
+impl TgsHandle {
+    /// Creates a new handle for the subsystem tree of the given [`ToplevelHandle`].
+    ///
+    /// Callbacks registered through [`tgs_on_shutdown`] run on the current tokio runtime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a tokio runtime.
+    pub fn new<ErrType: ErrTypeTraits>(toplevel: &ToplevelHandle<ErrType>) -> Self {
+        let cancellation_token = toplevel.get_cancellation_token().clone();
+        let toplevel = toplevel.clone();
+        Self {
+            request_shutdown: Box::new(move || toplevel.request_shutdown()),
+            cancellation_token,
+            runtime: tokio::runtime::Handle::current(),
+        }
+    }
+
+    /// Converts the handle into a pointer that can be passed to C code.
+    ///
+    /// The pointer has to be released through [`tgs_handle_free`].
+    pub fn into_raw(self) -> *mut TgsHandle {
+        Box::into_raw(Box::new(self))
+    }
+}
+
+/// The user data of a C callback.
+struct UserData(*mut c_void);
+
+// SAFETY: The caller of `tgs_on_shutdown` guarantees that the user data
+// may be used from any thread.
+unsafe impl Send for UserData {}
+
+impl UserData {
+    fn into_inner(self) -> *mut c_void {
+        self.0
+    }
+}
+
+/// Triggers a shutdown of the entire subsystem tree.
+///
+/// Does nothing if `handle` is null.
+///
+/// # Safety
+///
+/// `handle` must be a valid pointer obtained from [`TgsHandle::into_raw`]
+/// that was not freed yet, or null.
+#[no_mangle]
+pub unsafe extern "C" fn tgs_request_shutdown(handle: *const TgsHandle) {
+    let Some(handle) = handle.as_ref() else {
+        return;
+    };
+    (handle.request_shutdown)();
+}
+
+/// Returns whether a shutdown of the subsystem tree was requested.
+///
+/// Returns `false` if `handle` is null.
+///
+/// # Safety
+///
+/// `handle` must be a valid pointer obtained from [`TgsHandle::into_raw`]
+/// that was not freed yet, or null.
+#[no_mangle]
+pub unsafe extern "C" fn tgs_is_shutting_down(handle: *const TgsHandle) -> bool {
+    let Some(handle) = handle.as_ref() else {
+        return false;
+    };
+    handle.cancellation_token.is_cancelled()
+}
+
+/// Registers a callback that gets called at most once, from a runtime thread, with
+/// `user_data` as its argument, after a shutdown of the subsystem tree was requested.
+///
+/// If the shutdown was already requested, the callback gets called soon. If the runtime
+/// shuts down before a shutdown was requested, it is never called. It should return
+/// quickly, as it blocks the runtime thread.
+/// Nothing gets registered if `handle` or `callback` is null.
+///
+/// # Safety
+///
+/// - `handle` must be a valid pointer obtained from `TgsHandle::into_raw`
+///   that was not freed yet, or null.
+/// - `callback` must be safe to call from any thread with `user_data`,
+///   as long as the runtime is alive.
+#[no_mangle]
+pub unsafe extern "C" fn tgs_on_shutdown(
+    handle: *const TgsHandle,
+    callback: Option<extern "C" fn(*mut c_void)>,
+    user_data: *mut c_void,
+) {
+    let (Some(handle), Some(callback)) = (handle.as_ref(), callback) else {
+        return;
+    };
+    let cancellation_token = handle.cancellation_token.clone();
+    let user_data = UserData(user_data);
+    handle.runtime.spawn(async move {
+        cancellation_token.cancelled().await;
+        callback(user_data.into_inner());
+    });
+}
+
+/// Releases a handle.
+///
+/// Callbacks that were registered through this handle stay registered.
+///
+/// # Safety
+///
+/// `handle` must be a valid pointer obtained from [`TgsHandle::into_raw`]
+/// that was not freed yet, or null. It must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn tgs_handle_free(handle: *mut TgsHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
@@ -0,0 +1,268 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use tokio::sync::mpsc;
+
+use crate::{ErrTypeTraits, IntoSubsystem, SubsystemHandle};
+
+/// A subsystem that buffers items and writes them out in batches.
+///
+/// Items get submitted through a [`FlusherSender`] and are passed to the flush function
+/// once the flush interval elapsed or the buffer reached its maximum size.
+///
+/// When a shutdown is requested, all remaining items get flushed one last time,
+/// limited by the [`final_flush_timeout`](Self::final_flush_timeout). Items that could
+/// not be flushed get counted and can be queried through [`dropped_items`](Self::dropped_items).
+///
+/// The subsystem finishes once a shutdown got requested, all senders got dropped or
+/// the flush function returns an error.
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{
+///     Flusher, IntoSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel,
+/// };
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let flusher = Flusher::new(|metrics: Vec<u32>| async move {
+///         tracing::info!("Writing {} metrics ...", metrics.len());
+///         Result::<()>::Ok(())
+///     })
+///     .interval(Duration::from_secs(10))
+///     .max_batch_size(100);
+///     let sender = flusher.sender();
+///     let dropped_items = flusher.dropped_items();
+///
+///     Toplevel::new(|s: SubsystemHandle| async move {
+///         s.start(SubsystemBuilder::new("metrics", flusher.into_subsystem()));
+///
+///         for metric in 0..10 {
+///             sender.push(metric);
+///         }
+///         s.request_shutdown();
+///     })
+///     .handle_shutdown_requests(Duration::from_millis(500))
+///     .await?;
+///
+///     assert_eq!(dropped_items.get(), 0);
+///     Ok(())
+/// }
+/// ```
+pub struct Flusher<T, F> {
+    flush: F,
+    interval: Duration,
+    max_batch_size: usize,
+    final_flush_timeout: Duration,
+    sender: mpsc::UnboundedSender<T>,
+    receiver: mpsc::UnboundedReceiver<T>,
+    dropped_items: DroppedItems,
+}
+
+/// Submits items to a [`Flusher`].
+///
+/// Returned by [`Flusher::sender`].
+pub struct FlusherSender<T> {
+    sender: mpsc::UnboundedSender<T>,
+    dropped_items: DroppedItems,
+}
+
+/// The number of items a [`Flusher`] dropped so far.
+///
+/// Returned by [`Flusher::dropped_items`].
+#[derive(Debug, Clone, Default)]
+pub struct DroppedItems(Arc<AtomicU64>);
+
+impl DroppedItems {
+    /// Returns the number of dropped items.
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn add(&self, count: usize) {
+        self.0.fetch_add(count as u64, Ordering::Relaxed);
+    }
+}
+
+impl<T> Clone for FlusherSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            dropped_items: self.dropped_items.clone(),
+        }
+    }
+}
+
+impl<T> FlusherSender<T> {
+    /// Submits an item to the flusher.
+    ///
+    /// # Returns
+    ///
+    /// `false` if the flusher already stopped. The item then gets counted as dropped.
+    pub fn push(&self, item: T) -> bool {
+        let accepted = self.sender.send(item).is_ok();
+        if !accepted {
+            self.dropped_items.add(1);
+        }
+        accepted
+    }
+}
+
+impl<T, F> Flusher<T, F> {
+    /// Creates a new flusher.
+    ///
+    /// # Arguments
+    ///
+    /// * `flush` - Gets called with every batch of items. Returning an error stops the flusher
+    ///   and makes the subsystem fail; the items of the failed batch get counted as dropped.
+    pub fn new(flush: F) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Self {
+            flush,
+            interval: Duration::from_secs(1),
+            max_batch_size: 1000,
+            final_flush_timeout: Duration::from_secs(5),
+            sender,
+            receiver,
+            dropped_items: DroppedItems::default(),
+        }
+    }
+
+    /// How often buffered items get flushed. Defaults to one second.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The number of buffered items that triggers an immediate flush. Defaults to 1000.
+    pub fn max_batch_size(mut self, max_batch_size: usize) -> Self {
+        self.max_batch_size = max_batch_size.max(1);
+        self
+    }
+
+    /// How long the final flush during shutdown may take. Defaults to five seconds.
+    ///
+    /// Should be shorter than the shutdown timeout of the [`Toplevel`](crate::Toplevel),
+    /// otherwise the flusher might get cancelled before it can report its dropped items.
+    pub fn final_flush_timeout(mut self, timeout: Duration) -> Self {
+        self.final_flush_timeout = timeout;
+        self
+    }
+
+    /// Returns a handle to submit items to the flusher.
+    pub fn sender(&self) -> FlusherSender<T> {
+        FlusherSender {
+            sender: self.sender.clone(),
+            dropped_items: self.dropped_items.clone(),
+        }
+    }
+
+    /// Returns a counter of the items that could not be flushed.
+    ///
+    /// The counter stays valid after the flusher was started.
+    pub fn dropped_items(&self) -> DroppedItems {
+        self.dropped_items.clone()
+    }
+}
+
+#[async_trait]
+impl<T, F, Fut, Err, ErrWrapper> IntoSubsystem<Err, ErrWrapper> for Flusher<T, F>
+where
+    T: Send + 'static,
+    F: FnMut(Vec<T>) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), Err>> + Send,
+    Err: Into<ErrWrapper> + Send + 'static,
+    ErrWrapper: ErrTypeTraits,
+{
+    async fn run(self, subsys: SubsystemHandle<ErrWrapper>) -> Result<(), Err> {
+        let Self {
+            mut flush,
+            interval,
+            max_batch_size,
+            final_flush_timeout,
+            sender,
+            mut receiver,
+            dropped_items,
+        } = self;
+        // Only the handed out senders should keep the flusher alive.
+        drop(sender);
+
+        let mut buffer = Vec::new();
+        let clock = Arc::clone(subsys.get_clock());
+        let mut next_flush = clock.sleep(interval);
+
+        let mut flush_batch = |batch: Vec<T>| {
+            let len = batch.len();
+            let flushed = flush(batch);
+            let dropped_items = dropped_items.clone();
+            async move {
+                let result = flushed.await;
+                if result.is_err() {
+                    dropped_items.add(len);
+                }
+                result
+            }
+        };
+
+        loop {
+            tokio::select! {
+                biased;
+                _ = subsys.on_shutdown_requested() => break,
+                item = receiver.recv() => match item {
+                    Some(item) => {
+                        buffer.push(item);
+                        if buffer.len() >= max_batch_size {
+                            next_flush = clock.sleep(interval);
+                            flush_batch(std::mem::take(&mut buffer)).await?;
+                        }
+                    }
+                    None => break,
+                },
+                () = &mut next_flush => {
+                    next_flush = clock.sleep(interval);
+                    if !buffer.is_empty() {
+                        flush_batch(std::mem::take(&mut buffer)).await?;
+                    }
+                }
+            }
+        }
+
+        receiver.close();
+        while let Ok(item) = receiver.try_recv() {
+            buffer.push(item);
+        }
+
+        if !buffer.is_empty() {
+            let len = buffer.len();
+            match subsys
+                .get_clock()
+                .timeout(final_flush_timeout, flush_batch(buffer))
+                .await
+            {
+                Some(result) => result?,
+                None => {
+                    tracing::warn!(
+                        "Final flush did not finish within {final_flush_timeout:?}; dropping {len} items."
+                    );
+                    dropped_items.add(len);
+                }
+            }
+        }
+
+        if dropped_items.get() > 0 {
+            tracing::warn!("Flusher dropped {} items.", dropped_items.get());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    any::Any,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::{ErrTypeTraits, ShutdownToken, ToplevelHandle};
+
+static GLOBAL: RwLock<Option<GlobalHandle>> = RwLock::new(None);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the handle of the [`Toplevel`](crate::Toplevel) that is installed globally.
+///
+/// Meant for deeply layered code bases, in which passing a [`SubsystemHandle`](crate::SubsystemHandle)
+/// down to every place that needs to observe or request a shutdown is impractical.
+/// Prefer passing handles explicitly where possible; the global handle hides the
+/// dependency on the subsystem tree and is shared by the entire process.
+///
+/// A Toplevel gets installed through [`ToplevelBuilder::install_global`](crate::ToplevelBuilder::install_global)
+/// and stays installed until it gets dropped, which usually happens at the end of
+/// [`handle_shutdown_requests`](crate::Toplevel::handle_shutdown_requests).
+///
+/// # Returns
+///
+/// The handle, or `None` if no Toplevel is installed.
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+///
+/// // Deep inside of a library, without access to a `SubsystemHandle`.
+/// fn on_fatal_config_error() {
+///     if let Some(global) = tokio_graceful_shutdown::global() {
+///         global.request_shutdown();
+///     }
+/// }
+///
+/// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+///     on_fatal_config_error();
+///     subsys.on_shutdown_requested().await;
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     Toplevel::builder()
+///         .install_global()
+///         .build(|s| async move {
+///             s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+///         })
+///         .handle_shutdown_requests(Duration::from_millis(500))
+///         .await
+///         .map_err(Into::into)
+/// }
+/// ```
+pub fn global() -> Option<GlobalHandle> {
+    GLOBAL
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone()
+}
+
+/// The handle of the globally installed [`Toplevel`](crate::Toplevel).
+///
+/// Obtained through [`global()`].
+#[derive(Clone)]
+pub struct GlobalHandle {
+    id: u64,
+    request_shutdown: Arc<dyn Fn() + Send + Sync>,
+    cancellation_token: CancellationToken,
+    toplevel_handle: Arc<dyn Any + Send + Sync>,
+}
+
+impl GlobalHandle {
+    /// Triggers a shutdown of the entire subsystem tree.
+    pub fn request_shutdown(&self) {
+        (self.request_shutdown)();
+    }
+
+    /// Returns whether a shutdown was requested.
+    pub fn is_shutdown_requested(&self) -> bool {
+        self.cancellation_token.is_cancelled()
+    }
+
+    /// Waits until a shutdown was requested.
+    pub async fn on_shutdown_requested(&self) {
+        self.cancellation_token.cancelled().await
+    }
+
+    /// Creates a [`ShutdownToken`] that will get triggered once the subsystem tree shuts down.
+    ///
+    /// Requesting a shutdown through the token does not shut down the subsystem tree.
+    pub fn shutdown_token(&self) -> ShutdownToken {
+        ShutdownToken::from(self.cancellation_token.child_token())
+    }
+
+    /// Returns the full [`ToplevelHandle`], for example to start additional subsystems.
+    ///
+    /// # Returns
+    ///
+    /// The handle, or `None` if the installed Toplevel uses a different error type.
+    pub fn toplevel_handle<ErrType: ErrTypeTraits>(&self) -> Option<ToplevelHandle<ErrType>> {
+        self.toplevel_handle
+            .downcast_ref::<ToplevelHandle<ErrType>>()
+            .cloned()
+    }
+}
+
+/// Keeps a [`Toplevel`](crate::Toplevel) installed globally; uninstalls it when dropped.
+pub(crate) struct GlobalGuard {
+    id: u64,
+}
+
+impl GlobalGuard {
+    /// Installs the given Toplevel globally, replacing the one that was installed before.
+    pub(crate) fn install<ErrType: ErrTypeTraits>(toplevel: ToplevelHandle<ErrType>) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+        let handle = GlobalHandle {
+            id,
+            request_shutdown: Arc::new({
+                let toplevel = toplevel.clone();
+                move || toplevel.request_shutdown()
+            }),
+            cancellation_token: toplevel.get_cancellation_token().clone(),
+            toplevel_handle: Arc::new(toplevel),
+        };
+
+        let previous = GLOBAL
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .replace(handle);
+        if previous.is_some() {
+            tracing::warn!("Replacing the globally installed Toplevel with a new one.");
+        }
+
+        Self { id }
+    }
+}
+
+impl Drop for GlobalGuard {
+    fn drop(&mut self) {
+        let mut global = GLOBAL
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        // Might have been replaced by a newer Toplevel in the meantime.
+        if global.as_ref().is_some_and(|handle| handle.id == self.id) {
+            *global = None;
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use tokio::time::{Instant, MissedTickBehavior};
+
+use crate::{ErrTypeTraits, ExitStatus, IntoSubsystem, SubsystemHandle};
+
+/// What happens to a tick of an [`IntervalSubsystem`] that is running when a shutdown
+/// is requested.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum InFlightTick {
+    /// Let the tick run to completion before the subsystem finishes.
+    ///
+    /// This is the default.
+    #[default]
+    Finish,
+    /// Cancel the tick right away, by dropping its future.
+    Cancel,
+}
+
+/// A subsystem that performs periodic work, like refreshing a cache.
+///
+/// Runs the tick function once per period, starting right away. Once a shutdown
+/// is requested, no further tick gets started; a tick that is running at that moment
+/// gets finished or cancelled, as configured through [`in_flight_tick`](Self::in_flight_tick).
+///
+/// If a tick was running when the shutdown was requested, the outcome shows up in the
+/// [exit status](crate::ExitStatus) of the subsystem, and therefore in the
+/// [`ShutdownReport`](crate::ShutdownReport): [`ExitStatus::Drained`] without dropped
+/// items if the tick was finished, and with one dropped item if it was cancelled.
+/// Otherwise, the subsystem ends as [`ExitStatus::Completed`].
+///
+/// The subsystem finishes once a shutdown got requested or the tick function
+/// returns an error.
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{
+///     InFlightTick, IntervalSubsystem, IntoSubsystem, SubsystemBuilder, SubsystemHandle,
+///     Toplevel,
+/// };
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let refresher = IntervalSubsystem::new(Duration::from_secs(60), || async {
+///         tracing::info!("Refreshing cache ...");
+///         Result::<()>::Ok(())
+///     })
+///     .in_flight_tick(InFlightTick::Cancel);
+///
+///     Toplevel::new(|s: SubsystemHandle| async move {
+///         s.start(SubsystemBuilder::new("refresher", refresher.into_subsystem()));
+///         s.request_shutdown();
+///     })
+///     .handle_shutdown_requests(Duration::from_millis(500))
+///     .await
+///     .map_err(Into::into)
+/// }
+/// ```
+pub struct IntervalSubsystem<F> {
+    period: Duration,
+    tick: F,
+    in_flight_tick: InFlightTick,
+    missed_tick_behavior: MissedTickBehavior,
+}
+
+impl<F> IntervalSubsystem<F> {
+    /// Creates a new interval subsystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `period` - The time between the starts of two ticks.
+    /// * `tick` - Creates the future of a single tick. Returning an error stops
+    ///   the subsystem and makes it fail.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn new(period: Duration, tick: F) -> Self {
+        assert!(
+            !period.is_zero(),
+            "The period of an interval must not be zero"
+        );
+        Self {
+            period,
+            tick,
+            in_flight_tick: InFlightTick::default(),
+            missed_tick_behavior: MissedTickBehavior::Delay,
+        }
+    }
+
+    /// What happens to a tick that is running when a shutdown is requested.
+    ///
+    /// Defaults to [`InFlightTick::Finish`].
+    pub fn in_flight_tick(mut self, in_flight_tick: InFlightTick) -> Self {
+        self.in_flight_tick = in_flight_tick;
+        self
+    }
+
+    /// What happens if a tick takes longer than the period.
+    ///
+    /// Defaults to [`MissedTickBehavior::Delay`], which starts the next tick right away
+    /// and continues with the full period from there.
+    pub fn missed_tick_behavior(mut self, missed_tick_behavior: MissedTickBehavior) -> Self {
+        self.missed_tick_behavior = missed_tick_behavior;
+        self
+    }
+}
+
+#[async_trait]
+impl<F, Fut, Err, ErrWrapper> IntoSubsystem<Err, ErrWrapper> for IntervalSubsystem<F>
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), Err>> + Send,
+    Err: Into<ErrWrapper> + Send + 'static,
+    ErrWrapper: ErrTypeTraits,
+{
+    async fn run(self, subsys: SubsystemHandle<ErrWrapper>) -> Result<(), Err> {
+        let Self {
+            period,
+            mut tick,
+            in_flight_tick,
+            missed_tick_behavior,
+        } = self;
+
+        let clock = Arc::clone(subsys.get_clock());
+        let mut deadline = clock.now();
+
+        loop {
+            tokio::select! {
+                biased;
+                _ = subsys.on_shutdown_requested() => return Ok(()),
+                _ = clock.sleep_until(deadline) => (),
+            }
+            deadline = next_deadline(deadline, clock.now(), period, missed_tick_behavior);
+
+            let tick = tick();
+            match in_flight_tick {
+                InFlightTick::Finish => {
+                    tick.await?;
+                    if subsys.is_shutdown_requested() {
+                        tracing::info!(
+                            "Finished the running tick after the shutdown was requested."
+                        );
+                        subsys.set_exit_status(ExitStatus::Drained { dropped: 0 });
+                        return Ok(());
+                    }
+                }
+                InFlightTick::Cancel => {
+                    tokio::select! {
+                        biased;
+                        _ = subsys.on_shutdown_requested() => {
+                            tracing::info!("Cancelled the running tick because of the shutdown.");
+                            subsys.set_exit_status(ExitStatus::Drained { dropped: 1 });
+                            return Ok(());
+                        },
+                        result = tick => result?,
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Computes the deadline of the tick after the one that was due at `deadline`,
+/// the same way [`tokio::time::Interval`] does.
+fn next_deadline(
+    deadline: Instant,
+    now: Instant,
+    period: Duration,
+    missed_tick_behavior: MissedTickBehavior,
+) -> Instant {
+    // Small delays don't count as a missed tick.
+    if now <= deadline + Duration::from_millis(5) {
+        return deadline + period;
+    }
+
+    match missed_tick_behavior {
+        MissedTickBehavior::Burst => deadline + period,
+        MissedTickBehavior::Delay => now + period,
+        MissedTickBehavior::Skip => {
+            let behind = (now - deadline).as_nanos() % period.as_nanos();
+            now + period - Duration::from_nanos(behind as u64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,43 @@
+use super::*;
+
+#[test]
+fn next_deadline_handles_missed_ticks() {
+    let start = Instant::now();
+    let period = Duration::from_millis(100);
+    let deadline = start + period;
+
+    for missed_tick_behavior in [
+        MissedTickBehavior::Burst,
+        MissedTickBehavior::Delay,
+        MissedTickBehavior::Skip,
+    ] {
+        // Ticks that are on time keep the period.
+        assert_eq!(
+            next_deadline(deadline, deadline, period, missed_tick_behavior),
+            deadline + period
+        );
+        assert_eq!(
+            next_deadline(
+                deadline,
+                deadline + Duration::from_millis(5),
+                period,
+                missed_tick_behavior
+            ),
+            deadline + period
+        );
+    }
+
+    let now = deadline + Duration::from_millis(250);
+    assert_eq!(
+        next_deadline(deadline, now, period, MissedTickBehavior::Burst),
+        deadline + period
+    );
+    assert_eq!(
+        next_deadline(deadline, now, period, MissedTickBehavior::Delay),
+        now + period
+    );
+    assert_eq!(
+        next_deadline(deadline, now, period, MissedTickBehavior::Skip),
+        deadline + 3 * period
+    );
+}
@@ -0,0 +1,91 @@
+use std::{future::Future, pin::Pin};
+
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+use crate::{ErrTypeTraits, ShutdownToken, SubsystemHandle};
+
+/// Converts the many ways of signalling a shutdown into one.
+///
+/// Libraries can accept an `impl IntoShutdownSignal` in their API, and callers
+/// can pass in whatever they already have:
+///
+/// - A [`ShutdownToken`] or a [`CancellationToken`]
+/// - A [`SubsystemHandle`], by reference
+/// - A [`watch::Receiver<bool>`], which signals a shutdown once it holds `true`.
+///   If the sender gets dropped before that, a shutdown never gets signalled.
+/// - Any other future, boxed with [`Box::pin`]
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{IntoShutdownSignal, SubsystemBuilder, SubsystemHandle, Toplevel};
+///
+/// // Library code
+/// async fn serve(shutdown: impl IntoShutdownSignal) {
+///     shutdown.into_shutdown_signal().await;
+///     // Stop accepting connections ...
+/// }
+///
+/// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+///     subsys.request_shutdown();
+///     serve(&subsys).await;
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     // Without a subsystem tree
+///     serve(Box::pin(tokio::time::sleep(Duration::from_millis(10)))).await;
+///
+///     Toplevel::new(|s| async move {
+///         s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+///     })
+///     .handle_shutdown_requests(Duration::from_millis(500))
+///     .await
+///     .map_err(Into::into)
+/// }
+/// ```
+pub trait IntoShutdownSignal {
+    /// Converts into a future that finishes once a shutdown is requested.
+    fn into_shutdown_signal(self) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+}
+
+impl IntoShutdownSignal for ShutdownToken {
+    fn into_shutdown_signal(self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        CancellationToken::from(self).into_shutdown_signal()
+    }
+}
+
+impl IntoShutdownSignal for CancellationToken {
+    fn into_shutdown_signal(self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(async move { self.cancelled().await })
+    }
+}
+
+impl<ErrType: ErrTypeTraits> IntoShutdownSignal for &SubsystemHandle<ErrType> {
+    fn into_shutdown_signal(self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        self.shutdown_token().into_shutdown_signal()
+    }
+}
+
+impl IntoShutdownSignal for watch::Receiver<bool> {
+    fn into_shutdown_signal(mut self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(async move {
+            if self.wait_for(|&shutdown| shutdown).await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        })
+    }
+}
+
+impl<F> IntoShutdownSignal for Pin<Box<F>>
+where
+    F: Future<Output = ()> + Send + ?Sized + 'static,
+{
+    fn into_shutdown_signal(self) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(self)
+    }
+}
@@ -18,6 +18,10 @@ type SubsystemFunction<Err, ErrWrapper> =
 /// by wrapping it in an async closure. This trait exists primarily
 /// for convenience.
 ///
+/// To ease the integration of existing code, this trait is also implemented
+/// for boxed futures and for async functions that don't take a [`SubsystemHandle`].
+/// Those subsystems are not notified about shutdown requests; they simply run to completion.
+///
 /// The template parameter of the trait is the error type
 /// that the subsytem returns.
 ///
@@ -55,7 +59,7 @@ type SubsystemFunction<Err, ErrWrapper> =
 ///
 pub trait IntoSubsystem<Err, ErrWrapper = BoxedError>
 where
-    Self: Sized + Send + Sync + 'static,
+    Self: Sized + Send + 'static,
     Err: Into<ErrWrapper>,
     ErrWrapper: ErrTypeTraits,
 {
@@ -77,3 +81,35 @@ where
         })
     }
 }
+
+/// Allows existing futures to be used as subsystems.
+///
+/// The subsystem finishes once the future finishes; shutdown requests
+/// are not forwarded to it.
+#[async_trait]
+impl<Err, ErrWrapper> IntoSubsystem<Err, ErrWrapper> for Pin<Box<SubsystemFuture<Err>>>
+where
+    Err: Into<ErrWrapper> + 'static,
+    ErrWrapper: ErrTypeTraits,
+{
+    async fn run(self, _subsys: SubsystemHandle<ErrWrapper>) -> Result<(), Err> {
+        self.await
+    }
+}
+
+/// Allows async functions that do not take a [`SubsystemHandle`] to be used as subsystems.
+///
+/// The subsystem finishes once the returned future finishes; shutdown requests
+/// are not forwarded to it.
+#[async_trait]
+impl<F, Fut, Err, ErrWrapper> IntoSubsystem<Err, ErrWrapper> for F
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), Err>> + Send + 'static,
+    Err: Into<ErrWrapper>,
+    ErrWrapper: ErrTypeTraits,
+{
+    async fn run(self, _subsys: SubsystemHandle<ErrWrapper>) -> Result<(), Err> {
+        self().await
+    }
+}
@@ -0,0 +1,123 @@
+//! Shutdown-aware helpers for [`tokio::io`].
+
+use std::{io, pin::pin, time::Duration};
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::{ErrTypeTraits, SubsystemHandle};
+
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// Copies data in both directions between `a` and `b`, until both directions
+/// reached EOF or a shutdown got requested.
+///
+/// On shutdown, no new data gets read from either stream. Data that was already read
+/// gets written out, then both write halves get flushed and shut down.
+/// If a peer does not accept the remaining data within `timeout`, the copy gets
+/// aborted with an [`io::ErrorKind::TimedOut`] error.
+///
+/// # Arguments
+///
+/// * `subsys` - The handle of the subsystem that runs the copy.
+/// * `a` - The first stream.
+/// * `b` - The second stream.
+/// * `timeout` - How long flushing and shutting down the streams may take after a shutdown got requested.
+///
+/// # Returns
+///
+/// The number of bytes copied from `a` to `b` and from `b` to `a`, respectively.
+///
+/// # Examples
+///
+/// ```no_run
+/// use miette::{IntoDiagnostic, Result};
+/// use tokio::{net::TcpStream, time::Duration};
+/// use tokio_graceful_shutdown::SubsystemHandle;
+///
+/// async fn proxy(subsys: SubsystemHandle, mut client: TcpStream) -> Result<()> {
+///     let mut upstream = TcpStream::connect("127.0.0.1:8080").await.into_diagnostic()?;
+///
+///     let (sent, received) = tokio_graceful_shutdown::io::copy_bidirectional(
+///         &subsys,
+///         &mut client,
+///         &mut upstream,
+///         Duration::from_secs(5),
+///     )
+///     .await
+///     .into_diagnostic()?;
+///
+///     tracing::info!("Proxied {sent} bytes upstream and {received} bytes downstream.");
+///     Ok(())
+/// }
+/// ```
+pub async fn copy_bidirectional<A, B, ErrType>(
+    subsys: &SubsystemHandle<ErrType>,
+    a: &mut A,
+    b: &mut B,
+    timeout: Duration,
+) -> io::Result<(u64, u64)>
+where
+    A: AsyncRead + AsyncWrite + Unpin,
+    B: AsyncRead + AsyncWrite + Unpin,
+    ErrType: ErrTypeTraits,
+{
+    let (mut a_reader, mut a_writer) = tokio::io::split(a);
+    let (mut b_reader, mut b_writer) = tokio::io::split(b);
+
+    let mut copy = pin!(async {
+        tokio::try_join!(
+            copy_until_shutdown(subsys, &mut a_reader, &mut b_writer),
+            copy_until_shutdown(subsys, &mut b_reader, &mut a_writer),
+        )
+    });
+
+    tokio::select! {
+        biased;
+        result = &mut copy => return result,
+        _ = subsys.on_shutdown_requested() => {}
+    }
+
+    match subsys.get_clock().timeout(timeout, copy).await {
+        Some(result) => result,
+        None => {
+            tracing::warn!("Peer did not finish the connection within {timeout:?}; aborting copy.");
+            Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "peer did not finish the connection in time",
+            ))
+        }
+    }
+}
+
+async fn copy_until_shutdown<R, W, ErrType>(
+    subsys: &SubsystemHandle<ErrType>,
+    reader: &mut R,
+    writer: &mut W,
+) -> io::Result<u64>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+    ErrType: ErrTypeTraits,
+{
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let mut copied = 0;
+
+    loop {
+        let len = tokio::select! {
+            biased;
+            _ = subsys.on_shutdown_requested() => break,
+            len = reader.read(&mut buffer) => len?,
+        };
+        if len == 0 {
+            break;
+        }
+
+        writer.write_all(&buffer[..len]).await?;
+        copied += len as u64;
+    }
+
+    writer.flush().await?;
+    writer.shutdown().await?;
+
+    Ok(copied)
+}
@@ -0,0 +1,100 @@
+use std::{
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{mpsc, Arc},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::utils::Mutex;
+
+pub(crate) type LastWordsCallback = Box<dyn FnOnce() + Send>;
+
+pub(crate) const DEFAULT_LAST_WORDS_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Callbacks that run once at the very end of the shutdown, whichever way it ends.
+pub(crate) struct LastWords {
+    timeout: Duration,
+    callbacks: Mutex<Vec<(Arc<str>, LastWordsCallback)>>,
+}
+
+impl Default for LastWords {
+    fn default() -> Self {
+        Self::new(DEFAULT_LAST_WORDS_TIMEOUT, Vec::new())
+    }
+}
+
+impl LastWords {
+    pub(crate) fn new(timeout: Duration, callbacks: Vec<(Arc<str>, LastWordsCallback)>) -> Self {
+        Self {
+            timeout,
+            callbacks: Mutex::new(callbacks),
+        }
+    }
+
+    /// Runs all callbacks that did not run yet, in the order in which they were registered.
+    ///
+    /// The callbacks run on a dedicated OS thread, so a callback that hangs can't delay
+    /// the caller for longer than the timeout; callbacks that did not get to run in time
+    /// are abandoned.
+    pub(crate) fn run(&self) {
+        let callbacks = std::mem::take(&mut *self.callbacks.lock());
+        if callbacks.is_empty() {
+            return;
+        }
+
+        let deadline = Instant::now() + self.timeout;
+
+        // Reports the name of every callback before it runs; closes once all of them ran.
+        let (progress_sender, progress) = mpsc::channel::<Arc<str>>();
+        let (callbacks_sender, callbacks_receiver) = mpsc::channel();
+        let span = tracing::Span::current();
+        let spawned = thread::Builder::new()
+            .name("last-words".into())
+            .spawn(move || {
+                let _span = span.enter();
+                if let Ok(callbacks) = callbacks_receiver.recv() {
+                    run_callbacks(callbacks, |name| {
+                        progress_sender.send(Arc::clone(name)).ok();
+                    });
+                }
+            });
+        if let Err(e) = spawned {
+            tracing::error!("Failed to start thread for last words, running them in place: {e}");
+            run_callbacks(callbacks, |_| ());
+            return;
+        }
+        callbacks_sender.send(callbacks).ok();
+
+        let mut current = None;
+        loop {
+            match progress.recv_timeout(deadline.saturating_duration_since(Instant::now())) {
+                Ok(name) => current = Some(name),
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+                Err(mpsc::RecvTimeoutError::Timeout) => {
+                    let current = current.as_deref().unwrap_or_default();
+                    tracing::error!(
+                        "Last words did not finish within {:?}; abandoning '{current}' and all that come after it.",
+                        self.timeout
+                    );
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn run_callbacks(
+    callbacks: Vec<(Arc<str>, LastWordsCallback)>,
+    mut on_start: impl FnMut(&Arc<str>),
+) {
+    for (name, callback) in callbacks {
+        on_start(&name);
+        tracing::debug!("Running last words '{name}' ...");
+        if catch_unwind(AssertUnwindSafe(callback)).is_err() {
+            tracing::error!("Last words '{name}' panicked.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
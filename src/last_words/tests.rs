@@ -0,0 +1,71 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use tracing_test::traced_test;
+
+use super::*;
+
+fn counting(counter: &Arc<AtomicU32>, expected: u32) -> (Arc<str>, LastWordsCallback) {
+    let counter = Arc::clone(counter);
+    (
+        Arc::from(format!("callback{expected}")),
+        Box::new(move || {
+            assert_eq!(counter.fetch_add(1, Ordering::Relaxed), expected);
+        }),
+    )
+}
+
+#[test]
+#[traced_test]
+fn callbacks_run_once_in_order() {
+    let counter = Arc::new(AtomicU32::new(0));
+
+    let last_words = LastWords::new(
+        Duration::from_secs(10),
+        vec![
+            counting(&counter, 0),
+            (
+                Arc::from("panicking"),
+                Box::new(|| panic!("Callback failed")),
+            ),
+            counting(&counter, 1),
+        ],
+    );
+
+    last_words.run();
+    last_words.run();
+
+    assert_eq!(counter.load(Ordering::Relaxed), 2);
+    assert!(logs_contain("Last words 'panicking' panicked."));
+}
+
+#[test]
+#[traced_test]
+fn hanging_callback_gets_abandoned() {
+    let counter = Arc::new(AtomicU32::new(0));
+    let (_release, hang) = mpsc::channel::<()>();
+
+    let last_words = LastWords::new(
+        Duration::from_millis(50),
+        vec![
+            counting(&counter, 0),
+            (
+                Arc::from("hanging"),
+                Box::new(move || {
+                    hang.recv().ok();
+                }),
+            ),
+            counting(&counter, 1),
+        ],
+    );
+
+    let start = Instant::now();
+    last_words.run();
+    let elapsed = start.elapsed();
+
+    assert!(elapsed >= Duration::from_millis(50));
+    assert!(elapsed < Duration::from_secs(5));
+    assert_eq!(counter.load(Ordering::Relaxed), 1);
+    assert!(logs_contain(
+        "Last words did not finish within 50ms; abandoning 'hanging' and all that come after it."
+    ));
+}
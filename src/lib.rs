@@ -83,6 +83,47 @@
 //! It enables the subsystem to start nested subsystems, to react to shutdown requests or
 //! to initiate a shutdown.
 //!
+//! # Feature flags
+//!
+//! - `parking_lot`: Uses [`parking_lot`](https://docs.rs/parking_lot) instead of `std::sync`
+//!   for internal locks.
+//! - `fault-injection`: Enables [`testing::FaultInjection`], which injects delays, panics
+//!   or hangs into chosen subsystems during shutdown. Only intended for testing.
+//! - `futures`: Adds integrations with the [`futures`](https://docs.rs/futures) crate,
+//!   like [`SubsystemHandle::abortable`] and [`StreamProcessor`].
+//! - `axum`: Allows extracting a [`ToplevelHandle`] in [`axum`](https://docs.rs/axum) handlers,
+//!   for example to implement an administrative shutdown route.
+//! - `actix-web`: Adds [`ActixWebServer`], which runs an [`actix-web`](https://docs.rs/actix-web)
+//!   server as a subsystem and stops it gracefully on shutdown.
+//! - `warp`: Adds [`WarpServer`], which runs a [`warp`](https://docs.rs/warp) server
+//!   as a subsystem and drains its connections on shutdown.
+//! - `sqlx`, `deadpool`: Implement [`AsyncClose`] for [`sqlx`](https://docs.rs/sqlx)
+//!   and [`deadpool`](https://docs.rs/deadpool) pools, so they can be closed
+//!   by a [`ResourceSubsystem`].
+//! - `udp`: Adds [`UdpSubsystem`], which serves a [`tokio::net::UdpSocket`] and
+//!   sends goodbye datagrams and queued datagrams on shutdown.
+//! - `coordination`: Enables the [`coordination`] module, which propagates shutdowns
+//!   between multiple processes.
+//! - `io`: Enables the [`io`] module, with shutdown-aware helpers for [`tokio::io`],
+//!   like proxying data between two streams.
+//! - `ffi`: Enables the [`ffi`] module, which exports C functions through which
+//!   embedded C/C++ code can request and observe the shutdown.
+//! - `status`: Enables the [`status`] module, which renders the state of the subsystem tree
+//!   as text or JSON, for status endpoints of any HTTP framework.
+//! - `global`: Enables [`global()`](crate::global()), through which code that has no access
+//!   to a [`SubsystemHandle`] can reach a [`Toplevel`] that opted in through
+//!   [`ToplevelBuilder::install_global`].
+//! - `madsim`: Compiles against the tokio shims of [`madsim`](https://docs.rs/madsim)
+//!   when built with `RUSTFLAGS="--cfg madsim"`, so the shutdown behavior can be part of
+//!   deterministic simulation tests. Signals are replaced by the simulated Ctrl-C of
+//!   `madsim`, and panics of subsystems abort the simulation, as `madsim` does not catch them.
+//!   The [`shutdown_watchdog`](ToplevelBuilder::shutdown_watchdog) and
+//!   [`last_words`](ToplevelBuilder::last_words) rely on a real thread
+//!   and should not be used within a simulation.
+//! - `task-dump`: Enables [`ToplevelBuilder::task_dump_on_stall`], which logs a task dump
+//!   of the runtime if the shutdown stalls. The dump itself requires `--cfg tokio_unstable`
+//!   and is only supported on some Linux platforms.
+//!
 
 #![deny(unreachable_pub)]
 #![deny(missing_docs)]
@@ -92,6 +133,10 @@
     test(attr(allow(dead_code)))
 )]
 
+// Without `--cfg madsim`, `madsim-tokio` simply re-exports `tokio`.
+#[cfg(feature = "madsim")]
+extern crate madsim_tokio as tokio;
+
 type BoxedError = Box<dyn std::error::Error + Send + Sync + 'static>;
 
 /// A collection of traits a custom error has to fulfill in order to be
@@ -105,22 +150,152 @@ impl<T> ErrTypeTraits for T where
 {
 }
 
+#[cfg(feature = "coordination")]
+pub mod coordination;
 pub mod errors;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "io")]
+pub mod io;
+#[cfg(feature = "status")]
+pub mod status;
+pub mod testing;
 
+#[cfg(feature = "actix-web")]
+mod actix_web_server;
+mod blocking_detector;
+mod clock;
+mod emergency_shutdown;
 mod error_action;
+mod exit_status;
+mod flusher;
 mod future_ext;
+#[cfg(feature = "global")]
+mod global;
+mod interval_subsystem;
+mod into_shutdown_signal;
 mod into_subsystem;
+mod last_words;
+mod middleware;
+mod panic_hook;
+mod profile;
+mod report_sink;
+mod resource_subsystem;
+mod retrying_subsystem;
 mod runner;
+mod select_with_shutdown;
+mod shared_resources;
+mod shutdown_announcement;
+mod shutdown_groups;
+mod shutdown_once_cell;
+mod shutdown_plan;
+mod shutdown_reason;
+mod shutdown_report;
+mod shutdown_snapshot;
+mod shutdown_state;
+mod shutdown_statistics;
+mod shutdown_token;
 mod signal_handling;
+mod spawn_hook;
+mod startup_race_policy;
+#[cfg(feature = "futures")]
+mod stream_processor;
 mod subsystem;
+mod subsystem_result;
+mod task_dump;
 mod toplevel;
+#[cfg(feature = "udp")]
+mod udp_subsystem;
 mod utils;
+#[cfg(feature = "warp")]
+mod warp_server;
 
+#[cfg(feature = "actix-web")]
+pub use actix_web_server::ActixWebServer;
+pub use clock::Clock;
+pub use clock::TokioClock;
+pub use emergency_shutdown::EmergencyHandle;
 pub use error_action::ErrorAction;
+pub use exit_status::ExitStatus;
+pub use flusher::DroppedItems;
+pub use flusher::Flusher;
+pub use flusher::FlusherSender;
 pub use future_ext::FutureExt;
+#[cfg(feature = "global")]
+pub use global::global;
+#[cfg(feature = "global")]
+pub use global::GlobalHandle;
+pub use interval_subsystem::InFlightTick;
+pub use interval_subsystem::IntervalSubsystem;
+pub use into_shutdown_signal::IntoShutdownSignal;
 pub use into_subsystem::IntoSubsystem;
+pub use middleware::SubsystemFuture;
+pub use middleware::SubsystemMiddleware;
+pub use profile::Profile;
+pub use report_sink::FileReportSink;
+pub use report_sink::ReportSink;
+pub use report_sink::ShutdownRecord;
+pub use resource_subsystem::AsyncClose;
+pub use resource_subsystem::ResourceSubsystem;
+pub use retrying_subsystem::RetryingSubsystem;
+pub use shutdown_once_cell::ShutdownOnceCell;
+pub use shutdown_plan::PlannedSubsystem;
+pub use shutdown_plan::ShutdownCycle;
+pub use shutdown_plan::ShutdownPlan;
+pub use shutdown_plan::ShutdownStage;
+pub use shutdown_reason::ShutdownReason;
+pub use shutdown_report::AggregatedSubsystems;
+pub use shutdown_report::ShutdownReport;
+pub use shutdown_report::ShutdownReportEntry;
+pub use shutdown_snapshot::ShutdownSnapshot;
+pub use shutdown_state::ShutdownState;
+pub use shutdown_statistics::ShutdownStatistics;
+pub use shutdown_token::ShutdownToken;
+pub use signal_handling::ReceivedSignal;
+pub use signal_handling::ShutdownSignal;
+pub use spawn_hook::SpawnHook;
+pub use spawn_hook::SpawnedTask;
+pub use startup_race_policy::StartupRacePolicy;
+#[cfg(feature = "futures")]
+pub use stream_processor::HandledItems;
+#[cfg(feature = "futures")]
+pub use stream_processor::StreamProcessor;
+pub use subsystem::DrainSummary;
+pub use subsystem::FailureRecord;
+pub use subsystem::LightweightChildCounts;
 pub use subsystem::NestedSubsystem;
+pub use subsystem::ShutdownAcknowledgement;
+pub use subsystem::ShutdownDeferralGuard;
+pub use subsystem::ShutdownPoller;
 pub use subsystem::SubsystemBuilder;
 pub use subsystem::SubsystemFinishedFuture;
 pub use subsystem::SubsystemHandle;
+pub use subsystem::SubsystemMetadata;
+pub use subsystem::SubsystemNode;
+pub use subsystem::SubsystemScope;
+pub use subsystem::SubsystemState;
+pub use subsystem::SubsystemTree;
+pub use subsystem::WeakSubsystemHandle;
+pub use subsystem::WorkPermit;
+pub use subsystem_result::AbortCause;
+pub use subsystem_result::SubsystemOutcome;
+pub use subsystem_result::SubsystemResult;
+pub use toplevel::ShutdownController;
 pub use toplevel::Toplevel;
+pub use toplevel::ToplevelBuilder;
+pub use toplevel::ToplevelHandle;
+#[cfg(feature = "udp")]
+pub use udp_subsystem::UdpSender;
+#[cfg(feature = "udp")]
+pub use udp_subsystem::UdpSubsystem;
+#[cfg(feature = "warp")]
+pub use warp_server::WarpServer;
+
+// Used by the macros of this crate; not part of the public API.
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "madsim")]
+    pub use madsim_tokio as tokio;
+    #[cfg(not(feature = "madsim"))]
+    pub use tokio;
+}
@@ -0,0 +1,96 @@
+use std::{future::Future, pin::Pin};
+
+use crate::{BoxedError, ErrTypeTraits, SubsystemNode};
+
+/// The future of a running subsystem, as seen by a [`SubsystemMiddleware`].
+pub type SubsystemFuture<ErrType = BoxedError> =
+    Pin<Box<dyn Future<Output = Result<(), ErrType>> + Send>>;
+
+/// Wraps the execution of every subsystem of a [`Toplevel`](crate::Toplevel).
+///
+/// Allows to apply cross-cutting behavior, like timing, tracing or the setup of a
+/// tenant context, to all subsystems uniformly, instead of decorating each subsystem
+/// manually. Registered through [`Toplevel::with_middleware`](crate::Toplevel::with_middleware)
+/// or [`ToplevelBuilder::middleware`](crate::ToplevelBuilder::middleware).
+///
+/// The middleware sees the future of the subsystem, together with the errors it returns,
+/// and runs inside of the subsystem's task. As subsystems can only be started once,
+/// the future must be awaited at most once; to restart failing subsystems,
+/// use a [`RetryingSubsystem`](crate::RetryingSubsystem) instead.
+///
+/// The unnamed root subsystem that gets passed to the [`Toplevel`](crate::Toplevel)
+/// itself does not get wrapped.
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::{Duration, Instant};
+/// use tokio_graceful_shutdown::{
+///     SubsystemBuilder, SubsystemFuture, SubsystemHandle, SubsystemMiddleware, SubsystemNode,
+///     Toplevel,
+/// };
+///
+/// struct Timing;
+///
+/// impl SubsystemMiddleware for Timing {
+///     fn wrap(&self, node: &SubsystemNode, subsystem: SubsystemFuture) -> SubsystemFuture {
+///         let name = node.name().to_string();
+///         Box::pin(async move {
+///             let start = Instant::now();
+///             let result = subsystem.await;
+///             tracing::info!("Subsystem '{name}' ran for {:?}.", start.elapsed());
+///             result
+///         })
+///     }
+/// }
+///
+/// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+///     subsys.request_shutdown();
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     Toplevel::with_middleware(Timing)
+///         .build(|s| async move {
+///             s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+///         })
+///         .handle_shutdown_requests(Duration::from_millis(500))
+///         .await
+///         .map_err(Into::into)
+/// }
+/// ```
+pub trait SubsystemMiddleware<ErrType: ErrTypeTraits = BoxedError>: Send + Sync + 'static {
+    /// Wraps the future of a subsystem that is about to start.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The position of the subsystem in the subsystem tree.
+    /// * `subsystem` - The future of the subsystem, or of the next middleware.
+    ///
+    /// # Returns
+    ///
+    /// The future that runs in place of the subsystem.
+    fn wrap(
+        &self,
+        node: &SubsystemNode,
+        subsystem: SubsystemFuture<ErrType>,
+    ) -> SubsystemFuture<ErrType>;
+}
+
+pub(crate) type BoxedMiddleware<ErrType> = Box<dyn SubsystemMiddleware<ErrType>>;
+
+/// Wraps a subsystem in all middlewares; the first one ends up outermost.
+pub(crate) fn apply_middlewares<ErrType: ErrTypeTraits>(
+    middlewares: &[BoxedMiddleware<ErrType>],
+    node: &SubsystemNode,
+    subsystem: SubsystemFuture<ErrType>,
+) -> SubsystemFuture<ErrType> {
+    middlewares
+        .iter()
+        .rev()
+        .fold(subsystem, |subsystem, middleware| {
+            middleware.wrap(node, subsystem)
+        })
+}
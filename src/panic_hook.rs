@@ -0,0 +1,144 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, OnceLock,
+    },
+};
+
+use tokio_util::sync::CancellationToken;
+
+use crate::{shutdown_statistics::ShutdownStatisticsCollector, utils::Mutex, ShutdownReason};
+
+// `PanicHookInfo` requires Rust 1.81.
+#[allow(deprecated)]
+type PanicInfo<'a> = std::panic::PanicInfo<'a>;
+type PanicHook = Arc<dyn Fn(&PanicInfo<'_>) + Sync + Send + 'static>;
+
+tokio::task_local! {
+    // Set while polling the task of a subsystem, whose panics get handled by its `ErrorAction`s.
+    static INSIDE_SUBSYSTEM: ();
+}
+
+/// The Toplevels that shut down on panics, and the panic hook that was installed before.
+#[derive(Default)]
+struct Registry {
+    listeners: Vec<(u64, Listener)>,
+    // Only set while our panic hook is installed.
+    previous_hook: Option<PanicHook>,
+}
+
+#[derive(Clone)]
+struct Listener {
+    shutdown_token: CancellationToken,
+    shutdown_statistics: Arc<ShutdownStatisticsCollector>,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(Default::default)
+}
+
+/// Serializes installing and restoring the panic hook.
+///
+/// Never held by the panic hook itself, as `std::panic::set_hook` waits for running hooks.
+fn installation() -> &'static Mutex<()> {
+    static INSTALLATION: OnceLock<Mutex<()>> = OnceLock::new();
+    INSTALLATION.get_or_init(Default::default)
+}
+
+/// Marks the given future as the task of a subsystem, so that its panics
+/// do not trigger [`Toplevel::shutdown_on_panic`](crate::Toplevel::shutdown_on_panic).
+pub(crate) fn mark_subsystem<F: Future>(future: F) -> impl Future<Output = F::Output> {
+    INSIDE_SUBSYSTEM.scope((), future)
+}
+
+fn on_panic(info: &PanicInfo<'_>) {
+    // Don't hold the lock while running foreign code.
+    let (previous_hook, listeners) = {
+        let registry = registry().lock();
+        let listeners = registry
+            .listeners
+            .iter()
+            .map(|(_, listener)| listener.clone())
+            .collect::<Vec<_>>();
+        (registry.previous_hook.clone(), listeners)
+    };
+
+    if let Some(previous_hook) = previous_hook {
+        previous_hook(info);
+    }
+
+    if INSIDE_SUBSYSTEM.try_with(|()| ()).is_ok() {
+        return;
+    }
+
+    for listener in listeners {
+        if !listener.shutdown_token.is_cancelled() {
+            listener
+                .shutdown_statistics
+                .record_shutdown_reason(ShutdownReason::from_panic(
+                    info.payload(),
+                    info.location(),
+                ));
+            listener.shutdown_token.cancel();
+        }
+    }
+}
+
+/// Keeps a [`Toplevel`](crate::Toplevel) subscribed to panics; unsubscribes it when dropped.
+///
+/// The panic hook is shared by all subscribed Toplevels. It gets installed with the first
+/// one and the previous hook gets restored once the last one is gone.
+pub(crate) struct PanicHookGuard {
+    id: u64,
+}
+
+impl PanicHookGuard {
+    pub(crate) fn install(
+        shutdown_token: CancellationToken,
+        shutdown_statistics: Arc<ShutdownStatisticsCollector>,
+    ) -> Self {
+        let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+
+        let _installation = installation().lock();
+        let is_installed = {
+            let mut registry = registry().lock();
+            registry.listeners.push((
+                id,
+                Listener {
+                    shutdown_token,
+                    shutdown_statistics,
+                },
+            ));
+            registry.previous_hook.is_some()
+        };
+
+        if !is_installed {
+            let previous_hook = std::panic::take_hook();
+            registry().lock().previous_hook = Some(Arc::from(previous_hook));
+            std::panic::set_hook(Box::new(on_panic));
+        }
+
+        Self { id }
+    }
+}
+
+impl Drop for PanicHookGuard {
+    fn drop(&mut self) {
+        let _installation = installation().lock();
+        let mut registry = registry().lock();
+        registry.listeners.retain(|(id, _)| *id != self.id);
+
+        // The panic hook cannot be replaced while panicking;
+        // it keeps forwarding to the previous one until the next guard gets dropped.
+        if registry.listeners.is_empty() && !std::thread::panicking() {
+            if let Some(previous_hook) = registry.previous_hook.take() {
+                drop(registry);
+                std::panic::set_hook(Box::new(move |info| previous_hook(info)));
+            }
+        }
+    }
+}
@@ -0,0 +1,41 @@
+/// A preset of shutdown settings for a typical environment.
+///
+/// Applied through [`ToplevelBuilder::profile`](crate::ToplevelBuilder::profile) or
+/// [`Toplevel::with_profile`](crate::Toplevel::with_profile). All profiles
+/// [catch signals](crate::Toplevel::catch_signals); the other settings are listed
+/// per profile and can be overridden individually.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Profile {
+    /// A container in a Kubernetes pod, which has 30 seconds to shut down
+    /// after `SIGTERM` by default.
+    ///
+    /// - A [drain delay](crate::ToplevelBuilder::drain_delay) of 5 seconds,
+    ///   until the endpoint of the pod is removed from all load balancers.
+    /// - A [shutdown timeout](crate::ToplevelBuilder::shutdown_timeout) of 20 seconds.
+    /// - A [shutdown watchdog](crate::ToplevelBuilder::shutdown_watchdog) that exits
+    ///   with `1` after 25 seconds, before the pod gets killed.
+    Kubernetes,
+    /// A systemd service, which has 90 seconds to shut down after `SIGTERM` by default.
+    ///
+    /// - A [shutdown timeout](crate::ToplevelBuilder::shutdown_timeout) of 60 seconds.
+    /// - A [shutdown watchdog](crate::ToplevelBuilder::shutdown_watchdog) that exits
+    ///   with `1` after 80 seconds, before the service gets killed.
+    Systemd,
+    /// A command line program that runs in a terminal.
+    ///
+    /// - [Catches `SIGHUP`](crate::ToplevelBuilder::catch_hangup), so closing
+    ///   the terminal shuts down gracefully.
+    /// - A [shutdown timeout](crate::ToplevelBuilder::shutdown_timeout) of 5 seconds.
+    /// - [Exits with `130`](crate::ToplevelBuilder::force_exit_on_repeated_signal)
+    ///   when pressing `Ctrl+C` a second time.
+    Cli,
+    /// A desktop application, which might have to save the work of the user.
+    ///
+    /// - [Catches `SIGHUP`](crate::ToplevelBuilder::catch_hangup), so closing
+    ///   the session shuts down gracefully.
+    /// - A [shutdown timeout](crate::ToplevelBuilder::shutdown_timeout) of 10 seconds.
+    /// - [Exits with `130`](crate::ToplevelBuilder::force_exit_on_repeated_signal)
+    ///   when receiving another signal during the shutdown.
+    Desktop,
+}
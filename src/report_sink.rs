@@ -0,0 +1,123 @@
+use std::{
+    fmt,
+    fs::OpenOptions,
+    io::Write,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{ShutdownReason, ShutdownReport, ShutdownStatistics};
+
+/// The outcome of a finished shutdown.
+///
+/// Passed to every [`ReportSink`] registered through
+/// [`ToplevelBuilder::report_sink`](crate::ToplevelBuilder::report_sink).
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ShutdownRecord {
+    /// The time at which the shutdown finished.
+    pub finished_at: SystemTime,
+    /// Why the shutdown got triggered.
+    pub reason: ShutdownReason,
+    /// Whether all subsystems finished in time and without errors.
+    pub succeeded: bool,
+    /// Whether the shutdown exceeded its shutdown timeout.
+    pub timed_out: bool,
+    /// How long the shutdown took, from the shutdown request until it finished.
+    pub shutdown_duration: Duration,
+    /// The results of all subsystems.
+    pub report: ShutdownReport,
+    /// The shutdown statistics of the subsystem tree.
+    pub statistics: ShutdownStatistics,
+}
+
+impl fmt::Display for ShutdownRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let finished_at = self
+            .finished_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let outcome = if self.timed_out {
+            "timed out"
+        } else if self.succeeded {
+            "succeeded"
+        } else {
+            "failed"
+        };
+
+        writeln!(
+            f,
+            "shutdown finished at {}.{:03} (unix time): {outcome}",
+            finished_at.as_secs(),
+            finished_at.subsec_millis()
+        )?;
+        writeln!(f, "reason: {:?}", self.reason)?;
+        writeln!(f, "duration: {:.2?}", self.shutdown_duration)?;
+        for entry in &self.report.entries {
+            writeln!(f, "  {entry}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Receives the [`ShutdownRecord`] of every finished shutdown, for example to archive it.
+///
+/// Registered through [`ToplevelBuilder::report_sink`](crate::ToplevelBuilder::report_sink).
+/// Closures taking a `&ShutdownRecord` implement this trait; [`FileReportSink`] appends
+/// the records to a file.
+///
+/// The sinks get called at the very end of the shutdown, before its result gets returned.
+/// They should not block for long.
+pub trait ReportSink: Send + Sync + 'static {
+    /// Persists the record of a finished shutdown.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The outcome of the shutdown.
+    fn persist(&self, record: &ShutdownRecord);
+}
+
+impl<F> ReportSink for F
+where
+    F: Fn(&ShutdownRecord) + Send + Sync + 'static,
+{
+    fn persist(&self, record: &ShutdownRecord) {
+        self(record)
+    }
+}
+
+/// A [`ReportSink`] that appends every [`ShutdownRecord`] to a file, as plain text.
+///
+/// The file gets created if it does not exist. Errors while writing get logged.
+pub struct FileReportSink {
+    path: PathBuf,
+}
+
+impl FileReportSink {
+    /// Creates a sink that appends to the given file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file the records get appended to.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl ReportSink for FileReportSink {
+    fn persist(&self, record: &ShutdownRecord) {
+        let written = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .and_then(|mut file| writeln!(file, "{record}"));
+        if let Err(e) = written {
+            tracing::error!(
+                "Failed to write the shutdown report to '{}': {e}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+pub(crate) type BoxedReportSink = Box<dyn ReportSink>;
@@ -0,0 +1,168 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::{ErrTypeTraits, IntoSubsystem, SubsystemHandle};
+
+/// A resource that has to be closed asynchronously, like a database pool.
+///
+/// Used by [`ResourceSubsystem`] and [`ToplevelBuilder::shared_resource`](crate::ToplevelBuilder::shared_resource).
+///
+/// # Examples
+///
+/// ```
+/// use tokio_graceful_shutdown::AsyncClose;
+///
+/// struct Connection;
+///
+/// #[async_trait::async_trait]
+/// impl AsyncClose for Connection {
+///     async fn close(&self) {
+///         tracing::info!("Sending goodbye message ...");
+///     }
+/// }
+/// ```
+#[async_trait]
+pub trait AsyncClose: Send + Sync + 'static {
+    /// Closes the resource.
+    async fn close(&self);
+}
+
+#[cfg(feature = "sqlx")]
+#[async_trait]
+impl<DB: sqlx::Database> AsyncClose for sqlx::Pool<DB> {
+    async fn close(&self) {
+        sqlx::Pool::close(self).await
+    }
+}
+
+#[cfg(feature = "deadpool")]
+#[async_trait]
+impl<M, W> AsyncClose for deadpool::managed::Pool<M, W>
+where
+    M: deadpool::managed::Manager + 'static,
+    W: From<deadpool::managed::Object<M>> + Send + Sync + 'static,
+{
+    async fn close(&self) {
+        deadpool::managed::Pool::close(self)
+    }
+}
+
+/// A subsystem that keeps a resource alive for its users and closes it afterwards.
+///
+/// The users of the resource get started as children of this subsystem.
+/// When a shutdown is requested, this subsystem waits for all of its children to finish
+/// and then closes the resource, limited by the [`close_timeout`](Self::close_timeout).
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{
+///     AsyncClose, IntoSubsystem, ResourceSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel,
+/// };
+///
+/// #[derive(Clone)]
+/// struct DatabasePool;
+///
+/// impl DatabasePool {
+///     async fn query(&self, query: &str) {
+///         tracing::info!("Executing '{query}' ...");
+///     }
+/// }
+///
+/// #[async_trait::async_trait]
+/// impl AsyncClose for DatabasePool {
+///     async fn close(&self) {
+///         tracing::info!("Closing database connections ...");
+///     }
+/// }
+///
+/// async fn api(subsys: SubsystemHandle, pool: DatabasePool) -> Result<()> {
+///     subsys.on_shutdown_requested().await;
+///     pool.query("UPDATE status SET online = false").await;
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let database = ResourceSubsystem::new(
+///         DatabasePool,
+///         |s: &SubsystemHandle, pool: &DatabasePool| {
+///             let pool = pool.clone();
+///             s.start(SubsystemBuilder::new("api", |s| api(s, pool)));
+///         },
+///     )
+///     .close_timeout(Duration::from_secs(5));
+///
+///     Toplevel::new(|s: SubsystemHandle| async move {
+///         s.start(SubsystemBuilder::new("database", database.into_subsystem()));
+///         s.request_shutdown();
+///     })
+///     .handle_shutdown_requests(Duration::from_secs(10))
+///     .await?;
+///
+///     Ok(())
+/// }
+/// ```
+pub struct ResourceSubsystem<R, U> {
+    resource: R,
+    users: U,
+    close_timeout: Duration,
+}
+
+impl<R, U> ResourceSubsystem<R, U> {
+    /// Creates a new resource subsystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `resource` - The resource to close after all of its users finished.
+    /// * `users` - Starts the subsystems that use the resource, as children of this subsystem.
+    pub fn new(resource: R, users: U) -> Self {
+        Self {
+            resource,
+            users,
+            close_timeout: Duration::from_secs(5),
+        }
+    }
+
+    /// How long closing the resource may take. Defaults to five seconds.
+    pub fn close_timeout(mut self, timeout: Duration) -> Self {
+        self.close_timeout = timeout;
+        self
+    }
+}
+
+#[async_trait]
+impl<R, U, ErrWrapper> IntoSubsystem<ErrWrapper, ErrWrapper> for ResourceSubsystem<R, U>
+where
+    R: AsyncClose,
+    U: FnOnce(&SubsystemHandle<ErrWrapper>, &R) + Send + 'static,
+    ErrWrapper: ErrTypeTraits,
+{
+    async fn run(self, subsys: SubsystemHandle<ErrWrapper>) -> Result<(), ErrWrapper> {
+        let Self {
+            resource,
+            users,
+            close_timeout,
+        } = self;
+
+        users(&subsys, &resource);
+
+        subsys.on_shutdown_requested().await;
+        subsys.wait_for_children().await;
+
+        tracing::debug!("Closing resource ...");
+        if subsys
+            .get_clock()
+            .timeout(close_timeout, resource.close())
+            .await
+            .is_none()
+        {
+            tracing::warn!("Resource did not close within {close_timeout:?}.");
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use std::{fmt::Display, future::Future, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::{ErrTypeTraits, IntoSubsystem, SubsystemHandle};
+
+type RetryCondition<Err> = Box<dyn Fn(&Err) -> bool + Send + Sync>;
+
+/// A subsystem that restarts its work with an exponential backoff when it fails.
+///
+/// Intended for subsystems that depend on flaky external services, like a
+/// connection to a message broker. Every attempt gets created by the factory, which
+/// receives the [`SubsystemHandle`] of this subsystem.
+///
+/// Once the retry budget is exhausted, or an error occurs that should not be retried
+/// (see [`retry_if`](Self::retry_if)), the error gets returned and handled like the error
+/// of any other subsystem. Errors that occur after a shutdown got requested are not retried.
+/// A shutdown request interrupts the backoff right away.
+///
+/// Every retry gets counted as a restart, see [`NestedSubsystem::restart_count`](crate::NestedSubsystem::restart_count).
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{
+///     IntoSubsystem, RetryingSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel,
+/// };
+///
+/// async fn consume(subsys: SubsystemHandle) -> Result<()> {
+///     subsys.request_shutdown();
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let consumer = RetryingSubsystem::new(|subsys: &SubsystemHandle| {
+///         let token = subsys.create_cancellation_token();
+///         async move {
+///             tracing::info!("Connecting to broker ...");
+///             token.cancelled().await;
+///             Result::<()>::Ok(())
+///         }
+///     })
+///     .initial_backoff(Duration::from_millis(200))
+///     .max_retries(10);
+///
+///     Toplevel::new(|s: SubsystemHandle| async move {
+///         s.start(SubsystemBuilder::new("consumer", consumer.into_subsystem()));
+///         s.start(SubsystemBuilder::new("main", consume));
+///     })
+///     .handle_shutdown_requests(Duration::from_millis(500))
+///     .await
+///     .map_err(Into::into)
+/// }
+/// ```
+pub struct RetryingSubsystem<F, Err> {
+    factory: F,
+    retry_if: RetryCondition<Err>,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    max_retries: u32,
+}
+
+impl<F, Err> RetryingSubsystem<F, Err> {
+    /// Creates a new retrying subsystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `factory` - Creates the future of a single attempt.
+    pub fn new(factory: F) -> Self {
+        Self {
+            factory,
+            retry_if: Box::new(|_| true),
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+            max_retries: 5,
+        }
+    }
+
+    /// Only retries errors for which the given condition holds, like connection errors.
+    ///
+    /// By default, all errors get retried.
+    pub fn retry_if(mut self, condition: impl Fn(&Err) -> bool + Send + Sync + 'static) -> Self {
+        self.retry_if = Box::new(condition);
+        self
+    }
+
+    /// The delay before the first retry. Defaults to 100 milliseconds.
+    ///
+    /// Doubles with every further retry, up to the [`max_backoff`](Self::max_backoff).
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// The maximum delay between two attempts. Defaults to 30 seconds.
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    /// How often a failed attempt gets retried before giving up. Defaults to 5.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+}
+
+#[async_trait]
+impl<F, Fut, Err, ErrWrapper> IntoSubsystem<Err, ErrWrapper> for RetryingSubsystem<F, Err>
+where
+    F: FnMut(&SubsystemHandle<ErrWrapper>) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), Err>> + Send,
+    Err: Into<ErrWrapper> + Display + Send + 'static,
+    ErrWrapper: ErrTypeTraits,
+{
+    async fn run(self, subsys: SubsystemHandle<ErrWrapper>) -> Result<(), Err> {
+        let Self {
+            mut factory,
+            retry_if,
+            initial_backoff,
+            max_backoff,
+            max_retries,
+        } = self;
+
+        let mut backoff = initial_backoff;
+        let mut retries = 0;
+
+        loop {
+            let Err(e) = factory(&subsys).await else {
+                return Ok(());
+            };
+
+            if subsys.is_shutdown_requested() || !retry_if(&e) {
+                return Err(e);
+            }
+            if retries >= max_retries {
+                tracing::warn!("Giving up after {retries} retries.");
+                return Err(e);
+            }
+            retries += 1;
+            subsys.record_restart(&e);
+
+            tracing::warn!(
+                "Attempt failed, retrying in {backoff:?} ({retries}/{max_retries}): {e}"
+            );
+            tokio::select! {
+                _ = subsys.get_clock().sleep(backoff) => (),
+                _ = subsys.on_shutdown_requested() => {
+                    tracing::debug!("Shutdown requested, not retrying.");
+                    return Ok(());
+                }
+            }
+
+            backoff = backoff.saturating_mul(2).min(max_backoff);
+        }
+    }
+}
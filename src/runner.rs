@@ -6,18 +6,52 @@
 //! Further, everything in here reacts properly to being dropped, including
 //! the runner itself, who cancels the subsystem on drop.
 
-use std::{future::Future, sync::Arc};
+use std::{future::Future, sync::Arc, time::Duration};
+
+use tracing::Instrument;
 
 use crate::{
-    errors::{SubsystemError, SubsystemFailure},
-    ErrTypeTraits, SubsystemHandle,
+    blocking_detector::detect_blocking,
+    clock::SharedClock,
+    errors::{InternalError, SubsystemError, SubsystemFailure},
+    middleware::{apply_middlewares, SubsystemFuture},
+    panic_hook::mark_subsystem,
+    shutdown_report::describe_failure,
+    spawn_hook::{BoxedSpawnHook, HookedTask},
+    subsystem::{RedirectedSubsystemHandle, ShutdownAcknowledgements, SubsystemStateTracker},
+    testing::LifecycleEventKind,
+    utils::{
+        log_lifecycle, remote_drop_collection::RemotelyDroppableItems, DEFAULT_LIFECYCLE_LOG_LEVEL,
+    },
+    AbortCause, ErrTypeTraits, PlannedSubsystem, SubsystemHandle, SubsystemNode, SubsystemOutcome,
+    SubsystemResult,
 };
 
+#[cfg(feature = "status")]
+use crate::subsystem::FailureHistory;
+#[cfg(feature = "task-dump")]
+use crate::task_dump::SubsystemTask;
+
 mod alive_guard;
 pub(crate) use self::alive_guard::AliveGuard;
 
 pub(crate) struct SubsystemRunner {
+    runner_ref: SubsystemRunnerRef,
+}
+
+/// Refers to a [`SubsystemRunner`] without cancelling it on drop.
+#[derive(Clone)]
+pub(crate) struct SubsystemRunnerRef {
+    name: Arc<str>,
     aborthandle: tokio::task::AbortHandle,
+    // The runners of the subsystem's children; allows walking the tree.
+    children: RemotelyDroppableItems<SubsystemRunner>,
+    plan: Arc<PlannedSubsystem>,
+    acknowledgements: Arc<ShutdownAcknowledgements>,
+    #[cfg(feature = "status")]
+    failure_history: Arc<FailureHistory>,
+    #[cfg(feature = "task-dump")]
+    task: SubsystemTask,
 }
 
 impl SubsystemRunner {
@@ -26,21 +60,270 @@ impl SubsystemRunner {
         subsystem: Subsys,
         subsystem_handle: SubsystemHandle<ErrType>,
         guard: AliveGuard,
+        runtime: Option<tokio::runtime::Handle>,
+        plan: PlannedSubsystem,
+        state: SubsystemStateTracker,
     ) -> Self
     where
         Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
         Fut: 'static + Future<Output = Result<(), Err>> + Send,
         Err: Into<ErrType>,
     {
-        let future = async { run_subsystem(name, subsystem, subsystem_handle, guard).await };
-        let aborthandle = tokio::spawn(future).abort_handle();
-        SubsystemRunner { aborthandle }
+        let children = subsystem_handle.get_children().clone();
+        let acknowledgements = Arc::clone(subsystem_handle.get_shutdown_acknowledgements());
+        #[cfg(feature = "status")]
+        let failure_history = Arc::clone(subsystem_handle.get_failure_history());
+        let task = subsystem_handle.get_task().clone();
+        let runner_name = Arc::clone(&name);
+        let shutdown_timeout = plan.shutdown_timeout;
+
+        // Only create a span if there is metadata or a custom log level,
+        // to keep the logs of other subsystems unchanged.
+        let span = if subsystem_handle.metadata().is_empty()
+            && subsystem_handle.get_lifecycle_log_level().is_none()
+        {
+            tracing::Span::none()
+        } else {
+            let span = tracing::info_span!(
+                "subsystem",
+                name = %name,
+                metadata = tracing::field::Empty
+            );
+            if !subsystem_handle.metadata().is_empty() {
+                span.record(
+                    "metadata",
+                    tracing::field::display(subsystem_handle.metadata()),
+                );
+            }
+            span
+        };
+
+        // The spawn hooks have to run in the context that starts the subsystem.
+        let config = subsystem_handle.get_config();
+        let task_spawner = TaskSpawner::new(
+            runtime.clone(),
+            &config.spawn_hooks,
+            subsystem_handle.node(),
+        );
+        let future = async move {
+            run_subsystem(
+                name,
+                subsystem,
+                subsystem_handle,
+                guard,
+                task_spawner,
+                shutdown_timeout,
+                state,
+            )
+            .await
+        }
+        .instrument(span);
+        let join_handle = spawn(runtime.as_ref(), future);
+        task.set_runner(&join_handle);
+        let aborthandle = join_handle.abort_handle();
+        SubsystemRunner {
+            runner_ref: SubsystemRunnerRef {
+                name: runner_name,
+                aborthandle,
+                children,
+                plan: Arc::new(plan),
+                acknowledgements,
+                #[cfg(feature = "status")]
+                failure_history,
+                #[cfg(feature = "task-dump")]
+                task,
+            },
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> SubsystemRunnerRef {
+        self.runner_ref.clone()
+    }
+
+    /// Aborts all unfinished subsystems of the given runners and their descendants,
+    /// children before their parents.
+    ///
+    /// Returns the names of the aborted subsystems, without the root subsystem.
+    pub(crate) fn abort_all(runners: Vec<SubsystemRunnerRef>) -> Vec<Arc<str>> {
+        Self::collect_unfinished(runners)
+            .into_iter()
+            .filter_map(|runner| {
+                tracing::warn!(
+                    "Aborting subsystem '{}' ... ({})",
+                    runner.name,
+                    runner.acknowledgements.progress()
+                );
+                runner.aborthandle.abort();
+                (!runner.is_root()).then_some(runner.name)
+            })
+            .collect()
+    }
+
+    /// Returns the names of all unfinished subsystems of the given runners and their descendants,
+    /// children before their parents, without the root subsystem.
+    pub(crate) fn unfinished(runners: Vec<SubsystemRunnerRef>) -> Vec<Arc<str>> {
+        Self::collect_unfinished(runners)
+            .into_iter()
+            .filter(|runner| !runner.is_root())
+            .map(|runner| runner.name)
+            .collect()
+    }
+
+    /// Returns the names and tasks of all unfinished subsystems of the given runners
+    /// and their descendants, children before their parents, without the root subsystem.
+    #[cfg(feature = "task-dump")]
+    pub(crate) fn unfinished_tasks(
+        runners: Vec<SubsystemRunnerRef>,
+    ) -> Vec<(Arc<str>, SubsystemTask)> {
+        Self::collect_unfinished(runners)
+            .into_iter()
+            .filter(|runner| !runner.is_root())
+            .map(|runner| (runner.name, runner.task))
+            .collect()
+    }
+
+    /// Returns the names and restart counts of all unfinished subsystems of the given runners
+    /// and their descendants that restarted at least once, children before their parents,
+    /// without the root subsystem.
+    #[cfg(feature = "status")]
+    pub(crate) fn restarted(runners: Vec<SubsystemRunnerRef>) -> Vec<(Arc<str>, u32)> {
+        Self::collect_unfinished(runners)
+            .into_iter()
+            .filter(|runner| !runner.is_root())
+            .map(|runner| {
+                let restarts = runner.failure_history.restart_count();
+                (runner.name, restarts)
+            })
+            .filter(|(_, restarts)| *restarts > 0)
+            .collect()
+    }
+
+    fn collect_unfinished(runners: Vec<SubsystemRunnerRef>) -> Vec<SubsystemRunnerRef> {
+        // Collect iteratively instead of recursively, as deeply nested
+        // subsystem trees could overflow the stack.
+        // Every runner gets collected after its parent.
+        let mut collected = Vec::new();
+        let mut pending = runners;
+        while let Some(runner) = pending.pop() {
+            pending.extend(runner.get_children());
+            collected.push(runner);
+        }
+
+        collected
+            .into_iter()
+            .rev()
+            .filter(|runner| !runner.is_finished())
+            .collect()
+    }
+}
+
+impl SubsystemRunnerRef {
+    pub(crate) fn is_root(&self) -> bool {
+        is_root(&self.name)
+    }
+
+    pub(crate) fn get_plan(&self) -> &Arc<PlannedSubsystem> {
+        &self.plan
+    }
+
+    pub(crate) fn get_children(&self) -> Vec<SubsystemRunnerRef> {
+        self.children.map_items(SubsystemRunner::get_ref)
+    }
+
+    pub(crate) fn is_finished(&self) -> bool {
+        self.aborthandle.is_finished()
     }
 }
 
 impl Drop for SubsystemRunner {
     fn drop(&mut self) {
-        self.aborthandle.abort()
+        self.runner_ref.aborthandle.abort()
+    }
+}
+
+fn spawn<F>(
+    runtime: Option<&tokio::runtime::Handle>,
+    future: F,
+) -> tokio::task::JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match runtime {
+        Some(runtime) => runtime.spawn(future),
+        None => tokio::spawn(future),
+    }
+}
+
+/// The root subsystem does not have a name and is not reported,
+/// neither in the statistics nor in the lists of running or aborted subsystems.
+fn is_root(name: &str) -> bool {
+    name.is_empty()
+}
+
+/// Spawns the task of a subsystem on its runtime, wrapped by the spawn hooks.
+///
+/// Also used for the helper tasks of a subsystem, so that they run in the same context.
+pub(crate) struct TaskSpawner {
+    runtime: Option<tokio::runtime::Handle>,
+    // Only set if there are spawn hooks.
+    hooked: Option<HookedTask>,
+}
+
+impl TaskSpawner {
+    /// Has to be called in the context that starts the subsystem.
+    pub(crate) fn new(
+        runtime: Option<tokio::runtime::Handle>,
+        spawn_hooks: &[BoxedSpawnHook],
+        node: &SubsystemNode,
+    ) -> Self {
+        Self {
+            runtime,
+            hooked: HookedTask::from_hooks(spawn_hooks, node),
+        }
+    }
+
+    /// Returns `None` through the join handle if a spawn hook did not run the future.
+    pub(crate) fn spawn<F>(self, future: F) -> tokio::task::JoinHandle<Option<F::Output>>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        match self.hooked {
+            Some(hooked) => spawn(self.runtime.as_ref(), hooked.run(future)),
+            None => spawn(self.runtime.as_ref(), async move { Some(future.await) }),
+        }
+    }
+}
+
+/// Joins the subsystem, aborting it if it exceeds its shutdown timeout.
+async fn join_with_shutdown_timeout<T>(
+    name: &str,
+    acknowledgements: &ShutdownAcknowledgements,
+    cancellation_token: &tokio_util::sync::CancellationToken,
+    shutdown_timeout: Option<Duration>,
+    clock: &SharedClock,
+    mut join_handle: tokio::task::JoinHandle<T>,
+) -> Result<T, tokio::task::JoinError> {
+    let Some(shutdown_timeout) = shutdown_timeout else {
+        return join_handle.await;
+    };
+
+    let timeout_expired = async {
+        cancellation_token.cancelled().await;
+        clock.sleep(shutdown_timeout).await;
+    };
+
+    tokio::select! {
+        result = &mut join_handle => result,
+        () = timeout_expired => {
+            tracing::warn!(
+                "Subsystem '{name}' exceeded its shutdown timeout of {shutdown_timeout:?}, aborting it ... ({})",
+                acknowledgements.progress()
+            );
+            join_handle.abort();
+            join_handle.await
+        }
     }
 }
 
@@ -49,15 +332,73 @@ async fn run_subsystem<Fut, Subsys, ErrType: ErrTypeTraits, Err>(
     subsystem: Subsys,
     mut subsystem_handle: SubsystemHandle<ErrType>,
     guard: AliveGuard,
+    task_spawner: TaskSpawner,
+    shutdown_timeout: Option<Duration>,
+    state: SubsystemStateTracker,
 ) where
     Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
     Fut: 'static + Future<Output = Result<(), Err>> + Send,
     Err: Into<ErrType>,
 {
     let mut redirected_subsystem_handle = subsystem_handle.delayed_clone();
+    let weak_subsystem_handle = subsystem_handle.downgrade();
+    let cancellation_token = subsystem_handle.get_cancellation_token().clone();
+    let instrumentation = Arc::clone(subsystem_handle.get_instrumentation());
+    let shutdown_statistics = Arc::clone(subsystem_handle.get_shutdown_statistics());
+    let acknowledgements = Arc::clone(subsystem_handle.get_shutdown_acknowledgements());
+    let failure_history = Arc::clone(subsystem_handle.get_failure_history());
+    let clock = Arc::clone(subsystem_handle.get_clock());
+    let task = subsystem_handle.get_task().clone();
+    let lifecycle_log_level = subsystem_handle
+        .get_lifecycle_log_level()
+        .unwrap_or(DEFAULT_LIFECYCLE_LOG_LEVEL);
+    log_lifecycle!(lifecycle_log_level, "Subsystem '{name}' started.");
 
-    let future = async { subsystem(subsystem_handle).await.map_err(|e| e.into()) };
-    let join_handle = tokio::spawn(future);
+    let subsystem: SubsystemFuture<ErrType> = {
+        let node = subsystem_handle.node().clone();
+        let config = Arc::clone(subsystem_handle.get_config());
+        let subsystem =
+            Box::pin(async move { subsystem(subsystem_handle).await.map_err(|e| e.into()) });
+        // The root subsystem only wraps the closure that was passed to the Toplevel.
+        if is_root(&name) {
+            subsystem
+        } else {
+            let subsystem = apply_middlewares(&config.middlewares, &node, subsystem);
+            match config.blocking_threshold {
+                Some(threshold) if cfg!(debug_assertions) => detect_blocking(
+                    Arc::clone(&name),
+                    threshold,
+                    cancellation_token.clone(),
+                    subsystem,
+                ),
+                _ => subsystem,
+            }
+        }
+    };
+
+    #[cfg(feature = "fault-injection")]
+    let future = {
+        let fault = instrumentation
+            .fault_injection
+            .as_ref()
+            .and_then(|fault_injection| fault_injection.fault_for(&name));
+        let cancellation_token = cancellation_token.clone();
+        let name = Arc::clone(&name);
+        let clock = Arc::clone(&clock);
+        async move {
+            let result = subsystem.await;
+            if let Some(fault) = fault {
+                if cancellation_token.is_cancelled() {
+                    fault.inject(&name, &clock).await;
+                }
+            }
+            result
+        }
+    };
+    #[cfg(not(feature = "fault-injection"))]
+    let future = subsystem;
+    let join_handle = task_spawner.spawn(mark_subsystem(future).in_current_span());
+    task.set_subsystem(&join_handle);
 
     // Abort on drop
     guard.on_cancel({
@@ -71,14 +412,51 @@ async fn run_subsystem<Fut, Subsys, ErrType: ErrTypeTraits, Err>(
         }
     });
 
-    let failure = match join_handle.await {
-        Ok(Ok(())) => None,
-        Ok(Err(e)) => Some(SubsystemError::Failed(name, SubsystemFailure(e))),
+    let join_result = state
+        .track_shutdown(
+            &cancellation_token,
+            join_with_shutdown_timeout(
+                &name,
+                &acknowledgements,
+                &cancellation_token,
+                shutdown_timeout,
+                &clock,
+                join_handle,
+            ),
+        )
+        .await;
+    if let Some(lifecycle_recorder) = &instrumentation.lifecycle_recorder {
+        lifecycle_recorder.record(&name, LifecycleEventKind::Finished);
+    }
+    log_lifecycle!(lifecycle_log_level, "Subsystem '{name}' finished.");
+
+    let (mut outcome, failure) = match join_result {
+        Ok(Some(Ok(()))) => (SubsystemOutcome::Succeeded, None),
+        Ok(Some(Err(e))) => (
+            SubsystemOutcome::Failed,
+            Some(SubsystemError::Failed(
+                Arc::clone(&name),
+                SubsystemFailure(e),
+            )),
+        ),
+        Ok(None) => (
+            SubsystemOutcome::Failed,
+            Some(SubsystemError::Internal(
+                Arc::clone(&name),
+                InternalError::SubsystemNotRun,
+            )),
+        ),
+        // Only cancelled by `join_with_shutdown_timeout`, as we still hold `guard`.
+        Err(e) if e.is_cancelled() => (
+            SubsystemOutcome::Aborted,
+            Some(SubsystemError::Aborted(Arc::clone(&name))),
+        ),
         Err(e) => {
-            // We can assume that this is a panic, because a cancellation
-            // can never happen as long as we still hold `guard`.
             assert!(e.is_panic());
-            Some(SubsystemError::Panicked(name))
+            (
+                SubsystemOutcome::Panicked,
+                Some(SubsystemError::Panicked(Arc::clone(&name))),
+            )
         }
     };
 
@@ -88,23 +466,91 @@ async fn run_subsystem<Fut, Subsys, ErrType: ErrTypeTraits, Err>(
     // it was decided to pass ownership instead.
     //
     // It is still important that the handle does not leak out of the subsystem.
-    let subsystem_handle = match redirected_subsystem_handle.try_recv() {
-        Ok(s) => s,
+    let (subsystem_handle, leaked) = match redirected_subsystem_handle.try_recv() {
+        Ok(s) => (s, false),
         Err(_) => {
             tracing::error!("The SubsystemHandle object must not be leaked out of the subsystem!");
-            panic!("The SubsystemHandle object must not be leaked out of the subsystem!");
+
+            // Don't wait for the leaked handle, it might never get dropped.
+            // Report the leak right away through a handle that shares its state.
+            match RedirectedSubsystemHandle::recover(&weak_subsystem_handle) {
+                Some(s) => (s, true),
+                None => {
+                    tracing::error!("The SubsystemHandle of '{name}' got lost.");
+                    return;
+                }
+            }
         }
     };
 
+    // Describe the failure for the shutdown report, before it gets raised.
+    let failure_message = failure
+        .as_ref()
+        .and_then(describe_failure)
+        .or_else(|| leaked.then(|| InternalError::SubsystemHandleLeaked.to_string()));
+
+    // Remember the failure, so that it can still be inspected after the subsystem finished.
+    if let Some(summary) = failure_message
+        .clone()
+        .or_else(|| failure.as_ref().map(ToString::to_string))
+    {
+        failure_history.record_failure(summary);
+    }
+
     // Raise potential errors
-    let joiner_token = subsystem_handle.joiner_token;
+    if leaked || failure.is_some() {
+        state.set_failed();
+    }
+    if leaked {
+        outcome = SubsystemOutcome::Failed;
+        subsystem_handle.raise_failure(SubsystemError::Internal(
+            Arc::clone(&name),
+            InternalError::SubsystemHandleLeaked,
+        ));
+    }
     if let Some(failure) = failure {
-        joiner_token.raise_failure(failure);
+        subsystem_handle.raise_failure(failure);
+    }
+
+    let exit_status =
+        (outcome == SubsystemOutcome::Succeeded).then(|| subsystem_handle.take_exit_status());
+    if let Some(exit_status) = &exit_status {
+        if !exit_status.is_clean() && subsystem_handle.strict_exit_statuses() {
+            state.set_failed();
+            subsystem_handle.raise_failure(SubsystemError::UncleanExit(
+                Arc::clone(&name),
+                exit_status.clone(),
+            ));
+        }
     }
 
     // Wait for children to finish before we destroy the `SubsystemHandle` object.
     // Otherwise the children would be cancelled immediately.
     //
     // This is the main mechanism that forwards a cancellation to all the children.
-    joiner_token.downgrade().join().await;
+    //
+    // A leaked handle keeps the subsystem alive for as long as it exists,
+    // so there is nothing to wait for.
+    if !leaked {
+        state
+            .track_shutdown(&cancellation_token, subsystem_handle.join())
+            .await;
+    }
+
+    if !is_root(&name) {
+        shutdown_statistics.record_result(
+            SubsystemResult {
+                name,
+                outcome,
+                exit_status,
+                shutdown_duration: state.shutdown_duration(),
+                // Only aborted by `join_with_shutdown_timeout`.
+                abort_cause: shutdown_timeout
+                    .filter(|_| outcome == SubsystemOutcome::Aborted)
+                    .map(AbortCause::SubsystemTimeout),
+            },
+            failure_message,
+        );
+    }
+    state.set_finished();
 }
@@ -1,4 +1,9 @@
-use std::sync::{Arc, Mutex};
+#[cfg(graceful_shutdown_loom)]
+use loom::sync::Arc;
+#[cfg(not(graceful_shutdown_loom))]
+use std::sync::Arc;
+
+use crate::utils::Mutex;
 
 struct Inner {
     finished_callback: Option<Box<dyn FnOnce() + Send>>,
@@ -32,13 +37,13 @@ impl AliveGuard {
     }
 
     pub(crate) fn on_cancel(&self, cancelled_callback: impl FnOnce() + 'static + Send) {
-        let mut inner = self.inner.lock().unwrap();
+        let mut inner = self.inner.lock();
         assert!(inner.cancelled_callback.is_none());
         inner.cancelled_callback = Some(Box::new(cancelled_callback));
     }
 
     pub(crate) fn on_finished(&self, finished_callback: impl FnOnce() + 'static + Send) {
-        let mut inner = self.inner.lock().unwrap();
+        let mut inner = self.inner.lock();
         assert!(inner.finished_callback.is_none());
         inner.finished_callback = Some(Box::new(finished_callback));
     }
@@ -58,5 +63,8 @@ impl Drop for Inner {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, not(graceful_shutdown_loom)))]
 mod tests;
+
+#[cfg(all(test, graceful_shutdown_loom))]
+mod loom_tests;
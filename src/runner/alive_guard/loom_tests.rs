@@ -0,0 +1,70 @@
+//! Exhaustively checks the interleavings of registering callbacks
+//! and dropping the guard from different threads.
+//!
+//! Run with `RUSTFLAGS="--cfg graceful_shutdown_loom" cargo test --release --lib loom_tests`.
+
+use loom::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use super::*;
+
+fn counting_callback(counter: &Arc<AtomicU32>) -> impl FnOnce() + Send + 'static {
+    let counter = Arc::clone(counter);
+    move || {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn concurrent_drops_call_callbacks_exactly_once() {
+    loom::model(|| {
+        let finished = Arc::new(AtomicU32::new(0));
+        let cancelled = Arc::new(AtomicU32::new(0));
+
+        let alive_guard = AliveGuard::new();
+        alive_guard.on_finished(counting_callback(&finished));
+        alive_guard.on_cancel(counting_callback(&cancelled));
+
+        let alive_guard2 = alive_guard.clone();
+        let thread = thread::spawn(move || drop(alive_guard2));
+        drop(alive_guard);
+        thread.join().unwrap();
+
+        assert_eq!(finished.load(Ordering::Relaxed), 1);
+        assert_eq!(cancelled.load(Ordering::Relaxed), 1);
+    });
+}
+
+// Mirrors spawning a subsystem: the runner might finish and drop its
+// guard while the spawning thread is still registering callbacks.
+#[test]
+fn registration_races_with_finishing_subsystem() {
+    loom::model(|| {
+        let finished = Arc::new(AtomicU32::new(0));
+        let cancelled = Arc::new(AtomicU32::new(0));
+
+        let alive_guard = AliveGuard::new();
+
+        let runner_guard = alive_guard.clone();
+        let runner = thread::spawn({
+            let cancelled = Arc::clone(&cancelled);
+            move || {
+                runner_guard.on_cancel(counting_callback(&cancelled));
+                drop(runner_guard);
+            }
+        });
+
+        alive_guard.on_finished(counting_callback(&finished));
+        drop(alive_guard);
+
+        runner.join().unwrap();
+
+        assert_eq!(finished.load(Ordering::Relaxed), 1);
+        assert_eq!(cancelled.load(Ordering::Relaxed), 1);
+    });
+}
@@ -0,0 +1,54 @@
+/// Waits on multiple futures, like [`tokio::select!`], but always includes
+/// a branch for the shutdown of the given subsystem.
+///
+/// The first argument is the [`SubsystemHandle`](crate::SubsystemHandle),
+/// followed by the mandatory `shutdown => <handler>` branch. All further branches
+/// use the syntax of [`tokio::select!`], including preconditions and an `else` branch.
+///
+/// As the shutdown branch can not be omitted, it is impossible to forget reacting
+/// to shutdown requests. The shutdown branch is always polled first; further branches
+/// are polled in the order in which they are written.
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::{sleep, Duration};
+/// use tokio_graceful_shutdown::{select_with_shutdown, SubsystemHandle};
+///
+/// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+///     let mut ticks = 0;
+///     loop {
+///         select_with_shutdown! { subsys,
+///             shutdown => {
+///                 tracing::info!("Shutting down after {ticks} ticks.");
+///                 break;
+///             },
+///             _ = sleep(Duration::from_millis(100)) => {
+///                 ticks += 1;
+///             },
+///         }
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[macro_export]
+macro_rules! select_with_shutdown {
+    (@select $subsys:expr, $shutdown_handler:block, $($branches:tt)*) => {
+        $crate::__private::tokio::select! {
+            biased;
+            _ = $subsys.on_shutdown_requested() => $shutdown_handler,
+            $($branches)*
+        }
+    };
+    ($subsys:expr, shutdown => $shutdown_handler:block, $($branches:tt)*) => {
+        $crate::select_with_shutdown!(@select $subsys, $shutdown_handler, $($branches)*)
+    };
+    ($subsys:expr, shutdown => $shutdown_handler:block $($branches:tt)*) => {
+        $crate::select_with_shutdown!(@select $subsys, $shutdown_handler, $($branches)*)
+    };
+    ($subsys:expr, shutdown => $shutdown_handler:expr, $($branches:tt)*) => {
+        $crate::select_with_shutdown!(@select $subsys, { $shutdown_handler }, $($branches)*)
+    };
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+use crate::{utils::JoinSet, AsyncClose};
+
+/// The name of a shared resource, the resource itself and the names of its users.
+pub(crate) type SharedResourceConfig = (Arc<str>, Box<dyn AsyncClose>, Vec<Arc<str>>);
+
+/// A resource that gets closed once all of its users finished.
+struct SharedResource {
+    name: Arc<str>,
+    used_by: Vec<Arc<str>>,
+    resource: Box<dyn AsyncClose>,
+    active_users: watch::Sender<u32>,
+}
+
+/// The shared resources of a subsystem tree.
+#[derive(Default)]
+pub(crate) struct SharedResources {
+    resources: Vec<Arc<SharedResource>>,
+}
+
+/// Marks a subsystem as an active user of a shared resource until it is finished.
+pub(crate) struct SharedResourceUsage {
+    resource: Arc<SharedResource>,
+}
+
+impl SharedResources {
+    pub(crate) fn new(resources: Vec<SharedResourceConfig>) -> Self {
+        Self {
+            resources: resources
+                .into_iter()
+                .map(|(name, resource, used_by)| {
+                    Arc::new(SharedResource {
+                        name,
+                        used_by,
+                        resource,
+                        active_users: watch::channel(0).0,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Registers the given subsystem with all resources it uses.
+    pub(crate) fn register_user(&self, name: &str) -> Vec<SharedResourceUsage> {
+        self.resources
+            .iter()
+            .filter(|resource| resource.used_by.iter().any(|user| user.as_ref() == name))
+            .map(|resource| {
+                resource
+                    .active_users
+                    .send_modify(|active_users| *active_users += 1);
+                SharedResourceUsage {
+                    resource: Arc::clone(resource),
+                }
+            })
+            .collect()
+    }
+
+    /// Closes every resource as soon as all of its users are finished.
+    pub(crate) async fn close_all(&self) {
+        let close_resource = |resource: &Arc<SharedResource>| {
+            let resource = Arc::clone(resource);
+            async move {
+                let mut active_users = resource.active_users.subscribe();
+                // Cannot fail, as the sender is owned by the resource itself.
+                let _ = active_users
+                    .wait_for(|&active_users| active_users == 0)
+                    .await;
+
+                tracing::debug!("Closing shared resource '{}' ...", resource.name);
+                resource.resource.close().await;
+            }
+        };
+
+        let mut closing = JoinSet::new();
+        for resource in &self.resources {
+            closing.spawn(close_resource(resource));
+        }
+        while closing.join_next().await.is_some() {}
+    }
+}
+
+impl Drop for SharedResourceUsage {
+    fn drop(&mut self) {
+        self.resource
+            .active_users
+            .send_modify(|active_users| *active_users -= 1);
+    }
+}
@@ -0,0 +1,40 @@
+use tokio::{sync::watch, time::Instant};
+
+/// Announces an upcoming shutdown to the entire subsystem tree,
+/// before the shutdown actually gets requested.
+pub(crate) struct ShutdownAnnouncement {
+    deadline: watch::Sender<Option<Instant>>,
+}
+
+impl ShutdownAnnouncement {
+    pub(crate) fn new() -> Self {
+        Self {
+            deadline: watch::channel(None).0,
+        }
+    }
+
+    /// Announces that the shutdown will get requested at the given time.
+    ///
+    /// Only the first announcement counts.
+    pub(crate) fn announce(&self, deadline: Instant) {
+        self.deadline.send_if_modified(|current| {
+            let modified = current.is_none();
+            if modified {
+                *current = Some(deadline);
+            }
+            modified
+        });
+    }
+
+    /// The time at which the shutdown will get requested, if it was announced.
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        *self.deadline.borrow()
+    }
+
+    /// Waits until a shutdown gets announced.
+    pub(crate) async fn wait(&self) {
+        let mut deadline = self.deadline.subscribe();
+        // Can not fail, as `self` holds the sender.
+        let _ = deadline.wait_for(Option::is_some).await;
+    }
+}
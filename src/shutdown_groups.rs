@@ -0,0 +1,139 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    clock::SharedClock,
+    runner::{SubsystemRunner, SubsystemRunnerRef},
+    utils::{
+        remote_drop_collection::{RemoteDrop, RemotelyDroppableItems},
+        Mutex,
+    },
+    AbortCause,
+};
+
+/// A group of subsystems that gets shut down together, within a time budget.
+pub(crate) struct ShutdownGroup {
+    name: Arc<str>,
+    budget: Duration,
+    cancellation_token: CancellationToken,
+    members: RemotelyDroppableItems<SubsystemRunnerRef>,
+    active_members: watch::Sender<u32>,
+}
+
+/// The shutdown groups of a subsystem tree, in the order in which they shut down.
+#[derive(Default)]
+pub(crate) struct ShutdownGroups {
+    groups: Vec<Arc<ShutdownGroup>>,
+}
+
+/// Keeps a subsystem registered in its shutdown group until the subsystem is finished.
+pub(crate) struct ShutdownGroupMembership {
+    _member: RemoteDrop<SubsystemRunnerRef>,
+    group: Arc<ShutdownGroup>,
+}
+
+impl ShutdownGroups {
+    pub(crate) fn new(groups: Vec<(Arc<str>, Duration)>) -> Self {
+        Self {
+            groups: groups
+                .into_iter()
+                .map(|(name, budget)| {
+                    Arc::new(ShutdownGroup {
+                        name,
+                        budget,
+                        cancellation_token: CancellationToken::new(),
+                        members: RemotelyDroppableItems::new(),
+                        active_members: watch::channel(0).0,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&Arc<ShutdownGroup>> {
+        self.groups.iter().find(|group| group.name.as_ref() == name)
+    }
+
+    /// Returns the names and budgets of the groups, in the order in which they shut down.
+    pub(crate) fn budgets(&self) -> Vec<(Arc<str>, Duration)> {
+        self.groups
+            .iter()
+            .map(|group| (Arc::clone(&group.name), group.budget))
+            .collect()
+    }
+
+    /// Shuts down the groups one after another.
+    ///
+    /// Groups that exceed their budget get aborted.
+    ///
+    /// Adds the names of the aborted subsystems to `aborted` as soon as they get aborted,
+    /// together with the reason they got aborted.
+    pub(crate) async fn shut_down(
+        &self,
+        clock: &SharedClock,
+        aborted: &Mutex<Vec<(Arc<str>, AbortCause)>>,
+    ) {
+        for group in &self.groups {
+            tracing::info!("Shutting down group '{}' ...", group.name);
+            group.cancellation_token.cancel();
+
+            let mut active_members = group.active_members.subscribe();
+            let finished = clock
+                .timeout(
+                    group.budget,
+                    active_members.wait_for(|&active_members| active_members == 0),
+                )
+                .await;
+
+            if finished.is_none() {
+                tracing::warn!(
+                    "Group '{}' exceeded its shutdown budget of {:?}, aborting it ...",
+                    group.name,
+                    group.budget
+                );
+                let cause = AbortCause::ShutdownGroupTimeout {
+                    group: Arc::clone(&group.name),
+                    budget: group.budget,
+                };
+                aborted.lock().extend(
+                    SubsystemRunner::abort_all(group.members.map_items(Clone::clone))
+                        .into_iter()
+                        .map(|name| (name, cause.clone())),
+                );
+            }
+        }
+    }
+}
+
+impl ShutdownGroup {
+    pub(crate) fn name(&self) -> &Arc<str> {
+        &self.name
+    }
+
+    /// The token that gets cancelled once it is the turn of this group to shut down.
+    pub(crate) fn get_cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
+    pub(crate) fn register(
+        self: &Arc<Self>,
+        member: SubsystemRunnerRef,
+    ) -> ShutdownGroupMembership {
+        self.active_members
+            .send_modify(|active_members| *active_members += 1);
+        ShutdownGroupMembership {
+            _member: self.members.insert(member),
+            group: Arc::clone(self),
+        }
+    }
+}
+
+impl Drop for ShutdownGroupMembership {
+    fn drop(&mut self) {
+        self.group
+            .active_members
+            .send_modify(|active_members| *active_members -= 1);
+    }
+}
@@ -0,0 +1,173 @@
+use std::future::Future;
+
+use tokio::sync::{Notify, OnceCell};
+
+use crate::{errors::NeverInitialized, ShutdownToken};
+
+/// A value that gets initialized asynchronously on first use, unless a shutdown
+/// gets in the way.
+///
+/// Works like [`tokio::sync::OnceCell`], but gives up once a shutdown is requested:
+/// an initialization that is still running gets cancelled, and every caller
+/// that waits for the value gets a [`NeverInitialized`] error instead of waiting forever.
+/// After the shutdown was requested, no new initialization gets started.
+///
+/// A value that was initialized before the shutdown stays available,
+/// so it can still be used while shutting down.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use miette::Result;
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{ShutdownOnceCell, SubsystemBuilder, SubsystemHandle, Toplevel};
+///
+/// struct Connection;
+///
+/// async fn connect() -> Connection {
+///     tracing::info!("Connecting ...");
+///     Connection
+/// }
+///
+/// async fn worker(
+///     subsys: SubsystemHandle,
+///     connection: Arc<ShutdownOnceCell<Connection>>,
+/// ) -> Result<()> {
+///     // Does not hang if the shutdown cancelled the connection attempt.
+///     let Ok(_connection) = connection.get_or_init(connect).await else {
+///         tracing::info!("Shutting down before the connection was established.");
+///         return Ok(());
+///     };
+///     subsys.on_shutdown_requested().await;
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     Toplevel::new(|s: SubsystemHandle| async move {
+///         let connection = Arc::new(ShutdownOnceCell::new(s.shutdown_token()));
+///         s.start(SubsystemBuilder::new("worker", |s| worker(s, connection)));
+///         s.request_shutdown();
+///     })
+///     .handle_shutdown_requests(Duration::from_millis(500))
+///     .await
+///     .map_err(Into::into)
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ShutdownOnceCell<T> {
+    cell: OnceCell<T>,
+    initialized: Notify,
+    shutdown_token: ShutdownToken,
+}
+
+impl<T> ShutdownOnceCell<T> {
+    /// Creates a new, uninitialized cell.
+    ///
+    /// # Arguments
+    ///
+    /// * `shutdown_token` - The token that cancels the initialization, usually
+    ///   created through [`SubsystemHandle::shutdown_token`](crate::SubsystemHandle::shutdown_token).
+    pub fn new(shutdown_token: impl Into<ShutdownToken>) -> Self {
+        Self {
+            cell: OnceCell::new(),
+            initialized: Notify::new(),
+            shutdown_token: shutdown_token.into(),
+        }
+    }
+
+    /// Returns the value, if it is initialized.
+    pub fn get(&self) -> Option<&T> {
+        self.cell.get()
+    }
+
+    /// Whether the value is initialized.
+    pub fn is_initialized(&self) -> bool {
+        self.cell.initialized()
+    }
+
+    /// Returns the value, initializing it if necessary.
+    ///
+    /// If another caller is initializing the value already, waits for it instead.
+    /// Should that initialization get cancelled, because its caller gave up on it,
+    /// this caller takes over with its own `init`.
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - Creates the value.
+    ///
+    /// # Returns
+    ///
+    /// The value, or [`NeverInitialized`] if a shutdown was requested before
+    /// the value was initialized.
+    pub async fn get_or_init<F, Fut>(&self, init: F) -> Result<&T, NeverInitialized>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        self.get_or_try_init(|| async { Ok(init().await) }).await
+    }
+
+    /// Returns the value, initializing it with a fallible function if necessary.
+    ///
+    /// Behaves like [`get_or_init`](Self::get_or_init); if `init` fails,
+    /// the value stays uninitialized and the error gets returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `init` - Creates the value.
+    ///
+    /// # Returns
+    ///
+    /// The value, the error of `init`, or [`NeverInitialized`] if a shutdown was
+    /// requested before the value was initialized.
+    pub async fn get_or_try_init<F, Fut, E>(&self, init: F) -> Result<&T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: From<NeverInitialized>,
+    {
+        if let Some(value) = self.cell.get() {
+            return Ok(value);
+        }
+
+        let value = tokio::select! {
+            biased;
+            _ = self.shutdown_token.on_shutdown_requested() => {
+                return Err(NeverInitialized.into());
+            },
+            result = self.cell.get_or_try_init(init) => result?,
+        };
+
+        self.initialized.notify_waiters();
+        Ok(value)
+    }
+
+    /// Waits until someone else initialized the value.
+    ///
+    /// # Returns
+    ///
+    /// The value, or [`NeverInitialized`] if a shutdown was requested before
+    /// the value was initialized.
+    pub async fn wait(&self) -> Result<&T, NeverInitialized> {
+        loop {
+            let initialized = self.initialized.notified();
+            tokio::pin!(initialized);
+            initialized.as_mut().enable();
+
+            if let Some(value) = self.cell.get() {
+                return Ok(value);
+            }
+
+            tokio::select! {
+                biased;
+                _ = self.shutdown_token.on_shutdown_requested() => {
+                    return self.cell.get().ok_or(NeverInitialized);
+                },
+                _ = initialized => (),
+            }
+        }
+    }
+}
@@ -0,0 +1,205 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use crate::{runner::SubsystemRunnerRef, shutdown_groups::ShutdownGroups};
+
+/// The names of the grouped ancestors of a subsystem, with the positions of their groups.
+type GroupedAncestors = Vec<(Arc<str>, usize)>;
+
+/// The shutdown of a subsystem tree as it would currently happen, without performing it.
+///
+/// Intended for validating complex configurations, for example in CI.
+///
+/// Created through [`Toplevel::dry_run_shutdown`](crate::Toplevel::dry_run_shutdown).
+#[derive(Debug, Clone)]
+pub struct ShutdownPlan {
+    /// The time limit of the entire shutdown, as configured through
+    /// [`ToplevelBuilder::shutdown_timeout`](crate::ToplevelBuilder::shutdown_timeout).
+    pub shutdown_timeout: Option<Duration>,
+    /// The subsystems that are not part of a shutdown group.
+    ///
+    /// They get signaled right away, in parallel to the first shutdown group.
+    pub ungrouped: Vec<PlannedSubsystem>,
+    /// The shutdown groups, in the order in which they shut down.
+    pub stages: Vec<ShutdownStage>,
+    /// Subsystems that wait for descendants of a later shutdown group.
+    ///
+    /// Those descendants only get signaled after the group of the subsystem finished,
+    /// so the group will exceed its budget and get aborted.
+    pub cycles: Vec<ShutdownCycle>,
+}
+
+/// A shutdown group of a [`ShutdownPlan`].
+#[derive(Debug, Clone)]
+pub struct ShutdownStage {
+    /// The name of the shutdown group.
+    pub group: Arc<str>,
+    /// The time budget of the shutdown group.
+    pub budget: Duration,
+    /// The members of the shutdown group, children before their parents.
+    pub subsystems: Vec<PlannedSubsystem>,
+}
+
+/// A subsystem of a [`ShutdownPlan`].
+#[derive(Debug, Clone)]
+pub struct PlannedSubsystem {
+    /// The full name of the subsystem.
+    pub name: Arc<str>,
+    /// The shutdown group of the subsystem, if it is part of one.
+    pub shutdown_group: Option<Arc<str>>,
+    /// The shutdown timeout of the subsystem, as configured through
+    /// [`SubsystemBuilder::shutdown_timeout`](crate::SubsystemBuilder::shutdown_timeout).
+    pub shutdown_timeout: Option<Duration>,
+    /// The timeout of the pre-shutdown hook of the subsystem, as configured through
+    /// [`SubsystemBuilder::pre_shutdown`](crate::SubsystemBuilder::pre_shutdown).
+    pub pre_shutdown_timeout: Option<Duration>,
+    /// Whether the subsystem is [detached](crate::SubsystemBuilder::detached),
+    /// meaning it only shuts down once its parent tells it to.
+    pub detached: bool,
+}
+
+/// A subsystem that waits for a descendant which is part of a later shutdown group.
+#[derive(Debug, Clone)]
+pub struct ShutdownCycle {
+    /// The name of the waiting subsystem.
+    pub subsystem: Arc<str>,
+    /// The shutdown group of the waiting subsystem.
+    pub group: Arc<str>,
+    /// The name of the descendant.
+    pub descendant: Arc<str>,
+    /// The shutdown group of the descendant.
+    pub descendant_group: Arc<str>,
+}
+
+impl ShutdownPlan {
+    pub(crate) fn new(
+        shutdown_timeout: Option<Duration>,
+        shutdown_groups: &ShutdownGroups,
+        runners: Vec<SubsystemRunnerRef>,
+    ) -> Self {
+        let mut ungrouped = Vec::new();
+        let mut stages = shutdown_groups
+            .budgets()
+            .into_iter()
+            .map(|(group, budget)| ShutdownStage {
+                group,
+                budget,
+                subsystems: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        let mut cycles = Vec::new();
+
+        // Walk iteratively instead of recursively, as deeply nested
+        // subsystem trees could overflow the stack.
+        // Every subsystem gets visited after its parent, together with
+        // the grouped ancestors it gets waited for by.
+        let mut visited = Vec::new();
+        let mut pending: Vec<(SubsystemRunnerRef, GroupedAncestors)> = runners
+            .into_iter()
+            .map(|runner| (runner, Vec::new()))
+            .collect();
+        while let Some((runner, mut grouped_ancestors)) = pending.pop() {
+            if runner.is_finished() {
+                continue;
+            }
+            let planned = Arc::clone(runner.get_plan());
+
+            let stage = planned
+                .shutdown_group
+                .as_ref()
+                .and_then(|group| stages.iter().position(|stage| stage.group == *group));
+
+            if let Some(stage) = stage {
+                for (ancestor, ancestor_stage) in &grouped_ancestors {
+                    if *ancestor_stage < stage {
+                        cycles.push(ShutdownCycle {
+                            subsystem: Arc::clone(ancestor),
+                            group: Arc::clone(&stages[*ancestor_stage].group),
+                            descendant: Arc::clone(&planned.name),
+                            descendant_group: Arc::clone(&stages[stage].group),
+                        });
+                    }
+                }
+                grouped_ancestors.push((Arc::clone(&planned.name), stage));
+            }
+
+            pending.extend(
+                runner
+                    .get_children()
+                    .into_iter()
+                    .map(|child| (child, grouped_ancestors.clone())),
+            );
+            if !runner.is_root() {
+                visited.push((planned, stage));
+            }
+        }
+
+        // Children finish before their parents.
+        for (planned, stage) in visited.into_iter().rev() {
+            match stage {
+                Some(stage) => stages[stage].subsystems.push(planned.as_ref().clone()),
+                None => ungrouped.push(planned.as_ref().clone()),
+            }
+        }
+
+        Self {
+            shutdown_timeout,
+            ungrouped,
+            stages,
+            cycles,
+        }
+    }
+
+    /// Returns whether the plan contains no [`cycles`](Self::cycles).
+    pub fn is_valid(&self) -> bool {
+        self.cycles.is_empty()
+    }
+}
+
+impl fmt::Display for ShutdownPlan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.shutdown_timeout {
+            Some(shutdown_timeout) => writeln!(f, "shutdown timeout: {shutdown_timeout:?}")?,
+            None => writeln!(f, "shutdown timeout: none")?,
+        }
+        writeln!(f, "ungrouped:")?;
+        for subsystem in &self.ungrouped {
+            writeln!(f, "  {subsystem}")?;
+        }
+        for (position, stage) in self.stages.iter().enumerate() {
+            writeln!(
+                f,
+                "group {} '{}' (budget: {:?}):",
+                position + 1,
+                stage.group,
+                stage.budget
+            )?;
+            for subsystem in &stage.subsystems {
+                writeln!(f, "  {subsystem}")?;
+            }
+        }
+        for cycle in &self.cycles {
+            writeln!(
+                f,
+                "cycle: '{}' in group '{}' waits for '{}' in later group '{}'",
+                cycle.subsystem, cycle.group, cycle.descendant, cycle.descendant_group
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PlannedSubsystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if let Some(shutdown_timeout) = self.shutdown_timeout {
+            write!(f, " (timeout: {shutdown_timeout:?})")?;
+        }
+        if let Some(pre_shutdown_timeout) = self.pre_shutdown_timeout {
+            write!(f, " (pre-shutdown hook: {pre_shutdown_timeout:?})")?;
+        }
+        if self.detached {
+            write!(f, " (detached)")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use crate::{errors::SubsystemError, ErrTypeTraits, ShutdownSignal};
+
+/// The reason why a subsystem is shutting down.
+///
+/// Can be queried through
+/// [`SubsystemHandle::on_shutdown_requested_with_reason`](crate::SubsystemHandle::on_shutdown_requested_with_reason)
+/// and [`SubsystemHandle::shutdown_reason`](crate::SubsystemHandle::shutdown_reason).
+///
+/// If multiple shutdown requests happen, the first one determines the reason.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ShutdownReason {
+    /// A shutdown of the entire subsystem tree was requested through
+    /// [`SubsystemHandle::request_shutdown`](crate::SubsystemHandle::request_shutdown)
+    /// or [`ToplevelHandle::request_shutdown`](crate::ToplevelHandle::request_shutdown).
+    Requested,
+    /// A signal like SIGINT or SIGTERM was received.
+    ///
+    /// Contains the first signal; all received signals are listed in
+    /// [`ShutdownStatistics::received_signals`](crate::ShutdownStatistics::received_signals).
+    Signal(ShutdownSignal),
+    /// The given subsystem returned an error that was not caught.
+    SubsystemFailed(Arc<str>),
+    /// The given subsystem panicked, and the panic was not caught.
+    SubsystemPanicked(Arc<str>),
+    /// The shutdown was triggered from outside of the subsystem tree, for example through
+    /// the cancellation token given to [`Toplevel::new_with_cancellation_token`](crate::Toplevel::new_with_cancellation_token)
+    /// or through the parent of a [`Toplevel::nested`](crate::Toplevel::nested).
+    External,
+    /// Only a part of the subsystem tree is shutting down, for example through
+    /// [`SubsystemHandle::request_local_shutdown`](crate::SubsystemHandle::request_local_shutdown),
+    /// [`NestedSubsystem::initiate_shutdown`](crate::NestedSubsystem::initiate_shutdown)
+    /// or because an error got caught through [`ErrorAction::CatchAndLocalShutdown`](crate::ErrorAction::CatchAndLocalShutdown).
+    Local,
+    /// A thread panicked while [`Toplevel::shutdown_on_panic`](crate::Toplevel::shutdown_on_panic)
+    /// was active.
+    Panic {
+        /// The name of the panicking thread, if it has one.
+        thread: Option<Arc<str>>,
+        /// The panic message.
+        message: Arc<str>,
+        /// The source location of the panic, if available.
+        location: Option<Arc<str>>,
+    },
+}
+
+impl ShutdownReason {
+    pub(crate) fn from_panic(
+        payload: &(dyn std::any::Any + Send),
+        location: Option<&std::panic::Location<'_>>,
+    ) -> Self {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            Arc::from(*message)
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            Arc::from(message.as_str())
+        } else {
+            Arc::from("Box<dyn Any>")
+        };
+
+        Self::Panic {
+            thread: std::thread::current().name().map(Arc::from),
+            message,
+            location: location.map(|location| Arc::from(location.to_string())),
+        }
+    }
+
+    pub(crate) fn from_error<ErrType: ErrTypeTraits>(error: &SubsystemError<ErrType>) -> Self {
+        match error {
+            SubsystemError::Panicked(name) => Self::SubsystemPanicked(Arc::clone(name)),
+            SubsystemError::Failed(name, _)
+            | SubsystemError::Internal(name, _)
+            | SubsystemError::Aborted(name)
+            | SubsystemError::UncleanExit(name, _) => Self::SubsystemFailed(Arc::clone(name)),
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use crate::{errors::SubsystemError, ErrTypeTraits, ExitStatus, SubsystemOutcome, SubsystemResult};
+
+/// The default of [`ToplevelBuilder::report_aggregation_threshold`](crate::ToplevelBuilder::report_aggregation_threshold).
+pub(crate) const DEFAULT_REPORT_AGGREGATION_THRESHOLD: usize = 10;
+
+/// The maximum number of failure messages kept per group of aggregated subsystems.
+const MAX_FAILURE_SAMPLES: usize = 3;
+
+/// The final results of all subsystems of a subsystem tree.
+///
+/// Identically-named subsystems, like one per connection, are rolled up into a single
+/// [`AggregatedSubsystems`] entry once there are at least as many of them as configured through
+/// [`ToplevelBuilder::report_aggregation_threshold`](crate::ToplevelBuilder::report_aggregation_threshold).
+///
+/// Logged once a shutdown finished with errors; can be queried through
+/// [`ToplevelHandle::shutdown_report`](crate::ToplevelHandle::shutdown_report).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShutdownReport {
+    /// The entries of the report, in the order in which their subsystems first finished.
+    pub entries: Vec<ShutdownReportEntry>,
+}
+
+/// An entry of a [`ShutdownReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShutdownReportEntry {
+    /// A single subsystem.
+    Single {
+        /// The result of the subsystem.
+        result: SubsystemResult,
+        /// The error message of the subsystem, if it returned an error.
+        failure: Option<String>,
+    },
+    /// A group of identically-named subsystems.
+    Aggregated(AggregatedSubsystems),
+}
+
+/// The rolled-up results of identically-named subsystems.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AggregatedSubsystems {
+    /// The full name of the subsystems.
+    pub name: Arc<str>,
+    /// The number of subsystems that returned successfully with [`ExitStatus::Completed`].
+    pub succeeded: u64,
+    /// The number of subsystems that returned successfully with [`ExitStatus::Drained`].
+    pub drained: u64,
+    /// The total number of work items the [`drained`](Self::drained) subsystems dropped.
+    pub dropped_items: u64,
+    /// The number of subsystems that returned successfully with [`ExitStatus::Skipped`].
+    pub skipped: u64,
+    /// The number of subsystems that returned successfully with [`ExitStatus::Degraded`].
+    pub degraded: u64,
+    /// The number of subsystems that returned an error.
+    pub failed: u64,
+    /// The number of subsystems that panicked.
+    pub panicked: u64,
+    /// The number of subsystems that did not finish in time and got aborted.
+    pub aborted: u64,
+    /// The error messages of the first few subsystems that returned an error.
+    pub failure_samples: Vec<String>,
+}
+
+impl AggregatedSubsystems {
+    fn new(name: Arc<str>) -> Self {
+        Self {
+            name,
+            succeeded: 0,
+            drained: 0,
+            dropped_items: 0,
+            skipped: 0,
+            degraded: 0,
+            failed: 0,
+            panicked: 0,
+            aborted: 0,
+            failure_samples: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, result: &SubsystemResult, failure: Option<String>) {
+        match result.outcome {
+            SubsystemOutcome::Succeeded => match &result.exit_status {
+                Some(ExitStatus::Drained { dropped }) => {
+                    self.drained += 1;
+                    self.dropped_items += dropped;
+                }
+                Some(ExitStatus::Skipped { .. }) => self.skipped += 1,
+                Some(ExitStatus::Degraded { .. }) => self.degraded += 1,
+                Some(ExitStatus::Completed) | None => self.succeeded += 1,
+            },
+            SubsystemOutcome::Failed => self.failed += 1,
+            SubsystemOutcome::Panicked => self.panicked += 1,
+            SubsystemOutcome::Aborted => self.aborted += 1,
+        }
+        if let Some(failure) = failure {
+            if self.failure_samples.len() < MAX_FAILURE_SAMPLES {
+                self.failure_samples.push(failure);
+            }
+        }
+    }
+}
+
+/// Collects the results of a subsystem tree into a [`ShutdownReport`].
+///
+/// Only keeps the individual results of a name until it reaches the threshold,
+/// so the memory usage stays bounded for subsystems that get started over and over.
+pub(crate) struct ShutdownReportCollector {
+    threshold: usize,
+    report: ShutdownReport,
+    // The positions of the entries of every name, for rolling them up.
+    positions: HashMap<Arc<str>, Vec<usize>>,
+}
+
+impl ShutdownReportCollector {
+    pub(crate) fn new() -> Self {
+        Self {
+            threshold: DEFAULT_REPORT_AGGREGATION_THRESHOLD,
+            report: ShutdownReport::default(),
+            positions: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn set_threshold(&mut self, threshold: usize) {
+        self.threshold = threshold;
+    }
+
+    pub(crate) fn record(&mut self, result: &SubsystemResult, failure: Option<String>) {
+        let positions = self.positions.entry(Arc::clone(&result.name)).or_default();
+
+        if let Some(&position) = positions.first() {
+            if let ShutdownReportEntry::Aggregated(aggregated) = &mut self.report.entries[position]
+            {
+                aggregated.add(result, failure);
+                return;
+            }
+        }
+
+        positions.push(self.report.entries.len());
+        self.report.entries.push(ShutdownReportEntry::Single {
+            result: result.clone(),
+            failure,
+        });
+
+        if positions.len() >= self.threshold.max(1) {
+            let mut aggregated = AggregatedSubsystems::new(Arc::clone(&result.name));
+            for &position in positions.iter() {
+                if let ShutdownReportEntry::Single { result, failure } =
+                    &mut self.report.entries[position]
+                {
+                    aggregated.add(result, failure.take());
+                }
+            }
+
+            // Keep the group at the position of its first member.
+            let first = positions[0];
+            self.report.entries[first] = ShutdownReportEntry::Aggregated(aggregated);
+            let removed = positions.split_off(1);
+            for &position in removed.iter().rev() {
+                self.report.entries.remove(position);
+            }
+            // Shift the positions of all entries behind the removed ones.
+            for other_positions in self.positions.values_mut() {
+                for other_position in other_positions.iter_mut() {
+                    let shift = removed.iter().filter(|&&r| r < *other_position).count();
+                    *other_position -= shift;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn report(&self) -> ShutdownReport {
+        self.report.clone()
+    }
+}
+
+/// Describes the error of a failed subsystem, for a [`ShutdownReport`].
+pub(crate) fn describe_failure<ErrType: ErrTypeTraits>(
+    failure: &SubsystemError<ErrType>,
+) -> Option<String> {
+    match failure {
+        SubsystemError::Failed(_, e) => Some(e.to_string()),
+        SubsystemError::Internal(_, e) => Some(e.to_string()),
+        SubsystemError::Panicked(_)
+        | SubsystemError::Aborted(_)
+        | SubsystemError::UncleanExit(_, _) => None,
+    }
+}
+
+fn describe_outcome(outcome: SubsystemOutcome) -> &'static str {
+    match outcome {
+        SubsystemOutcome::Succeeded => "ok",
+        SubsystemOutcome::Failed => "failed",
+        SubsystemOutcome::Panicked => "panicked",
+        SubsystemOutcome::Aborted => "timed out",
+    }
+}
+
+impl fmt::Display for ShutdownReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (position, entry) in self.entries.iter().enumerate() {
+            if position > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{entry}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ShutdownReportEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Single { result, failure } => {
+                match &result.exit_status {
+                    Some(exit_status) if *exit_status != ExitStatus::Completed => {
+                        write!(f, "{}: {exit_status}", result.name)?
+                    }
+                    _ => write!(f, "{}: {}", result.name, describe_outcome(result.outcome))?,
+                }
+                if let Some(failure) = failure {
+                    write!(f, " ({failure})")?;
+                }
+                if let Some(abort_cause) = &result.abort_cause {
+                    write!(f, " ({abort_cause})")?;
+                }
+                Ok(())
+            }
+            Self::Aggregated(aggregated) => write!(f, "{aggregated}"),
+        }
+    }
+}
+
+impl fmt::Display for AggregatedSubsystems {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.name)?;
+        let dropped_items = match self.dropped_items {
+            0 => String::new(),
+            1 => " (1 item dropped)".to_string(),
+            dropped_items => format!(" ({dropped_items} items dropped)"),
+        };
+        let counts = [
+            (
+                self.succeeded,
+                describe_outcome(SubsystemOutcome::Succeeded),
+                "",
+            ),
+            (self.drained, "drained", dropped_items.as_str()),
+            (self.skipped, "skipped", ""),
+            (self.degraded, "degraded", ""),
+            (self.failed, describe_outcome(SubsystemOutcome::Failed), ""),
+            (
+                self.panicked,
+                describe_outcome(SubsystemOutcome::Panicked),
+                "",
+            ),
+            (
+                self.aborted,
+                describe_outcome(SubsystemOutcome::Aborted),
+                "",
+            ),
+        ];
+        let mut first = true;
+        for (count, description, details) in counts {
+            if count > 0 {
+                if !first {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{count} {description}{details}")?;
+                first = false;
+            }
+        }
+        for failure in &self.failure_samples {
+            write!(f, "\n  e.g. {failure}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
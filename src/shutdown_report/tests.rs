@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use super::*;
+use crate::AbortCause;
+
+fn result(name: &str, outcome: SubsystemOutcome) -> SubsystemResult {
+    SubsystemResult {
+        name: name.into(),
+        outcome,
+        exit_status: None,
+        shutdown_duration: None,
+        abort_cause: None,
+    }
+}
+
+#[test]
+fn keeps_single_entries_below_threshold() {
+    let mut collector = ShutdownReportCollector::new();
+    collector.set_threshold(3);
+
+    collector.record(&result("/a", SubsystemOutcome::Succeeded), None);
+    collector.record(
+        &result("/b", SubsystemOutcome::Failed),
+        Some("broken".into()),
+    );
+    collector.record(&result("/a", SubsystemOutcome::Aborted), None);
+
+    let report = collector.report();
+    assert_eq!(report.entries.len(), 3);
+    assert_eq!(
+        report.to_string(),
+        "/a: ok\n/b: failed (broken)\n/a: timed out"
+    );
+}
+
+#[test]
+fn shows_abort_causes() {
+    let mut collector = ShutdownReportCollector::new();
+
+    let aborted = |name: &str, abort_cause: AbortCause| SubsystemResult {
+        abort_cause: Some(abort_cause),
+        ..result(name, SubsystemOutcome::Aborted)
+    };
+
+    collector.record(
+        &aborted(
+            "/a",
+            AbortCause::SubsystemTimeout(Duration::from_millis(100)),
+        ),
+        None,
+    );
+    collector.record(
+        &aborted(
+            "/b",
+            AbortCause::ShutdownGroupTimeout {
+                group: "db".into(),
+                budget: Duration::from_secs(2),
+            },
+        ),
+        None,
+    );
+    collector.record(
+        &aborted("/c", AbortCause::ShutdownTimeout(Duration::from_secs(5))),
+        None,
+    );
+
+    assert_eq!(
+        collector.report().to_string(),
+        "/a: timed out (exceeded its shutdown timeout of 100ms)\n\
+         /b: timed out (shutdown group 'db' exceeded its budget of 2s)\n\
+         /c: timed out (shutdown exceeded its timeout of 5s)"
+    );
+}
+
+#[test]
+fn rolls_up_identically_named_subsystems() {
+    let mut collector = ShutdownReportCollector::new();
+    collector.set_threshold(3);
+
+    collector.record(&result("/a", SubsystemOutcome::Succeeded), None);
+    collector.record(&result("/b", SubsystemOutcome::Succeeded), None);
+    collector.record(
+        &result("/a", SubsystemOutcome::Failed),
+        Some("first".into()),
+    );
+    collector.record(&result("/c", SubsystemOutcome::Succeeded), None);
+    collector.record(&result("/a", SubsystemOutcome::Aborted), None);
+    collector.record(&result("/a", SubsystemOutcome::Panicked), None);
+    for n in 0..5 {
+        collector.record(
+            &result("/a", SubsystemOutcome::Failed),
+            Some(format!("error {n}")),
+        );
+    }
+    collector.record(&result("/b", SubsystemOutcome::Succeeded), None);
+
+    let report = collector.report();
+    assert_eq!(report.entries.len(), 4);
+    assert_eq!(
+        report.entries[0],
+        ShutdownReportEntry::Aggregated(AggregatedSubsystems {
+            name: "/a".into(),
+            succeeded: 1,
+            drained: 0,
+            dropped_items: 0,
+            skipped: 0,
+            degraded: 0,
+            failed: 6,
+            panicked: 1,
+            aborted: 1,
+            failure_samples: vec!["first".into(), "error 0".into(), "error 1".into()],
+        })
+    );
+    assert_eq!(
+        report.to_string(),
+        "/a: 1 ok, 6 failed, 1 panicked, 1 timed out\n  e.g. first\n  e.g. error 0\n  e.g. error 1\n/b: ok\n/c: ok\n/b: ok"
+    );
+}
+
+#[test]
+fn threshold_of_zero_aggregates_everything() {
+    let mut collector = ShutdownReportCollector::new();
+    collector.set_threshold(0);
+
+    collector.record(&result("/a", SubsystemOutcome::Succeeded), None);
+
+    assert_eq!(collector.report().to_string(), "/a: 1 ok");
+}
+
+#[test]
+fn shows_exit_statuses() {
+    let mut collector = ShutdownReportCollector::new();
+    collector.set_threshold(4);
+
+    let with_status = |name: &str, exit_status: ExitStatus| SubsystemResult {
+        exit_status: Some(exit_status),
+        ..result(name, SubsystemOutcome::Succeeded)
+    };
+
+    collector.record(&with_status("/a", ExitStatus::Completed), None);
+    collector.record(
+        &with_status("/b", ExitStatus::Drained { dropped: 12 }),
+        None,
+    );
+    collector.record(
+        &with_status(
+            "/c",
+            ExitStatus::Skipped {
+                reason: "nothing to do".into(),
+            },
+        ),
+        None,
+    );
+    collector.record(
+        &with_status(
+            "/d",
+            ExitStatus::Degraded {
+                reason: "cache unavailable".into(),
+            },
+        ),
+        None,
+    );
+    for dropped in [0, 3, 4] {
+        collector.record(&with_status("/e", ExitStatus::Drained { dropped }), None);
+    }
+    collector.record(&with_status("/e", ExitStatus::Completed), None);
+
+    assert_eq!(
+        collector.report().to_string(),
+        "/a: ok\n/b: drained with 12 items dropped\n/c: skipped (nothing to do)\n/d: degraded (cache unavailable)\n/e: 1 ok, 3 drained (7 items dropped)"
+    );
+}
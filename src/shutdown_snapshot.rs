@@ -0,0 +1,22 @@
+use std::sync::Arc;
+
+use crate::{ShutdownReason, ShutdownStatistics};
+
+/// The state of a subsystem tree at the moment its shutdown got triggered.
+///
+/// Passed to the hook registered through
+/// [`ToplevelBuilder::on_shutdown_triggered`](crate::ToplevelBuilder::on_shutdown_triggered),
+/// for example to persist what was running and why the program stopped,
+/// for a later analysis of an incident.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ShutdownSnapshot {
+    /// Why the shutdown got triggered.
+    pub reason: ShutdownReason,
+    /// The names of all subsystems that were running, sorted by name.
+    pub running_subsystems: Vec<Arc<str>>,
+    /// The shutdown statistics of the subsystem tree.
+    pub statistics: ShutdownStatistics,
+}
+
+pub(crate) type ShutdownHook = Box<dyn FnOnce(&ShutdownSnapshot) + Send>;
@@ -0,0 +1,12 @@
+/// The lifecycle state of a subsystem tree.
+///
+/// Can be queried through [`ToplevelHandle::shutdown_state`](crate::ToplevelHandle::shutdown_state).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownState {
+    /// No shutdown was requested yet.
+    Running,
+    /// A shutdown was requested, and the subsystems are shutting down.
+    ShuttingDown,
+    /// The [`Toplevel`](crate::Toplevel) finished its shutdown, or got dropped.
+    Finished,
+}
@@ -0,0 +1,194 @@
+use std::sync::{
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+    OnceLock,
+};
+
+use tokio::{sync::mpsc, time::Instant};
+
+use crate::{
+    clock::SharedClock, shutdown_report::ShutdownReportCollector, utils::Mutex, ReceivedSignal,
+    ShutdownReason, ShutdownReport, ShutdownSignal, SubsystemResult,
+};
+
+/// Statistics about the shutdown state of a subsystem tree.
+///
+/// Intended for debugging and metrics.
+///
+/// Can be queried through
+/// [`SubsystemHandle::shutdown_statistics`](crate::SubsystemHandle::shutdown_statistics) and
+/// [`ToplevelHandle::shutdown_statistics`](crate::ToplevelHandle::shutdown_statistics).
+#[derive(Debug, Clone)]
+pub struct ShutdownStatistics {
+    /// Whether a shutdown of the subsystem tree was requested.
+    pub is_shutdown_requested: bool,
+    /// The time at which the shutdown was requested, if it was requested already.
+    pub shutdown_requested_at: Option<Instant>,
+    /// How often [`request_shutdown`](crate::SubsystemHandle::request_shutdown) was called,
+    /// including repeated calls.
+    pub request_count: u64,
+    /// The number of tasks that are currently waiting in
+    /// [`on_shutdown_requested`](crate::SubsystemHandle::on_shutdown_requested).
+    pub waiter_count: usize,
+    /// The number of subsystems that acknowledged the shutdown through
+    /// [`acknowledge_shutdown`](crate::SubsystemHandle::acknowledge_shutdown).
+    pub acknowledged_count: usize,
+    /// The number of subsystems that acknowledged the shutdown and are still cleaning up.
+    pub draining_count: usize,
+    /// All signals caught through [`Toplevel::catch_signals`](crate::Toplevel::catch_signals),
+    /// in the order in which they were received.
+    ///
+    /// The first one initiates the shutdown; later ones skip the
+    /// shutdown confirmation and the drain delay.
+    pub received_signals: Vec<ReceivedSignal>,
+}
+
+/// Collects the shutdown statistics of a subsystem tree.
+pub(crate) struct ShutdownStatisticsCollector {
+    #[cfg(feature = "status")]
+    created_at: Instant,
+    shutdown_requested_at: OnceLock<Instant>,
+    shutdown_reason: OnceLock<ShutdownReason>,
+    request_count: AtomicU64,
+    waiter_count: AtomicUsize,
+    acknowledged_count: AtomicUsize,
+    draining_count: AtomicUsize,
+    received_signals: Mutex<Vec<ReceivedSignal>>,
+    result_subscribers: Mutex<Vec<mpsc::UnboundedSender<SubsystemResult>>>,
+    report: Mutex<ShutdownReportCollector>,
+    clock: SharedClock,
+}
+
+impl ShutdownStatisticsCollector {
+    pub(crate) fn new(clock: SharedClock) -> Self {
+        Self {
+            #[cfg(feature = "status")]
+            created_at: clock.now(),
+            shutdown_requested_at: OnceLock::new(),
+            shutdown_reason: OnceLock::new(),
+            request_count: AtomicU64::new(0),
+            waiter_count: AtomicUsize::new(0),
+            acknowledged_count: AtomicUsize::new(0),
+            draining_count: AtomicUsize::new(0),
+            received_signals: Mutex::new(Vec::new()),
+            result_subscribers: Mutex::new(Vec::new()),
+            report: Mutex::new(ShutdownReportCollector::new()),
+            clock,
+        }
+    }
+
+    /// The time at which the subsystem tree got created.
+    #[cfg(feature = "status")]
+    pub(crate) fn created_at(&self) -> Instant {
+        self.created_at
+    }
+
+    /// Records that a shutdown was requested.
+    pub(crate) fn record_shutdown_requested(&self) {
+        self.shutdown_requested_at.get_or_init(|| self.clock.now());
+    }
+
+    /// Records the reason of the shutdown, unless a reason was recorded already.
+    ///
+    /// Has to happen before the shutdown gets triggered, so that
+    /// the reason is available to all subsystems that observe the shutdown.
+    pub(crate) fn record_shutdown_reason(&self, reason: ShutdownReason) {
+        // Ignore errors; the first reason wins.
+        let _ = self.shutdown_reason.set(reason);
+    }
+
+    /// Returns the recorded reason of the shutdown.
+    pub(crate) fn shutdown_reason(&self) -> Option<&ShutdownReason> {
+        self.shutdown_reason.get()
+    }
+
+    /// Records a call of `request_shutdown`.
+    pub(crate) fn record_request(&self) {
+        self.request_count.fetch_add(1, Ordering::Relaxed);
+        self.record_shutdown_reason(ShutdownReason::Requested);
+        self.record_shutdown_requested();
+    }
+
+    /// Records a received signal.
+    pub(crate) fn record_signal(&self, signal: ShutdownSignal) {
+        self.received_signals.lock().push(ReceivedSignal {
+            signal,
+            received_at: self.clock.now(),
+        });
+    }
+
+    /// Subscribes to the results of all subsystems that finish from now on.
+    #[cfg(feature = "futures")]
+    pub(crate) fn subscribe_results(&self) -> mpsc::UnboundedReceiver<SubsystemResult> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.result_subscribers.lock().push(sender);
+        receiver
+    }
+
+    /// Records the final result of a subsystem, with the error message if it returned an error.
+    pub(crate) fn record_result(&self, result: SubsystemResult, failure: Option<String>) {
+        self.report.lock().record(&result, failure);
+        self.result_subscribers
+            .lock()
+            .retain(|subscriber| subscriber.send(result.clone()).is_ok());
+    }
+
+    /// Sets the minimum number of identically-named subsystems that get rolled up in the report.
+    pub(crate) fn set_report_aggregation_threshold(&self, threshold: usize) {
+        self.report.lock().set_threshold(threshold);
+    }
+
+    /// Returns the report of the results of all subsystems that finished so far.
+    pub(crate) fn report(&self) -> ShutdownReport {
+        self.report.lock().report()
+    }
+
+    /// Records a shutdown acknowledgment of a subsystem.
+    ///
+    /// `first` is set for the first acknowledgment of the subsystem, and
+    /// `draining` if the subsystem had no other pending acknowledgment.
+    pub(crate) fn record_acknowledgement(&self, first: bool, draining: bool) {
+        if first {
+            self.acknowledged_count.fetch_add(1, Ordering::Relaxed);
+        }
+        if draining {
+            self.draining_count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Records that a subsystem finished the cleanup of all of its acknowledgments.
+    pub(crate) fn record_cleanup_done(&self) {
+        self.draining_count.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Registers a waiter; the waiter is deregistered when the returned guard is dropped.
+    pub(crate) fn register_waiter(&self) -> WaiterGuard<'_> {
+        self.waiter_count.fetch_add(1, Ordering::Relaxed);
+        WaiterGuard { collector: self }
+    }
+
+    pub(crate) fn snapshot(&self, is_shutdown_requested: bool) -> ShutdownStatistics {
+        if is_shutdown_requested {
+            self.record_shutdown_requested();
+        }
+
+        ShutdownStatistics {
+            is_shutdown_requested,
+            shutdown_requested_at: self.shutdown_requested_at.get().copied(),
+            request_count: self.request_count.load(Ordering::Relaxed),
+            waiter_count: self.waiter_count.load(Ordering::Relaxed),
+            acknowledged_count: self.acknowledged_count.load(Ordering::Relaxed),
+            draining_count: self.draining_count.load(Ordering::Relaxed),
+            received_signals: self.received_signals.lock().clone(),
+        }
+    }
+}
+
+pub(crate) struct WaiterGuard<'a> {
+    collector: &'a ShutdownStatisticsCollector,
+}
+
+impl Drop for WaiterGuard<'_> {
+    fn drop(&mut self) {
+        self.collector.waiter_count.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,89 @@
+use tokio_util::sync::CancellationToken;
+
+/// A signal that requests a shutdown, usable without a [`Toplevel`](crate::Toplevel).
+///
+/// Meant for libraries that want to accept a shutdown signal in their API without
+/// depending on the subsystem tree: the library waits on the token, and the application
+/// passes in a token that is connected to its subsystems through
+/// [`SubsystemHandle::shutdown_token`](crate::SubsystemHandle::shutdown_token).
+/// In tests or in programs that do not use a subsystem tree, the token can be created
+/// and triggered directly.
+///
+/// Creating and triggering the token does not require a tokio runtime, and
+/// [`on_shutdown_requested`](ShutdownToken::on_shutdown_requested) works with any executor.
+///
+/// All clones of a token share the same state.
+///
+/// # Examples
+///
+/// ```
+/// use tokio_graceful_shutdown::ShutdownToken;
+///
+/// // Library code
+/// async fn serve(shutdown: ShutdownToken) {
+///     shutdown.on_shutdown_requested().await;
+///     // Stop accepting connections ...
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let shutdown = ShutdownToken::new();
+///     let server = tokio::spawn(serve(shutdown.clone()));
+///
+///     shutdown.request_shutdown();
+///     server.await.unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownToken {
+    token: CancellationToken,
+}
+
+impl ShutdownToken {
+    /// Creates a new token on which no shutdown was requested yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests a shutdown from everyone who waits on this token or one of its children.
+    ///
+    /// Has no effect if a shutdown was already requested.
+    pub fn request_shutdown(&self) {
+        self.token.cancel();
+    }
+
+    /// Whether a shutdown was requested.
+    pub fn is_shutdown_requested(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Waits until a shutdown was requested.
+    ///
+    /// Returns immediately if a shutdown was already requested.
+    pub async fn on_shutdown_requested(&self) {
+        self.token.cancelled().await;
+    }
+
+    /// Creates a child token that receives the shutdown requests of this token.
+    ///
+    /// Requesting a shutdown on the child does not affect this token.
+    pub fn child_token(&self) -> Self {
+        Self {
+            token: self.token.child_token(),
+        }
+    }
+}
+
+impl From<CancellationToken> for ShutdownToken {
+    fn from(token: CancellationToken) -> Self {
+        Self { token }
+    }
+}
+
+impl From<ShutdownToken> for CancellationToken {
+    /// Allows a [`ShutdownToken`] to shut down a subsystem tree,
+    /// for example through [`Toplevel::shutdown_on`](crate::Toplevel::shutdown_on).
+    fn from(shutdown_token: ShutdownToken) -> Self {
+        shutdown_token.token
+    }
+}
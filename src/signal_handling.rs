@@ -1,41 +1,196 @@
-/// Waits for a signal that requests a graceful shutdown, like SIGTERM or SIGINT.
-#[cfg(unix)]
-async fn wait_for_signal_impl() {
-    use tokio::signal::unix::{signal, SignalKind};
-
-    // Infos here:
-    // https://www.gnu.org/software/libc/manual/html_node/Termination-Signals.html
-    let mut signal_terminate = signal(SignalKind::terminate()).unwrap();
-    let mut signal_interrupt = signal(SignalKind::interrupt()).unwrap();
-
-    tokio::select! {
-        _ = signal_terminate.recv() => tracing::debug!("Received SIGTERM."),
-        _ = signal_interrupt.recv() => tracing::debug!("Received SIGINT."),
-    };
+use std::fmt;
+
+use tokio::time::Instant;
+
+/// An operating system signal that requests a graceful shutdown.
+///
+/// Caught through [`Toplevel::catch_signals`](crate::Toplevel::catch_signals).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ShutdownSignal {
+    /// `SIGTERM`, on Unix.
+    Terminate,
+    /// `SIGINT`, on Unix.
+    Interrupt,
+    /// `SIGHUP`, on Unix.
+    ///
+    /// Only caught if enabled through
+    /// [`ToplevelBuilder::catch_hangup`](crate::ToplevelBuilder::catch_hangup).
+    Hangup,
+    /// `CTRL_C`, on Windows.
+    CtrlC,
+    /// `CTRL_BREAK`, on Windows.
+    CtrlBreak,
+    /// `CTRL_CLOSE`, on Windows.
+    CtrlClose,
+    /// `CTRL_SHUTDOWN`, on Windows.
+    CtrlShutdown,
+}
+
+impl fmt::Display for ShutdownSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Terminate => "SIGTERM",
+            Self::Interrupt => "SIGINT",
+            Self::Hangup => "SIGHUP",
+            Self::CtrlC => "CTRL_C",
+            Self::CtrlBreak => "CTRL_BREAK",
+            Self::CtrlClose => "CTRL_CLOSE",
+            Self::CtrlShutdown => "CTRL_SHUTDOWN",
+        })
+    }
 }
 
-/// Waits for a signal that requests a graceful shutdown, Ctrl-C (SIGINT).
-#[cfg(windows)]
-async fn wait_for_signal_impl() {
-    use tokio::signal::windows;
+/// A signal that was received, and when.
+///
+/// Listed in [`ShutdownStatistics::received_signals`](crate::ShutdownStatistics::received_signals).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReceivedSignal {
+    /// The signal.
+    pub signal: ShutdownSignal,
+    /// The time at which the signal was received.
+    pub received_at: Instant,
+}
 
-    // Infos here:
-    // https://learn.microsoft.com/en-us/windows/console/handlerroutine
-    let mut signal_c = windows::ctrl_c().unwrap();
-    let mut signal_break = windows::ctrl_break().unwrap();
-    let mut signal_close = windows::ctrl_close().unwrap();
-    let mut signal_shutdown = windows::ctrl_shutdown().unwrap();
+/// Which signals to listen for, configured through the [`ToplevelBuilder`](crate::ToplevelBuilder).
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct SignalSet {
+    pub(crate) catch_hangup: bool,
+    pub(crate) ctrl_c_only: bool,
+}
 
-    tokio::select! {
-        _ = signal_c.recv() => tracing::debug!("Received CTRL_C."),
-        _ = signal_break.recv() => tracing::debug!("Received CTRL_BREAK."),
-        _ = signal_close.recv() => tracing::debug!("Received CTRL_CLOSE."),
-        _ = signal_shutdown.recv() => tracing::debug!("Received CTRL_SHUTDOWN."),
+/// Waits for a signal that might not be listened for.
+#[cfg(all(any(unix, windows), not(madsim)))]
+macro_rules! recv_optional {
+    ($signal:expr) => {
+        async {
+            match &mut $signal {
+                Some(signal) => signal.recv().await,
+                None => std::future::pending().await,
+            }
+        }
     };
 }
 
-/// Registers signal handlers and waits for a signal that
-/// indicates a shutdown request.
-pub(crate) async fn wait_for_signal() {
-    wait_for_signal_impl().await
+/// Listens for signals that request a graceful shutdown, like SIGTERM or SIGINT.
+#[cfg(all(unix, not(madsim)))]
+pub(crate) struct SignalListener {
+    signal_interrupt: tokio::signal::unix::Signal,
+    signal_terminate: Option<tokio::signal::unix::Signal>,
+    signal_hangup: Option<tokio::signal::unix::Signal>,
+}
+
+#[cfg(all(unix, not(madsim)))]
+impl SignalListener {
+    pub(crate) fn new(signal_set: SignalSet) -> std::io::Result<Self> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        // Registering a handler replaces the default behavior for good,
+        // so only do it for the signals that are requested.
+        let full = !signal_set.ctrl_c_only;
+        let catch_hangup = full && signal_set.catch_hangup;
+
+        // Infos here:
+        // https://www.gnu.org/software/libc/manual/html_node/Termination-Signals.html
+        Ok(Self {
+            signal_interrupt: signal(SignalKind::interrupt())?,
+            signal_terminate: full.then(|| signal(SignalKind::terminate())).transpose()?,
+            signal_hangup: catch_hangup
+                .then(|| signal(SignalKind::hangup()))
+                .transpose()?,
+        })
+    }
+
+    pub(crate) async fn recv(&mut self) -> ShutdownSignal {
+        let signal = tokio::select! {
+            _ = self.signal_interrupt.recv() => ShutdownSignal::Interrupt,
+            _ = recv_optional!(self.signal_terminate) => ShutdownSignal::Terminate,
+            _ = recv_optional!(self.signal_hangup) => ShutdownSignal::Hangup,
+        };
+        tracing::debug!("Received {signal}.");
+        signal
+    }
+}
+
+/// Listens for console events that request a graceful shutdown, like Ctrl-C.
+#[cfg(all(windows, not(madsim)))]
+pub(crate) struct SignalListener {
+    signal_c: tokio::signal::windows::CtrlC,
+    signal_break: Option<tokio::signal::windows::CtrlBreak>,
+    signal_close: Option<tokio::signal::windows::CtrlClose>,
+    signal_shutdown: Option<tokio::signal::windows::CtrlShutdown>,
+}
+
+#[cfg(all(windows, not(madsim)))]
+impl SignalListener {
+    /// SIGHUP does not exist on Windows, so [`SignalSet::catch_hangup`] gets ignored.
+    pub(crate) fn new(signal_set: SignalSet) -> std::io::Result<Self> {
+        use tokio::signal::windows;
+
+        let full = !signal_set.ctrl_c_only;
+
+        // Infos here:
+        // https://learn.microsoft.com/en-us/windows/console/handlerroutine
+        Ok(Self {
+            signal_c: windows::ctrl_c()?,
+            signal_break: full.then(windows::ctrl_break).transpose()?,
+            signal_close: full.then(windows::ctrl_close).transpose()?,
+            signal_shutdown: full.then(windows::ctrl_shutdown).transpose()?,
+        })
+    }
+
+    pub(crate) async fn recv(&mut self) -> ShutdownSignal {
+        let signal = tokio::select! {
+            _ = self.signal_c.recv() => ShutdownSignal::CtrlC,
+            _ = recv_optional!(self.signal_break) => ShutdownSignal::CtrlBreak,
+            _ = recv_optional!(self.signal_close) => ShutdownSignal::CtrlClose,
+            _ = recv_optional!(self.signal_shutdown) => ShutdownSignal::CtrlShutdown,
+        };
+        tracing::debug!("Received {signal}.");
+        signal
+    }
+}
+
+/// Stands in for platforms on which tokio can not listen for signals.
+#[cfg(not(any(unix, windows, madsim)))]
+pub(crate) struct SignalListener {
+    _private: (),
+}
+
+#[cfg(not(any(unix, windows, madsim)))]
+impl SignalListener {
+    pub(crate) fn new(_signal_set: SignalSet) -> std::io::Result<Self> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "signals are not supported on this platform",
+        ))
+    }
+
+    pub(crate) async fn recv(&mut self) -> ShutdownSignal {
+        std::future::pending().await
+    }
+}
+
+/// Listens for the simulated Ctrl-C of `madsim`, sent through `Handle::send_ctrl_c`.
+#[cfg(madsim)]
+pub(crate) struct SignalListener {
+    _private: (),
+}
+
+#[cfg(madsim)]
+impl SignalListener {
+    /// The simulation only sends Ctrl-C, so the [`SignalSet`] gets ignored.
+    pub(crate) fn new(_signal_set: SignalSet) -> std::io::Result<Self> {
+        Ok(Self { _private: () })
+    }
+
+    pub(crate) async fn recv(&mut self) -> ShutdownSignal {
+        if tokio::signal::ctrl_c().await.is_err() {
+            // Never resolve, like a signal that is never sent.
+            std::future::pending::<()>().await;
+        }
+        let signal = ShutdownSignal::Interrupt;
+        tracing::debug!("Received {signal}.");
+        signal
+    }
 }
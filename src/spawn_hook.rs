@@ -0,0 +1,169 @@
+use std::{future::Future, pin::Pin};
+
+use tokio::sync::oneshot;
+
+use crate::SubsystemNode;
+
+/// The task that runs the code of a subsystem, as seen by a [`SpawnHook`].
+pub type SpawnedTask = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Gets invoked whenever the task of a subsystem gets spawned.
+///
+/// Unlike a [`SubsystemMiddleware`](crate::SubsystemMiddleware), which runs inside of the
+/// subsystem's task, the hook runs in the context of the code that starts the subsystem,
+/// usually the task of its parent. This allows carrying context that tokio does not propagate
+/// into spawned tasks on its own, like the current `tracing` span, task-local values like
+/// request IDs or tenant contexts, or custom allocator scopes, into every subsystem uniformly.
+/// Registered through [`ToplevelBuilder::spawn_hook`](crate::ToplevelBuilder::spawn_hook).
+///
+/// The hook also wraps the other tasks that the crate spawns for a subsystem: its
+/// [lightweight children](crate::SubsystemHandle::start_lightweight), the tasks of its
+/// [scopes](crate::SubsystemHandle::scope), and helper tasks, for example to forward shutdowns to
+/// transferable subsystems or to members of shutdown groups, or to run a
+/// [pre-shutdown hook](crate::SubsystemBuilder::pre_shutdown). Their `node` is the one of the
+/// subsystem they belong to.
+///
+/// The hook applies to the root subsystem that gets passed to the [`Toplevel`](crate::Toplevel)
+/// as well; its name is empty. As a result, context that is present when the
+/// [`Toplevel`](crate::Toplevel) gets created reaches all subsystems of the tree.
+///
+/// The returned task must run the given task to completion. If it drops the given task
+/// instead, the subsystem fails with
+/// [`InternalError::SubsystemNotRun`](crate::errors::InternalError::SubsystemNotRun).
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{
+///     SpawnHook, SpawnedTask, SubsystemBuilder, SubsystemHandle, SubsystemNode, Toplevel,
+/// };
+///
+/// tokio::task_local! {
+///     static TENANT: String;
+/// }
+///
+/// struct PropagateTenant;
+///
+/// impl SpawnHook for PropagateTenant {
+///     fn wrap(&self, _node: &SubsystemNode, task: SpawnedTask) -> SpawnedTask {
+///         match TENANT.try_with(Clone::clone) {
+///             Ok(tenant) => Box::pin(TENANT.scope(tenant, task)),
+///             Err(_) => task,
+///         }
+///     }
+/// }
+///
+/// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+///     tracing::info!("Running for tenant '{}'.", TENANT.get());
+///     subsys.request_shutdown();
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let toplevel = TENANT.sync_scope("acme".to_string(), || {
+///         Toplevel::builder()
+///             .spawn_hook(PropagateTenant)
+///             .build(|s| async move {
+///                 s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+///             })
+///     });
+///
+///     toplevel
+///         .handle_shutdown_requests(Duration::from_millis(500))
+///         .await
+///         .map_err(Into::into)
+/// }
+/// ```
+pub trait SpawnHook: Send + Sync + 'static {
+    /// Wraps the task of a subsystem that is about to be spawned.
+    ///
+    /// # Arguments
+    ///
+    /// * `node` - The position of the subsystem in the subsystem tree.
+    /// * `task` - The task of the subsystem, or of the next hook.
+    ///
+    /// # Returns
+    ///
+    /// The task that gets spawned in place of the subsystem's task.
+    fn wrap(&self, node: &SubsystemNode, task: SpawnedTask) -> SpawnedTask;
+}
+
+pub(crate) type BoxedSpawnHook = Box<dyn SpawnHook>;
+
+/// The task of a subsystem, already wrapped by the spawn hooks,
+/// that still waits for the subsystem's future.
+pub(crate) struct HookedTask {
+    task: SpawnedTask,
+    subsystem: oneshot::Sender<SpawnedTask>,
+}
+
+impl HookedTask {
+    /// Returns `None` if there are no hooks.
+    ///
+    /// Has to be called in the context that starts the subsystem.
+    pub(crate) fn from_hooks(hooks: &[BoxedSpawnHook], node: &SubsystemNode) -> Option<Self> {
+        (!hooks.is_empty()).then(|| Self::new(hooks, node))
+    }
+
+    /// Passes a placeholder through all hooks; the first one ends up outermost.
+    ///
+    /// Has to be called in the context that starts the subsystem.
+    pub(crate) fn new(hooks: &[BoxedSpawnHook], node: &SubsystemNode) -> Self {
+        let (subsystem, receiver) = oneshot::channel::<SpawnedTask>();
+        let placeholder: SpawnedTask = Box::pin(async move {
+            if let Ok(subsystem) = receiver.await {
+                subsystem.await;
+            }
+        });
+
+        Self {
+            task: hooks
+                .iter()
+                .rev()
+                .fold(placeholder, |task, hook| hook.wrap(node, task)),
+            subsystem,
+        }
+    }
+
+    /// Fills in the future of the subsystem.
+    ///
+    /// Returns the task to spawn, which yields the output of the subsystem,
+    /// or `None` if a hook dropped it without running it to completion.
+    pub(crate) fn run<F>(self, subsystem: F) -> impl Future<Output = Option<F::Output>> + Send
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        self.subsystem
+            .send(Box::pin(async move {
+                sender.send(subsystem.await).ok();
+            }))
+            .ok();
+
+        let task = self.task;
+        async move {
+            task.await;
+            receiver.await.ok()
+        }
+    }
+}
+
+/// Runs a future within the spawn hooks, inside of the current task.
+///
+/// Used for the tasks of a subsystem that get spawned without a runner,
+/// like its lightweight children and the tasks of its scopes.
+/// Returns `None` if a hook dropped the future without running it to completion.
+pub(crate) async fn run_hooked<F>(hooked: Option<HookedTask>, future: F) -> Option<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    match hooked {
+        Some(hooked) => hooked.run(future).await,
+        None => Some(future.await),
+    }
+}
@@ -0,0 +1,28 @@
+/// Determines how shutdown requests that race with the startup of the program get handled.
+///
+/// A shutdown can be requested before all subsystems got started, or even before
+/// [`Toplevel::handle_shutdown_requests`](crate::Toplevel::handle_shutdown_requests)
+/// got called, for example by a signal, by a subsystem that failed right away or by
+/// a [`ToplevelHandle`](crate::ToplevelHandle).
+///
+/// Configured through [`ToplevelBuilder::startup_race_policy`](crate::ToplevelBuilder::startup_race_policy).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum StartupRacePolicy {
+    /// Keep starting subsystems; subsystems that get started after the shutdown request
+    /// see it right away and are expected to shut down immediately.
+    ///
+    /// This is the default.
+    #[default]
+    StartThenShutdown,
+    /// Do not start any subsystems after a shutdown of the subsystem tree got requested.
+    ///
+    /// The [`NestedSubsystem`](crate::NestedSubsystem) of a skipped subsystem is already
+    /// finished and reports no errors.
+    SkipRemaining,
+    /// Hold back shutdown requests until
+    /// [`Toplevel::handle_shutdown_requests`](crate::Toplevel::handle_shutdown_requests)
+    /// or [`Toplevel::run`](crate::Toplevel::run) gets called.
+    ///
+    /// Until then, subsystems can start undisturbed and do not see the shutdown request.
+    Buffer,
+}
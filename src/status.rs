@@ -0,0 +1,168 @@
+//! Status reports for status endpoints, without depending on a specific HTTP framework.
+//!
+//! A [`StatusReport`] summarizes the running subsystems, their restarts, the uptime and,
+//! once a shutdown got requested, its progress. It can be rendered as
+//! plain text through its [`Display`](fmt::Display) implementation, or as JSON
+//! through [`StatusReport::to_json`]; the result can be returned as the body
+//! of a `/status` route of any HTTP framework.
+//!
+//! # Examples
+//!
+//! ```
+//! use miette::Result;
+//! use tokio::time::Duration;
+//! use tokio_graceful_shutdown::{
+//!     status::StatusReport, SubsystemBuilder, SubsystemHandle, Toplevel, ToplevelHandle,
+//! };
+//!
+//! // The handler of a `/status` route
+//! async fn status(toplevel: ToplevelHandle) -> String {
+//!     StatusReport::new(&toplevel).to_string()
+//! }
+//!
+//! async fn web(subsys: SubsystemHandle) -> Result<()> {
+//!     subsys.on_shutdown_requested().await;
+//!     Ok(())
+//! }
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<()> {
+//!     let toplevel = Toplevel::new(|s| async move {
+//!         s.start(SubsystemBuilder::new("web", web));
+//!     });
+//!
+//!     let handle = toplevel.handle();
+//!     tokio::spawn(async move {
+//!         tracing::info!("{}", status(handle.clone()).await);
+//!         handle.request_shutdown();
+//!     });
+//!
+//!     toplevel
+//!         .handle_shutdown_requests(Duration::from_millis(500))
+//!         .await
+//!         .map_err(Into::into)
+//! }
+//! ```
+
+use std::{fmt, sync::Arc, time::Duration};
+
+use crate::{ErrTypeTraits, ShutdownState, ToplevelHandle};
+
+/// A snapshot of the state of a subsystem tree.
+#[derive(Debug, Clone)]
+pub struct StatusReport {
+    /// The lifecycle state of the subsystem tree.
+    pub state: ShutdownState,
+    /// How long the subsystem tree exists already.
+    pub uptime: Duration,
+    /// How long the shutdown is in progress already, if it was requested.
+    pub shutdown_duration: Option<Duration>,
+    /// The names of all subsystems that are still running, sorted by name.
+    pub running_subsystems: Vec<Arc<str>>,
+    /// The names and restart counts of all running subsystems that restarted at least once,
+    /// sorted by name.
+    pub restarted_subsystems: Vec<(Arc<str>, u32)>,
+}
+
+impl StatusReport {
+    /// Takes a snapshot of the subsystem tree of the given [`ToplevelHandle`].
+    pub fn new<ErrType: ErrTypeTraits>(toplevel: &ToplevelHandle<ErrType>) -> Self {
+        let mut running_subsystems = toplevel.running_subsystems();
+        running_subsystems.sort();
+        let mut restarted_subsystems = toplevel.restarted_subsystems();
+        restarted_subsystems.sort();
+
+        Self {
+            state: toplevel.shutdown_state(),
+            uptime: toplevel.created_at().elapsed(),
+            shutdown_duration: toplevel
+                .shutdown_statistics()
+                .shutdown_requested_at
+                .map(|requested_at| requested_at.elapsed()),
+            running_subsystems,
+            restarted_subsystems,
+        }
+    }
+
+    /// Renders the report as a JSON object.
+    ///
+    /// Durations are given in milliseconds.
+    ///
+    /// ```json
+    /// {"state":"running","uptime_ms":1500,"shutdown_duration_ms":null,"running_subsystems":["/web"],"restarts":{"/web":2}}
+    /// ```
+    pub fn to_json(&self) -> String {
+        let shutdown_duration = match self.shutdown_duration {
+            Some(duration) => duration.as_millis().to_string(),
+            None => "null".to_string(),
+        };
+        let running_subsystems = self
+            .running_subsystems
+            .iter()
+            .map(|name| json_string(name))
+            .collect::<Vec<_>>()
+            .join(",");
+        let restarts = self
+            .restarted_subsystems
+            .iter()
+            .map(|(name, restarts)| format!("{}:{restarts}", json_string(name)))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!(
+            r#"{{"state":"{}","uptime_ms":{},"shutdown_duration_ms":{},"running_subsystems":[{}],"restarts":{{{}}}}}"#,
+            state_name(self.state),
+            self.uptime.as_millis(),
+            shutdown_duration,
+            running_subsystems,
+            restarts
+        )
+    }
+}
+
+impl fmt::Display for StatusReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "state: {}", state_name(self.state))?;
+        writeln!(f, "uptime: {:.2?}", self.uptime)?;
+        if let Some(shutdown_duration) = self.shutdown_duration {
+            writeln!(f, "shutting down for: {shutdown_duration:.2?}")?;
+        }
+        writeln!(f, "running subsystems: {}", self.running_subsystems.len())?;
+        for name in &self.running_subsystems {
+            writeln!(f, "  {name}")?;
+        }
+        if !self.restarted_subsystems.is_empty() {
+            writeln!(f, "restarted subsystems:")?;
+            for (name, restarts) in &self.restarted_subsystems {
+                writeln!(f, "  {name}: {restarts} restarts")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn state_name(state: ShutdownState) -> &'static str {
+    match state {
+        ShutdownState::Running => "running",
+        ShutdownState::ShuttingDown => "shutting_down",
+        ShutdownState::Finished => "finished",
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
@@ -0,0 +1,168 @@
+use std::{
+    future::Future,
+    pin::pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use async_trait::async_trait;
+use futures_util::{FutureExt as _, Stream, StreamExt};
+
+use crate::{ErrTypeTraits, IntoSubsystem, SubsystemHandle};
+
+/// A subsystem that processes the items of a [`Stream`].
+///
+/// Stops pulling new items from the stream once a shutdown is requested;
+/// the item that is currently being handled gets finished first.
+/// Optionally, items that are already buffered in the stream get handled as well,
+/// see [`drain_on_shutdown`](Self::drain_on_shutdown).
+///
+/// The subsystem finishes once the stream ends, a shutdown got requested or the handler
+/// returns an error. The number of handled items gets logged and can be queried
+/// through [`handled_items`](Self::handled_items).
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{
+///     IntoSubsystem, StreamProcessor, SubsystemBuilder, SubsystemHandle, Toplevel,
+/// };
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let events = futures_util::stream::iter(["a", "b", "c"]);
+///
+///     let processor = StreamProcessor::new(events, |event| async move {
+///         tracing::info!("Processing event '{event}' ...");
+///         Result::<()>::Ok(())
+///     });
+///     let handled_items = processor.handled_items();
+///
+///     Toplevel::new(|s: SubsystemHandle| async move {
+///         s.start(SubsystemBuilder::new("events", processor.into_subsystem()));
+///     })
+///     .handle_shutdown_requests(Duration::from_millis(500))
+///     .await?;
+///
+///     assert_eq!(handled_items.get(), 3);
+///     Ok(())
+/// }
+/// ```
+pub struct StreamProcessor<S, H> {
+    stream: S,
+    handler: H,
+    drain_on_shutdown: bool,
+    handled_items: HandledItems,
+}
+
+/// The number of items a [`StreamProcessor`] handled so far.
+///
+/// Returned by [`StreamProcessor::handled_items`].
+#[derive(Debug, Clone, Default)]
+pub struct HandledItems(Arc<AtomicU64>);
+
+impl HandledItems {
+    /// Returns the number of successfully handled items.
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    fn increment(&self) {
+        self.0.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl<S, H> StreamProcessor<S, H> {
+    /// Creates a new stream processor.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The stream whose items should be processed.
+    /// * `handler` - Gets called for every item. Returning an error stops the processing
+    ///   and makes the subsystem fail.
+    pub fn new(stream: S, handler: H) -> Self {
+        Self {
+            stream,
+            handler,
+            drain_on_shutdown: false,
+            handled_items: HandledItems::default(),
+        }
+    }
+
+    /// Whether items that are already buffered in the stream should still be
+    /// handled after a shutdown was requested.
+    ///
+    /// Only items that are immediately available get handled; the processor
+    /// does not wait for new items. Disabled by default.
+    pub fn drain_on_shutdown(mut self, drain_on_shutdown: bool) -> Self {
+        self.drain_on_shutdown = drain_on_shutdown;
+        self
+    }
+
+    /// Returns a counter of the items that were handled successfully.
+    ///
+    /// The counter stays valid after the processor was started.
+    pub fn handled_items(&self) -> HandledItems {
+        self.handled_items.clone()
+    }
+}
+
+#[async_trait]
+impl<S, H, Fut, Err, ErrWrapper> IntoSubsystem<Err, ErrWrapper> for StreamProcessor<S, H>
+where
+    S: Stream + Send + 'static,
+    S::Item: Send,
+    H: FnMut(S::Item) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), Err>> + Send,
+    Err: Into<ErrWrapper> + Send + 'static,
+    ErrWrapper: ErrTypeTraits,
+{
+    async fn run(self, subsys: SubsystemHandle<ErrWrapper>) -> Result<(), Err> {
+        let Self {
+            stream,
+            mut handler,
+            drain_on_shutdown,
+            handled_items,
+        } = self;
+        let mut stream = pin!(stream);
+
+        let result = async {
+            loop {
+                let item = tokio::select! {
+                    biased;
+                    _ = subsys.on_shutdown_requested() => break,
+                    item = stream.next() => item,
+                };
+
+                match item {
+                    Some(item) => {
+                        handler(item).await?;
+                        handled_items.increment();
+                    }
+                    None => return Ok(()),
+                }
+            }
+
+            if drain_on_shutdown {
+                while let Some(Some(item)) = stream.next().now_or_never() {
+                    handler(item).await?;
+                    handled_items.increment();
+                }
+            }
+
+            Ok(())
+        }
+        .await;
+
+        tracing::info!(
+            "Stream processing finished after handling {} items.",
+            handled_items.get()
+        );
+
+        result
+    }
+}
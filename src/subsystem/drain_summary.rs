@@ -0,0 +1,9 @@
+/// The outcome of [`SubsystemHandle::drain`](crate::SubsystemHandle::drain).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct DrainSummary {
+    /// The number of items that were handled successfully.
+    pub processed: u64,
+    /// The number of buffered items that were not handled before the deadline.
+    pub dropped: u64,
+}
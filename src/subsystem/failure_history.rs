@@ -0,0 +1,71 @@
+use std::{
+    collections::VecDeque,
+    fmt::Display,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use tokio::time::Instant;
+
+use crate::{clock::SharedClock, utils::Mutex};
+
+/// How many failures of a single subsystem are remembered.
+const MAX_RECORDED_FAILURES: usize = 16;
+
+/// A single failure of a subsystem.
+///
+/// Returned by [`NestedSubsystem::failure_history`](crate::NestedSubsystem::failure_history)
+/// and [`SubsystemHandle::failure_history`](crate::SubsystemHandle::failure_history).
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct FailureRecord {
+    /// When the failure happened.
+    pub at: Instant,
+    /// A short description of the failure, usually the error message.
+    pub summary: String,
+}
+
+/// Counts the restarts of a subsystem and remembers its most recent failures.
+pub(crate) struct FailureHistory {
+    restarts: AtomicU32,
+    recent: Mutex<VecDeque<FailureRecord>>,
+    clock: SharedClock,
+}
+
+impl FailureHistory {
+    pub(crate) fn new(clock: SharedClock) -> Self {
+        Self {
+            restarts: AtomicU32::new(0),
+            recent: Mutex::new(VecDeque::new()),
+            clock,
+        }
+    }
+
+    /// Records a failure, dropping the oldest one if the history is full.
+    pub(crate) fn record_failure(&self, summary: impl Display) {
+        let record = FailureRecord {
+            at: self.clock.now(),
+            summary: summary.to_string(),
+        };
+
+        let mut recent = self.recent.lock();
+        if recent.len() >= MAX_RECORDED_FAILURES {
+            recent.pop_front();
+        }
+        recent.push_back(record);
+    }
+
+    /// Records a failure after which the subsystem restarts its work.
+    pub(crate) fn record_restart(&self, summary: impl Display) {
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+        self.record_failure(summary);
+    }
+
+    pub(crate) fn restart_count(&self) -> u32 {
+        self.restarts.load(Ordering::Relaxed)
+    }
+
+    /// The most recent failures, oldest first.
+    pub(crate) fn recent(&self) -> Vec<FailureRecord> {
+        self.recent.lock().iter().cloned().collect()
+    }
+}
@@ -0,0 +1,163 @@
+use std::{
+    future::Future,
+    panic::{catch_unwind, AssertUnwindSafe},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+};
+
+use pin_project_lite::pin_project;
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    errors::{InternalError, SubsystemError, SubsystemFailure},
+    panic_hook::mark_subsystem,
+    spawn_hook::{run_hooked, HookedTask},
+    ErrTypeTraits,
+};
+
+/// The lightweight children of a subsystem.
+///
+/// Only keeps aggregate counters instead of per-child bookkeeping.
+/// Aborts all children that are still running when dropped.
+pub(crate) struct LightweightChildren {
+    counters: Arc<Counters>,
+    abort_token: CancellationToken,
+}
+
+struct Counters {
+    running: watch::Sender<usize>,
+    started: AtomicU64,
+    failed: AtomicU64,
+    panicked: AtomicU64,
+    aborted: AtomicU64,
+}
+
+/// Aggregate counters of the lightweight children of a subsystem.
+///
+/// Returned by [`SubsystemHandle::lightweight_child_counts`](crate::SubsystemHandle::lightweight_child_counts).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LightweightChildCounts {
+    /// The number of lightweight children that are currently running.
+    pub running: usize,
+    /// The number of lightweight children that were started.
+    pub started: u64,
+    /// The number of lightweight children that returned an error.
+    pub failed: u64,
+    /// The number of lightweight children that panicked.
+    pub panicked: u64,
+    /// The number of lightweight children that got aborted, because their parent got aborted.
+    pub aborted: u64,
+}
+
+impl LightweightChildren {
+    pub(crate) fn new() -> Self {
+        Self {
+            counters: Arc::new(Counters {
+                running: watch::channel(0).0,
+                started: AtomicU64::new(0),
+                failed: AtomicU64::new(0),
+                panicked: AtomicU64::new(0),
+                aborted: AtomicU64::new(0),
+            }),
+            abort_token: CancellationToken::new(),
+        }
+    }
+
+    /// Spawns a lightweight child, wrapped by the spawn hooks of its parent.
+    ///
+    /// Failures and panics get reported through `raise_failure`, under the given name.
+    pub(crate) fn spawn<ErrType: ErrTypeTraits>(
+        &self,
+        name: Arc<str>,
+        hooked: Option<HookedTask>,
+        future: impl Future<Output = Result<(), ErrType>> + Send + 'static,
+        raise_failure: impl FnOnce(SubsystemError<ErrType>) + Send + 'static,
+    ) {
+        let counters = Arc::clone(&self.counters);
+        let abort_token = self.abort_token.clone();
+
+        counters.started.fetch_add(1, Ordering::Relaxed);
+        counters.running.send_modify(|running| *running += 1);
+
+        tokio::spawn(async move {
+            let result = tokio::select! {
+                // Also catches panics of the hooks, so that the counters stay consistent.
+                result = CatchUnwind { future: run_hooked(hooked, mark_subsystem(future)) } => result,
+                _ = abort_token.cancelled() => {
+                    counters.aborted.fetch_add(1, Ordering::Relaxed);
+                    Ok(Some(Ok(())))
+                },
+            };
+
+            match result {
+                Ok(Some(Ok(()))) => (),
+                Ok(Some(Err(e))) => {
+                    counters.failed.fetch_add(1, Ordering::Relaxed);
+                    raise_failure(SubsystemError::Failed(name, SubsystemFailure(e)));
+                }
+                Err(()) => {
+                    counters.panicked.fetch_add(1, Ordering::Relaxed);
+                    raise_failure(SubsystemError::Panicked(name));
+                }
+                Ok(None) => {
+                    counters.failed.fetch_add(1, Ordering::Relaxed);
+                    raise_failure(SubsystemError::Internal(
+                        name,
+                        InternalError::SubsystemNotRun,
+                    ));
+                }
+            }
+
+            counters.running.send_modify(|running| *running -= 1);
+        });
+    }
+
+    /// Waits until all lightweight children are finished.
+    pub(crate) async fn wait_for_children(&self) {
+        let mut running = self.counters.running.subscribe();
+        // Can not fail, as `self` holds the sender.
+        let _ = running.wait_for(|&running| running == 0).await;
+    }
+
+    pub(crate) fn counts(&self) -> LightweightChildCounts {
+        LightweightChildCounts {
+            running: *self.counters.running.borrow(),
+            started: self.counters.started.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+            panicked: self.counters.panicked.load(Ordering::Relaxed),
+            aborted: self.counters.aborted.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Drop for LightweightChildren {
+    fn drop(&mut self) {
+        self.abort_token.cancel();
+    }
+}
+
+pin_project! {
+    /// Catches panics of the wrapped future, without spawning a separate task.
+    struct CatchUnwind<F> {
+        #[pin]
+        future: F,
+    }
+}
+
+impl<F: Future> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, ()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let future = self.project().future;
+        match catch_unwind(AssertUnwindSafe(|| future.poll(cx))) {
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Ok(Poll::Pending) => Poll::Pending,
+            Err(_) => Poll::Ready(Err(())),
+        }
+    }
+}
@@ -1,21 +1,53 @@
+mod drain_summary;
 mod error_collector;
+mod failure_history;
+mod lightweight_children;
 mod nested_subsystem;
+mod readiness;
+mod shutdown_acknowledgement;
+mod shutdown_deferral;
+mod shutdown_poller;
+mod shutdown_sequence;
 mod subsystem_builder;
 mod subsystem_finished_future;
 mod subsystem_handle;
+mod subsystem_metadata;
+mod subsystem_node;
+mod subsystem_scope;
+mod subsystem_state;
+mod subsystem_tree;
+mod transfer;
+mod tree_config;
+mod work_permit;
 
-use std::{
-    future::Future,
-    pin::Pin,
-    sync::{Arc, Mutex},
-};
+use std::{future::Future, pin::Pin, sync::Arc};
 
+pub use drain_summary::DrainSummary;
+pub use failure_history::FailureRecord;
+pub use lightweight_children::LightweightChildCounts;
+pub use shutdown_acknowledgement::ShutdownAcknowledgement;
+pub use shutdown_deferral::ShutdownDeferralGuard;
+pub use shutdown_poller::ShutdownPoller;
 pub use subsystem_builder::SubsystemBuilder;
 pub use subsystem_handle::SubsystemHandle;
+pub use subsystem_handle::WeakSubsystemHandle;
+pub use subsystem_metadata::SubsystemMetadata;
+pub use subsystem_node::SubsystemNode;
+pub use subsystem_scope::SubsystemScope;
+pub use subsystem_state::SubsystemState;
+pub use subsystem_tree::SubsystemTree;
+pub use work_permit::WorkPermit;
 
-pub(crate) use subsystem_handle::root_handle;
+pub(crate) use failure_history::FailureHistory;
+pub(crate) use shutdown_acknowledgement::ShutdownAcknowledgements;
+pub(crate) use subsystem_handle::{root_handle, RedirectedSubsystemHandle};
+pub(crate) use subsystem_state::SubsystemStateTracker;
+pub(crate) use tree_config::TreeConfig;
 
-use crate::{utils::JoinerTokenRef, ErrTypeTraits, ErrorAction};
+use crate::{
+    utils::{JoinerTokenRef, Mutex},
+    ErrTypeTraits, ErrorAction,
+};
 
 use atomic::Atomic;
 use tokio_util::sync::CancellationToken;
@@ -34,6 +66,10 @@ pub struct NestedSubsystem<ErrType: ErrTypeTraits> {
     cancellation_token: CancellationToken,
     errors: Mutex<error_collector::ErrorCollector<ErrType>>,
     error_actions: Arc<ErrorActions>,
+    state: SubsystemStateTracker,
+    failure_history: Arc<FailureHistory>,
+    // Only set for transferable subsystems.
+    transfer: Option<Arc<transfer::Transfer<ErrType>>>,
 }
 
 pub(crate) struct ErrorActions {
@@ -1,8 +1,13 @@
 use std::sync::atomic::Ordering;
 
-use crate::{errors::SubsystemJoinError, ErrTypeTraits, ErrorAction};
+use tokio::sync::watch;
 
-use super::{NestedSubsystem, SubsystemFinishedFuture};
+use crate::{
+    errors::{SubsystemJoinError, TransferError},
+    ErrTypeTraits, ErrorAction, SubsystemHandle, SubsystemState,
+};
+
+use super::{FailureRecord, NestedSubsystem, SubsystemFinishedFuture};
 
 impl<ErrType: ErrTypeTraits> NestedSubsystem<ErrType> {
     /// Wait for the subsystem to be finished.
@@ -52,7 +57,7 @@ impl<ErrType: ErrTypeTraits> NestedSubsystem<ErrType> {
     pub async fn join(&self) -> Result<(), SubsystemJoinError<ErrType>> {
         self.joiner.join().await;
 
-        let errors = self.errors.lock().unwrap().finish();
+        let errors = self.errors.lock().finish();
         if errors.is_empty() {
             Ok(())
         } else {
@@ -60,6 +65,58 @@ impl<ErrType: ErrTypeTraits> NestedSubsystem<ErrType> {
         }
     }
 
+    /// Moves the subsystem, together with all of its children, to a new parent.
+    ///
+    /// From then on, the subsystem follows the shutdown of the new parent instead of the old one,
+    /// the new parent waits for it to finish, and its errors get propagated to the new parent.
+    /// Its name stays the same.
+    ///
+    /// Only possible for subsystems that were started as
+    /// [`transferable`](crate::SubsystemBuilder::transferable).
+    ///
+    /// # Arguments
+    ///
+    /// * `new_parent` - The handle of the new parent.
+    ///
+    /// # Returns
+    ///
+    /// A [`TransferError`] if the subsystem could not be moved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::sync::mpsc;
+    /// use tokio_graceful_shutdown::{NestedSubsystem, SubsystemBuilder, SubsystemHandle};
+    ///
+    /// type Connection = NestedSubsystem<Box<dyn std::error::Error + Send + Sync>>;
+    ///
+    /// async fn connection(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn warmup(subsys: SubsystemHandle, pool: mpsc::Sender<Connection>) -> Result<()> {
+    ///     let connection = subsys.start(SubsystemBuilder::new("connection", connection).transferable());
+    ///     // ... warm up the connection ...
+    ///     pool.send(connection).await.ok();
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn pool(subsys: SubsystemHandle, mut connections: mpsc::Receiver<Connection>) -> Result<()> {
+    ///     while let Some(connection) = connections.recv().await {
+    ///         connection.transfer_to(&subsys)?;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn transfer_to(&self, new_parent: &SubsystemHandle<ErrType>) -> Result<(), TransferError> {
+        match &self.transfer {
+            Some(transfer) => new_parent.adopt_transferred(transfer),
+            None => Err(TransferError::NotTransferable),
+        }
+    }
+
     /// Signals the subsystem and all of its children to shut down.
     pub fn initiate_shutdown(&self) {
         self.cancellation_token.cancel()
@@ -90,4 +147,88 @@ impl<ErrType: ErrTypeTraits> NestedSubsystem<ErrType> {
     pub fn finished(&self) -> SubsystemFinishedFuture {
         SubsystemFinishedFuture::new(self.joiner.clone())
     }
+
+    /// Subscribes to the lifecycle state of the subsystem.
+    ///
+    /// Allows other components to react when the subsystem starts shutting down,
+    /// fails or finishes, without polling.
+    ///
+    /// Subsystems that get aborted because of a shutdown timeout remain in their last state.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, SubsystemState};
+    ///
+    /// async fn database(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     let database = subsys.start(SubsystemBuilder::new("database", database));
+    ///
+    ///     let mut database_state = database.state();
+    ///     tokio::spawn(async move {
+    ///         if let Ok(state) = database_state
+    ///             .wait_for(|state| *state == SubsystemState::Failed)
+    ///             .await
+    ///         {
+    ///             tracing::warn!("Database is {:?}, switching to read-only mode.", *state);
+    ///         }
+    ///     });
+    ///
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn state(&self) -> watch::Receiver<SubsystemState> {
+        self.state.subscribe()
+    }
+
+    /// How often the subsystem restarted its work.
+    ///
+    /// Restarts get reported through [`SubsystemHandle::record_restart`],
+    /// for example by a [`RetryingSubsystem`](crate::RetryingSubsystem).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::{sleep, Duration};
+    /// use tokio_graceful_shutdown::{IntoSubsystem, RetryingSubsystem, SubsystemBuilder, SubsystemHandle};
+    ///
+    /// async fn connect() -> Result<()> {
+    ///     Err(miette::miette!("Connection refused"))
+    /// }
+    ///
+    /// async fn supervisor(subsys: SubsystemHandle) -> Result<()> {
+    ///     let broker = subsys.start(SubsystemBuilder::new(
+    ///         "broker",
+    ///         RetryingSubsystem::new(|_: &SubsystemHandle| connect()).into_subsystem(),
+    ///     ));
+    ///
+    ///     while !subsys.is_shutdown_requested() {
+    ///         if broker.restart_count() >= 3 {
+    ///             for failure in broker.failure_history() {
+    ///                 tracing::warn!("Broker is flapping: {}", failure.summary);
+    ///             }
+    ///         }
+    ///         sleep(Duration::from_secs(1)).await;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn restart_count(&self) -> u32 {
+        self.failure_history.restart_count()
+    }
+
+    /// The most recent failures of the subsystem, oldest first.
+    ///
+    /// Includes the failures it restarted after, as well as the one it finished with.
+    /// Only a limited number of failures is kept; older ones get dropped.
+    pub fn failure_history(&self) -> Vec<FailureRecord> {
+        self.failure_history.recent()
+    }
 }
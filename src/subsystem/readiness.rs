@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use tokio::sync::watch;
+
+/// Keeps track of the subsystems of a tree that did not report readiness yet.
+pub(crate) struct Readiness {
+    pending: watch::Sender<usize>,
+}
+
+impl Readiness {
+    pub(crate) fn new() -> Self {
+        Self {
+            pending: watch::channel(0).0,
+        }
+    }
+
+    /// Registers a subsystem that is not ready until the returned value gets dropped.
+    pub(crate) fn register(self: &Arc<Self>) -> PendingReadiness {
+        self.pending.send_modify(|pending| *pending += 1);
+        PendingReadiness {
+            readiness: Arc::clone(self),
+        }
+    }
+
+    /// Waits until all registered subsystems are ready.
+    pub(crate) async fn wait_ready(&self) {
+        let mut pending = self.pending.subscribe();
+        // Can not fail, as `self` holds the sender.
+        let _ = pending.wait_for(|&pending| pending == 0).await;
+    }
+}
+
+/// Marks a subsystem as not ready yet.
+pub(crate) struct PendingReadiness {
+    readiness: Arc<Readiness>,
+}
+
+impl Drop for PendingReadiness {
+    fn drop(&mut self) {
+        self.readiness.pending.send_modify(|pending| *pending -= 1);
+    }
+}
@@ -0,0 +1,98 @@
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use crate::shutdown_statistics::ShutdownStatisticsCollector;
+
+/// The shutdown acknowledgments of a subsystem.
+pub(crate) struct ShutdownAcknowledgements {
+    acknowledged: AtomicBool,
+    // The number of acknowledgments whose cleanup is not done yet.
+    pending: AtomicUsize,
+    shutdown_statistics: Arc<ShutdownStatisticsCollector>,
+}
+
+/// How far a subsystem got with its shutdown, according to its acknowledgments.
+///
+/// Used to diagnose subsystems that did not finish in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ShutdownProgress {
+    /// The subsystem never acknowledged the shutdown.
+    Unacknowledged,
+    /// The subsystem acknowledged the shutdown and is still cleaning up.
+    Draining,
+    /// The subsystem finished its cleanup, but did not return yet.
+    CleanedUp,
+}
+
+impl ShutdownAcknowledgements {
+    pub(crate) fn new(shutdown_statistics: Arc<ShutdownStatisticsCollector>) -> Self {
+        Self {
+            acknowledged: AtomicBool::new(false),
+            pending: AtomicUsize::new(0),
+            shutdown_statistics,
+        }
+    }
+
+    pub(crate) fn acknowledge(self: &Arc<Self>) -> ShutdownAcknowledgement {
+        let first = !self.acknowledged.swap(true, Ordering::AcqRel);
+        let draining = self.pending.fetch_add(1, Ordering::AcqRel) == 0;
+        self.shutdown_statistics
+            .record_acknowledgement(first, draining);
+
+        ShutdownAcknowledgement {
+            acknowledgements: Arc::clone(self),
+        }
+    }
+
+    pub(crate) fn progress(&self) -> ShutdownProgress {
+        if !self.acknowledged.load(Ordering::Acquire) {
+            ShutdownProgress::Unacknowledged
+        } else if self.pending.load(Ordering::Acquire) > 0 {
+            ShutdownProgress::Draining
+        } else {
+            ShutdownProgress::CleanedUp
+        }
+    }
+}
+
+impl fmt::Display for ShutdownProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Unacknowledged => "it did not acknowledge the shutdown",
+            Self::Draining => "it is still draining",
+            Self::CleanedUp => "it finished its cleanup, but did not return",
+        })
+    }
+}
+
+/// Acknowledges a shutdown request of a subsystem until its cleanup is done.
+///
+/// Returned by [`SubsystemHandle::acknowledge_shutdown`](crate::SubsystemHandle::acknowledge_shutdown).
+///
+/// Dropping this guard, or calling [`done`](Self::done), marks the cleanup as done.
+#[must_use = "The cleanup is marked as done once the acknowledgment gets dropped"]
+pub struct ShutdownAcknowledgement {
+    acknowledgements: Arc<ShutdownAcknowledgements>,
+}
+
+impl ShutdownAcknowledgement {
+    /// Marks the cleanup as done.
+    ///
+    /// Equivalent to dropping the acknowledgment, but more explicit.
+    pub fn done(self) {}
+}
+
+impl Drop for ShutdownAcknowledgement {
+    fn drop(&mut self) {
+        if self.acknowledgements.pending.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.acknowledgements
+                .shutdown_statistics
+                .record_cleanup_done();
+        }
+    }
+}
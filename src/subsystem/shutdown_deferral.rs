@@ -0,0 +1,89 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use tokio::{sync::watch, time::Instant};
+
+use crate::clock::SharedClock;
+
+/// The set of currently active shutdown deferrals of a subsystem.
+///
+/// Every deferral carries a deadline after which it expires automatically.
+pub(crate) struct ShutdownDeferrals {
+    deadlines: watch::Sender<HashMap<u64, Instant>>,
+    next_id: AtomicU64,
+    clock: SharedClock,
+}
+
+impl ShutdownDeferrals {
+    pub(crate) fn new(clock: SharedClock) -> Self {
+        Self {
+            deadlines: watch::channel(HashMap::new()).0,
+            next_id: AtomicU64::new(0),
+            clock,
+        }
+    }
+
+    pub(crate) fn defer(self: &Arc<Self>, deadline: Instant) -> ShutdownDeferralGuard {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.deadlines.send_modify(|deadlines| {
+            deadlines.insert(id, deadline);
+        });
+
+        ShutdownDeferralGuard {
+            deferrals: Arc::clone(self),
+            id,
+        }
+    }
+
+    /// Whether at least one deferral is active and not expired yet.
+    pub(crate) fn is_deferred(&self) -> bool {
+        let now = self.clock.now();
+        self.deadlines
+            .borrow()
+            .values()
+            .any(|deadline| *deadline > now)
+    }
+
+    /// Waits until all deferrals are released or expired.
+    pub(crate) async fn wait_for_release(&self) {
+        let mut subscriber = self.deadlines.subscribe();
+
+        loop {
+            let latest_deadline = subscriber.borrow_and_update().values().max().copied();
+
+            match latest_deadline {
+                None => return,
+                Some(deadline) => {
+                    tokio::select! {
+                        _ = self.clock.sleep_until(deadline) => return,
+                        _ = subscriber.changed() => (),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Defers the delivery of a shutdown request to its subsystem while it is held.
+///
+/// Returned by [`SubsystemHandle::defer_shutdown`](crate::SubsystemHandle::defer_shutdown).
+///
+/// Dropping this guard releases the deferral.
+#[must_use = "The shutdown is only deferred while the guard is held"]
+pub struct ShutdownDeferralGuard {
+    deferrals: Arc<ShutdownDeferrals>,
+    id: u64,
+}
+
+impl Drop for ShutdownDeferralGuard {
+    fn drop(&mut self) {
+        self.deferrals.deadlines.send_modify(|deadlines| {
+            deadlines.remove(&self.id);
+        });
+    }
+}
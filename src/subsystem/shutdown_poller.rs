@@ -0,0 +1,74 @@
+use crate::{BoxedError, ErrTypeTraits, SubsystemHandle};
+
+/// A cheap, possibly stale view of whether a shutdown was requested, for hot loops.
+///
+/// Checking [`SubsystemHandle::is_shutdown_requested`] briefly takes the lock of the
+/// subsystem's cancellation token; doing that for every packet or row of a
+/// high-throughput loop adds up, and threads that poll the same subsystem
+/// concurrently contend on it.
+/// The poller instead only checks every `check_interval` calls and answers from a
+/// local copy in between, so most calls are a counter decrement.
+///
+/// The answer is stale for at most `check_interval - 1` calls after the shutdown
+/// was requested; once it is `true`, it stays `true`.
+///
+/// Created through [`SubsystemHandle::shutdown_poller`].
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio_graceful_shutdown::SubsystemHandle;
+///
+/// async fn checksum(subsys: SubsystemHandle, rows: &[u64]) -> Result<u64> {
+///     let mut poller = subsys.shutdown_poller(1024);
+///     let mut checksum = 0u64;
+///     for row in rows {
+///         if poller.is_shutdown_requested_fast() {
+///             break;
+///         }
+///         checksum = checksum.wrapping_add(*row);
+///     }
+///     Ok(checksum)
+/// }
+/// ```
+pub struct ShutdownPoller<'a, ErrType: ErrTypeTraits = BoxedError> {
+    subsys: &'a SubsystemHandle<ErrType>,
+    check_interval: u32,
+    calls_until_check: u32,
+    shutdown_requested: bool,
+}
+
+impl<'a, ErrType: ErrTypeTraits> ShutdownPoller<'a, ErrType> {
+    pub(crate) fn new(subsys: &'a SubsystemHandle<ErrType>, check_interval: u32) -> Self {
+        Self {
+            subsys,
+            check_interval: check_interval.max(1),
+            // The first call always checks.
+            calls_until_check: 0,
+            shutdown_requested: false,
+        }
+    }
+
+    /// Whether a shutdown was requested, as of the last check.
+    ///
+    /// Checks the actual state of the subsystem on the first call
+    /// and on every `check_interval`th call after that.
+    pub fn is_shutdown_requested_fast(&mut self) -> bool {
+        if !self.shutdown_requested {
+            if self.calls_until_check == 0 {
+                self.shutdown_requested = self.subsys.is_shutdown_requested();
+                self.calls_until_check = self.check_interval;
+            }
+            self.calls_until_check -= 1;
+        }
+        self.shutdown_requested
+    }
+
+    /// Checks the actual state of the subsystem right away,
+    /// like [`SubsystemHandle::is_shutdown_requested`], and updates the local copy.
+    pub fn refresh(&mut self) -> bool {
+        self.calls_until_check = 0;
+        self.is_shutdown_requested_fast()
+    }
+}
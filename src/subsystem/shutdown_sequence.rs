@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::utils::{JoinerTokenRef, Mutex};
+
+/// Shuts down the children of a subsystem one at a time, in a seeded order.
+///
+/// Only used with [`ToplevelBuilder::deterministic_shutdown`](crate::ToplevelBuilder::deterministic_shutdown).
+pub(crate) struct ShutdownSequence {
+    seed: u64,
+    state: Mutex<SequenceState>,
+}
+
+enum SequenceState {
+    // No child got registered yet, so nobody waits for the shutdown.
+    Idle,
+    Collecting(Vec<SequencedChild>),
+    Started,
+}
+
+struct SequencedChild {
+    name: Arc<str>,
+    cancellation_token: CancellationToken,
+    joiner: JoinerTokenRef,
+}
+
+impl ShutdownSequence {
+    /// Mixes the name of the subsystem into the seed, so that the children of
+    /// different subsystems do not all get shuffled the same way.
+    pub(crate) fn new(seed: u64, name: &str) -> Self {
+        // FNV-1a
+        let name_hash = name.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+
+        Self {
+            seed: seed ^ name_hash,
+            state: Mutex::new(SequenceState::Idle),
+        }
+    }
+
+    /// Adds a child to the sequence.
+    ///
+    /// If the sequence already started, the child gets cancelled right away instead.
+    /// Returns `true` for the first child; the caller then has to spawn [`run`](Self::run).
+    pub(crate) fn register(
+        &self,
+        name: Arc<str>,
+        cancellation_token: CancellationToken,
+        joiner: JoinerTokenRef,
+    ) -> bool {
+        let child = SequencedChild {
+            name,
+            cancellation_token,
+            joiner,
+        };
+
+        let mut state = self.state.lock();
+        match &mut *state {
+            SequenceState::Idle => {
+                *state = SequenceState::Collecting(vec![child]);
+                true
+            }
+            SequenceState::Collecting(children) => {
+                // Don't accumulate the children of long running subsystems.
+                children.retain(|child| !child.joiner.is_finished());
+                children.push(child);
+                false
+            }
+            SequenceState::Started => {
+                child.cancellation_token.cancel();
+                false
+            }
+        }
+    }
+
+    /// Waits for `trigger`, then cancels and joins the children one at a time.
+    ///
+    /// Returns early if the subsystem finishes without a shutdown.
+    pub(crate) async fn run(self: Arc<Self>, trigger: CancellationToken, joiner: JoinerTokenRef) {
+        tokio::select! {
+            biased;
+            _ = joiner.join() => return,
+            _ = trigger.cancelled() => (),
+        }
+
+        let mut children = match std::mem::replace(&mut *self.state.lock(), SequenceState::Started)
+        {
+            SequenceState::Collecting(children) => children,
+            SequenceState::Idle | SequenceState::Started => Vec::new(),
+        };
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+        shuffle(&mut children, self.seed);
+
+        for child in children {
+            child.cancellation_token.cancel();
+            child.joiner.join().await;
+        }
+    }
+}
+
+/// Fisher-Yates shuffle, driven by SplitMix64.
+fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    };
+
+    for i in (1..items.len()).rev() {
+        let j = (next() % (i as u64 + 1)) as usize;
+        items.swap(i, j);
+    }
+}
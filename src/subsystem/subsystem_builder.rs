@@ -1,6 +1,13 @@
-use std::{borrow::Cow, future::Future, marker::PhantomData};
+use std::{borrow::Cow, future::Future, marker::PhantomData, pin::Pin, time::Duration};
 
-use crate::{ErrTypeTraits, ErrorAction, SubsystemHandle};
+use tokio::sync::Semaphore;
+use tracing::level_filters::LevelFilter;
+
+use crate::{ErrTypeTraits, ErrorAction, SubsystemHandle, SubsystemMetadata};
+
+/// An async step that runs before the children of a subsystem get signaled to shut down.
+pub(crate) type PreShutdownHook =
+    Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
 
 /// Configures a subsystem before it gets spawned through
 /// [`SubsystemHandle::start`].
@@ -16,6 +23,18 @@ where
     pub(crate) failure_action: ErrorAction,
     pub(crate) panic_action: ErrorAction,
     pub(crate) detached: bool,
+    pub(crate) transferable: bool,
+    pub(crate) shutdown_group: Option<Cow<'a, str>>,
+    pub(crate) metadata: SubsystemMetadata,
+    pub(crate) max_children: Option<usize>,
+    pub(crate) max_work_permits: Option<usize>,
+    pub(crate) runtime: Option<tokio::runtime::Handle>,
+    pub(crate) pre_shutdown: Option<(Duration, PreShutdownHook)>,
+    pub(crate) shutdown_timeout: Option<Duration>,
+    pub(crate) lifecycle_log_level: Option<LevelFilter>,
+    pub(crate) reports_readiness: bool,
+    // Only set for the sidecars of a `Toplevel`.
+    pub(crate) sidecar: bool,
     #[allow(clippy::type_complexity)]
     _phantom: PhantomData<fn() -> (Fut, ErrType, Err)>,
 }
@@ -33,7 +52,7 @@ where
     /// # Arguments
     ///
     /// * `name` - The name of the subsystem. Primarily to identify the
-    ///            subsystem in error messages.
+    ///   subsystem in error messages.
     /// * `subsystem` - The subsystem function that the subsystem will execute.
     pub fn new(name: impl Into<Cow<'a, str>>, subsystem: Subsys) -> Self {
         Self {
@@ -42,6 +61,17 @@ where
             failure_action: ErrorAction::Forward,
             panic_action: ErrorAction::Forward,
             detached: false,
+            transferable: false,
+            shutdown_group: None,
+            metadata: SubsystemMetadata::default(),
+            max_children: None,
+            max_work_permits: None,
+            runtime: None,
+            pre_shutdown: None,
+            shutdown_timeout: None,
+            lifecycle_log_level: None,
+            reports_readiness: false,
+            sidecar: false,
             _phantom: Default::default(),
         }
     }
@@ -68,6 +98,41 @@ where
         self
     }
 
+    /// Isolates errors in this subsystem and its children from the rest of the subsystem tree.
+    ///
+    /// Failures and panics anywhere in this subtree only shut down the subtree
+    /// instead of the entire program. The errors can be retrieved through
+    /// [`NestedSubsystem::join`](crate::NestedSubsystem::join).
+    ///
+    /// Shorthand for setting both [`on_failure`](Self::on_failure) and
+    /// [`on_panic`](Self::on_panic) to [`ErrorAction::CatchAndLocalShutdown`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle};
+    ///
+    /// async fn tenant_pipeline(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     // A failing tenant does not take down the other tenants.
+    ///     for tenant in ["tenant_a", "tenant_b"] {
+    ///         subsys.start(SubsystemBuilder::new(tenant, tenant_pipeline).isolate_errors());
+    ///     }
+    ///
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn isolate_errors(self) -> Self {
+        self.on_failure(ErrorAction::CatchAndLocalShutdown)
+            .on_panic(ErrorAction::CatchAndLocalShutdown)
+    }
+
     /// Detaches the subsystem from the parent, causing a shutdown request to not
     /// be propagated from the parent to the child automatically.
     ///
@@ -78,4 +143,255 @@ where
         self.detached = true;
         self
     }
+
+    /// Allows moving the subsystem to a different parent while it is running,
+    /// through [`NestedSubsystem::transfer_to`](crate::NestedSubsystem::transfer_to).
+    ///
+    /// Useful for handing off work between supervisors, like moving accepted
+    /// connections from a warmup supervisor to the main pool.
+    ///
+    /// Transferable subsystems can not be part of a
+    /// [`shutdown_group`](Self::shutdown_group), as they follow the shutdown of their parent.
+    pub fn transferable(mut self) -> Self {
+        self.transferable = true;
+        self
+    }
+
+    /// Limits how long this subsystem may take to shut down.
+    ///
+    /// If the subsystem does not finish within the given time after it received
+    /// a shutdown request, it gets aborted and reported as [`SubsystemError::Aborted`](crate::errors::SubsystemError::Aborted).
+    /// This error is handled like a failure; see [`on_failure`](Self::on_failure).
+    ///
+    /// Its children are not affected; they can be limited individually.
+    ///
+    /// By default, only the shutdown timeout of the entire subsystem tree applies.
+    ///
+    /// # Arguments
+    ///
+    /// * `shutdown_timeout` - The maximum time the subsystem may take to shut down.
+    pub fn shutdown_timeout(mut self, shutdown_timeout: Duration) -> Self {
+        self.shutdown_timeout = Some(shutdown_timeout);
+        self
+    }
+
+    /// Assigns the subsystem to a shutdown group.
+    ///
+    /// Subsystems in a shutdown group do not receive the shutdown request of the
+    /// entire subsystem tree right away; instead, the groups get shut down one
+    /// after another, each within its own time budget.
+    /// Groups are configured through [`ToplevelBuilder::shutdown_group`](crate::ToplevelBuilder::shutdown_group).
+    ///
+    /// Local shutdowns of the parent still reach the subsystem immediately.
+    ///
+    /// # Arguments
+    ///
+    /// * `shutdown_group` - The name of the shutdown group.
+    pub fn shutdown_group(mut self, shutdown_group: impl Into<Cow<'a, str>>) -> Self {
+        self.shutdown_group = Some(shutdown_group.into());
+        self
+    }
+
+    /// Attaches a key/value pair of metadata to the subsystem, like `tenant=acme`.
+    ///
+    /// Setting the same key again overwrites its value.
+    ///
+    /// For more information, see [`SubsystemMetadata`].
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of the metadata entry.
+    /// * `value` - The value of the metadata entry.
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Sets the level of the lifecycle logs of this subsystem, like when it starts and finishes.
+    ///
+    /// Allows silencing chatty subsystems through [`LevelFilter::OFF`], or making the
+    /// lifecycle of a flaky subsystem visible through [`LevelFilter::INFO`].
+    /// Warnings and errors, like aborted subsystems, are not affected.
+    ///
+    /// Subsystems with a custom level log within a `subsystem` span that carries their `name`,
+    /// so their other logs can be filtered as well, for example through
+    /// `RUST_LOG=info,[subsystem{name=/acceptor}]=warn`.
+    ///
+    /// The level is inherited by the children of this subsystem, unless they set their own.
+    /// Defaults to [`LevelFilter::DEBUG`].
+    ///
+    /// # Arguments
+    ///
+    /// * `level` - The level of the lifecycle logs.
+    pub fn lifecycle_log_level(mut self, level: impl Into<LevelFilter>) -> Self {
+        self.lifecycle_log_level = Some(level.into());
+        self
+    }
+
+    /// Makes the subsystem count as not ready until it calls
+    /// [`SubsystemHandle::mark_ready`], or until it finishes.
+    ///
+    /// [Sidecars](crate::ToplevelBuilder::sidecar) only start once all subsystems
+    /// that report readiness are ready. Subsystems that do not report readiness
+    /// count as ready right away.
+    pub fn reports_readiness(mut self) -> Self {
+        self.reports_readiness = true;
+        self
+    }
+
+    /// Limits the number of children that this subsystem can have at the same time.
+    ///
+    /// Provides backpressure for acceptor-style subsystems that spawn a child
+    /// per connection. Beyond the limit, [`SubsystemHandle::try_start`] returns an error,
+    /// and [`SubsystemHandle::start_when_available`] waits until another child finished.
+    /// All other ways of starting a child, like [`SubsystemHandle::start`],
+    /// [`SubsystemHandle::adopt`] or [`SubsystemHandle::start_tree`], do not start it;
+    /// the child fails with [`InternalError::ChildLimitReached`](crate::errors::InternalError::ChildLimitReached)
+    /// instead. This error is only reported through the [`NestedSubsystem`](crate::NestedSubsystem)
+    /// of the child and never forwarded to its parent.
+    ///
+    /// By default, the number of children is not limited.
+    /// Limits above [`Semaphore::MAX_PERMITS`](tokio::sync::Semaphore::MAX_PERMITS)
+    /// get lowered to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_children` - The maximum number of concurrently running children.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_children` is zero, as no child could ever be started.
+    pub fn max_children(mut self, max_children: usize) -> Self {
+        assert!(max_children > 0, "max_children must be at least 1");
+        self.max_children = Some(max_children.min(Semaphore::MAX_PERMITS));
+        self
+    }
+
+    /// Limits the number of work permits that this subsystem can hand out at the same time.
+    ///
+    /// Beyond the limit, [`SubsystemHandle::acquire_permit`] waits until another permit
+    /// got released.
+    ///
+    /// By default, the number of work permits is not limited.
+    /// Limits above [`Semaphore::MAX_PERMITS`](tokio::sync::Semaphore::MAX_PERMITS)
+    /// get lowered to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_work_permits` - The maximum number of outstanding work permits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_work_permits` is zero, as no permit could ever be acquired.
+    pub fn max_work_permits(mut self, max_work_permits: usize) -> Self {
+        assert!(max_work_permits > 0, "max_work_permits must be at least 1");
+        self.max_work_permits = Some(max_work_permits.min(Semaphore::MAX_PERMITS));
+        self
+    }
+
+    /// Spawns the subsystem onto the given runtime instead of the current one.
+    ///
+    /// This allows placing heavy subsystems on a dedicated runtime while
+    /// they still remain part of the same subsystem tree.
+    /// Nested subsystems of this subsystem will be spawned onto
+    /// the current runtime of the subsystem, which is the given runtime,
+    /// unless configured otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `runtime` - The [`Handle`](tokio::runtime::Handle) of the runtime to spawn the subsystem on.
+    pub fn runtime(mut self, runtime: tokio::runtime::Handle) -> Self {
+        self.runtime = Some(runtime);
+        self
+    }
+
+    /// Registers an async step that runs once a shutdown of this subsystem was requested,
+    /// before its children get signaled to shut down.
+    ///
+    /// Designed for clustered services, where a subsystem has to hand off its leadership
+    /// or deregister from service discovery while its workers are still running.
+    /// The subsystem itself receives the shutdown request right away; only the
+    /// shutdown requests of its children get delayed until the hook finished.
+    ///
+    /// If the hook does not finish within the given timeout, it gets cancelled and
+    /// the children get signaled anyway.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout` - The maximum time the hook is allowed to take.
+    /// * `hook` - The async step that should be executed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle};
+    ///
+    /// async fn deregister_from_service_discovery() {
+    ///     tracing::info!("Deregistering ...");
+    /// }
+    ///
+    /// async fn worker(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn node(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.start(SubsystemBuilder::new("worker", worker));
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn root(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.start(
+    ///         SubsystemBuilder::new("node", node)
+    ///             .pre_shutdown(Duration::from_secs(5), deregister_from_service_discovery),
+    ///     );
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn pre_shutdown<Hook, HookFut>(mut self, timeout: Duration, hook: Hook) -> Self
+    where
+        Hook: FnOnce() -> HookFut + Send + 'static,
+        HookFut: Future<Output = ()> + Send + 'static,
+    {
+        self.pre_shutdown = Some((timeout, Box::new(move || Box::pin(hook()))));
+        self
+    }
+
+    /// Replaces the subsystem function, keeping all other settings.
+    ///
+    /// Also takes ownership of all borrowed settings, so the result can be stored.
+    pub(crate) fn map_subsystem<Fut2, Subsys2>(
+        self,
+        map: impl FnOnce(Subsys) -> Subsys2,
+    ) -> SubsystemBuilder<'static, ErrType, Err, Fut2, Subsys2>
+    where
+        Subsys2: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut2 + Send,
+        Fut2: 'static + Future<Output = Result<(), Err>> + Send,
+    {
+        SubsystemBuilder {
+            name: Cow::Owned(self.name.into_owned()),
+            subsystem: map(self.subsystem),
+            failure_action: self.failure_action,
+            panic_action: self.panic_action,
+            detached: self.detached,
+            transferable: self.transferable,
+            shutdown_group: self
+                .shutdown_group
+                .map(|shutdown_group| Cow::Owned(shutdown_group.into_owned())),
+            metadata: self.metadata,
+            max_children: self.max_children,
+            max_work_permits: self.max_work_permits,
+            runtime: self.runtime,
+            pre_shutdown: self.pre_shutdown,
+            shutdown_timeout: self.shutdown_timeout,
+            lifecycle_log_level: self.lifecycle_log_level,
+            reports_readiness: self.reports_readiness,
+            sidecar: self.sidecar,
+            _phantom: Default::default(),
+        }
+    }
 }
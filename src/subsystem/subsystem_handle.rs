@@ -1,42 +1,183 @@
 use std::{
     future::Future,
-    mem::ManuallyDrop,
-    sync::{atomic::Ordering, Arc, Mutex},
+    sync::{atomic::Ordering, Arc, OnceLock, Weak},
+    time::Duration,
 };
 
 use atomic::Atomic;
-use tokio::sync::{mpsc, oneshot};
+use tokio::sync::{mpsc, oneshot, watch, OwnedSemaphorePermit, Semaphore};
 use tokio_util::sync::CancellationToken;
+use tracing::level_filters::LevelFilter;
 
 use crate::{
-    errors::{handle_dropped_error, SubsystemError},
-    runner::{AliveGuard, SubsystemRunner},
-    utils::{remote_drop_collection::RemotelyDroppableItems, JoinerToken},
-    BoxedError, ErrTypeTraits, ErrorAction, NestedSubsystem, SubsystemBuilder,
+    clock::SharedClock,
+    errors::{
+        handle_dropped_error, handle_unhandled_stopreason, CancelledByShutdown, ChildLimitReached,
+        InternalError, ShutdownTimeoutElapsed, SubsystemError, TransferError,
+    },
+    runner::{AliveGuard, SubsystemRunner, SubsystemRunnerRef, TaskSpawner},
+    shared_resources::SharedResources,
+    shutdown_announcement::ShutdownAnnouncement,
+    shutdown_groups::ShutdownGroups,
+    shutdown_statistics::{ShutdownStatistics, ShutdownStatisticsCollector},
+    spawn_hook::HookedTask,
+    task_dump::SubsystemTask,
+    testing::{Instrumentation, LifecycleEventKind},
+    utils::{
+        log_lifecycle, remote_drop_collection::RemotelyDroppableItems, resume_panic, JoinerToken,
+        JoinerTokenRef, Mutex, ReparentError, DEFAULT_LIFECYCLE_LOG_LEVEL,
+    },
+    BoxedError, DrainSummary, ErrTypeTraits, ErrorAction, ExitStatus, NestedSubsystem,
+    PlannedSubsystem, ShutdownPoller, ShutdownReason, ShutdownToken, StartupRacePolicy,
+    SubsystemBuilder, SubsystemMetadata, SubsystemNode, SubsystemTree,
 };
 
-use super::{error_collector::ErrorCollector, ErrorActions};
+use super::{
+    error_collector::ErrorCollector,
+    failure_history::{FailureHistory, FailureRecord},
+    lightweight_children::{LightweightChildCounts, LightweightChildren},
+    readiness::{PendingReadiness, Readiness},
+    shutdown_acknowledgement::{ShutdownAcknowledgement, ShutdownAcknowledgements},
+    shutdown_deferral::{ShutdownDeferralGuard, ShutdownDeferrals},
+    shutdown_sequence::ShutdownSequence,
+    subsystem_builder::PreShutdownHook,
+    subsystem_scope::SubsystemScope,
+    subsystem_state::SubsystemStateTracker,
+    transfer::{forward_owner_shutdown, Registration, Transfer, TreeId},
+    work_permit::{WorkPermit, WorkPermits},
+    ErrorActions, TreeConfig,
+};
 
 struct Inner<ErrType: ErrTypeTraits> {
-    name: Arc<str>,
+    node: SubsystemNode,
+    // Only set if configured by this subsystem or one of its ancestors.
+    lifecycle_log_level: Option<LevelFilter>,
+    // A child of the parent's `children_cancellation_token`; it gets cancelled together
+    // with its parent, but can also be cancelled on its own for a partial shutdown.
     cancellation_token: CancellationToken,
+    // Differs from `cancellation_token` if the children get signaled after a pre-shutdown hook.
+    children_cancellation_token: CancellationToken,
     toplevel_cancellation_token: CancellationToken,
     joiner_token: JoinerToken<ErrType>,
     children: RemotelyDroppableItems<SubsystemRunner>,
+    // Only set in deterministic shutdown mode.
+    shutdown_sequence: Option<Arc<ShutdownSequence>>,
+    // Only set if the number of children is limited.
+    child_permits: Option<Arc<Semaphore>>,
+    // Allocated lazily, as most subsystems never defer their shutdown.
+    shutdown_deferrals: OnceLock<Arc<ShutdownDeferrals>>,
+    // Allocated lazily, as most subsystems never acquire work permits.
+    work_permits: OnceLock<Arc<WorkPermits>>,
+    max_work_permits: Option<usize>,
+    // Allocated lazily, as most subsystems never start lightweight children.
+    lightweight_children: OnceLock<LightweightChildren>,
+    exit_status: Mutex<Option<ExitStatus>>,
+    task: SubsystemTask,
+    shutdown_statistics: Arc<ShutdownStatisticsCollector>,
+    tree_id: TreeId,
+    shutdown_acknowledgements: Arc<ShutdownAcknowledgements>,
+    shutdown_groups: Arc<ShutdownGroups>,
+    shared_resources: Arc<SharedResources>,
+    readiness: Arc<Readiness>,
+    shutdown_announcement: Arc<ShutdownAnnouncement>,
+    // Only set while a subsystem that reports readiness is not ready yet.
+    pending_readiness: Mutex<Option<PendingReadiness>>,
+    failure_history: Arc<FailureHistory>,
+    config: Arc<TreeConfig<ErrType>>,
+    // Only configured by testing utilities; shared by the entire tree.
+    instrumentation: Arc<Instrumentation>,
+    clock: SharedClock,
 }
 
 /// The handle given to each subsystem through which the subsystem can interact with this crate.
 pub struct SubsystemHandle<ErrType: ErrTypeTraits = BoxedError> {
-    inner: ManuallyDrop<Inner<ErrType>>,
+    // Only shared with handles upgraded from a `WeakSubsystemHandle`.
+    inner: Arc<Inner<ErrType>>,
     // When dropped, redirect Self into this channel.
     // Required as a workaround for https://stackoverflow.com/questions/77172947/async-lifetime-issues-of-pass-by-reference-parameters.
-    drop_redirect: Option<oneshot::Sender<WeakSubsystemHandle<ErrType>>>,
+    drop_redirect: Option<oneshot::Sender<RedirectedSubsystemHandle<ErrType>>>,
+}
+
+/// A reference to a subsystem that does not keep it alive.
+///
+/// Intended for observers like metrics scrapers or admin interfaces
+/// that occasionally need to interact with a subsystem, but should not
+/// prevent it from finishing.
+///
+/// Created through [`SubsystemHandle::downgrade`].
+pub struct WeakSubsystemHandle<ErrType: ErrTypeTraits = BoxedError> {
+    inner: Weak<Inner<ErrType>>,
+}
+
+impl<ErrType: ErrTypeTraits> Clone for WeakSubsystemHandle<ErrType> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Weak::clone(&self.inner),
+        }
+    }
+}
+
+impl<ErrType: ErrTypeTraits> WeakSubsystemHandle<ErrType> {
+    /// Attempts to get a [`SubsystemHandle`] to the subsystem.
+    ///
+    /// The returned handle keeps the subsystem alive, so it should
+    /// only be held as long as it is needed.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the subsystem and all of its children are already finished.
+    pub fn upgrade(&self) -> Option<SubsystemHandle<ErrType>> {
+        self.inner.upgrade().map(|inner| SubsystemHandle {
+            inner,
+            drop_redirect: None,
+        })
+    }
+}
+
+pub(crate) struct RedirectedSubsystemHandle<ErrType: ErrTypeTraits> {
+    inner: Arc<Inner<ErrType>>,
+    // Kept separately, so the children stay alive while waiting for them
+    children: RemotelyDroppableItems<SubsystemRunner>,
 }
 
-pub(crate) struct WeakSubsystemHandle<ErrType: ErrTypeTraits> {
-    pub(crate) joiner_token: JoinerToken<ErrType>,
-    // Children are stored here to keep them alive
-    _children: RemotelyDroppableItems<SubsystemRunner>,
+impl<ErrType: ErrTypeTraits> RedirectedSubsystemHandle<ErrType> {
+    /// Recovers the state of a subsystem whose handle got leaked out of it.
+    pub(crate) fn recover(subsystem_handle: &WeakSubsystemHandle<ErrType>) -> Option<Self> {
+        subsystem_handle.inner.upgrade().map(|inner| Self {
+            children: inner.children.clone(),
+            inner,
+        })
+    }
+
+    pub(crate) fn raise_failure(&self, failure: SubsystemError<ErrType>) {
+        self.inner.joiner_token.raise_failure(failure);
+    }
+
+    pub(crate) fn take_exit_status(&self) -> ExitStatus {
+        self.inner.exit_status.lock().take().unwrap_or_default()
+    }
+
+    pub(crate) fn strict_exit_statuses(&self) -> bool {
+        self.inner.config.strict_exit_statuses
+    }
+
+    /// Releases the subsystem and waits for all of its children to finish.
+    pub(crate) async fn join(self) {
+        // Finished subsystems do not hold back sidecars any more.
+        drop(self.inner.pending_readiness.lock().take());
+
+        if let Some(work_permits) = self.inner.work_permits.get() {
+            work_permits.wait_for_release().await;
+        }
+        if let Some(lightweight_children) = self.inner.lightweight_children.get() {
+            lightweight_children.wait_for_children().await;
+        }
+
+        let joiner_token_ref = self.inner.joiner_token.get_ref();
+        let _children = self.children;
+        drop(self.inner);
+        joiner_token_ref.join().await;
+    }
 }
 
 impl<ErrType: ErrTypeTraits> SubsystemHandle<ErrType> {
@@ -44,10 +185,17 @@ impl<ErrType: ErrTypeTraits> SubsystemHandle<ErrType> {
     ///
     /// Once called, the subsystem will be started immediately, similar to [`tokio::spawn`].
     ///
+    /// If this subsystem already has the [maximum number of children](SubsystemBuilder::max_children),
+    /// the subsystem does not get started and fails with
+    /// [`InternalError::ChildLimitReached`](crate::errors::InternalError::ChildLimitReached).
+    /// This error only gets reported through the returned [`NestedSubsystem`];
+    /// it is never forwarded, so hitting the limit does not shut down this subsystem.
+    /// To wait for a free slot instead, use [`start_when_available`](Self::start_when_available).
+    ///
     /// # Arguments
     ///
     /// * `builder` - The [`SubsystemBuilder`] that contains all the information
-    ///               about the subsystem that should be spawned.
+    ///   about the subsystem that should be spawned.
     ///
     /// # Returns
     ///
@@ -82,74 +230,559 @@ impl<ErrType: ErrTypeTraits> SubsystemHandle<ErrType> {
         Err: Into<ErrType>,
     {
         self.start_with_abs_name(
-            Arc::from(format!("{}/{}", self.inner.name, builder.name)),
-            builder.subsystem,
-            ErrorActions {
-                on_failure: Atomic::new(builder.failure_action),
-                on_panic: Atomic::new(builder.panic_action),
-            },
-            builder.detached,
+            self.child_name(&builder.name),
+            builder,
+            self.try_acquire_child_permit(),
         )
     }
 
+    /// Starts a pre-built tree of subsystems as children of this subsystem.
+    ///
+    /// For more information, see [`SubsystemTree`].
+    ///
+    /// Its root node counts towards the [maximum number of children](SubsystemBuilder::max_children);
+    /// beyond it, none of the tree gets started, like with [`start`](Self::start).
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The tree of subsystems that should be spawned.
+    ///
+    /// # Returns
+    ///
+    /// The [`NestedSubsystem`] of the root node of the tree.
+    pub fn start_tree(&self, tree: SubsystemTree<ErrType>) -> NestedSubsystem<ErrType> {
+        tree.start_on(self)
+    }
+
+    /// Starts a nested subsystem, unless this subsystem already has the maximum number of children.
+    ///
+    /// Behaves like [`start`](Self::start) otherwise.
+    ///
+    /// The maximum number of children can be configured through
+    /// [`SubsystemBuilder::max_children`]; if none is configured, this never fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `builder` - The [`SubsystemBuilder`] that contains all the information
+    ///   about the subsystem that should be spawned.
+    ///
+    /// # Returns
+    ///
+    /// A [`NestedSubsystem`] that can be used to control or join the subsystem,
+    /// or [`ChildLimitReached`] if the maximum number of children is reached.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle};
+    ///
+    /// async fn connection(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn acceptor(subsys: SubsystemHandle) -> Result<()> {
+    ///     // Started with `SubsystemBuilder::new("acceptor", acceptor).max_children(10000)`
+    ///     if subsys
+    ///         .try_start(SubsystemBuilder::new("connection", connection))
+    ///         .is_err()
+    ///     {
+    ///         tracing::warn!("Too many connections, rejecting connection.");
+    ///     }
+    ///
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn try_start<Err, Fut, Subsys>(
+        &self,
+        builder: SubsystemBuilder<ErrType, Err, Fut, Subsys>,
+    ) -> Result<NestedSubsystem<ErrType>, ChildLimitReached>
+    where
+        Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
+        Fut: 'static + Future<Output = Result<(), Err>> + Send,
+        Err: Into<ErrType>,
+    {
+        let permit = self.try_acquire_child_permit()?;
+        Ok(self.start_with_abs_name(self.child_name(&builder.name), builder, Ok(permit)))
+    }
+
+    /// Starts a nested subsystem, waiting until this subsystem has less than
+    /// the maximum number of children.
+    ///
+    /// Behaves like [`start`](Self::start) otherwise.
+    ///
+    /// The maximum number of children can be configured through
+    /// [`SubsystemBuilder::max_children`]; if none is configured, this never waits.
+    ///
+    /// # Arguments
+    ///
+    /// * `builder` - The [`SubsystemBuilder`] that contains all the information
+    ///   about the subsystem that should be spawned.
+    ///
+    /// # Returns
+    ///
+    /// A [`NestedSubsystem`] that can be used to control or join the subsystem.
+    pub async fn start_when_available<Err, Fut, Subsys>(
+        &self,
+        builder: SubsystemBuilder<'_, ErrType, Err, Fut, Subsys>,
+    ) -> NestedSubsystem<ErrType>
+    where
+        Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
+        Fut: 'static + Future<Output = Result<(), Err>> + Send,
+        Err: Into<ErrType>,
+    {
+        let permit = match &self.inner.child_permits {
+            // The semaphore never gets closed, so acquiring can not fail.
+            Some(child_permits) => Arc::clone(child_permits).acquire_owned().await.ok(),
+            None => None,
+        };
+        self.start_with_abs_name(self.child_name(&builder.name), builder, Ok(permit))
+    }
+
+    /// Places an already spawned task under the supervision of this subsystem.
+    ///
+    /// The task gets registered as a nested subsystem with the given name.
+    /// It is joined during shutdown, and its outcome appears in the shutdown report
+    /// like the one of any other subsystem; errors returned by the task
+    /// and panics inside of it are reported as failures.
+    ///
+    /// This is useful for integrating third-party libraries that spawn their own tasks.
+    ///
+    /// Counts towards the [maximum number of children](SubsystemBuilder::max_children);
+    /// beyond it, the task gets aborted, like with [`start`](Self::start).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the nested subsystem.
+    /// * `join_handle` - The handle of the task.
+    /// * `abort_on_shutdown` - Whether the task should get aborted once a shutdown is requested,
+    ///   instead of waiting for it to finish by itself.
+    ///
+    /// # Returns
+    ///
+    /// A [`NestedSubsystem`] that can be used to control or join the task.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::{sleep, Duration};
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     // Spawned by some library that knows nothing about graceful shutdowns
+    ///     let join_handle = tokio::spawn(async {
+    ///         sleep(Duration::from_secs(10)).await;
+    ///         Result::<()>::Ok(())
+    ///     });
+    ///
+    ///     subsys.adopt("library_task", join_handle, true);
+    ///
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn adopt<Err>(
+        &self,
+        name: &str,
+        join_handle: tokio::task::JoinHandle<Result<(), Err>>,
+        abort_on_shutdown: bool,
+    ) -> NestedSubsystem<ErrType>
+    where
+        Err: Into<ErrType> + Send + 'static,
+    {
+        // Don't leave the task running if the subsystem itself gets cancelled,
+        // or if it does not get started at all.
+        let abort_on_drop = AbortOnDrop(join_handle.abort_handle());
+        self.start(SubsystemBuilder::new(
+            name,
+            move |subsys: SubsystemHandle<ErrType>| async move {
+                let _abort_on_drop = abort_on_drop;
+                let mut join_handle = join_handle;
+
+                let join_result = if abort_on_shutdown {
+                    tokio::select! {
+                        join_result = &mut join_handle => join_result,
+                        _ = subsys.on_shutdown_requested() => {
+                            join_handle.abort();
+                            join_handle.await
+                        }
+                    }
+                } else {
+                    join_handle.await
+                };
+
+                match join_result {
+                    Ok(result) => result,
+                    Err(e) if e.is_panic() => resume_panic(e),
+                    Err(_) => {
+                        if !subsys.is_shutdown_requested() {
+                            tracing::warn!("Adopted task '{}' got aborted.", subsys.name());
+                        }
+                        Ok(())
+                    }
+                }
+            },
+        ))
+    }
+
+    /// Starts a lightweight child, like the handler of a single connection.
+    ///
+    /// Lightweight children skip the bookkeeping of regular subsystems: they do not
+    /// appear in the subsystem tree, in shutdown reports or in lifecycle logs, and are
+    /// only tracked through aggregate counters, see [`lightweight_child_counts`](Self::lightweight_child_counts).
+    /// This trades introspection detail for throughput, for acceptors that start
+    /// a child per connection.
+    ///
+    /// Lightweight children still get cancelled and joined like regular children:
+    /// the given [`CancellationToken`] gets cancelled once this subsystem shuts down,
+    /// and this subsystem does not finish before all of its lightweight children finished.
+    /// If this subsystem gets aborted, its lightweight children get aborted as well.
+    ///
+    /// Errors and panics of lightweight children are reported as failures of this subsystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `child` - The function that creates the future of the child,
+    ///   from the cancellation token of the child.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::net::{TcpListener, TcpStream};
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    /// use tokio_util::sync::CancellationToken;
+    ///
+    /// async fn connection(stream: TcpStream, cancellation_token: CancellationToken) -> Result<()> {
+    ///     cancellation_token.cancelled().await;
+    ///     drop(stream);
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn acceptor(subsys: SubsystemHandle) -> Result<()> {
+    ///     let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    ///
+    ///     loop {
+    ///         tokio::select! {
+    ///             _ = subsys.on_shutdown_requested() => break,
+    ///             Ok((stream, _)) = listener.accept() => {
+    ///                 subsys.start_lightweight(|token| connection(stream, token));
+    ///             }
+    ///         }
+    ///     }
+    ///
+    ///     tracing::info!(
+    ///         "Waiting for {} connections ...",
+    ///         subsys.lightweight_child_counts().running
+    ///     );
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn start_lightweight<Err, Fut, Child>(&self, child: Child)
+    where
+        Child: FnOnce(CancellationToken) -> Fut,
+        Fut: 'static + Future<Output = Result<(), Err>> + Send,
+        Err: Into<ErrType>,
+    {
+        let future = child(self.inner.children_cancellation_token.child_token());
+        let parent = Arc::downgrade(&self.inner);
+
+        self.inner
+            .lightweight_children
+            .get_or_init(LightweightChildren::new)
+            .spawn(
+                Arc::clone(self.inner.node.shared_name()),
+                HookedTask::from_hooks(&self.inner.config.spawn_hooks, &self.inner.node),
+                async move { future.await.map_err(Into::into) },
+                move |e| match parent.upgrade() {
+                    Some(parent) => parent.joiner_token.raise_failure(e),
+                    None => handle_unhandled_stopreason(Some(e)),
+                },
+            );
+    }
+
+    /// Queries the aggregate counters of the lightweight children of this subsystem.
+    ///
+    /// For more information, see [`start_lightweight`](Self::start_lightweight).
+    pub fn lightweight_child_counts(&self) -> LightweightChildCounts {
+        self.inner
+            .lightweight_children
+            .get()
+            .map(LightweightChildren::counts)
+            .unwrap_or_default()
+    }
+
+    /// Composes the absolute name of a child of this subsystem.
+    fn child_name(&self, name: &str) -> Arc<str> {
+        Arc::from(self.inner.node.child_name(name))
+    }
+
+    fn try_acquire_child_permit(&self) -> Result<Option<OwnedSemaphorePermit>, ChildLimitReached> {
+        match &self.inner.child_permits {
+            Some(child_permits) => Arc::clone(child_permits)
+                .try_acquire_owned()
+                .map(Some)
+                .map_err(|_| ChildLimitReached),
+            None => Ok(None),
+        }
+    }
+
+    /// Starts a subsystem under the given absolute name, ignoring the name of the builder.
+    ///
+    /// Rejects the subsystem if no child permit could be acquired.
     pub(crate) fn start_with_abs_name<Err, Fut, Subsys>(
         &self,
         name: Arc<str>,
-        subsystem: Subsys,
-        error_actions: ErrorActions,
-        detached: bool,
+        builder: SubsystemBuilder<ErrType, Err, Fut, Subsys>,
+        child_permit: Result<Option<OwnedSemaphorePermit>, ChildLimitReached>,
     ) -> NestedSubsystem<ErrType>
     where
         Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
         Fut: 'static + Future<Output = Result<(), Err>> + Send,
         Err: Into<ErrType>,
     {
+        let SubsystemBuilder {
+            subsystem,
+            failure_action,
+            panic_action,
+            detached,
+            transferable,
+            shutdown_group,
+            metadata,
+            max_children,
+            max_work_permits,
+            runtime,
+            pre_shutdown,
+            shutdown_timeout,
+            lifecycle_log_level,
+            reports_readiness,
+            sidecar,
+            ..
+        } = builder;
+        let lifecycle_log_level = lifecycle_log_level.or(self.inner.lifecycle_log_level);
+        let error_actions = ErrorActions {
+            on_failure: Atomic::new(failure_action),
+            on_panic: Atomic::new(panic_action),
+        };
+
+        if self.inner.config.startup_race_policy == StartupRacePolicy::SkipRemaining
+            && self.inner.toplevel_cancellation_token.is_cancelled()
+        {
+            log_lifecycle!(
+                lifecycle_log_level.unwrap_or(DEFAULT_LIFECYCLE_LOG_LEVEL),
+                "Not starting subsystem '{name}', as a shutdown is in progress."
+            );
+            return self.skipped_subsystem(error_actions);
+        }
+
+        let Ok(child_permit) = child_permit else {
+            tracing::error!(
+                "Not starting subsystem '{name}', as its parent already has the maximum number of children."
+            );
+            return self.rejected_subsystem(name, error_actions, InternalError::ChildLimitReached);
+        };
+
+        let node = self.inner.node.child(
+            Arc::clone(&name),
+            if self.inner.node.metadata().is_empty() {
+                metadata
+            } else {
+                let mut inherited = self.inner.node.metadata().clone();
+                inherited.extend(metadata);
+                inherited
+            },
+        );
+        if let Some(max_depth) = self.inner.config.max_depth {
+            if node.depth() > max_depth {
+                tracing::error!(
+                    "Not starting subsystem '{name}', as it exceeds the maximum depth of {max_depth}."
+                );
+                return self.rejected_subsystem(
+                    name,
+                    error_actions,
+                    InternalError::MaxDepthExceeded { max_depth },
+                );
+            }
+        }
+
+        if let Some(lifecycle_recorder) = &self.inner.instrumentation.lifecycle_recorder {
+            lifecycle_recorder.record(&name, LifecycleEventKind::Started);
+        }
+
         let alive_guard = AliveGuard::new();
 
         let (error_sender, errors) = mpsc::unbounded_channel();
 
+        let shutdown_group = shutdown_group.as_deref().and_then(|group_name| {
+            if transferable {
+                tracing::warn!(
+                    "Transferable subsystem '{name}' can not be part of shutdown group '{group_name}'; ignoring it."
+                );
+                return None;
+            }
+            let shutdown_group = self.inner.shutdown_groups.get(group_name);
+            if shutdown_group.is_none() {
+                tracing::warn!(
+                    "Subsystem '{name}' is in unknown shutdown group '{group_name}'; ignoring it."
+                );
+            }
+            shutdown_group
+        });
+
+        let owner =
+            transferable.then(|| watch::channel(self.inner.children_cancellation_token.clone()).0);
+
+        let mut forwarded_owner = None;
+        let mut forwards_local_shutdown = false;
+        let mut shutdown_sequence = None;
         let cancellation_token = if detached {
             CancellationToken::new()
-        } else {
+        } else if sidecar {
+            // Sidecars see the shutdown before their siblings do.
             self.inner.cancellation_token.child_token()
+        } else if let Some(owner) = &owner {
+            forwarded_owner = Some(owner.subscribe());
+            CancellationToken::new()
+        } else if let Some(shutdown_group) = shutdown_group {
+            forwards_local_shutdown = true;
+            shutdown_group.get_cancellation_token().child_token()
+        } else if let Some(sequence) = &self.inner.shutdown_sequence {
+            // Gets cancelled by the sequence, in turn with its siblings.
+            shutdown_sequence = Some(sequence);
+            CancellationToken::new()
+        } else {
+            self.inner.children_cancellation_token.child_token()
         };
 
         let error_actions = Arc::new(error_actions);
 
-        let (joiner_token, joiner_token_ref) = self.inner.joiner_token.child_token({
-            let cancellation_token = cancellation_token.clone();
-            let error_actions = Arc::clone(&error_actions);
-            move |e| {
-                let error_action = match &e {
-                    SubsystemError::Failed(_, _) => {
-                        error_actions.on_failure.load(Ordering::Relaxed)
-                    }
-                    SubsystemError::Panicked(_) => error_actions.on_panic.load(Ordering::Relaxed),
-                };
-
-                match error_action {
-                    ErrorAction::Forward => Some(e),
-                    ErrorAction::CatchAndLocalShutdown => {
-                        handle_dropped_error(error_sender.send(e));
-                        cancellation_token.cancel();
-                        None
-                    }
-                }
+        let on_error = apply_error_actions(
+            cancellation_token.clone(),
+            Arc::clone(&error_actions),
+            error_sender,
+        );
+        let (joiner_token, joiner_token_ref) = if transferable {
+            self.inner.joiner_token.transferable_child_token(on_error)
+        } else {
+            self.inner.joiner_token.child_token(on_error)
+        };
+        // The helper tasks run in the same context as the subsystem itself.
+        let helper_spawner =
+            || TaskSpawner::new(runtime.clone(), &self.inner.config.spawn_hooks, &node);
+        if let Some(owner) = forwarded_owner {
+            helper_spawner().spawn(forward_owner_shutdown(
+                cancellation_token.clone(),
+                owner,
+                joiner_token_ref.clone(),
+            ));
+        }
+        if forwards_local_shutdown {
+            helper_spawner().spawn(
+                self.forward_local_shutdown(cancellation_token.clone(), joiner_token_ref.clone()),
+            );
+        }
+        if let Some(sequence) = shutdown_sequence {
+            let is_first = sequence.register(
+                Arc::clone(&name),
+                cancellation_token.clone(),
+                joiner_token_ref.clone(),
+            );
+            if is_first {
+                helper_spawner().spawn(Arc::clone(sequence).run(
+                    self.inner.children_cancellation_token.clone(),
+                    self.inner.joiner_token.get_ref(),
+                ));
             }
+        }
+        let transfer = owner.map(|owner| {
+            Arc::new(Transfer {
+                joiner_token: joiner_token.downgrade(),
+                owner,
+                registration: Mutex::new(Registration::default()),
+                tree_id: self.inner.tree_id,
+            })
         });
 
+        let pre_shutdown_timeout = pre_shutdown.as_ref().map(|(timeout, _)| *timeout);
+        let children_cancellation_token = match pre_shutdown {
+            Some(pre_shutdown) => run_pre_shutdown_hook(
+                &name,
+                lifecycle_log_level.unwrap_or(DEFAULT_LIFECYCLE_LOG_LEVEL),
+                &cancellation_token,
+                &joiner_token_ref,
+                pre_shutdown,
+                &self.inner.clock,
+                helper_spawner(),
+            ),
+            None => cancellation_token.clone(),
+        };
+
+        let failure_history = Arc::new(FailureHistory::new(Arc::clone(&self.inner.clock)));
+
         let child_handle = SubsystemHandle {
-            inner: ManuallyDrop::new(Inner {
-                name: Arc::clone(&name),
+            inner: Arc::new(Inner {
+                node,
+                lifecycle_log_level,
                 cancellation_token: cancellation_token.clone(),
+                children_cancellation_token,
                 toplevel_cancellation_token: self.inner.toplevel_cancellation_token.clone(),
                 joiner_token,
                 children: RemotelyDroppableItems::new(),
+                shutdown_sequence: self
+                    .inner
+                    .config
+                    .deterministic_shutdown
+                    .map(|seed| Arc::new(ShutdownSequence::new(seed, &name))),
+                child_permits: max_children
+                    .map(|max_children| Arc::new(Semaphore::new(max_children))),
+                shutdown_deferrals: OnceLock::new(),
+                work_permits: OnceLock::new(),
+                max_work_permits,
+                lightweight_children: OnceLock::new(),
+                exit_status: Mutex::new(None),
+                task: Default::default(),
+                shutdown_statistics: Arc::clone(&self.inner.shutdown_statistics),
+                tree_id: self.inner.tree_id,
+                shutdown_acknowledgements: Arc::new(ShutdownAcknowledgements::new(Arc::clone(
+                    &self.inner.shutdown_statistics,
+                ))),
+                shutdown_groups: Arc::clone(&self.inner.shutdown_groups),
+                shared_resources: Arc::clone(&self.inner.shared_resources),
+                readiness: Arc::clone(&self.inner.readiness),
+                shutdown_announcement: Arc::clone(&self.inner.shutdown_announcement),
+                pending_readiness: Mutex::new(
+                    reports_readiness.then(|| self.inner.readiness.register()),
+                ),
+                failure_history: Arc::clone(&failure_history),
+                config: Arc::clone(&self.inner.config),
+                instrumentation: Arc::clone(&self.inner.instrumentation),
+                clock: Arc::clone(&self.inner.clock),
             }),
             drop_redirect: None,
         };
 
-        let runner = SubsystemRunner::new(name, subsystem, child_handle, alive_guard.clone());
+        let shared_resource_usages = self.inner.shared_resources.register_user(&name);
+        let state = SubsystemStateTracker::new(Arc::clone(&self.inner.clock));
+
+        let plan = PlannedSubsystem {
+            name: Arc::clone(&name),
+            shutdown_group: shutdown_group.map(|shutdown_group| Arc::clone(shutdown_group.name())),
+            shutdown_timeout,
+            pre_shutdown_timeout,
+            detached,
+        };
+        let runner = SubsystemRunner::new(
+            name,
+            subsystem,
+            child_handle,
+            alive_guard.clone(),
+            runtime,
+            plan,
+            state.clone(),
+        );
 
         // Shenanigans to juggle child ownership
         //
@@ -157,78 +790,822 @@ impl<ErrType: ErrTypeTraits> SubsystemHandle<ErrType> {
         // If the subsystem ends before `on_finished` was able to be called, nothing bad happens.
         // alive_guard will keep the guard alive and the callback will only be called inside of
         // the guard's drop() implementation.
+        let shutdown_group_membership =
+            shutdown_group.map(|shutdown_group| shutdown_group.register(runner.get_ref()));
         let child_dropper = self.inner.children.insert(runner);
-        alive_guard.on_finished(|| {
-            drop(child_dropper);
-        });
+        match &transfer {
+            // Transfers move the registration to the new parent.
+            Some(transfer) => {
+                *transfer.registration.lock() = Registration {
+                    child_dropper: Some(child_dropper),
+                    child_permit,
+                };
+                let transfer = Arc::clone(transfer);
+                alive_guard.on_finished(move || {
+                    drop(shutdown_group_membership);
+                    drop(shared_resource_usages);
+                    drop(std::mem::take(&mut *transfer.registration.lock()));
+                });
+            }
+            None => alive_guard.on_finished(|| {
+                drop(child_permit);
+                drop(shutdown_group_membership);
+                drop(shared_resource_usages);
+                drop(child_dropper);
+            }),
+        }
 
         NestedSubsystem {
             joiner: joiner_token_ref,
             cancellation_token,
             errors: Mutex::new(ErrorCollector::new(errors)),
             error_actions,
+            state,
+            failure_history,
+            transfer,
         }
     }
 
-    /// Waits until all the children of this subsystem are finished.
-    pub async fn wait_for_children(&self) {
-        self.inner.joiner_token.join_children().await
+    /// Moves a transferable subsystem, together with all of its children,
+    /// to this subsystem.
+    ///
+    /// For more information, see [`NestedSubsystem::transfer_to`].
+    pub(crate) fn adopt_transferred(
+        &self,
+        transfer: &Transfer<ErrType>,
+    ) -> Result<(), TransferError> {
+        if transfer.tree_id != self.inner.tree_id {
+            return Err(TransferError::InvalidParent);
+        }
+
+        let child_permit = self
+            .try_acquire_child_permit()
+            .map_err(|ChildLimitReached| TransferError::ChildLimitReached)?;
+
+        let mut registration = transfer.registration.lock();
+        let Some(child_dropper) = registration.child_dropper.take() else {
+            return Err(TransferError::Finished);
+        };
+
+        if let Err(e) = transfer.joiner_token.reparent(&self.inner.joiner_token) {
+            registration.child_dropper = Some(child_dropper);
+            return Err(match e {
+                ReparentError::Finished => TransferError::Finished,
+                ReparentError::Cycle => TransferError::InvalidParent,
+            });
+        }
+
+        // Only fails if the old parent is already gone, in which case
+        // the subsystem got cancelled together with it.
+        registration.child_dropper = child_dropper.move_to(&self.inner.children);
+        registration.child_permit = child_permit;
+        drop(registration);
+
+        transfer
+            .owner
+            .send_replace(self.inner.children_cancellation_token.clone());
+
+        Ok(())
     }
 
-    // For internal use only - should never be used by users.
-    // Required as a short-lived second reference inside of `runner`.
-    pub(crate) fn delayed_clone(&mut self) -> oneshot::Receiver<WeakSubsystemHandle<ErrType>> {
-        let (sender, receiver) = oneshot::channel();
+    /// Creates the [`NestedSubsystem`] of a subsystem that did not get started.
+    fn skipped_subsystem(&self, error_actions: ErrorActions) -> NestedSubsystem<ErrType> {
+        // Dropping the joiner token right away marks the subsystem as finished.
+        let (_, joiner_token_ref) = self.inner.joiner_token.child_token(Some);
+        let (_, errors) = mpsc::unbounded_channel();
 
-        let previous = self.drop_redirect.replace(sender);
-        assert!(previous.is_none());
+        let cancellation_token = CancellationToken::new();
+        cancellation_token.cancel();
 
-        receiver
+        let state = SubsystemStateTracker::new(Arc::clone(&self.inner.clock));
+        state.set_finished();
+
+        NestedSubsystem {
+            joiner: joiner_token_ref,
+            cancellation_token,
+            errors: Mutex::new(ErrorCollector::new(errors)),
+            error_actions: Arc::new(error_actions),
+            state,
+            failure_history: Arc::new(FailureHistory::new(Arc::clone(&self.inner.clock))),
+            transfer: None,
+        }
+    }
+
+    /// Creates a child that failed right away, without running its subsystem.
+    ///
+    /// The failure gets handled like the one of any other child.
+    fn rejected_subsystem(
+        &self,
+        name: Arc<str>,
+        error_actions: ErrorActions,
+        error: InternalError,
+    ) -> NestedSubsystem<ErrType> {
+        let (error_sender, errors) = mpsc::unbounded_channel();
+
+        let cancellation_token = CancellationToken::new();
+        cancellation_token.cancel();
+
+        let error_actions = Arc::new(error_actions);
+        let (joiner_token, joiner_token_ref) =
+            self.inner.joiner_token.child_token(apply_error_actions(
+                cancellation_token.clone(),
+                Arc::clone(&error_actions),
+                error_sender.clone(),
+            ));
+        // Hitting the child limit is backpressure, not a failure of the parent;
+        // only report it through the returned `NestedSubsystem`.
+        if matches!(error, InternalError::ChildLimitReached) {
+            let _ = error_sender.send(SubsystemError::Internal(name, error));
+        } else {
+            joiner_token.raise_failure(SubsystemError::Internal(name, error));
+        }
+        // Dropping the joiner token marks the subsystem as finished.
+        drop(joiner_token);
+
+        let state = SubsystemStateTracker::new(Arc::clone(&self.inner.clock));
+        state.set_finished();
+
+        NestedSubsystem {
+            joiner: joiner_token_ref,
+            cancellation_token,
+            errors: Mutex::new(ErrorCollector::new(errors)),
+            error_actions,
+            state,
+            failure_history: Arc::new(FailureHistory::new(Arc::clone(&self.inner.clock))),
+            transfer: None,
+        }
+    }
+
+    /// Forwards local shutdowns of this subsystem to a child in a shutdown group.
+    ///
+    /// Shutdowns of the entire tree reach the child through its group instead.
+    fn forward_local_shutdown(
+        &self,
+        child_token: CancellationToken,
+        child_joiner: JoinerTokenRef,
+    ) -> impl Future<Output = ()> + Send + 'static {
+        let cancellation_token = self.inner.children_cancellation_token.clone();
+        let toplevel_cancellation_token = self.inner.toplevel_cancellation_token.clone();
+        async move {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => {
+                    if !toplevel_cancellation_token.is_cancelled() {
+                        child_token.cancel();
+                    }
+                },
+                _ = child_token.cancelled() => (),
+                // Don't outlive a child that finished without a shutdown.
+                _ = child_joiner.join() => (),
+            }
+        }
+    }
+
+    /// Waits until all the children of this subsystem are finished.
+    ///
+    /// Includes the children started through [`start_lightweight`](Self::start_lightweight).
+    pub async fn wait_for_children(&self) {
+        self.inner.joiner_token.join_children().await;
+        if let Some(lightweight_children) = self.inner.lightweight_children.get() {
+            lightweight_children.wait_for_children().await;
+        }
+    }
+
+    /// Aborts all unfinished descendants of this subsystem.
+    ///
+    /// Returns the names of the aborted subsystems, without the root subsystem.
+    pub(crate) fn abort_children(&self) -> Vec<Arc<str>> {
+        SubsystemRunner::abort_all(self.inner.children.map_items(SubsystemRunner::get_ref))
+    }
+
+    /// Returns the runners of the children of this subsystem.
+    pub(crate) fn get_child_runners(&self) -> Vec<SubsystemRunnerRef> {
+        self.inner.children.map_items(SubsystemRunner::get_ref)
+    }
+
+    /// Returns the names of all unfinished descendants of this subsystem,
+    /// without the root subsystem.
+    pub(crate) fn unfinished_children(&self) -> Vec<Arc<str>> {
+        SubsystemRunner::unfinished(self.inner.children.map_items(SubsystemRunner::get_ref))
+    }
+
+    #[cfg(feature = "status")]
+    pub(crate) fn restarted_children(&self) -> Vec<(Arc<str>, u32)> {
+        SubsystemRunner::restarted(self.inner.children.map_items(SubsystemRunner::get_ref))
+    }
+
+    /// Subscribes to the `(alive, children)` state of this subsystem,
+    /// where `children` is the number of all of its descendants.
+    pub(crate) fn watch_children(&self) -> tokio::sync::watch::Receiver<(bool, u32)> {
+        self.inner.joiner_token.watch()
+    }
+
+    // For internal use only - should never be used by users.
+    // Required as a short-lived second reference inside of `runner`.
+    pub(crate) fn delayed_clone(
+        &mut self,
+    ) -> oneshot::Receiver<RedirectedSubsystemHandle<ErrType>> {
+        let (sender, receiver) = oneshot::channel();
+
+        let previous = self.drop_redirect.replace(sender);
+        assert!(previous.is_none());
+
+        receiver
+    }
+
+    /// Wait for the shutdown mode to be triggered.
+    ///
+    /// Once the shutdown mode is entered, all existing calls to this
+    /// method will be released and future calls to this method will
+    /// return immediately.
+    ///
+    /// This is the primary method of subsystems to react to
+    /// the shutdown requests. Most often, it will be used in [`tokio::select`]
+    /// statements to cancel other code as soon as the shutdown is requested.
+    ///
+    /// Waiting is purely notification based and does not consume any CPU time while idle.
+    /// Wakeups can't get lost: a shutdown that gets triggered between creating and
+    /// polling the returned future will still resolve it.
+    ///
+    /// Waiters are cheap; it is fine for a huge number of tasks, like one per connection,
+    /// to wait on the same subsystem. See `benches/shutdown_waiters.rs`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::{sleep, Duration};
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    ///
+    /// async fn countdown() {
+    ///     for i in (1..10).rev() {
+    ///         tracing::info!("Countdown: {}", i);
+    ///         sleep(Duration::from_millis(1000)).await;
+    ///     }
+    /// }
+    ///
+    /// async fn countdown_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     tracing::info!("Starting countdown ...");
+    ///
+    ///     // This cancels the countdown as soon as shutdown
+    ///     // mode was entered
+    ///     tokio::select! {
+    ///         _ = subsys.on_shutdown_requested() => {
+    ///             tracing::info!("Countdown cancelled.");
+    ///         },
+    ///         _ = countdown() => {
+    ///             tracing::info!("Countdown finished.");
+    ///         }
+    ///     };
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn on_shutdown_requested(&self) {
+        let _waiter = self.inner.shutdown_statistics.register_waiter();
+        self.inner.cancellation_token.cancelled().await;
+        if let Some(shutdown_deferrals) = self.inner.shutdown_deferrals.get() {
+            // Boxed, as it would otherwise bloat the future of every single waiter.
+            Box::pin(shutdown_deferrals.wait_for_release()).await;
+        }
+    }
+
+    /// Wait for the shutdown mode to be triggered, and acknowledge it until the cleanup is done.
+    ///
+    /// Behaves like [`on_shutdown_requested`](Self::on_shutdown_requested), but returns a
+    /// [`ShutdownAcknowledgement`] that has to be dropped, or explicitly
+    /// [`done`](ShutdownAcknowledgement::done), once the cleanup of the subsystem is finished.
+    ///
+    /// The acknowledgments show up in the [`shutdown_statistics`](Self::shutdown_statistics),
+    /// and allow telling subsystems that are still draining apart from subsystems
+    /// that ignore the shutdown, if they have to be aborted.
+    ///
+    /// # Returns
+    ///
+    /// The acknowledgment of the shutdown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::{sleep, Duration};
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    ///
+    /// async fn flush_connections() {
+    ///     sleep(Duration::from_millis(10)).await;
+    /// }
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     let acknowledgement = subsys.acknowledge_shutdown().await;
+    ///
+    ///     tracing::info!("Flushing connections ...");
+    ///     flush_connections().await;
+    ///     acknowledgement.done();
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn acknowledge_shutdown(&self) -> ShutdownAcknowledgement {
+        self.on_shutdown_requested().await;
+        self.inner.shutdown_acknowledgements.acknowledge()
+    }
+
+    /// Wait for the shutdown mode to be triggered, and return the reason of the shutdown.
+    ///
+    /// Behaves like [`on_shutdown_requested`](Self::on_shutdown_requested).
+    /// The reason allows subsystems to react differently to different kinds of shutdowns,
+    /// like skipping an expensive flush if the database subsystem is the one that failed.
+    ///
+    /// # Returns
+    ///
+    /// The [`ShutdownReason`] of the shutdown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio_graceful_shutdown::{ShutdownReason, SubsystemHandle};
+    ///
+    /// async fn cache_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     match subsys.on_shutdown_requested_with_reason().await {
+    ///         ShutdownReason::SubsystemFailed(name) if &*name == "/database" => {
+    ///             tracing::warn!("Database failed, skipping flush of the cache.");
+    ///         }
+    ///         _ => {
+    ///             tracing::info!("Flushing cache ...");
+    ///         }
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn on_shutdown_requested_with_reason(&self) -> ShutdownReason {
+        self.on_shutdown_requested().await;
+        self.current_shutdown_reason()
+    }
+
+    /// Returns the reason of the shutdown, if a shutdown should be performed now.
+    ///
+    /// For more information, see [`is_shutdown_requested`](Self::is_shutdown_requested)
+    /// and [`on_shutdown_requested_with_reason`](Self::on_shutdown_requested_with_reason).
+    pub fn shutdown_reason(&self) -> Option<ShutdownReason> {
+        self.is_shutdown_requested()
+            .then(|| self.current_shutdown_reason())
+    }
+
+    /// Waits until a shutdown is about to happen, before it actually gets requested.
+    ///
+    /// With a [drain delay](crate::ToplevelBuilder::drain_delay), a received signal
+    /// announces the shutdown right away, while the shutdown itself only gets requested
+    /// once the delay is over. This allows servers to tell their clients in advance,
+    /// for example through an HTTP/2 `GOAWAY` frame, a `Connection: close` header or
+    /// a redirect to another instance, while still serving them.
+    ///
+    /// Shutdowns that were not announced count as imminent once they get requested.
+    ///
+    /// # Returns
+    ///
+    /// The time that is left until the shutdown gets requested;
+    /// zero if it already is.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    ///
+    /// async fn server(subsys: SubsystemHandle) -> Result<()> {
+    ///     let remaining = subsys.on_shutdown_imminent().await;
+    ///     tracing::info!("Sending GOAWAY to all clients, shutting down in {remaining:?} ...");
+    ///
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn on_shutdown_imminent(&self) -> Duration {
+        tokio::select! {
+            biased;
+            _ = self.inner.cancellation_token.cancelled() => (),
+            _ = self.inner.shutdown_announcement.wait() => (),
+        }
+        self.shutdown_imminent().unwrap_or_default()
+    }
+
+    /// Returns the time that is left until a shutdown gets requested,
+    /// if it is about to happen.
+    ///
+    /// For more information, see [`on_shutdown_imminent`](Self::on_shutdown_imminent).
+    pub fn shutdown_imminent(&self) -> Option<Duration> {
+        if self.inner.cancellation_token.is_cancelled() {
+            return Some(Duration::ZERO);
+        }
+        self.inner
+            .shutdown_announcement
+            .deadline()
+            .map(|deadline| deadline.saturating_duration_since(self.inner.clock.now()))
+    }
+
+    fn current_shutdown_reason(&self) -> ShutdownReason {
+        if self.inner.toplevel_cancellation_token.is_cancelled() {
+            // No reason gets recorded if the shutdown comes from outside of the tree.
+            self.inner
+                .shutdown_statistics
+                .shutdown_reason()
+                .cloned()
+                .unwrap_or(ShutdownReason::External)
+        } else {
+            ShutdownReason::Local
+        }
+    }
+
+    /// Returns the metadata of this subsystem, including the metadata
+    /// inherited from its parents.
+    ///
+    /// For more information, see [`SubsystemMetadata`].
+    pub fn metadata(&self) -> &SubsystemMetadata {
+        self.inner.node.metadata()
+    }
+
+    /// Returns the full name of this subsystem, like `/parent/child`.
+    pub fn name(&self) -> &str {
+        self.inner.node.name()
+    }
+
+    /// Returns the position of this subsystem in the subsystem tree.
+    ///
+    /// For more information, see [`SubsystemNode`].
+    pub fn node(&self) -> &SubsystemNode {
+        &self.inner.node
+    }
+
+    /// Returns the parent of this subsystem.
+    ///
+    /// Only allows reading information about the parent; for more information,
+    /// see [`SubsystemNode`].
+    ///
+    /// # Returns
+    ///
+    /// `None` for the root subsystem.
+    pub fn parent(&self) -> Option<&SubsystemNode> {
+        self.inner.node.parent()
+    }
+
+    /// Sets the status with which this subsystem ends once it returns `Ok`.
+    ///
+    /// Can be called repeatedly; the last status wins. Has no effect if the subsystem
+    /// returns an error or panics.
+    ///
+    /// For more information, see [`ExitStatus`].
+    ///
+    /// # Arguments
+    ///
+    /// * `exit_status` - The status of the subsystem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::sync::mpsc;
+    /// use tokio_graceful_shutdown::{ExitStatus, SubsystemHandle};
+    ///
+    /// async fn consumer(subsys: SubsystemHandle, mut queue: mpsc::Receiver<String>) -> Result<()> {
+    ///     while let Some(item) = queue.recv().await {
+    ///         if subsys.is_shutdown_requested() {
+    ///             queue.close();
+    ///             let dropped = 1 + std::iter::from_fn(|| queue.try_recv().ok()).count();
+    ///             subsys.set_exit_status(ExitStatus::Drained {
+    ///                 dropped: dropped as u64,
+    ///             });
+    ///             break;
+    ///         }
+    ///         tracing::info!("Processing {item} ...");
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn set_exit_status(&self, exit_status: ExitStatus) {
+        *self.inner.exit_status.lock() = Some(exit_status);
+    }
+
+    /// Reports that this subsystem finished starting up.
+    ///
+    /// Only has an effect on subsystems that were started with
+    /// [`SubsystemBuilder::reports_readiness`]; the [sidecars](crate::ToplevelBuilder::sidecar)
+    /// of the subsystem tree wait for them to be ready. Calling it repeatedly is harmless.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    ///
+    /// async fn connect_to_database() {
+    ///     tracing::info!("Connecting ...");
+    /// }
+    ///
+    /// async fn database(subsys: SubsystemHandle) -> Result<()> {
+    ///     connect_to_database().await;
+    ///     subsys.mark_ready();
+    ///
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn mark_ready(&self) {
+        drop(self.inner.pending_readiness.lock().take());
+    }
+
+    /// Reports that this subsystem failed and restarts its work.
+    ///
+    /// Increments the [`restart_count`](Self::restart_count) and adds the failure to the
+    /// [`failure_history`](Self::failure_history). Called by [`RetryingSubsystem`](crate::RetryingSubsystem)
+    /// before every retry; custom supervision loops can call it as well.
+    ///
+    /// Failures that end the subsystem get recorded automatically.
+    ///
+    /// # Arguments
+    ///
+    /// * `reason` - Describes the failure, usually the error that caused the restart.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::{sleep, Duration};
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    ///
+    /// async fn poll_sensor() -> Result<()> {
+    ///     Err(miette::miette!("Sensor not responding"))
+    /// }
+    ///
+    /// async fn sensor(subsys: SubsystemHandle) -> Result<()> {
+    ///     while !subsys.is_shutdown_requested() {
+    ///         if let Err(e) = poll_sensor().await {
+    ///             subsys.record_restart(&e);
+    ///             if subsys.restart_count() > 10 {
+    ///                 return Err(e);
+    ///             }
+    ///         }
+    ///         sleep(Duration::from_millis(100)).await;
+    ///     }
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn record_restart(&self, reason: impl std::fmt::Display) {
+        self.inner.failure_history.record_restart(reason);
+    }
+
+    /// How often this subsystem restarted its work.
+    ///
+    /// For more information, see [`record_restart`](Self::record_restart).
+    pub fn restart_count(&self) -> u32 {
+        self.inner.failure_history.restart_count()
+    }
+
+    /// The most recent failures of this subsystem, oldest first.
+    ///
+    /// Only a limited number of failures is kept; older ones get dropped.
+    pub fn failure_history(&self) -> Vec<FailureRecord> {
+        self.inner.failure_history.recent()
+    }
+
+    /// Handles the items of a job queue, and drains it once a shutdown is requested.
+    ///
+    /// Until a shutdown is requested, every item of the queue gets passed to the handler.
+    /// Once a shutdown is requested, the queue gets closed, so senders can't submit new
+    /// items, and the items that are still buffered get handled until the queue is empty or
+    /// the deadline elapsed. An item whose handler already started gets finished, even if
+    /// the deadline elapses in the meantime. All items that remain after the deadline
+    /// get dropped.
+    ///
+    /// If a shutdown was requested, the [exit status](Self::set_exit_status) of the
+    /// subsystem gets set to [`ExitStatus::Drained`] with the number of dropped items.
+    /// If all senders got dropped before that, the queue simply ends and the exit
+    /// status is left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `receiver` - The receiving end of the job queue.
+    /// * `handler` - Gets called for every item. Returning an error stops the draining
+    ///   right away and gets returned, without touching the exit status.
+    /// * `deadline` - The maximum time to keep handling buffered items after
+    ///   a shutdown was requested.
+    ///
+    /// # Returns
+    ///
+    /// How many items were handled and dropped, or the error of the handler.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::{sync::mpsc, time::Duration};
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    ///
+    /// async fn worker(subsys: SubsystemHandle, jobs: mpsc::Receiver<String>) -> Result<()> {
+    ///     let summary = subsys
+    ///         .drain(
+    ///             jobs,
+    ///             |job| async move {
+    ///                 tracing::info!("Processing {job} ...");
+    ///                 Result::<()>::Ok(())
+    ///             },
+    ///             Duration::from_secs(5),
+    ///         )
+    ///         .await?;
+    ///
+    ///     tracing::info!("Processed {} jobs.", summary.processed);
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn drain<T, F, Fut, E>(
+        &self,
+        mut receiver: mpsc::Receiver<T>,
+        mut handler: F,
+        deadline: Duration,
+    ) -> Result<DrainSummary, E>
+    where
+        F: FnMut(T) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+    {
+        let mut summary = DrainSummary::default();
+
+        loop {
+            let item = tokio::select! {
+                biased;
+                () = self.on_shutdown_requested() => break,
+                item = receiver.recv() => item,
+            };
+            match item {
+                Some(item) => {
+                    handler(item).await?;
+                    summary.processed += 1;
+                }
+                None => return Ok(summary),
+            }
+        }
+
+        receiver.close();
+        let mut deadline = self.inner.clock.sleep(deadline);
+        loop {
+            let item = tokio::select! {
+                biased;
+                () = &mut deadline => break,
+                item = receiver.recv() => item,
+            };
+            match item {
+                Some(item) => {
+                    handler(item).await?;
+                    summary.processed += 1;
+                }
+                None => break,
+            }
+        }
+        while receiver.try_recv().is_ok() {
+            summary.dropped += 1;
+        }
+
+        tracing::debug!(
+            "Drained job queue after processing {} items, dropping {}.",
+            summary.processed,
+            summary.dropped
+        );
+        self.set_exit_status(ExitStatus::Drained {
+            dropped: summary.dropped,
+        });
+        Ok(summary)
+    }
+
+    /// Wait for the shutdown mode to be triggered, for at most the given duration.
+    ///
+    /// Behaves like [`on_shutdown_requested`](Self::on_shutdown_requested), but returns
+    /// early if no shutdown was requested within the given duration. This is useful
+    /// for performing periodic work until a shutdown arrives.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - The maximum time to wait for the shutdown request.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(())` if a shutdown was requested, or [`ShutdownTimeoutElapsed`]
+    /// if the duration passed first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    ///
+    /// async fn health_check_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     while subsys
+    ///         .on_shutdown_requested_timeout(Duration::from_secs(5))
+    ///         .await
+    ///         .is_err()
+    ///     {
+    ///         tracing::info!("Performing periodic health check ...");
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn on_shutdown_requested_timeout(
+        &self,
+        duration: Duration,
+    ) -> Result<(), ShutdownTimeoutElapsed> {
+        self.inner
+            .clock
+            .timeout(duration, self.on_shutdown_requested())
+            .await
+            .ok_or(ShutdownTimeoutElapsed)
     }
 
-    /// Wait for the shutdown mode to be triggered.
+    /// Keeps the given value alive until the shutdown mode is triggered, and drops it afterwards.
     ///
-    /// Once the shutdown mode is entered, all existing calls to this
-    /// method will be released and future calls to this method will
-    /// return immediately.
+    /// Intended for subsystems that only perform some initialization and then have
+    /// to keep a resource, like a server handle or a file lock, alive until shutdown.
+    /// Like [`on_shutdown_requested`](Self::on_shutdown_requested), waiting does not
+    /// consume any CPU time while idle.
     ///
-    /// This is the primary method of subsystems to react to
-    /// the shutdown requests. Most often, it will be used in [`tokio::select`]
-    /// statements to cancel other code as soon as the shutdown is requested.
+    /// # Arguments
+    ///
+    /// * `resource` - The value that should be kept alive.
     ///
     /// # Examples
     ///
     /// ```
     /// use miette::Result;
-    /// use tokio::time::{sleep, Duration};
     /// use tokio_graceful_shutdown::SubsystemHandle;
     ///
-    /// async fn countdown() {
-    ///     for i in (1..10).rev() {
-    ///         tracing::info!("Countdown: {}", i);
-    ///         sleep(Duration::from_millis(1000)).await;
+    /// struct Registration;
+    ///
+    /// impl Drop for Registration {
+    ///     fn drop(&mut self) {
+    ///         tracing::info!("Deregistering service ...");
     ///     }
     /// }
     ///
-    /// async fn countdown_subsystem(subsys: SubsystemHandle) -> Result<()> {
-    ///     tracing::info!("Starting countdown ...");
+    /// async fn registration_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     tracing::info!("Registering service ...");
+    ///     let registration = Registration;
     ///
-    ///     // This cancels the countdown as soon as shutdown
-    ///     // mode was entered
-    ///     tokio::select! {
-    ///         _ = subsys.on_shutdown_requested() => {
-    ///             tracing::info!("Countdown cancelled.");
-    ///         },
-    ///         _ = countdown() => {
-    ///             tracing::info!("Countdown finished.");
-    ///         }
-    ///     };
+    ///     subsys.idle_until_shutdown(registration).await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn idle_until_shutdown<T>(&self, resource: T) {
+        self.on_shutdown_requested().await;
+        drop(resource);
+    }
+
+    /// Runs one piece of work, unless a shutdown was requested.
+    ///
+    /// Meant for the top of busy loops. A `tokio::select!` that prefers its work branch
+    /// never gets to its shutdown branch while there is always work available, for example
+    /// while a channel has a backlog; this delays the shutdown until the work runs dry.
+    /// Instead, this checks for a shutdown request synchronously before every piece of
+    /// work, and only waits for a shutdown request while the work is idle.
+    ///
+    /// Work that is ready right away takes precedence over a shutdown request that arrives
+    /// while it gets polled; the next call then returns [`CancelledByShutdown`].
+    ///
+    /// # Arguments
+    ///
+    /// * `work` - The next piece of work, like receiving the next item of a channel.
+    ///
+    /// # Returns
+    ///
+    /// The result of the work, or [`CancelledByShutdown`] if a shutdown was requested
+    /// before the work finished.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::sync::mpsc;
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    ///
+    /// async fn worker(subsys: SubsystemHandle, mut jobs: mpsc::Receiver<u32>) -> Result<()> {
+    ///     while let Ok(Some(job)) = subsys.unless_shutdown_requested(jobs.recv()).await {
+    ///         tracing::info!("Processing job {job} ...");
+    ///     }
     ///
     ///     Ok(())
     /// }
     /// ```
-    pub async fn on_shutdown_requested(&self) {
-        self.inner.cancellation_token.cancelled().await
+    pub async fn unless_shutdown_requested<F: Future>(
+        &self,
+        work: F,
+    ) -> Result<F::Output, CancelledByShutdown> {
+        if self.is_shutdown_requested() {
+            return Err(CancelledByShutdown);
+        }
+
+        tokio::select! {
+            biased;
+            output = work => Ok(output),
+            () = self.on_shutdown_requested() => Err(CancelledByShutdown),
+        }
     }
 
     /// Returns whether a shutdown should be performed now.
@@ -276,6 +1653,123 @@ impl<ErrType: ErrTypeTraits> SubsystemHandle<ErrType> {
     /// ```
     pub fn is_shutdown_requested(&self) -> bool {
         self.inner.cancellation_token.is_cancelled()
+            && !self
+                .inner
+                .shutdown_deferrals
+                .get()
+                .is_some_and(|shutdown_deferrals| shutdown_deferrals.is_deferred())
+    }
+
+    /// Creates a [`ShutdownPoller`] for checking for a shutdown in hot loops.
+    ///
+    /// The poller only checks the actual state every `check_interval` calls,
+    /// so its answer may be stale for up to `check_interval - 1` calls.
+    /// A `check_interval` of `0` is treated as `1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `check_interval` - How many calls of
+    ///   [`is_shutdown_requested_fast`](ShutdownPoller::is_shutdown_requested_fast)
+    ///   share one check of the actual state.
+    pub fn shutdown_poller(&self, check_interval: u32) -> ShutdownPoller<'_, ErrType> {
+        ShutdownPoller::new(self, check_interval)
+    }
+
+    /// Defers the delivery of shutdown requests to this subsystem.
+    ///
+    /// While the returned guard is held, [`on_shutdown_requested`](SubsystemHandle::on_shutdown_requested)
+    /// will not resolve and [`is_shutdown_requested`](SubsystemHandle::is_shutdown_requested)
+    /// will return `false`. This is useful for protecting short operations that must not be
+    /// interrupted, like writes to a write-ahead log.
+    ///
+    /// The deferral expires automatically after `max_defer_time`, to guarantee that
+    /// a shutdown can't be blocked indefinitely.
+    ///
+    /// Note that this only affects the two methods mentioned above; cancellation tokens and
+    /// [`cancel_on_shutdown`](crate::FutureExt::cancel_on_shutdown) are not deferred, and neither
+    /// are nested subsystems.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_defer_time` - The maximum time the guard stays effective.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::{sleep, Duration};
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    ///
+    /// async fn write_to_log() {
+    ///     sleep(Duration::from_millis(10)).await;
+    /// }
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     loop {
+    ///         tokio::select! {
+    ///             _ = subsys.on_shutdown_requested() => break,
+    ///             _ = sleep(Duration::from_millis(100)) => {
+    ///                 let _guard = subsys.defer_shutdown(Duration::from_secs(1));
+    ///                 write_to_log().await;
+    ///             }
+    ///         }
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn defer_shutdown(&self, max_defer_time: Duration) -> ShutdownDeferralGuard {
+        self.inner
+            .shutdown_deferrals
+            .get_or_init(|| Arc::new(ShutdownDeferrals::new(Arc::clone(&self.inner.clock))))
+            .defer(self.inner.clock.now() + max_defer_time)
+    }
+
+    /// Acquires a permit to perform a unit of work.
+    ///
+    /// Permits are no longer granted once a shutdown of this subsystem was requested.
+    /// Permits that are still outstanding at that point delay the completion of the
+    /// subsystem until they are released, even if the subsystem function already returned.
+    /// This implements the common pattern of finishing in-flight jobs while accepting no new ones.
+    ///
+    /// The number of outstanding permits can be limited through
+    /// [`SubsystemBuilder::max_work_permits`]; beyond the limit, this waits for a permit
+    /// to be released.
+    ///
+    /// # Returns
+    ///
+    /// A [`WorkPermit`] that has to be held while the work is performed,
+    /// or [`CancelledByShutdown`] if a shutdown was requested.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::{sleep, Duration};
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    ///
+    /// async fn process_job() {
+    ///     sleep(Duration::from_millis(100)).await;
+    /// }
+    ///
+    /// async fn worker(subsys: SubsystemHandle) -> Result<()> {
+    ///     while let Ok(permit) = subsys.acquire_permit().await {
+    ///         tokio::spawn(async move {
+    ///             process_job().await;
+    ///             drop(permit);
+    ///         });
+    ///     }
+    ///
+    ///     // The subsystem only finishes once all spawned jobs released their permits.
+    ///     Ok(())
+    /// }
+    /// ```
+    pub async fn acquire_permit(&self) -> Result<WorkPermit, CancelledByShutdown> {
+        self.inner
+            .work_permits
+            .get_or_init(|| Arc::new(WorkPermits::new(self.inner.max_work_permits)))
+            .acquire(&self.inner.cancellation_token)
+            .await
     }
 
     /// Triggers a shutdown of the entire subsystem tree.
@@ -298,6 +1792,7 @@ impl<ErrType: ErrTypeTraits> SubsystemHandle<ErrType> {
     /// }
     /// ```
     pub fn request_shutdown(&self) {
+        self.inner.shutdown_statistics.record_request();
         self.inner.toplevel_cancellation_token.cancel();
     }
 
@@ -311,6 +1806,84 @@ impl<ErrType: ErrTypeTraits> SubsystemHandle<ErrType> {
         &self.inner.cancellation_token
     }
 
+    /// Delivers shutdown requests that got held back by [`StartupRacePolicy::Buffer`]
+    /// or by a shutdown hook.
+    pub(crate) fn release_shutdown_requests(&self) {
+        if self.inner.cancellation_token.is_cancelled() {
+            self.inner.children_cancellation_token.cancel();
+        }
+    }
+
+    pub(crate) fn get_children(&self) -> &RemotelyDroppableItems<SubsystemRunner> {
+        &self.inner.children
+    }
+
+    pub(crate) fn get_shutdown_groups(&self) -> &Arc<ShutdownGroups> {
+        &self.inner.shutdown_groups
+    }
+
+    pub(crate) fn get_shared_resources(&self) -> &Arc<SharedResources> {
+        &self.inner.shared_resources
+    }
+
+    pub(crate) fn get_readiness(&self) -> &Arc<Readiness> {
+        &self.inner.readiness
+    }
+
+    pub(crate) fn get_shutdown_announcement(&self) -> &Arc<ShutdownAnnouncement> {
+        &self.inner.shutdown_announcement
+    }
+
+    pub(crate) fn get_failure_history(&self) -> &Arc<FailureHistory> {
+        &self.inner.failure_history
+    }
+
+    /// The level of the lifecycle logs, if configured by this subsystem or one of its ancestors.
+    pub(crate) fn get_lifecycle_log_level(&self) -> Option<LevelFilter> {
+        self.inner.lifecycle_log_level
+    }
+
+    pub(crate) fn get_clock(&self) -> &SharedClock {
+        &self.inner.clock
+    }
+
+    pub(crate) fn get_task(&self) -> &SubsystemTask {
+        &self.inner.task
+    }
+
+    pub(crate) fn get_instrumentation(&self) -> &Arc<Instrumentation> {
+        &self.inner.instrumentation
+    }
+
+    pub(crate) fn get_config(&self) -> &Arc<TreeConfig<ErrType>> {
+        &self.inner.config
+    }
+
+    /// Creates another handle to the same subsystem.
+    pub(crate) fn share(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            drop_redirect: None,
+        }
+    }
+
+    pub(crate) fn get_shutdown_acknowledgements(&self) -> &Arc<ShutdownAcknowledgements> {
+        &self.inner.shutdown_acknowledgements
+    }
+
+    pub(crate) fn get_shutdown_statistics(&self) -> &Arc<ShutdownStatisticsCollector> {
+        &self.inner.shutdown_statistics
+    }
+
+    /// Queries statistics about the shutdown state of the entire subsystem tree.
+    ///
+    /// Intended for debugging and metrics. For more information, see [`ShutdownStatistics`].
+    pub fn shutdown_statistics(&self) -> ShutdownStatistics {
+        self.inner
+            .shutdown_statistics
+            .snapshot(self.inner.toplevel_cancellation_token.is_cancelled())
+    }
+
     /// Creates a cancellation token that will get triggered once the
     /// subsystem shuts down.
     ///
@@ -322,22 +1895,149 @@ impl<ErrType: ErrTypeTraits> SubsystemHandle<ErrType> {
     pub fn create_cancellation_token(&self) -> CancellationToken {
         self.inner.cancellation_token.child_token()
     }
+
+    /// Creates a [`ShutdownToken`] that will get triggered once the
+    /// subsystem shuts down.
+    ///
+    /// Allows passing the shutdown of the subsystem to libraries that accept
+    /// a [`ShutdownToken`] in their API. Requesting a shutdown through the token
+    /// does not shut down the subsystem.
+    pub fn shutdown_token(&self) -> ShutdownToken {
+        ShutdownToken::from(self.create_cancellation_token())
+    }
+
+    /// Wraps a future into an [`Abortable`](futures_util::future::Abortable)
+    /// that gets aborted once the subsystem shuts down.
+    ///
+    /// This allows code that already uses
+    /// [`Abortable`](futures_util::future::Abortable) futures to get
+    /// cancelled by this crate without rewriting it.
+    /// The returned [`AbortHandle`](futures_util::future::AbortHandle) can
+    /// still be used to abort the future manually.
+    ///
+    /// No background task is involved; the shutdown is observed while the
+    /// returned future gets polled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::{sleep, Duration};
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     let (_abort_handle, abortable) = subsys.abortable(sleep(Duration::from_secs(10)));
+    ///
+    ///     if abortable.await.is_err() {
+    ///         tracing::info!("Aborted because of a shutdown.");
+    ///     }
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    #[cfg(feature = "futures")]
+    pub fn abortable<F: Future>(
+        &self,
+        future: F,
+    ) -> (
+        futures_util::future::AbortHandle,
+        futures_util::future::Abortable<impl Future<Output = F::Output>>,
+    ) {
+        let (abort_handle, abort_registration) = futures_util::future::AbortHandle::new_pair();
+
+        let cancellation_token = self.inner.cancellation_token.clone();
+        let abort_on_shutdown = abort_handle.clone();
+        let future = async move {
+            tokio::select! {
+                result = future => result,
+                () = cancellation_token.cancelled() => {
+                    // `Abortable` checks the abort flag again after polling
+                    // this future, so it resolves to `Err(Aborted)` right away.
+                    abort_on_shutdown.abort();
+                    std::future::pending().await
+                }
+            }
+        };
+
+        (
+            abort_handle,
+            futures_util::future::Abortable::new(future, abort_registration),
+        )
+    }
+
+    /// Creates a [`SubsystemScope`] for short-lived tasks that belong to this subsystem.
+    ///
+    /// Unlike nested subsystems, the tasks of a scope don't have names and are not
+    /// registered in the subsystem tree. They get cancelled once this subsystem shuts down,
+    /// and their errors and panics propagate to this subsystem through [`SubsystemScope::join`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::{sleep, Duration};
+    /// use tokio_graceful_shutdown::SubsystemHandle;
+    ///
+    /// async fn fetch(id: u32) -> Result<()> {
+    ///     sleep(Duration::from_millis(100)).await;
+    ///     tracing::info!("Fetched {id}.");
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     let mut scope = subsys.scope();
+    ///     for id in 0..3 {
+    ///         scope.spawn(fetch(id));
+    ///     }
+    ///     scope.join().await?;
+    ///
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn scope<Err: Send + 'static>(&self) -> SubsystemScope<Err> {
+        let config = Arc::clone(&self.inner.config);
+        let node = self.inner.node.clone();
+        SubsystemScope::new(
+            self.inner.cancellation_token.child_token(),
+            Box::new(move || HookedTask::from_hooks(&config.spawn_hooks, &node)),
+        )
+    }
+
+    /// Creates a [`WeakSubsystemHandle`] that refers to this subsystem
+    /// without keeping it alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio_graceful_shutdown::{SubsystemHandle, WeakSubsystemHandle};
+    ///
+    /// async fn observer(subsys: WeakSubsystemHandle) {
+    ///     if let Some(subsys) = subsys.upgrade() {
+    ///         tracing::info!("Statistics: {:?}", subsys.shutdown_statistics());
+    ///     }
+    /// }
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     tokio::spawn(observer(subsys.downgrade()));
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn downgrade(&self) -> WeakSubsystemHandle<ErrType> {
+        WeakSubsystemHandle {
+            inner: Arc::downgrade(&self.inner),
+        }
+    }
 }
 
 impl<ErrType: ErrTypeTraits> Drop for SubsystemHandle<ErrType> {
     fn drop(&mut self) {
-        // SAFETY: This is how ManuallyDrop is meant to be used.
-        // `self.inner` won't ever be used again because `self` will be gone after this
-        // function is finished.
-        // This takes the `self.inner` object and makes it droppable again.
-        //
-        // This workaround is required to take ownership for the `self.drop_redirect` channel.
-        let inner = unsafe { ManuallyDrop::take(&mut self.inner) };
-
         if let Some(redirect) = self.drop_redirect.take() {
-            let redirected_self = WeakSubsystemHandle {
-                joiner_token: inner.joiner_token,
-                _children: inner.children,
+            let redirected_self = RedirectedSubsystemHandle {
+                inner: Arc::clone(&self.inner),
+                children: self.inner.children.clone(),
             };
 
             // ignore error; an error would indicate that there is no receiver.
@@ -347,27 +2047,154 @@ impl<ErrType: ErrTypeTraits> Drop for SubsystemHandle<ErrType> {
     }
 }
 
+/// Creates the error handler of a child, which either forwards its errors
+/// to the parent or catches them, depending on its [`ErrorAction`]s.
+fn apply_error_actions<ErrType: ErrTypeTraits>(
+    cancellation_token: CancellationToken,
+    error_actions: Arc<ErrorActions>,
+    error_sender: mpsc::UnboundedSender<SubsystemError<ErrType>>,
+) -> impl Fn(SubsystemError<ErrType>) -> Option<SubsystemError<ErrType>> + Sync + Send + 'static {
+    move |e| {
+        let error_action = match &e {
+            SubsystemError::Failed(_, _)
+            | SubsystemError::Internal(_, _)
+            | SubsystemError::Aborted(_)
+            | SubsystemError::UncleanExit(_, _) => error_actions.on_failure.load(Ordering::Relaxed),
+            SubsystemError::Panicked(_) => error_actions.on_panic.load(Ordering::Relaxed),
+        };
+
+        match error_action {
+            ErrorAction::Forward => Some(e),
+            ErrorAction::CatchAndLocalShutdown => {
+                handle_dropped_error(error_sender.send(e));
+                cancellation_token.cancel();
+                None
+            }
+        }
+    }
+}
+
+/// Runs the pre-shutdown hook of a subsystem once its shutdown was requested.
+///
+/// Returns the token that signals the children of the subsystem,
+/// which gets cancelled once the hook finished or timed out.
+fn run_pre_shutdown_hook(
+    name: &Arc<str>,
+    lifecycle_log_level: LevelFilter,
+    cancellation_token: &CancellationToken,
+    joiner_token_ref: &JoinerTokenRef,
+    (timeout, hook): (Duration, PreShutdownHook),
+    clock: &SharedClock,
+    spawner: TaskSpawner,
+) -> CancellationToken {
+    let children_cancellation_token = CancellationToken::new();
+
+    spawner.spawn({
+        let name = Arc::clone(name);
+        let cancellation_token = cancellation_token.clone();
+        let children_cancellation_token = children_cancellation_token.clone();
+        let joiner_token_ref = joiner_token_ref.clone();
+        let clock = Arc::clone(clock);
+        async move {
+            tokio::select! {
+                biased;
+                // Don't wait forever if the subsystem finished without a shutdown.
+                _ = joiner_token_ref.join() => return,
+                _ = cancellation_token.cancelled() => (),
+            }
+
+            log_lifecycle!(
+                lifecycle_log_level,
+                "Running pre-shutdown hook of subsystem '{name}' ..."
+            );
+            if clock.timeout(timeout, hook()).await.is_none() {
+                tracing::warn!(
+                    "Pre-shutdown hook of subsystem '{name}' did not finish within {timeout:?}; cancelling it."
+                );
+            }
+            children_cancellation_token.cancel();
+        }
+    });
+
+    children_cancellation_token
+}
+
 pub(crate) fn root_handle<ErrType: ErrTypeTraits>(
+    cancellation_token: CancellationToken,
     on_error: impl Fn(SubsystemError<ErrType>) + Sync + Send + 'static,
+    shutdown_groups: ShutdownGroups,
+    shared_resources: SharedResources,
+    config: TreeConfig<ErrType>,
+    instrumentation: Instrumentation,
+    clock: SharedClock,
 ) -> SubsystemHandle<ErrType> {
-    let cancellation_token = CancellationToken::new();
+    let shutdown_statistics = Arc::new(ShutdownStatisticsCollector::new(Arc::clone(&clock)));
+
+    // When buffering, running a shutdown hook or stopping sidecars first, the children only see
+    // a shutdown request once it gets released.
+    let children_cancellation_token = match config.startup_race_policy {
+        StartupRacePolicy::Buffer => CancellationToken::new(),
+        _ if config.has_shutdown_hook || config.has_sidecars => CancellationToken::new(),
+        StartupRacePolicy::StartThenShutdown | StartupRacePolicy::SkipRemaining => {
+            cancellation_token.clone()
+        }
+    };
 
     SubsystemHandle {
-        inner: ManuallyDrop::new(Inner {
-            name: Arc::from(""),
+        inner: Arc::new(Inner {
+            node: SubsystemNode::new_root(Arc::clone(&config.name_separator)),
+            lifecycle_log_level: None,
             cancellation_token: cancellation_token.clone(),
+            children_cancellation_token,
             toplevel_cancellation_token: cancellation_token.clone(),
-            joiner_token: JoinerToken::new(move |e| {
-                on_error(e);
-                cancellation_token.cancel();
-                None
+            joiner_token: JoinerToken::new({
+                let shutdown_statistics = Arc::clone(&shutdown_statistics);
+                move |e| {
+                    shutdown_statistics.record_shutdown_reason(ShutdownReason::from_error(&e));
+                    on_error(e);
+                    shutdown_statistics.record_shutdown_requested();
+                    cancellation_token.cancel();
+                    None
+                }
             })
             .0,
             children: RemotelyDroppableItems::new(),
+            shutdown_sequence: config
+                .deterministic_shutdown
+                .map(|seed| Arc::new(ShutdownSequence::new(seed, ""))),
+            child_permits: None,
+            shutdown_deferrals: OnceLock::new(),
+            work_permits: OnceLock::new(),
+            max_work_permits: None,
+            lightweight_children: OnceLock::new(),
+            exit_status: Mutex::new(None),
+            task: Default::default(),
+            shutdown_acknowledgements: Arc::new(ShutdownAcknowledgements::new(Arc::clone(
+                &shutdown_statistics,
+            ))),
+            shutdown_statistics,
+            tree_id: TreeId::new(),
+            shutdown_groups: Arc::new(shutdown_groups),
+            shared_resources: Arc::new(shared_resources),
+            readiness: Arc::new(Readiness::new()),
+            shutdown_announcement: Arc::new(ShutdownAnnouncement::new()),
+            pending_readiness: Mutex::new(None),
+            failure_history: Arc::new(FailureHistory::new(Arc::clone(&clock))),
+            config: Arc::new(config),
+            instrumentation: Arc::new(instrumentation),
+            clock,
         }),
         drop_redirect: None,
     }
 }
 
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
 #[cfg(test)]
 mod tests;
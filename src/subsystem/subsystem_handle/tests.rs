@@ -4,7 +4,15 @@ use super::*;
 
 #[tokio::test]
 async fn recursive_cancellation() {
-    let root_handle = root_handle::<BoxedError>(|_| {});
+    let root_handle = root_handle::<BoxedError>(
+        CancellationToken::new(),
+        |_| {},
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Arc::new(crate::TokioClock),
+    );
 
     let (drop_sender, mut drop_receiver) = tokio::sync::mpsc::channel::<()>(1);
 
@@ -30,7 +38,15 @@ async fn recursive_cancellation() {
 
 #[tokio::test]
 async fn recursive_cancellation_2() {
-    let root_handle = root_handle(|_| {});
+    let root_handle = root_handle(
+        CancellationToken::new(),
+        |_| {},
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Arc::new(crate::TokioClock),
+    );
 
     let (drop_sender, mut drop_receiver) = tokio::sync::mpsc::channel::<()>(1);
 
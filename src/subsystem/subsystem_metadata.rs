@@ -0,0 +1,65 @@
+use std::fmt::Display;
+
+/// Structured key/value metadata of a subsystem, like `component=ingest` or `tenant=acme`.
+///
+/// Attached through [`SubsystemBuilder::metadata`](crate::SubsystemBuilder::metadata) and
+/// inherited by all children of the subsystem.
+///
+/// The metadata gets recorded in the `subsystem` tracing span that surrounds the subsystem,
+/// so it shows up in all of its logs, including the errors reported by the
+/// [`Toplevel`](crate::Toplevel). It can be queried through
+/// [`SubsystemHandle::metadata`](crate::SubsystemHandle::metadata).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubsystemMetadata {
+    entries: Vec<(String, String)>,
+}
+
+impl SubsystemMetadata {
+    /// Sets the value of a key, overwriting a previous value.
+    pub(crate) fn insert(&mut self, key: String, value: String) {
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => self.entries.push((key, value)),
+        }
+    }
+
+    /// Merges the given metadata into this one; the given values take precedence.
+    pub(crate) fn extend(&mut self, other: SubsystemMetadata) {
+        for (key, value) in other.entries {
+            self.insert(key, value);
+        }
+    }
+
+    /// Retrieves the value of the given key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Iterates over all key/value pairs, in the order in which they were added.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Returns whether no metadata is attached.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Display for SubsystemMetadata {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, (key, value)) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,21 @@
+use super::*;
+
+#[test]
+fn later_values_take_precedence() {
+    let mut metadata = SubsystemMetadata::default();
+    metadata.insert("component".into(), "ingest".into());
+    metadata.insert("tenant".into(), "acme".into());
+
+    let mut overrides = SubsystemMetadata::default();
+    overrides.insert("tenant".into(), "globex".into());
+    overrides.insert("shard".into(), "3".into());
+    metadata.extend(overrides);
+
+    assert_eq!(metadata.get("component"), Some("ingest"));
+    assert_eq!(metadata.get("tenant"), Some("globex"));
+    assert_eq!(metadata.get("missing"), None);
+    assert_eq!(
+        metadata.to_string(),
+        "component=ingest tenant=globex shard=3"
+    );
+}
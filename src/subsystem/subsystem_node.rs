@@ -0,0 +1,177 @@
+use std::{fmt, sync::Arc};
+
+use crate::SubsystemMetadata;
+
+/// A read-only view of the position of a subsystem in the subsystem tree.
+///
+/// Allows a subsystem to navigate upwards, for example to log its full position,
+/// to compose the name of a sibling or to find the top-level subsystem it belongs to,
+/// without any global lookups.
+///
+/// Does not grant any control over the subsystems it refers to,
+/// and does not keep them from finishing.
+///
+/// Retrieved through [`SubsystemHandle::node`](crate::SubsystemHandle::node)
+/// and [`SubsystemHandle::parent`](crate::SubsystemHandle::parent).
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio_graceful_shutdown::SubsystemHandle;
+///
+/// async fn worker(subsys: SubsystemHandle) -> Result<()> {
+///     let node = subsys.node();
+///     tracing::info!(
+///         "Started '{}' at depth {}, below '{}'.",
+///         node.local_name(),
+///         node.depth(),
+///         node.root().name(),
+///     );
+///
+///     if let Some(parent) = subsys.parent() {
+///         tracing::info!("My sibling is called '{}'.", parent.child_name("sibling"));
+///     }
+///
+///     subsys.on_shutdown_requested().await;
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct SubsystemNode {
+    inner: Arc<NodeInner>,
+}
+
+struct NodeInner {
+    name: Arc<str>,
+    depth: usize,
+    metadata: SubsystemMetadata,
+    parent: Option<SubsystemNode>,
+    name_separator: Arc<str>,
+}
+
+impl SubsystemNode {
+    /// Creates the node of the unnamed root of the tree.
+    pub(crate) fn new_root(name_separator: Arc<str>) -> Self {
+        Self {
+            inner: Arc::new(NodeInner {
+                name: Arc::from(""),
+                depth: 0,
+                metadata: SubsystemMetadata::default(),
+                parent: None,
+                name_separator,
+            }),
+        }
+    }
+
+    /// Creates the node of a child.
+    ///
+    /// Unnamed children, like the root subsystem, take the place of their parent.
+    pub(crate) fn child(&self, name: Arc<str>, metadata: SubsystemMetadata) -> Self {
+        let (depth, parent) = if name.is_empty() {
+            (self.inner.depth, self.inner.parent.clone())
+        } else {
+            (self.inner.depth + 1, Some(self.clone()))
+        };
+
+        Self {
+            inner: Arc::new(NodeInner {
+                name,
+                depth,
+                metadata,
+                parent,
+                name_separator: Arc::clone(&self.inner.name_separator),
+            }),
+        }
+    }
+
+    pub(crate) fn shared_name(&self) -> &Arc<str> {
+        &self.inner.name
+    }
+
+    /// Returns the full name of the subsystem, like `/parent/child`.
+    ///
+    /// The root subsystem has an empty name.
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// Returns the name of the subsystem without the names of its parents,
+    /// as it was given to its [`SubsystemBuilder`](crate::SubsystemBuilder).
+    pub fn local_name(&self) -> &str {
+        let parent_name = self.parent().map(SubsystemNode::name).unwrap_or_default();
+        self.inner.name[parent_name.len()..]
+            .strip_prefix(&*self.inner.name_separator)
+            .unwrap_or(&self.inner.name)
+    }
+
+    /// Returns the number of named subsystems from the root down to this subsystem.
+    ///
+    /// The root subsystem has a depth of zero, its children have a depth of one.
+    pub fn depth(&self) -> usize {
+        self.inner.depth
+    }
+
+    /// Returns the metadata of the subsystem, including the metadata
+    /// inherited from its parents.
+    pub fn metadata(&self) -> &SubsystemMetadata {
+        &self.inner.metadata
+    }
+
+    /// Returns the parent of the subsystem.
+    ///
+    /// # Returns
+    ///
+    /// `None` for the root subsystem.
+    pub fn parent(&self) -> Option<&SubsystemNode> {
+        self.inner.parent.as_ref()
+    }
+
+    /// Returns the top-level subsystem this subsystem belongs to,
+    /// which is its ancestor at a depth of one.
+    ///
+    /// Returns the subsystem itself if it has a depth of one or less.
+    pub fn root(&self) -> &SubsystemNode {
+        let mut node = self;
+        while node.depth() > 1 {
+            match node.parent() {
+                Some(parent) => node = parent,
+                None => break,
+            }
+        }
+        node
+    }
+
+    /// Iterates over the ancestors of the subsystem, starting with its parent
+    /// and ending with the root subsystem.
+    pub fn ancestors(&self) -> impl Iterator<Item = &SubsystemNode> {
+        std::iter::successors(self.parent(), |node| node.parent())
+    }
+
+    /// Composes the full name that a child of this subsystem with the given name would have.
+    ///
+    /// Useful for locating siblings, through the [`parent`](Self::parent) of a subsystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the child, as it would be given to its [`SubsystemBuilder`](crate::SubsystemBuilder).
+    pub fn child_name(&self, name: &str) -> String {
+        let mut joined = String::with_capacity(
+            self.inner.name.len() + self.inner.name_separator.len() + name.len(),
+        );
+        joined.push_str(&self.inner.name);
+        joined.push_str(&self.inner.name_separator);
+        joined.push_str(name);
+        joined
+    }
+}
+
+impl fmt::Debug for SubsystemNode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SubsystemNode")
+            .field("name", &self.inner.name)
+            .field("depth", &self.inner.depth)
+            .field("metadata", &self.inner.metadata)
+            .finish()
+    }
+}
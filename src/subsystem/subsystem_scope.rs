@@ -0,0 +1,83 @@
+use std::future::Future;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    panic_hook::mark_subsystem,
+    spawn_hook::{run_hooked, HookedTask},
+    utils::{resume_panic, JoinSet},
+};
+
+/// Creates the spawn hooks of the subsystem for a task of the scope.
+pub(crate) type HookFactory = Box<dyn Fn() -> Option<HookedTask> + Send + Sync>;
+
+/// A group of short-lived tasks that are bound to the lifetime of a subsystem.
+///
+/// Created through [`SubsystemHandle::scope`](crate::SubsystemHandle::scope).
+///
+/// The tasks get cancelled once the subsystem shuts down, and
+/// dropping the scope aborts all tasks that are still running.
+/// Errors and panics of the tasks get propagated through [`join`](Self::join).
+#[must_use = "Dropping the scope aborts its tasks right away"]
+pub struct SubsystemScope<Err> {
+    tasks: JoinSet<Result<(), Err>>,
+    cancellation_token: CancellationToken,
+    hooks: HookFactory,
+}
+
+impl<Err: Send + 'static> SubsystemScope<Err> {
+    pub(crate) fn new(cancellation_token: CancellationToken, hooks: HookFactory) -> Self {
+        Self {
+            tasks: JoinSet::new(),
+            cancellation_token,
+            hooks,
+        }
+    }
+
+    /// Spawns a task in this scope.
+    ///
+    /// Once the subsystem shuts down, the task gets cancelled and counts as successful.
+    /// Like the subsystem itself, the task gets wrapped by the
+    /// [spawn hooks](crate::ToplevelBuilder::spawn_hook).
+    ///
+    /// # Arguments
+    ///
+    /// * `future` - The future that should be run.
+    pub fn spawn<Fut>(&mut self, future: Fut)
+    where
+        Fut: Future<Output = Result<(), Err>> + Send + 'static,
+    {
+        let cancellation_token = self.cancellation_token.clone();
+        let hooked = (self.hooks)();
+        self.tasks.spawn(async move {
+            tokio::select! {
+                result = run_hooked(hooked, mark_subsystem(future)) => {
+                    result.expect("A spawn hook did not run a task of the scope to completion")
+                }
+                _ = cancellation_token.cancelled() => Ok(()),
+            }
+        });
+    }
+
+    /// Waits for all tasks of this scope to finish.
+    ///
+    /// If a task fails, the remaining tasks get aborted and its error gets returned.
+    /// If a task panics, the remaining tasks get aborted and the panic gets resumed,
+    /// so it propagates to the subsystem.
+    ///
+    /// # Returns
+    ///
+    /// The error of the first task that failed.
+    pub async fn join(mut self) -> Result<(), Err> {
+        while let Some(join_result) = self.tasks.join_next().await {
+            match join_result {
+                Ok(Ok(())) => (),
+                Ok(Err(e)) => return Err(e),
+                Err(e) if e.is_panic() => resume_panic(e),
+                // Only the scope itself aborts its tasks.
+                Err(_) => (),
+            }
+        }
+        Ok(())
+    }
+}
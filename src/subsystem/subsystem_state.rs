@@ -0,0 +1,96 @@
+use std::{
+    future::Future,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use tokio::{sync::watch, time::Instant};
+use tokio_util::sync::CancellationToken;
+
+use crate::clock::SharedClock;
+
+/// The lifecycle state of a single subsystem.
+///
+/// Can be watched through [`NestedSubsystem::state`](crate::NestedSubsystem::state).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubsystemState {
+    /// The subsystem is running and did not receive a shutdown request yet.
+    Running,
+    /// The subsystem received a shutdown request and is shutting down.
+    ShuttingDown,
+    /// The subsystem and all of its children finished.
+    Finished,
+    /// The subsystem returned an error or panicked.
+    ///
+    /// Entered as soon as the subsystem failed, even if its children are still running.
+    Failed,
+}
+
+/// Keeps the [`SubsystemState`] of a subsystem up to date.
+#[derive(Clone)]
+pub(crate) struct SubsystemStateTracker {
+    state: Arc<watch::Sender<SubsystemState>>,
+    shutdown_requested_at: Arc<OnceLock<Instant>>,
+    clock: SharedClock,
+}
+
+impl SubsystemStateTracker {
+    pub(crate) fn new(clock: SharedClock) -> Self {
+        Self {
+            state: Arc::new(watch::channel(SubsystemState::Running).0),
+            shutdown_requested_at: Arc::new(OnceLock::new()),
+            clock,
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> watch::Receiver<SubsystemState> {
+        self.state.subscribe()
+    }
+
+    /// Runs the given future, and enters the `ShuttingDown` state
+    /// if the token gets cancelled meanwhile.
+    pub(crate) async fn track_shutdown<F: Future>(
+        &self,
+        cancellation_token: &CancellationToken,
+        future: F,
+    ) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        tokio::select! {
+            biased;
+            output = &mut future => output,
+            _ = cancellation_token.cancelled() => {
+                self.shutdown_requested_at.get_or_init(|| self.clock.now());
+                self.state.send_if_modified(|state| {
+                    let modified = *state == SubsystemState::Running;
+                    if modified {
+                        *state = SubsystemState::ShuttingDown;
+                    }
+                    modified
+                });
+                future.await
+            }
+        }
+    }
+
+    /// The time that passed since the subsystem received its shutdown request.
+    pub(crate) fn shutdown_duration(&self) -> Option<Duration> {
+        self.shutdown_requested_at
+            .get()
+            .map(|requested_at| self.clock.now().saturating_duration_since(*requested_at))
+    }
+
+    pub(crate) fn set_failed(&self) {
+        self.state.send_replace(SubsystemState::Failed);
+    }
+
+    /// Enters the `Finished` state, unless the subsystem failed.
+    pub(crate) fn set_finished(&self) {
+        self.state.send_if_modified(|state| {
+            let modified = *state != SubsystemState::Failed;
+            if modified {
+                *state = SubsystemState::Finished;
+            }
+            modified
+        });
+    }
+}
@@ -0,0 +1,116 @@
+use std::future::Future;
+
+use crate::{BoxedError, ErrTypeTraits, NestedSubsystem, SubsystemBuilder, SubsystemHandle};
+
+type StartFn<ErrType> = Box<
+    dyn FnOnce(&SubsystemHandle<ErrType>, Vec<SubsystemTree<ErrType>>) -> NestedSubsystem<ErrType>
+        + Send,
+>;
+
+/// Describes a subsystem together with all of its children, before any of them get started.
+///
+/// Allows composing a tree of subsystems up front, for example from a configuration file,
+/// and reusing fragments of it across binaries.
+///
+/// Started through [`SubsystemHandle::start_tree`] or [`Toplevel::start`](crate::Toplevel::start).
+/// The children of a node get started as soon as the node itself starts,
+/// before its subsystem function runs.
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, SubsystemTree, Toplevel};
+///
+/// async fn worker(subsys: SubsystemHandle) -> Result<()> {
+///     subsys.on_shutdown_requested().await;
+///     Ok(())
+/// }
+///
+/// fn workers(count: usize) -> SubsystemTree {
+///     SubsystemTree::group("workers").children(
+///         (0..count).map(|i| SubsystemTree::new(SubsystemBuilder::new(format!("{i}"), worker))),
+///     )
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+///         s.request_shutdown();
+///     });
+///     toplevel.start(workers(4));
+///
+///     toplevel
+///         .handle_shutdown_requests(Duration::from_millis(500))
+///         .await
+///         .map_err(Into::into)
+/// }
+/// ```
+pub struct SubsystemTree<ErrType: ErrTypeTraits = BoxedError> {
+    start: StartFn<ErrType>,
+    children: Vec<SubsystemTree<ErrType>>,
+}
+
+impl<ErrType: ErrTypeTraits> SubsystemTree<ErrType> {
+    /// Creates a tree node from the given subsystem, without any children yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `builder` - The subsystem of this node, including all of its settings.
+    pub fn new<Err, Fut, Subsys>(builder: SubsystemBuilder<'_, ErrType, Err, Fut, Subsys>) -> Self
+    where
+        Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
+        Fut: 'static + Future<Output = Result<(), Err>> + Send,
+        Err: 'static + Into<ErrType>,
+    {
+        let builder = builder.map_subsystem(|subsystem| subsystem);
+
+        Self {
+            start: Box::new(move |parent, children| {
+                parent.start(builder.map_subsystem(|subsystem| {
+                    move |s: SubsystemHandle<ErrType>| async move {
+                        for child in children {
+                            s.start_tree(child);
+                        }
+                        subsystem(s).await
+                    }
+                }))
+            }),
+            children: Vec::new(),
+        }
+    }
+
+    /// Creates a tree node that only hosts its children.
+    ///
+    /// The node finishes once all of its children are finished.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the node.
+    pub fn group(name: impl Into<String>) -> Self {
+        Self::new(SubsystemBuilder::new(
+            name.into(),
+            |s: SubsystemHandle<ErrType>| async move {
+                s.wait_for_children().await;
+                Result::<(), ErrType>::Ok(())
+            },
+        ))
+    }
+
+    /// Adds a child to this node.
+    pub fn child(mut self, child: SubsystemTree<ErrType>) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    /// Adds multiple children to this node.
+    pub fn children(mut self, children: impl IntoIterator<Item = SubsystemTree<ErrType>>) -> Self {
+        self.children.extend(children);
+        self
+    }
+
+    pub(crate) fn start_on(self, parent: &SubsystemHandle<ErrType>) -> NestedSubsystem<ErrType> {
+        (self.start)(parent, self.children)
+    }
+}
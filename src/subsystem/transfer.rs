@@ -0,0 +1,75 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tokio::sync::{watch, OwnedSemaphorePermit};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    runner::SubsystemRunner,
+    utils::{remote_drop_collection::RemoteDrop, JoinerTokenRef, Mutex, WeakJoinerToken},
+    ErrTypeTraits,
+};
+
+/// Identifies a subsystem tree.
+///
+/// Subsystems can only be transferred within their own tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct TreeId(u64);
+
+impl TreeId {
+    pub(crate) fn new() -> Self {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Allows moving a [`transferable`](crate::SubsystemBuilder::transferable)
+/// subsystem to a new parent.
+pub(crate) struct Transfer<ErrType: ErrTypeTraits> {
+    pub(crate) joiner_token: WeakJoinerToken<ErrType>,
+    // The token of the current parent that signals its children to shut down.
+    pub(crate) owner: watch::Sender<CancellationToken>,
+    pub(crate) registration: Mutex<Registration>,
+    pub(crate) tree_id: TreeId,
+}
+
+/// The parts of a subsystem that are registered at its current parent.
+///
+/// Released once the subsystem is finished.
+#[derive(Default)]
+pub(crate) struct Registration {
+    pub(crate) child_dropper: Option<RemoteDrop<SubsystemRunner>>,
+    pub(crate) child_permit: Option<OwnedSemaphorePermit>,
+}
+
+/// Forwards the shutdown of the current parent to a transferable subsystem.
+///
+/// Follows the subsystem to its new parent when it gets transferred.
+/// Returns once the subsystem got cancelled or finished.
+pub(crate) async fn forward_owner_shutdown(
+    cancellation_token: CancellationToken,
+    mut owner: watch::Receiver<CancellationToken>,
+    joiner: JoinerTokenRef,
+) {
+    loop {
+        let owner_token = owner.borrow_and_update().clone();
+        tokio::select! {
+            _ = owner_token.cancelled() => {
+                cancellation_token.cancel();
+                return;
+            },
+            _ = cancellation_token.cancelled() => return,
+            _ = joiner.join() => return,
+            changed = owner.changed() => {
+                if changed.is_err() {
+                    // Can no longer be transferred; stay with the current parent.
+                    tokio::select! {
+                        _ = owner_token.cancelled() => cancellation_token.cancel(),
+                        _ = cancellation_token.cancelled() => (),
+                        _ = joiner.join() => (),
+                    }
+                    return;
+                }
+            },
+        }
+    }
+}
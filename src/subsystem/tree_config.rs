@@ -0,0 +1,49 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{
+    middleware::BoxedMiddleware, spawn_hook::BoxedSpawnHook, BoxedError, ErrTypeTraits,
+    StartupRacePolicy,
+};
+
+/// Settings that are shared by the entire subsystem tree.
+pub(crate) struct TreeConfig<ErrType: ErrTypeTraits = BoxedError> {
+    pub(crate) startup_race_policy: StartupRacePolicy,
+    /// Separates the names of parents and children in absolute subsystem names.
+    pub(crate) name_separator: Arc<str>,
+    /// The maximum depth of the tree; the children of the root subsystem have a depth of one.
+    pub(crate) max_depth: Option<usize>,
+    /// Whether subsystems that end with an unclean exit status count as failed.
+    pub(crate) strict_exit_statuses: bool,
+    /// Whether a hook has to run before the subsystems see a shutdown request.
+    pub(crate) has_shutdown_hook: bool,
+    /// Whether sidecars have to shut down before the subsystems see a shutdown request.
+    pub(crate) has_sidecars: bool,
+    /// Whether panics outside of subsystems initiate a shutdown.
+    pub(crate) shutdown_on_panic: bool,
+    /// Wrap the execution of all subsystems, outermost first.
+    pub(crate) middlewares: Vec<BoxedMiddleware<ErrType>>,
+    /// Polls of subsystems that take longer than this get reported while shutting down.
+    pub(crate) blocking_threshold: Option<Duration>,
+    /// Wrap the tasks of all subsystems when they get spawned, outermost first.
+    pub(crate) spawn_hooks: Vec<BoxedSpawnHook>,
+    /// If set, children get shut down one at a time, in an order derived from this seed.
+    pub(crate) deterministic_shutdown: Option<u64>,
+}
+
+impl<ErrType: ErrTypeTraits> Default for TreeConfig<ErrType> {
+    fn default() -> Self {
+        Self {
+            startup_race_policy: StartupRacePolicy::default(),
+            name_separator: Arc::from("/"),
+            max_depth: None,
+            strict_exit_statuses: false,
+            has_shutdown_hook: false,
+            has_sidecars: false,
+            shutdown_on_panic: false,
+            middlewares: Vec::new(),
+            blocking_threshold: None,
+            spawn_hooks: Vec::new(),
+            deterministic_shutdown: None,
+        }
+    }
+}
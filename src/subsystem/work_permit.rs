@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
+
+use crate::errors::CancelledByShutdown;
+
+/// The work permits of a subsystem.
+///
+/// Keeps track of the outstanding permits, so that the shutdown
+/// of the subsystem can wait for them to be released.
+pub(crate) struct WorkPermits {
+    // Only set if the number of permits is limited.
+    semaphore: Option<Arc<Semaphore>>,
+    outstanding: watch::Sender<usize>,
+}
+
+impl WorkPermits {
+    pub(crate) fn new(max_permits: Option<usize>) -> Self {
+        Self {
+            semaphore: max_permits.map(|max_permits| Arc::new(Semaphore::new(max_permits))),
+            outstanding: watch::channel(0).0,
+        }
+    }
+
+    /// Acquires a permit, unless a shutdown was requested first.
+    pub(crate) async fn acquire(
+        self: &Arc<Self>,
+        cancellation_token: &CancellationToken,
+    ) -> Result<WorkPermit, CancelledByShutdown> {
+        let permit = match &self.semaphore {
+            Some(semaphore) => tokio::select! {
+                biased;
+                _ = cancellation_token.cancelled() => return Err(CancelledByShutdown),
+                // The semaphore never gets closed, so acquiring can not fail.
+                permit = Arc::clone(semaphore).acquire_owned() => permit.ok(),
+            },
+            None if cancellation_token.is_cancelled() => return Err(CancelledByShutdown),
+            None => None,
+        };
+
+        self.outstanding
+            .send_modify(|outstanding| *outstanding += 1);
+        Ok(WorkPermit {
+            _permit: permit,
+            permits: Arc::clone(self),
+        })
+    }
+
+    /// Waits until all outstanding permits are released.
+    pub(crate) async fn wait_for_release(&self) {
+        let mut outstanding = self.outstanding.subscribe();
+        // Can not fail, as `self` holds the sender.
+        let _ = outstanding.wait_for(|&outstanding| outstanding == 0).await;
+    }
+}
+
+/// A permit to perform a unit of work in a subsystem.
+///
+/// Returned by [`SubsystemHandle::acquire_permit`](crate::SubsystemHandle::acquire_permit).
+///
+/// The subsystem does not finish while permits are outstanding,
+/// so dropping this permit marks the work as done.
+#[must_use = "The work is only protected while the permit is held"]
+pub struct WorkPermit {
+    _permit: Option<OwnedSemaphorePermit>,
+    permits: Arc<WorkPermits>,
+}
+
+impl Drop for WorkPermit {
+    fn drop(&mut self) {
+        self.permits
+            .outstanding
+            .send_modify(|outstanding| *outstanding -= 1);
+    }
+}
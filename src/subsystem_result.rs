@@ -0,0 +1,81 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+use crate::ExitStatus;
+
+/// How a subsystem ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SubsystemOutcome {
+    /// The subsystem returned successfully.
+    Succeeded,
+    /// The subsystem returned an error.
+    Failed,
+    /// The subsystem panicked.
+    Panicked,
+    /// The subsystem did not finish in time and got aborted.
+    ///
+    /// The policy that aborted it is given by [`SubsystemResult::abort_cause`].
+    Aborted,
+}
+
+/// The policy that aborted a subsystem.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AbortCause {
+    /// The subsystem exceeded its own shutdown timeout,
+    /// configured through [`SubsystemBuilder::shutdown_timeout`](crate::SubsystemBuilder::shutdown_timeout).
+    SubsystemTimeout(Duration),
+    /// The shutdown group of the subsystem exceeded its shutdown budget,
+    /// configured through [`ToplevelBuilder::shutdown_group`](crate::ToplevelBuilder::shutdown_group).
+    ShutdownGroupTimeout {
+        /// The name of the shutdown group.
+        group: Arc<str>,
+        /// The shutdown budget of the group.
+        budget: Duration,
+    },
+    /// The shutdown of the entire subsystem tree exceeded its shutdown timeout.
+    ShutdownTimeout(Duration),
+}
+
+impl fmt::Display for AbortCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SubsystemTimeout(timeout) => {
+                write!(f, "exceeded its shutdown timeout of {timeout:?}")
+            }
+            Self::ShutdownGroupTimeout { group, budget } => write!(
+                f,
+                "shutdown group '{group}' exceeded its budget of {budget:?}"
+            ),
+            Self::ShutdownTimeout(timeout) => {
+                write!(f, "shutdown exceeded its timeout of {timeout:?}")
+            }
+        }
+    }
+}
+
+/// The final outcome of a subsystem.
+///
+/// Yielded by [`Toplevel::shutdown_results_stream`](crate::Toplevel::shutdown_results_stream)
+/// once the subsystem and all of its children finished.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubsystemResult {
+    /// The full name of the subsystem, like `/parent/child`.
+    pub name: Arc<str>,
+    /// How the subsystem ended.
+    pub outcome: SubsystemOutcome,
+    /// The exit status the subsystem set through
+    /// [`SubsystemHandle::set_exit_status`](crate::SubsystemHandle::set_exit_status).
+    ///
+    /// Only set if the subsystem [succeeded](SubsystemOutcome::Succeeded).
+    pub exit_status: Option<ExitStatus>,
+    /// How long the subsystem took to shut down, measured from the moment it
+    /// received its shutdown request.
+    ///
+    /// `None` if the subsystem finished without receiving a shutdown request.
+    pub shutdown_duration: Option<Duration>,
+    /// The policy that aborted the subsystem.
+    ///
+    /// Only set if the subsystem got [aborted](SubsystemOutcome::Aborted).
+    pub abort_cause: Option<AbortCause>,
+}
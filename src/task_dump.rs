@@ -0,0 +1,97 @@
+//! Task dumps of the runtime, captured once a shutdown stalls.
+//!
+//! Task dumps are an unstable feature of tokio; they are only available with
+//! `--cfg tokio_unstable`, on Linux atop `aarch64`, `x86`, `x86_64` and `s390x`.
+
+#[cfg(feature = "task-dump")]
+use std::{sync::Arc, time::Duration};
+
+#[cfg(feature = "task-dump")]
+use crate::{
+    clock::SharedClock, runner::SubsystemRunner,
+    utils::remote_drop_collection::RemotelyDroppableItems,
+};
+
+#[cfg_attr(
+    all(
+        feature = "task-dump",
+        tokio_unstable,
+        not(madsim),
+        target_os = "linux",
+        any(
+            target_arch = "aarch64",
+            target_arch = "x86",
+            target_arch = "x86_64",
+            target_arch = "s390x"
+        )
+    ),
+    path = "task_dump/supported.rs"
+)]
+#[cfg_attr(
+    not(all(
+        feature = "task-dump",
+        tokio_unstable,
+        not(madsim),
+        target_os = "linux",
+        any(
+            target_arch = "aarch64",
+            target_arch = "x86",
+            target_arch = "x86_64",
+            target_arch = "s390x"
+        )
+    )),
+    path = "task_dump/unsupported.rs"
+)]
+mod platform;
+
+pub(crate) use platform::SubsystemTask;
+
+/// Captures a task dump if the shutdown does not finish in time.
+///
+/// Stops watching the shutdown once dropped.
+#[cfg(feature = "task-dump")]
+pub(crate) struct StallDetector(tokio::task::AbortHandle);
+
+#[cfg(feature = "task-dump")]
+impl StallDetector {
+    /// Starts watching a shutdown that just began.
+    ///
+    /// # Arguments
+    ///
+    /// * `stall_timeout` - The time after which the shutdown is considered stalled.
+    /// * `children` - The runners of the children of the root subsystem.
+    pub(crate) fn start(
+        stall_timeout: Duration,
+        clock: SharedClock,
+        children: RemotelyDroppableItems<SubsystemRunner>,
+    ) -> Self {
+        let join_handle = tokio::spawn(async move {
+            clock.sleep(stall_timeout).await;
+
+            let tasks =
+                SubsystemRunner::unfinished_tasks(children.map_items(SubsystemRunner::get_ref));
+            let names = tasks
+                .iter()
+                .map(|(name, _)| format!("'{name}'"))
+                .collect::<Vec<_>>();
+            tracing::warn!(
+                "Shutdown stalled for {stall_timeout:?}; still waiting for: {}",
+                names.join(", ")
+            );
+
+            platform::log_task_dump(tasks).await;
+        });
+
+        Self(join_handle.abort_handle())
+    }
+}
+
+#[cfg(feature = "task-dump")]
+impl Drop for StallDetector {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+#[cfg(feature = "task-dump")]
+type SubsystemTasks = Vec<(Arc<str>, SubsystemTask)>;
@@ -0,0 +1,68 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+
+use tokio::task::Id;
+
+use super::SubsystemTasks;
+
+/// The maximum time to wait for a task dump.
+///
+/// Capturing a dump never finishes if a worker thread is blocked.
+const TASK_DUMP_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Remembers the tasks of a subsystem, to attribute them in task dumps.
+#[derive(Clone, Default)]
+pub(crate) struct SubsystemTask {
+    ids: Arc<TaskIds>,
+}
+
+#[derive(Default)]
+struct TaskIds {
+    runner: OnceLock<Id>,
+    subsystem: OnceLock<Id>,
+}
+
+impl SubsystemTask {
+    /// Remembers the task that manages the subsystem.
+    pub(crate) fn set_runner<T>(&self, join_handle: &tokio::task::JoinHandle<T>) {
+        self.ids.runner.set(join_handle.id()).ok();
+    }
+
+    /// Remembers the task that runs the subsystem function.
+    pub(crate) fn set_subsystem<T>(&self, join_handle: &tokio::task::JoinHandle<T>) {
+        self.ids.subsystem.set(join_handle.id()).ok();
+    }
+}
+
+pub(super) async fn log_task_dump(tasks: SubsystemTasks) {
+    let mut owners = HashMap::new();
+    for (name, task) in &tasks {
+        if let Some(id) = task.ids.subsystem.get() {
+            owners.insert(*id, format!("subsystem '{name}'"));
+        }
+        if let Some(id) = task.ids.runner.get() {
+            owners.insert(*id, format!("runner of subsystem '{name}'"));
+        }
+    }
+
+    let handle = tokio::runtime::Handle::current();
+    let Ok(dump) = tokio::time::timeout(TASK_DUMP_TIMEOUT, handle.dump()).await else {
+        tracing::warn!("Unable to capture a task dump within {TASK_DUMP_TIMEOUT:?}.");
+        return;
+    };
+
+    let mut rendered = String::new();
+    for task in dump.tasks().iter() {
+        let id = task.id();
+        match owners.get(&id) {
+            Some(owner) => write!(rendered, "\nTask {id} ({owner}):\n{}", task.trace()),
+            None => write!(rendered, "\nTask {id}:\n{}", task.trace()),
+        }
+        .ok();
+    }
+    tracing::warn!("Task dump:{rendered}");
+}
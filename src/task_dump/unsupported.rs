@@ -0,0 +1,19 @@
+#[cfg(feature = "task-dump")]
+use super::SubsystemTasks;
+
+/// Does not remember anything, as task dumps are not supported.
+#[derive(Clone, Default)]
+pub(crate) struct SubsystemTask;
+
+impl SubsystemTask {
+    pub(crate) fn set_runner<T>(&self, _join_handle: &tokio::task::JoinHandle<T>) {}
+
+    pub(crate) fn set_subsystem<T>(&self, _join_handle: &tokio::task::JoinHandle<T>) {}
+}
+
+#[cfg(feature = "task-dump")]
+pub(super) async fn log_task_dump(_tasks: SubsystemTasks) {
+    tracing::warn!(
+        "Unable to capture a task dump; task dumps require `--cfg tokio_unstable` and are only supported on Linux."
+    );
+}
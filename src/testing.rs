@@ -0,0 +1,16 @@
+//! Utilities for testing subsystems.
+
+#[cfg(feature = "fault-injection")]
+mod fault_injection;
+mod instrumentation;
+mod lifecycle_recorder;
+mod mock_subsystem_handle;
+mod test_toplevel;
+
+#[cfg(feature = "fault-injection")]
+pub use fault_injection::{Fault, FaultInjection};
+pub(crate) use instrumentation::Instrumentation;
+pub(crate) use lifecycle_recorder::LifecycleRecorder;
+pub use lifecycle_recorder::{LifecycleEvent, LifecycleEventKind};
+pub use mock_subsystem_handle::MockSubsystemHandle;
+pub use test_toplevel::{TestReport, TestToplevel};
@@ -0,0 +1,93 @@
+use std::time::Duration;
+
+use crate::clock::SharedClock;
+
+type FaultPolicy = Box<dyn Fn(&str) -> Option<Fault> + Send + Sync>;
+
+/// A fault that gets injected into a subsystem during shutdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Fault {
+    /// Delays the completion of the subsystem by the given duration.
+    Delay(Duration),
+    /// Makes the subsystem panic instead of finishing.
+    Panic,
+    /// Makes the subsystem hang forever instead of finishing.
+    ///
+    /// Only a shutdown timeout can end the shutdown in this case.
+    Hang,
+}
+
+impl Fault {
+    pub(crate) async fn inject(self, name: &str, clock: &SharedClock) {
+        tracing::warn!("Injecting fault into subsystem '{name}': {self:?}");
+        match self {
+            Fault::Delay(duration) => clock.sleep(duration).await,
+            Fault::Panic => panic!("Injected panic in subsystem '{name}'"),
+            Fault::Hang => std::future::pending().await,
+        }
+    }
+}
+
+/// A policy that injects faults into chosen subsystems during shutdown.
+///
+/// Intended for testing how a service copes with misbehaving subsystems,
+/// like slow connection drains, crashing cleanup code or deadlocks.
+///
+/// Faults only get injected if the subsystem finishes while a shutdown
+/// is in progress; they take effect right after the subsystem function returned.
+///
+/// Registered through [`ToplevelBuilder::fault_injection`](crate::ToplevelBuilder::fault_injection).
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{
+///     testing::{Fault, FaultInjection},
+///     SubsystemBuilder, SubsystemHandle, Toplevel,
+/// };
+///
+/// async fn database(subsys: SubsystemHandle) -> Result<()> {
+///     subsys.on_shutdown_requested().await;
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let result = Toplevel::builder()
+///         .fault_injection(FaultInjection::new(|name| {
+///             (name == "/database").then_some(Fault::Hang)
+///         }))
+///         .build(|s| async move {
+///             s.start(SubsystemBuilder::new("database", database));
+///             s.request_shutdown();
+///         })
+///         .handle_shutdown_requests(Duration::from_millis(100))
+///         .await;
+///
+///     assert!(result.is_err());
+/// }
+/// ```
+pub struct FaultInjection {
+    policy: FaultPolicy,
+}
+
+impl FaultInjection {
+    /// Creates a new fault injection policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Receives the full name of a subsystem, like `/parent/child`,
+    ///   and decides which fault, if any, should get injected into it.
+    pub fn new(policy: impl Fn(&str) -> Option<Fault> + Send + Sync + 'static) -> Self {
+        Self {
+            policy: Box::new(policy),
+        }
+    }
+
+    pub(crate) fn fault_for(&self, name: &str) -> Option<Fault> {
+        (self.policy)(name)
+    }
+}
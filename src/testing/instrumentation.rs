@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+#[cfg(feature = "fault-injection")]
+use super::FaultInjection;
+use super::LifecycleRecorder;
+
+/// Testing hooks that are shared by the entire subsystem tree.
+#[derive(Default)]
+pub(crate) struct Instrumentation {
+    pub(crate) lifecycle_recorder: Option<Arc<LifecycleRecorder>>,
+    #[cfg(feature = "fault-injection")]
+    pub(crate) fault_injection: Option<FaultInjection>,
+}
+
+impl Instrumentation {
+    /// Records the lifecycle of all subsystems into the given recorder.
+    pub(crate) fn recording(lifecycle_recorder: Arc<LifecycleRecorder>) -> Self {
+        Self {
+            lifecycle_recorder: Some(lifecycle_recorder),
+            #[cfg(feature = "fault-injection")]
+            fault_injection: None,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use tokio::time::Instant;
+
+use crate::{clock::SharedClock, utils::Mutex};
+
+/// What happened to a subsystem in a [`LifecycleEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LifecycleEventKind {
+    /// The subsystem got started.
+    Started,
+    /// The subsystem function returned or panicked.
+    ///
+    /// Its children might still be running.
+    Finished,
+}
+
+/// A recorded event in the lifecycle of a subsystem.
+#[derive(Debug, Clone)]
+pub struct LifecycleEvent {
+    /// The full name of the subsystem, like `/parent/child`.
+    pub name: String,
+    /// What happened.
+    pub kind: LifecycleEventKind,
+    /// When it happened.
+    ///
+    /// Uses the [`Clock`](crate::Clock) of the subsystem tree, so with the default
+    /// clock it follows the virtual time if the time is paused.
+    pub timestamp: Instant,
+}
+
+/// Records the lifecycle events of all subsystems of a tree.
+pub(crate) struct LifecycleRecorder {
+    events: Mutex<Vec<LifecycleEvent>>,
+    clock: SharedClock,
+}
+
+impl LifecycleRecorder {
+    pub(crate) fn new(clock: SharedClock) -> Arc<Self> {
+        Arc::new(Self {
+            events: Mutex::new(Vec::new()),
+            clock,
+        })
+    }
+
+    pub(crate) fn record(&self, name: &str, kind: LifecycleEventKind) {
+        self.events.lock().push(LifecycleEvent {
+            name: name.to_string(),
+            kind,
+            timestamp: self.clock.now(),
+        });
+    }
+
+    pub(crate) fn events(&self) -> Vec<LifecycleEvent> {
+        self.events.lock().clone()
+    }
+}
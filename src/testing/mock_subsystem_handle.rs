@@ -0,0 +1,128 @@
+use std::sync::Arc;
+
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    clock::SharedClock, errors::SubsystemError, subsystem, utils::Mutex, BoxedError, ErrTypeTraits,
+    SubsystemHandle, TokioClock,
+};
+
+use super::{Instrumentation, LifecycleEvent, LifecycleEventKind, LifecycleRecorder};
+
+/// A standalone stand-in for the parent of a subsystem.
+///
+/// Provides [`SubsystemHandle`]s that can be passed to a subsystem directly,
+/// without the need of a [`Toplevel`](crate::Toplevel). The shutdown can be
+/// triggered manually, and all subsystems that get started through the handles
+/// are recorded for assertions.
+///
+/// Nested subsystems still get spawned normally, so this has to be used
+/// inside of a tokio runtime.
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio_graceful_shutdown::{testing::MockSubsystemHandle, SubsystemBuilder, SubsystemHandle};
+///
+/// async fn worker(subsys: SubsystemHandle) -> Result<()> {
+///     subsys.on_shutdown_requested().await;
+///     Ok(())
+/// }
+///
+/// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+///     subsys.start(SubsystemBuilder::new("worker", worker));
+///     subsys.on_shutdown_requested().await;
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mock = MockSubsystemHandle::new();
+///     mock.request_shutdown();
+///
+///     my_subsystem(mock.handle()).await.unwrap();
+///
+///     mock.wait_for_children().await;
+///     assert_eq!(mock.started_subsystems(), ["/worker"]);
+/// }
+/// ```
+pub struct MockSubsystemHandle<ErrType: ErrTypeTraits = BoxedError> {
+    root_handle: SubsystemHandle<ErrType>,
+    lifecycle_recorder: Arc<LifecycleRecorder>,
+    errors: Arc<Mutex<Vec<SubsystemError<ErrType>>>>,
+}
+
+impl<ErrType: ErrTypeTraits> MockSubsystemHandle<ErrType> {
+    /// Creates a new mock handle.
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        let clock: SharedClock = Arc::new(TokioClock);
+        let lifecycle_recorder = LifecycleRecorder::new(Arc::clone(&clock));
+        let errors = Arc::new(Mutex::new(Vec::new()));
+
+        let root_handle = subsystem::root_handle(
+            CancellationToken::new(),
+            {
+                let errors = Arc::clone(&errors);
+                move |e| errors.lock().push(e)
+            },
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Instrumentation::recording(Arc::clone(&lifecycle_recorder)),
+            clock,
+        );
+
+        Self {
+            root_handle,
+            lifecycle_recorder,
+            errors,
+        }
+    }
+
+    /// Creates a [`SubsystemHandle`] that can be passed to the subsystem under test.
+    ///
+    /// All handles created by this function share the same state.
+    pub fn handle(&self) -> SubsystemHandle<ErrType> {
+        self.root_handle.share()
+    }
+
+    /// Triggers the shutdown of the handles and all subsystems started through them.
+    pub fn request_shutdown(&self) {
+        self.root_handle.request_shutdown();
+    }
+
+    /// Returns whether a shutdown was requested, either through
+    /// [`request_shutdown`](MockSubsystemHandle::request_shutdown), through one of the
+    /// handles or because a nested subsystem failed.
+    pub fn is_shutdown_requested(&self) -> bool {
+        self.root_handle.is_shutdown_requested()
+    }
+
+    /// Returns the names of all subsystems that were started through the handles,
+    /// including nested ones, in the order they were started.
+    pub fn started_subsystems(&self) -> Vec<String> {
+        self.lifecycle_recorder
+            .events()
+            .into_iter()
+            .filter(|event| event.kind == LifecycleEventKind::Started)
+            .map(|event| event.name)
+            .collect()
+    }
+
+    /// Returns the lifecycle events of all subsystems that were started through the handles.
+    pub fn lifecycle_events(&self) -> Vec<LifecycleEvent> {
+        self.lifecycle_recorder.events()
+    }
+
+    /// Waits until all subsystems that were started through the handles are finished.
+    pub async fn wait_for_children(&self) {
+        self.root_handle.wait_for_children().await
+    }
+
+    /// Takes the errors that nested subsystems propagated up to the handles.
+    pub fn take_errors(&self) -> Vec<SubsystemError<ErrType>> {
+        std::mem::take(&mut *self.errors.lock())
+    }
+}
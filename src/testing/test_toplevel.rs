@@ -0,0 +1,196 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    clock::SharedClock, errors::GracefulShutdownError, BoxedError, ErrTypeTraits, SubsystemHandle,
+    TokioClock, Toplevel, ToplevelHandle,
+};
+
+use super::{Instrumentation, LifecycleEvent, LifecycleEventKind, LifecycleRecorder};
+
+/// A [`Toplevel`] that records the lifecycle of all of its subsystems.
+///
+/// All timestamps use tokio's clock, so this works well together with
+/// paused time, for example through `#[tokio::test(start_paused = true)]`.
+/// With paused time, timeouts and sleeps inside of the subsystems complete
+/// instantly, while the recorded timestamps still reflect the virtual time.
+///
+/// Errors in the result are sorted by subsystem name, see
+/// [`ToplevelBuilder::sorted_errors`](crate::ToplevelBuilder::sorted_errors).
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::{sleep, Duration};
+/// use tokio_graceful_shutdown::{testing::TestToplevel, SubsystemBuilder, SubsystemHandle};
+///
+/// async fn subsystem(delay: Duration, subsys: SubsystemHandle) -> Result<()> {
+///     subsys.on_shutdown_requested().await;
+///     sleep(delay).await;
+///     Ok(())
+/// }
+///
+/// #[tokio::main(flavor = "current_thread")]
+/// async fn main() {
+///     let report = TestToplevel::new(|s| async move {
+///         s.start(SubsystemBuilder::new("db", |s| subsystem(Duration::from_millis(200), s)));
+///         s.start(SubsystemBuilder::new("web", |s| subsystem(Duration::from_millis(100), s)));
+///         s.request_shutdown();
+///     })
+///     .run(Duration::from_secs(1))
+///     .await;
+///
+///     assert!(report.result().is_ok());
+///     report.assert_shutdown_order(["/web", "/db"]);
+///     report.assert_completed_within(Duration::from_millis(500));
+/// }
+/// ```
+pub struct TestToplevel<ErrType: ErrTypeTraits = BoxedError> {
+    toplevel: Toplevel<ErrType>,
+    lifecycle_recorder: Arc<LifecycleRecorder>,
+    clock: SharedClock,
+}
+
+impl<ErrType: ErrTypeTraits> TestToplevel<ErrType> {
+    /// Creates a new TestToplevel object.
+    ///
+    /// # Arguments
+    ///
+    /// * `subsystem` - The subsystem that should be spawned as the root node.
+    pub fn new<Fut, Subsys>(subsystem: Subsys) -> Self
+    where
+        Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
+        Fut: 'static + Future<Output = ()> + Send,
+    {
+        let clock: SharedClock = Arc::new(TokioClock);
+        let lifecycle_recorder = LifecycleRecorder::new(Arc::clone(&clock));
+        let mut toplevel = Toplevel::new_impl(
+            CancellationToken::new(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Instrumentation::recording(Arc::clone(&lifecycle_recorder)),
+            Arc::clone(&clock),
+            subsystem,
+        );
+        toplevel.sorted_errors = true;
+
+        Self {
+            toplevel,
+            lifecycle_recorder,
+            clock,
+        }
+    }
+
+    /// Creates a [`ToplevelHandle`] that allows interacting with the
+    /// subsystem tree from outside, for example to request a shutdown.
+    pub fn handle(&self) -> ToplevelHandle<ErrType> {
+        self.toplevel.handle()
+    }
+
+    /// Runs the subsystem tree until it is shut down.
+    ///
+    /// For more information, see [`Toplevel::handle_shutdown_requests`].
+    ///
+    /// # Arguments
+    ///
+    /// * `shutdown_timeout` - The maximum time that is allowed to pass after a shutdown was initiated.
+    pub async fn run(self, shutdown_timeout: Duration) -> TestReport<ErrType> {
+        let handle = self.toplevel.handle();
+        let result = self
+            .toplevel
+            .handle_shutdown_requests(shutdown_timeout)
+            .await;
+        let finished_at = self.clock.now();
+
+        TestReport {
+            result,
+            events: self.lifecycle_recorder.events(),
+            shutdown_requested_at: handle.shutdown_statistics().shutdown_requested_at,
+            finished_at,
+        }
+    }
+}
+
+/// The outcome of running a [`TestToplevel`].
+pub struct TestReport<ErrType: ErrTypeTraits = BoxedError> {
+    result: Result<(), GracefulShutdownError<ErrType>>,
+    events: Vec<LifecycleEvent>,
+    shutdown_requested_at: Option<Instant>,
+    finished_at: Instant,
+}
+
+impl<ErrType: ErrTypeTraits> TestReport<ErrType> {
+    /// The result of the shutdown, as returned by [`Toplevel::handle_shutdown_requests`].
+    pub fn result(&self) -> &Result<(), GracefulShutdownError<ErrType>> {
+        &self.result
+    }
+
+    /// Converts the report into the result of the shutdown.
+    pub fn into_result(self) -> Result<(), GracefulShutdownError<ErrType>> {
+        self.result
+    }
+
+    /// All recorded lifecycle events, in the order they happened.
+    pub fn events(&self) -> &[LifecycleEvent] {
+        &self.events
+    }
+
+    /// The names of all subsystems, in the order their subsystem functions finished.
+    pub fn shutdown_order(&self) -> Vec<&str> {
+        self.events
+            .iter()
+            .filter(|event| event.kind == LifecycleEventKind::Finished && !event.name.is_empty())
+            .map(|event| event.name.as_str())
+            .collect()
+    }
+
+    /// The time between the shutdown request and the end of the shutdown.
+    ///
+    /// `None` if no shutdown was requested.
+    pub fn shutdown_duration(&self) -> Option<Duration> {
+        self.shutdown_requested_at
+            .map(|requested_at| self.finished_at - requested_at)
+    }
+
+    /// Asserts that the given subsystems finished in the given order.
+    ///
+    /// Subsystems that are not listed are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `expected` - The full names of the subsystems, like `/parent/child`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the subsystems finished in a different order, or if one of them did not finish.
+    #[track_caller]
+    pub fn assert_shutdown_order<'a>(&self, expected: impl IntoIterator<Item = &'a str>) {
+        let expected = expected.into_iter().collect::<Vec<_>>();
+        let actual = self
+            .shutdown_order()
+            .into_iter()
+            .filter(|name| expected.contains(name))
+            .collect::<Vec<_>>();
+
+        assert_eq!(actual, expected, "Subsystems finished in unexpected order");
+    }
+
+    /// Asserts that the shutdown completed within the given time after it was requested.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the shutdown took longer, or if no shutdown was requested.
+    #[track_caller]
+    pub fn assert_completed_within(&self, max_duration: Duration) {
+        let shutdown_duration = self.shutdown_duration().expect("No shutdown was requested");
+
+        assert!(
+            shutdown_duration <= max_duration,
+            "Shutdown took {shutdown_duration:?}, expected at most {max_duration:?}"
+        );
+    }
+}
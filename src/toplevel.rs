@@ -1,16 +1,152 @@
-use std::{future::Future, sync::Arc, time::Duration};
+use std::{
+    future::Future,
+    panic::{catch_unwind, AssertUnwindSafe},
+    pin::Pin,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
-use atomic::Atomic;
-use tokio::sync::mpsc;
+use tokio::{sync::mpsc, time::Instant};
 use tokio_util::sync::CancellationToken;
 
+#[cfg(feature = "axum")]
+mod axum_extractor;
+mod shutdown_controller;
+mod shutdown_watchdog;
+mod toplevel_builder;
+mod toplevel_handle;
+pub use shutdown_controller::ShutdownController;
+pub use toplevel_builder::ToplevelBuilder;
+pub use toplevel_handle::ToplevelHandle;
+
+use shutdown_watchdog::ShutdownWatchdog;
+
+#[cfg(feature = "global")]
+use crate::global::GlobalGuard;
+#[cfg(feature = "task-dump")]
+use crate::task_dump::StallDetector;
+
 use crate::{
+    clock::SharedClock,
+    emergency_shutdown::EmergencyShutdown,
     errors::{handle_dropped_error, GracefulShutdownError, SubsystemError},
-    signal_handling::wait_for_signal,
-    subsystem::{self, ErrorActions},
-    BoxedError, ErrTypeTraits, ErrorAction, NestedSubsystem, SubsystemHandle,
+    last_words::LastWords,
+    panic_hook::PanicHookGuard,
+    report_sink::{BoxedReportSink, ShutdownRecord},
+    shared_resources::SharedResources,
+    shutdown_announcement::ShutdownAnnouncement,
+    shutdown_groups::ShutdownGroups,
+    shutdown_snapshot::ShutdownHook,
+    shutdown_statistics::ShutdownStatisticsCollector,
+    signal_handling::{SignalListener, SignalSet},
+    subsystem::{self, TreeConfig},
+    testing::Instrumentation,
+    utils::Mutex,
+    AbortCause, BoxedError, EmergencyHandle, ErrTypeTraits, NestedSubsystem, Profile, ShutdownPlan,
+    ShutdownReason, ShutdownSignal, ShutdownSnapshot, SubsystemBuilder, SubsystemHandle,
+    SubsystemMiddleware, SubsystemOutcome, SubsystemResult, SubsystemTree, TokioClock,
 };
 
+/// A [`SignalListener`] that records every received signal in the shutdown statistics.
+struct RecordingSignalListener {
+    signals: SignalListener,
+    shutdown_statistics: Arc<ShutdownStatisticsCollector>,
+}
+
+impl RecordingSignalListener {
+    async fn recv(&mut self) -> ShutdownSignal {
+        let signal = self.signals.recv().await;
+        self.shutdown_statistics.record_signal(signal);
+        signal
+    }
+}
+
+/// Initiates a shutdown once a signal is received.
+///
+/// Further signals skip the shutdown confirmation and the drain delay,
+/// and force the process to exit if the shutdown is already in progress
+/// and [`SignalHandling::repeated_signal_exit_code`] is set.
+async fn handle_signals(
+    mut signals: RecordingSignalListener,
+    shutdown_token: CancellationToken,
+    shutdown_announcement: Arc<ShutdownAnnouncement>,
+    signal_handling: SignalHandling,
+    emergency_shutdown: Arc<EmergencyShutdown>,
+    clock: SharedClock,
+) {
+    let SignalHandling {
+        drain_delay,
+        shutdown_confirmation,
+        repeated_signal_exit_code,
+        ..
+    } = signal_handling;
+
+    let signal = signals.recv().await;
+
+    if let Some(shutdown_confirmation) = shutdown_confirmation {
+        tokio::select! {
+            confirmed = shutdown_confirmation() => {
+                if !confirmed {
+                    tracing::warn!("Shutdown request was refused.");
+                    signals.recv().await;
+                    tracing::warn!("Received another signal, shutting down anyway.");
+                }
+            },
+            _ = signals.recv() => {
+                tracing::warn!("Received another signal, skipping shutdown confirmation.");
+            },
+            _ = shutdown_token.cancelled() => (),
+        }
+    }
+
+    if !drain_delay.is_zero() && !shutdown_token.is_cancelled() {
+        tracing::info!("Delaying shutdown by {drain_delay:?} ...");
+        shutdown_announcement.announce(clock.now() + drain_delay);
+        tokio::select! {
+            _ = clock.sleep(drain_delay) => (),
+            _ = signals.recv() => {
+                tracing::warn!("Received another signal, skipping drain delay.");
+            },
+            _ = shutdown_token.cancelled() => (),
+        }
+    }
+
+    signals
+        .shutdown_statistics
+        .record_shutdown_reason(ShutdownReason::Signal(signal));
+    shutdown_token.cancel();
+
+    loop {
+        let signal = signals.recv().await;
+        match repeated_signal_exit_code {
+            Some(exit_code) => emergency_shutdown.exit(
+                &format!("Received {signal} while shutting down, forcing exit."),
+                exit_code,
+            ),
+            None => tracing::warn!("Received {signal} while shutting down."),
+        }
+    }
+}
+
+/// A user-provided callback that decides whether a signal-initiated
+/// shutdown may proceed.
+pub(crate) type ShutdownConfirmation =
+    Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = bool> + Send>> + Send>;
+
+/// Starts a sidecar next to the root subsystem.
+pub(crate) type Sidecar<ErrType> =
+    Box<dyn FnOnce(&SubsystemHandle<ErrType>) -> NestedSubsystem<ErrType> + Send>;
+
+/// How received signals get handled, configured through the [`ToplevelBuilder`].
+#[derive(Default)]
+pub(crate) struct SignalHandling {
+    pub(crate) drain_delay: Duration,
+    pub(crate) shutdown_confirmation: Option<ShutdownConfirmation>,
+    pub(crate) signal_set: SignalSet,
+    // Exit code of the forced exit on a signal during the shutdown.
+    pub(crate) repeated_signal_exit_code: Option<i32>,
+}
+
 /// Acts as the root of the subsystem tree and forms the entry point for
 /// any interaction with this crate.
 ///
@@ -42,9 +178,32 @@ use crate::{
 ///
 #[must_use = "This toplevel must be consumed by calling `handle_shutdown_requests` on it."]
 pub struct Toplevel<ErrType: ErrTypeTraits = BoxedError> {
-    root_handle: SubsystemHandle<ErrType>,
-    toplevel_subsys: NestedSubsystem<ErrType>,
+    root_handle: Arc<SubsystemHandle<ErrType>>,
     errors: mpsc::UnboundedReceiver<SubsystemError<ErrType>>,
+    // Errors that were already received while waiting for the shutdown.
+    // Stored here instead of locally, so they do not get lost silently
+    // if the shutdown future gets dropped.
+    received_errors: Vec<SubsystemError<ErrType>>,
+    shutdown_timeout: Option<Duration>,
+    shutdown_on_idle: bool,
+    shutdown_watchdog: Option<(Duration, i32)>,
+    #[cfg(feature = "task-dump")]
+    task_dump_on_stall: Option<Duration>,
+    emergency_shutdown: Arc<EmergencyShutdown>,
+    last_words: Arc<LastWords>,
+    on_shutdown_triggered: Option<ShutdownHook>,
+    report_sinks: Vec<BoxedReportSink>,
+    // Cancelled once the root subsystem returned.
+    root_returned: CancellationToken,
+    // Sidecars that did not start yet, and the ones that did.
+    sidecars: Vec<Sidecar<ErrType>>,
+    started_sidecars: Vec<NestedSubsystem<ErrType>>,
+    #[cfg(feature = "global")]
+    global_guard: Option<GlobalGuard>,
+    // Whether the shutdown got handled, meaning the subsystems are not running any more.
+    shutdown_handled: bool,
+    panic_hook_guard: Option<PanicHookGuard>,
+    pub(crate) sorted_errors: bool,
 }
 
 impl<ErrType: ErrTypeTraits> Toplevel<ErrType> {
@@ -55,16 +214,112 @@ impl<ErrType: ErrTypeTraits> Toplevel<ErrType> {
     /// # Arguments
     ///
     /// * `subsystem` - The subsystem that should be spawned as the root node.
-    ///                 Usually the job of this subsystem is to spawn further subsystems.
+    ///   Usually the job of this subsystem is to spawn further subsystems.
     #[allow(clippy::new_without_default)]
     pub fn new<Fut, Subsys>(subsystem: Subsys) -> Self
+    where
+        Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
+        Fut: 'static + Future<Output = ()> + Send,
+    {
+        Self::new_with_cancellation_token(CancellationToken::new(), subsystem)
+    }
+
+    /// Creates a new nested Toplevel object.
+    ///
+    /// This method is identical to [`new`](Toplevel::new), except that the returned
+    /// Toplevel will receive shutdown requests from the given [`SubsystemHandle`].
+    ///
+    /// This allows running an entire subsystem tree as a component of a larger
+    /// application, for example inside of a library that wants to offer
+    /// graceful shutdown without owning `main()`.
+    ///
+    /// A shutdown initiated inside of the nested Toplevel will not be
+    /// propagated to the parent subsystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `parent` - The subsystem whose shutdown requests should be forwarded
+    ///   to the nested Toplevel.
+    /// * `subsystem` - The subsystem that should be spawned as the root node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+    ///
+    /// async fn library_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn library_entry_point(subsys: SubsystemHandle) -> Result<()> {
+    ///     Toplevel::nested(&subsys, |s| async move {
+    ///         s.start(SubsystemBuilder::new("LibrarySubsystem", library_subsystem));
+    ///     })
+    ///     .handle_shutdown_requests(Duration::from_millis(500))
+    ///     .await
+    ///     .map_err(Into::into)
+    /// }
+    /// ```
+    pub fn nested<ParentErrType, Fut, Subsys>(
+        parent: &SubsystemHandle<ParentErrType>,
+        subsystem: Subsys,
+    ) -> Self
+    where
+        ParentErrType: ErrTypeTraits,
+        Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
+        Fut: 'static + Future<Output = ()> + Send,
+    {
+        Self::new_with_cancellation_token(parent.get_cancellation_token().clone(), subsystem)
+    }
+
+    /// Creates a new Toplevel object that is driven by an external
+    /// [`CancellationToken`].
+    ///
+    /// Cancelling the given token initiates a shutdown of the Toplevel.
+    /// A shutdown initiated inside of the Toplevel will not cancel the given token.
+    ///
+    /// # Arguments
+    ///
+    /// * `cancellation_token` - The token that triggers the shutdown of this Toplevel.
+    /// * `subsystem` - The subsystem that should be spawned as the root node.
+    pub fn new_with_cancellation_token<Fut, Subsys>(
+        cancellation_token: CancellationToken,
+        subsystem: Subsys,
+    ) -> Self
+    where
+        Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
+        Fut: 'static + Future<Output = ()> + Send,
+    {
+        Self::new_impl(
+            cancellation_token,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Arc::new(TokioClock),
+            subsystem,
+        )
+    }
+
+    pub(crate) fn new_impl<Fut, Subsys>(
+        cancellation_token: CancellationToken,
+        shutdown_groups: ShutdownGroups,
+        shared_resources: SharedResources,
+        tree_config: TreeConfig<ErrType>,
+        instrumentation: Instrumentation,
+        clock: SharedClock,
+        subsystem: Subsys,
+    ) -> Self
     where
         Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
         Fut: 'static + Future<Output = ()> + Send,
     {
         let (error_sender, errors) = mpsc::unbounded_channel();
 
-        let root_handle = subsystem::root_handle(move |e| {
+        let on_error = move |e: SubsystemError<ErrType>| {
             match &e {
                 SubsystemError::Panicked(name) => {
                     tracing::error!("Uncaught panic from subsytem '{name}'.")
@@ -72,31 +327,112 @@ impl<ErrType: ErrTypeTraits> Toplevel<ErrType> {
                 SubsystemError::Failed(name, e) => {
                     tracing::error!("Uncaught error from subsystem '{name}': {e}",)
                 }
+                SubsystemError::Internal(name, e) => {
+                    tracing::error!("Uncaught internal error from subsystem '{name}': {e}")
+                }
+                SubsystemError::Aborted(name) => {
+                    tracing::error!("Subsystem '{name}' got aborted.")
+                }
+                SubsystemError::UncleanExit(name, exit_status) => {
+                    tracing::error!("Subsystem '{name}' {exit_status}.")
+                }
             };
 
             handle_dropped_error(error_sender.send(e));
+        };
+
+        let shutdown_on_panic = tree_config.shutdown_on_panic;
+        let root_handle = subsystem::root_handle(
+            cancellation_token.child_token(),
+            on_error,
+            shutdown_groups,
+            shared_resources,
+            tree_config,
+            instrumentation,
+            clock,
+        );
+
+        // Has to be installed before the root subsystem starts, to not miss its panics.
+        let panic_hook_guard = shutdown_on_panic.then(|| {
+            PanicHookGuard::install(
+                root_handle.get_cancellation_token().clone(),
+                Arc::clone(root_handle.get_shutdown_statistics()),
+            )
         });
 
-        let toplevel_subsys = root_handle.start_with_abs_name(
+        let root_returned = CancellationToken::new();
+        root_handle.start_with_abs_name(
             Arc::from(""),
-            move |s| async move {
-                subsystem(s).await;
-                Result::<(), ErrType>::Ok(())
-            },
-            ErrorActions {
-                on_failure: Atomic::new(ErrorAction::Forward),
-                on_panic: Atomic::new(ErrorAction::Forward),
-            },
-            false,
+            SubsystemBuilder::new("", {
+                let root_returned = root_returned.clone();
+                move |s| async move {
+                    subsystem(s).await;
+                    root_returned.cancel();
+                    Result::<(), ErrType>::Ok(())
+                }
+            }),
+            Ok(None),
         );
 
         Self {
-            root_handle,
-            toplevel_subsys,
+            root_handle: Arc::new(root_handle),
             errors,
+            received_errors: Vec::new(),
+            shutdown_timeout: None,
+            shutdown_on_idle: true,
+            shutdown_watchdog: None,
+            #[cfg(feature = "task-dump")]
+            task_dump_on_stall: None,
+            emergency_shutdown: Default::default(),
+            last_words: Default::default(),
+            on_shutdown_triggered: None,
+            report_sinks: Vec::new(),
+            root_returned,
+            sidecars: Vec::new(),
+            started_sidecars: Vec::new(),
+            #[cfg(feature = "global")]
+            global_guard: None,
+            shutdown_handled: false,
+            panic_hook_guard,
+            sorted_errors: false,
         }
     }
 
+    /// Creates a [`ToplevelBuilder`] that allows configuring
+    /// all aspects of the Toplevel object in one place.
+    ///
+    /// For more information, see [`ToplevelBuilder`].
+    pub fn builder() -> ToplevelBuilder<ErrType> {
+        ToplevelBuilder::new()
+    }
+
+    /// Creates a [`ToplevelBuilder`] with the settings of a [`Profile`]
+    /// for a typical environment.
+    ///
+    /// For more information, see [`ToplevelBuilder::profile`].
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The environment that the program runs in.
+    pub fn with_profile(profile: Profile) -> ToplevelBuilder<ErrType> {
+        ToplevelBuilder::new().profile(profile)
+    }
+
+    /// Creates a [`ToplevelBuilder`] that wraps the execution of all subsystems
+    /// in the given middleware.
+    ///
+    /// For more information, see [`SubsystemMiddleware`].
+    ///
+    /// # Arguments
+    ///
+    /// * `middleware` - The middleware to add; more can be added through
+    ///   [`ToplevelBuilder::middleware`].
+    pub fn with_middleware(
+        middleware: impl SubsystemMiddleware<ErrType>,
+    ) -> ToplevelBuilder<ErrType> {
+        ToplevelBuilder::new().middleware(middleware)
+    }
+
     /// Registers signal handlers to initiate a program shutdown when certain operating system
     /// signals get received.
     ///
@@ -111,6 +447,17 @@ impl<ErrType: ErrTypeTraits> Toplevel<ErrType> {
     /// - On Unix:
     ///     - `SIGINT`
     ///     - `SIGTERM`
+    ///     - `SIGHUP`, if enabled through [`ToplevelBuilder::catch_hangup`]
+    ///
+    /// With [`ToplevelBuilder::ctrl_c_only`], only `CTRL_C` and `SIGINT` get handled.
+    ///
+    /// On all other platforms, signals are not supported by tokio; this function
+    /// only logs an error there, so it can be called unconditionally by
+    /// cross-platform programs.
+    ///
+    /// The first received signal becomes the [`ShutdownReason::Signal`].
+    /// All received signals, including the ones that arrive during the shutdown,
+    /// are listed in [`ShutdownStatistics::received_signals`](crate::ShutdownStatistics::received_signals).
     ///
     /// # Caveats
     ///
@@ -119,16 +466,349 @@ impl<ErrType: ErrTypeTraits> Toplevel<ErrType> {
     /// Especially the caveats from [tokio::signal::unix::Signal] are important for Unix targets.
     ///
     pub fn catch_signals(self) -> Self {
+        self.catch_signals_impl(SignalHandling::default())
+    }
+
+    pub(crate) fn catch_signals_impl(self, signal_handling: SignalHandling) -> Self {
         let shutdown_token = self.root_handle.get_cancellation_token().clone();
+        let shutdown_announcement = Arc::clone(self.root_handle.get_shutdown_announcement());
+        let emergency_shutdown = Arc::clone(&self.emergency_shutdown);
+        let shutdown_statistics = Arc::clone(self.root_handle.get_shutdown_statistics());
+        let clock = Arc::clone(self.root_handle.get_clock());
+        let mut root_state = self.root_handle.watch_children();
 
         tokio::spawn(async move {
-            wait_for_signal().await;
-            shutdown_token.cancel();
+            let signals = match SignalListener::new(signal_handling.signal_set) {
+                Ok(signals) => signals,
+                Err(e) => {
+                    tracing::error!("Failed to register signal handlers: {e}");
+                    return;
+                }
+            };
+            let signals = RecordingSignalListener {
+                signals,
+                shutdown_statistics,
+            };
+
+            // Keep listening until the Toplevel is gone, to record all signals.
+            tokio::select! {
+                () = handle_signals(signals, shutdown_token, shutdown_announcement, signal_handling, emergency_shutdown, clock) => (),
+                _ = root_state.wait_for(|&(alive, _)| !alive) => (),
+            }
         });
 
         self
     }
 
+    /// Installs a panic hook that initiates a shutdown when any thread panics.
+    ///
+    /// Complements [`ErrorAction`](crate::ErrorAction)s by covering panics outside of
+    /// subsystems, like in spawned tasks or threads, so the rest of the program
+    /// winds down instead of continuing in a broken state.
+    /// The shutdown reason is [`ShutdownReason::Panic`].
+    /// Panics inside of subsystems are not affected and still get handled
+    /// according to the [`ErrorAction`](crate::ErrorAction)s of the subsystem.
+    ///
+    /// The previously installed panic hook still gets called. It gets restored
+    /// once this Toplevel is dropped, unless another Toplevel still shuts down on panics.
+    ///
+    /// Only covers panics that happen after this got called; the root subsystem already runs
+    /// at that point. To also cover the panics of tasks that the root subsystem spawns right
+    /// away, use [`ToplevelBuilder::shutdown_on_panic`], which installs the hook before the
+    /// root subsystem starts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     Toplevel::builder()
+    ///         .shutdown_on_panic()
+    ///         .build(|s| async move {
+    ///             s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+    ///
+    ///             // Not a subsystem, but still shuts down the program.
+    ///             let _ = tokio::spawn(async { panic!("Background task crashed") }).await;
+    ///         })
+    ///     .handle_shutdown_requests(Duration::from_millis(500))
+    ///     .await
+    ///     .map_err(Into::into)
+    /// }
+    /// ```
+    pub fn shutdown_on_panic(mut self) -> Self {
+        self.panic_hook_guard = Some(PanicHookGuard::install(
+            self.root_handle.get_cancellation_token().clone(),
+            Arc::clone(self.root_handle.get_shutdown_statistics()),
+        ));
+
+        self
+    }
+
+    /// Initiates a shutdown once the given [`CancellationToken`] gets cancelled.
+    ///
+    /// Together with [`create_cancellation_token`](Toplevel::create_cancellation_token),
+    /// this allows linking multiple independent Toplevel objects, so that a shutdown
+    /// of one of them causes a shutdown of the other.
+    ///
+    /// # Arguments
+    ///
+    /// * `cancellation_token` - The token that initiates the shutdown when cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::Toplevel;
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let control_plane = Toplevel::<miette::Report>::new(|s| async move {
+    ///         s.request_shutdown();
+    ///     });
+    ///
+    ///     // If the control plane shuts down, shut down the data plane as well.
+    ///     let data_plane = Toplevel::<miette::Report>::new(|s| async move {
+    ///         s.on_shutdown_requested().await;
+    ///     })
+    ///     .shutdown_on(control_plane.create_cancellation_token());
+    ///
+    ///     let (control_result, data_result) = tokio::join!(
+    ///         control_plane.handle_shutdown_requests(Duration::from_millis(500)),
+    ///         data_plane.handle_shutdown_requests(Duration::from_millis(500)),
+    ///     );
+    ///     control_result?;
+    ///     data_result?;
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn shutdown_on(self, cancellation_token: CancellationToken) -> Self {
+        let shutdown_token = self.root_handle.get_cancellation_token().clone();
+
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => shutdown_token.cancel(),
+                _ = shutdown_token.cancelled() => (),
+            }
+        });
+
+        self
+    }
+
+    /// Starts a pre-built tree of subsystems next to the root subsystem.
+    ///
+    /// For more information, see [`SubsystemTree`].
+    ///
+    /// # Arguments
+    ///
+    /// * `tree` - The tree of subsystems that should be spawned.
+    ///
+    /// # Returns
+    ///
+    /// The [`NestedSubsystem`] of the root node of the tree.
+    pub fn start(&self, tree: SubsystemTree<ErrType>) -> NestedSubsystem<ErrType> {
+        self.root_handle.start_tree(tree)
+    }
+
+    /// Creates a [`ToplevelHandle`] through which outside code can interact
+    /// with the subsystem tree.
+    ///
+    /// The handle can be cloned freely and stays usable while the
+    /// Toplevel object is alive, including while
+    /// [`handle_shutdown_requests`](Toplevel::handle_shutdown_requests) is being awaited.
+    pub fn handle(&self) -> ToplevelHandle<ErrType> {
+        ToplevelHandle::new(&self.root_handle)
+    }
+
+    /// Determines how the subsystem tree would currently shut down, without cancelling anything.
+    ///
+    /// Reports the order in which the running subsystems would be shut down, together with
+    /// their configured timeouts, and detects subsystems that wait for descendants of a later
+    /// [shutdown group](ToplevelBuilder::shutdown_group), which would prevent their own group
+    /// from ever finishing in time.
+    ///
+    /// Intended for validating complex configurations, for example in CI.
+    ///
+    /// # Returns
+    ///
+    /// The [`ShutdownPlan`] of the running subsystems.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::{sleep, Duration};
+    /// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, SubsystemTree, Toplevel};
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let toplevel = Toplevel::builder()
+    ///         .shutdown_group("http", Duration::from_secs(10))
+    ///         .shutdown_group("storage", Duration::from_secs(5))
+    ///         .shutdown_timeout(Duration::from_secs(20))
+    ///         .build(|_| async {});
+    ///
+    ///     toplevel.start(
+    ///         SubsystemTree::new(SubsystemBuilder::new("http", my_subsystem).shutdown_group("http"))
+    ///             .child(SubsystemTree::new(SubsystemBuilder::new("sessions", my_subsystem))),
+    ///     );
+    ///     toplevel.start(SubsystemTree::new(
+    ///         SubsystemBuilder::new("database", my_subsystem).shutdown_group("storage"),
+    ///     ));
+    ///
+    ///     // Give the subsystems time to start their children.
+    ///     sleep(Duration::from_millis(10)).await;
+    ///     let plan = toplevel.dry_run_shutdown();
+    ///     println!("{plan}");
+    ///     assert!(plan.is_valid());
+    ///
+    ///     toplevel.handle().request_shutdown();
+    ///     toplevel.run().await.map_err(Into::into)
+    /// }
+    /// ```
+    pub fn dry_run_shutdown(&self) -> ShutdownPlan {
+        ShutdownPlan::new(
+            self.shutdown_timeout,
+            self.root_handle.get_shutdown_groups(),
+            self.root_handle.get_child_runners(),
+        )
+    }
+
+    /// Creates a stream of the final results of the subsystems.
+    ///
+    /// Yields the [`SubsystemResult`] of every subsystem that finishes after this
+    /// method got called, as soon as the subsystem and all of its children finished.
+    /// Subsystems that get aborted because of a shutdown timeout are reported as well.
+    /// Ends once the entire subsystem tree finished.
+    ///
+    /// Allows reporting the progress of a shutdown live, instead of all at once at the end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures_util::StreamExt;
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{
+    ///     SubsystemBuilder, SubsystemHandle, SubsystemOutcome, Toplevel,
+    /// };
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.request_shutdown();
+    ///     Ok(())
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let toplevel = Toplevel::new(|s| async move {
+    ///         s.start(SubsystemBuilder::new("web", my_subsystem));
+    ///     });
+    ///
+    ///     let results = toplevel.shutdown_results_stream();
+    ///     tokio::spawn(async move {
+    ///         let mut results = std::pin::pin!(results);
+    ///         while let Some(result) = results.next().await {
+    ///             let symbol = match result.outcome {
+    ///                 SubsystemOutcome::Succeeded => "✓",
+    ///                 _ => "✗",
+    ///             };
+    ///             let duration = result.shutdown_duration.unwrap_or_default();
+    ///             println!("{symbol} {} ({duration:.1?})", result.name);
+    ///         }
+    ///     });
+    ///
+    ///     toplevel
+    ///         .handle_shutdown_requests(Duration::from_millis(1000))
+    ///         .await
+    ///         .map_err(Into::into)
+    /// }
+    /// ```
+    #[cfg(feature = "futures")]
+    pub fn shutdown_results_stream(
+        &self,
+    ) -> impl futures_util::Stream<Item = SubsystemResult> + Send + 'static {
+        let results = self
+            .root_handle
+            .get_shutdown_statistics()
+            .subscribe_results();
+        let root_state = self.root_handle.watch_children();
+
+        futures_util::stream::unfold(
+            (results, root_state),
+            |(mut results, mut root_state)| async move {
+                let result = tokio::select! {
+                    biased;
+                    result = results.recv() => result,
+                    // All results got reported before the tree finished.
+                    _ = root_state.wait_for(|&(alive, _)| !alive) => results.try_recv().ok(),
+                }?;
+                Some((result, (results, root_state)))
+            },
+        )
+    }
+
+    /// Creates an [`EmergencyHandle`], through which detectors of fatal conditions
+    /// can skip the graceful shutdown and terminate the process right away.
+    ///
+    /// Only the critical finalizers registered through
+    /// [`ToplevelBuilder::critical_finalizer`] run before the process exits.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{SubsystemHandle, Toplevel};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let toplevel = Toplevel::builder()
+    ///         .critical_finalizer("flush audit log", || {
+    ///             eprintln!("Flushing audit log ...");
+    ///         })
+    ///         .emergency_exit_code(70)
+    ///         .build(|s: SubsystemHandle| async move {
+    ///             s.on_shutdown_requested().await;
+    ///         });
+    ///
+    ///     let emergency = toplevel.emergency_handle();
+    ///     std::thread::spawn(move || {
+    ///         // A custom watchdog that detected an unrecoverable condition
+    ///         emergency.trigger("Memory limit exceeded");
+    ///     });
+    ///
+    ///     toplevel
+    ///         .handle_shutdown_requests(Duration::from_millis(500))
+    ///         .await
+    ///         .map_err(Into::into)
+    /// }
+    /// ```
+    pub fn emergency_handle(&self) -> EmergencyHandle {
+        EmergencyHandle::new(Arc::clone(&self.emergency_shutdown))
+    }
+
+    /// Creates a cancellation token that will get triggered once the
+    /// Toplevel enters shutdown mode.
+    ///
+    /// Cancelling the returned token does not cause a shutdown of the Toplevel.
+    pub fn create_cancellation_token(&self) -> CancellationToken {
+        self.root_handle.get_cancellation_token().child_token()
+    }
+
     /// Performs a clean program shutdown, once a shutdown is requested or all subsystems have
     /// finished.
     ///
@@ -139,7 +819,8 @@ impl<ErrType: ErrTypeTraits> Toplevel<ErrType> {
     /// to determine the return code of the entire program.
     ///
     /// When the shutdown takes longer than the given timeout, an error will be returned and remaining subsystems
-    /// will be cancelled.
+    /// will be aborted, children before their parents. Every aborted subsystem gets reported
+    /// as [`SubsystemError::Aborted`] in the returned error.
     ///
     /// # Arguments
     ///
@@ -150,65 +831,395 @@ impl<ErrType: ErrTypeTraits> Toplevel<ErrType> {
     /// An error of type [`GracefulShutdownError`] if an error occurred.
     ///
     pub async fn handle_shutdown_requests(
-        mut self,
+        self,
         shutdown_timeout: Duration,
     ) -> Result<(), GracefulShutdownError<ErrType>> {
-        let collect_errors = move || {
-            let mut errors = vec![];
-            self.errors.close();
-            while let Ok(e) = self.errors.try_recv() {
-                errors.push(e);
+        self.handle_shutdown_requests_impl(Some(shutdown_timeout))
+            .await
+    }
+
+    /// Performs a clean program shutdown, using the shutdown timeout
+    /// configured through [`ToplevelBuilder::shutdown_timeout`].
+    ///
+    /// If no shutdown timeout was configured, this waits for all subsystems
+    /// to finish without a time limit.
+    ///
+    /// For more information, see [`handle_shutdown_requests`](Toplevel::handle_shutdown_requests).
+    ///
+    /// # Returns
+    ///
+    /// An error of type [`GracefulShutdownError`] if an error occurred.
+    ///
+    pub async fn run(self) -> Result<(), GracefulShutdownError<ErrType>> {
+        let shutdown_timeout = self.shutdown_timeout;
+        self.handle_shutdown_requests_impl(shutdown_timeout).await
+    }
+
+    async fn handle_shutdown_requests_impl(
+        mut self,
+        shutdown_timeout: Option<Duration>,
+    ) -> Result<(), GracefulShutdownError<ErrType>> {
+        let last_words = Arc::clone(&self.last_words);
+        let report_sinks = std::mem::take(&mut self.report_sinks);
+        let root_handle = Arc::clone(&self.root_handle);
+        let result = self.shut_down(shutdown_timeout).await;
+        if !report_sinks.is_empty() {
+            persist_report(&report_sinks, &root_handle, &result);
+        }
+        last_words.run();
+        result
+    }
+
+    async fn shut_down(
+        mut self,
+        shutdown_timeout: Option<Duration>,
+    ) -> Result<(), GracefulShutdownError<ErrType>> {
+        let clock = Arc::clone(self.root_handle.get_clock());
+
+        let sidecars_ready = {
+            let root_returned = self.root_returned.clone();
+            let readiness = Arc::clone(self.root_handle.get_readiness());
+            async move {
+                root_returned.cancelled().await;
+                readiness.wait_ready().await;
             }
-            drop(self.errors);
-            errors.into_boxed_slice()
         };
+        let mut sidecars_ready = std::pin::pin!(sidecars_ready);
 
-        tokio::select!(
-            _ = self.toplevel_subsys.join() => {
-                tracing::info!("All subsystems finished.");
+        loop {
+            tokio::select!(
+                _ = self.root_handle.wait_for_children(), if self.shutdown_on_idle => {
+                    tracing::info!("All subsystems finished.");
 
-                // Not really necessary, but for good measure.
-                self.root_handle.request_shutdown();
+                    // Not really necessary, but for good measure.
+                    self.root_handle.request_shutdown();
 
-                let errors = collect_errors();
-                let result = if errors.is_empty() {
-                    Ok(())
-                } else {
-                    Err(GracefulShutdownError::SubsystemsFailed(errors))
-                };
-                return result;
-            },
-            _ = self.root_handle.on_shutdown_requested() => {
-                tracing::info!("Shutting down ...");
+                    let close_resources = self.root_handle.get_shared_resources().close_all();
+                    let closed = match shutdown_timeout {
+                        Some(shutdown_timeout) => {
+                            clock.timeout(shutdown_timeout, close_resources).await.is_some()
+                        }
+                        None => {
+                            close_resources.await;
+                            true
+                        }
+                    };
+                    if !closed {
+                        tracing::error!("Closing shared resources timed out!");
+                    }
+
+                    let errors = self.collect_errors();
+                    let result = if errors.is_empty() {
+                        Ok(())
+                    } else {
+                        Err(GracefulShutdownError::SubsystemsFailed(errors))
+                    };
+                    return result;
+                },
+                _ = self.root_handle.get_cancellation_token().cancelled() => {
+                    self.root_handle.get_shutdown_statistics().record_shutdown_requested();
+                    if let Some(hook) = self.on_shutdown_triggered.take() {
+                        self.run_shutdown_hook(hook);
+                    }
+                    tracing::info!("Shutting down ...");
+                    break;
+                },
+                _ = &mut sidecars_ready, if !self.sidecars.is_empty() => self.start_sidecars(),
+            );
+        }
+        let shutdown_started = clock.now();
+
+        let _watchdog = self.shutdown_watchdog.map(|(limit, exit_code)| {
+            ShutdownWatchdog::exit_process(limit, exit_code, Arc::clone(&self.last_words))
+        });
+        #[cfg(feature = "task-dump")]
+        let _stall_detector = self.task_dump_on_stall.map(|stall_timeout| {
+            StallDetector::start(
+                stall_timeout,
+                Arc::clone(&clock),
+                self.root_handle.get_children().clone(),
+            )
+        });
+
+        let shutdown_groups = Arc::clone(self.root_handle.get_shutdown_groups());
+        let shared_resources = Arc::clone(self.root_handle.get_shared_resources());
+        let sidecars = std::mem::take(&mut self.started_sidecars);
+        let root_handle = Arc::clone(&self.root_handle);
+        // Filled while shutting down, so the aborted groups get reported even if the shutdown times out.
+        let group_aborted = Mutex::new(Vec::new());
+        let shut_down = async {
+            if !sidecars.is_empty() {
+                tracing::debug!("Waiting for {} sidecar(s) to finish ...", sidecars.len());
+                for sidecar in &sidecars {
+                    sidecar.finished().await;
+                }
             }
-        );
+            root_handle.release_shutdown_requests();
+
+            tokio::join!(
+                shutdown_groups.shut_down(&clock, &group_aborted),
+                self.wait_for_subsystems(),
+                shared_resources.close_all()
+            );
+        };
+        let join_result = match shutdown_timeout {
+            Some(shutdown_timeout) => clock.timeout(shutdown_timeout, shut_down).await,
+            None => {
+                shut_down.await;
+                Some(())
+            }
+        };
 
-        match tokio::time::timeout(shutdown_timeout, self.toplevel_subsys.join()).await {
-            Ok(result) => {
-                // An `Err` here would indicate a programming error,
-                // because the toplevel subsys doesn't catch any errors;
-                // it only forwards them.
-                assert!(result.is_ok());
+        let group_aborted = std::mem::take(&mut *group_aborted.lock());
 
-                let errors = collect_errors();
+        match join_result {
+            Some(()) if !group_aborted.is_empty() => {
+                tracing::error!("Shutdown finished, but some shutdown groups had to be aborted!");
+                let aborted = group_aborted;
+                self.report_aborted(&aborted, shutdown_started);
+                log_report(self.root_handle.get_shutdown_statistics());
+                self.received_errors.extend(
+                    aborted
+                        .into_iter()
+                        .map(|(name, _)| SubsystemError::Aborted(name)),
+                );
+                Err(GracefulShutdownError::ShutdownTimeout(
+                    self.collect_errors(),
+                ))
+            }
+            Some(_) => {
+                let shutdown_statistics = Arc::clone(self.root_handle.get_shutdown_statistics());
+                let errors = self.collect_errors();
                 if errors.is_empty() {
                     tracing::info!("Shutdown finished.");
                     Ok(())
                 } else {
                     tracing::warn!("Shutdown finished with errors.");
+                    log_report(&shutdown_statistics);
                     Err(GracefulShutdownError::SubsystemsFailed(errors))
                 }
             }
-            Err(_) => {
+            None => {
                 tracing::error!("Shutdown timed out!");
-                Err(GracefulShutdownError::ShutdownTimeout(collect_errors()))
+
+                // Only reachable with a shutdown timeout.
+                let cause = AbortCause::ShutdownTimeout(shutdown_timeout.unwrap_or_default());
+
+                // Abort the remaining subsystems explicitly, to be able to report them.
+                let mut aborted = group_aborted;
+                aborted.extend(
+                    self.root_handle
+                        .abort_children()
+                        .into_iter()
+                        .map(|name| (name, cause.clone())),
+                );
+                self.report_aborted(&aborted, shutdown_started);
+                log_report(self.root_handle.get_shutdown_statistics());
+                self.received_errors.extend(
+                    aborted
+                        .into_iter()
+                        .map(|(name, _)| SubsystemError::Aborted(name)),
+                );
+
+                Err(GracefulShutdownError::ShutdownTimeout(
+                    self.collect_errors(),
+                ))
+            }
+        }
+    }
+
+    /// Starts the sidecars, unless the shutdown already began.
+    fn start_sidecars(&mut self) {
+        let sidecars = std::mem::take(&mut self.sidecars);
+        if self.root_handle.get_cancellation_token().is_cancelled() {
+            tracing::debug!("Not starting sidecars, as a shutdown is in progress.");
+            return;
+        }
+
+        tracing::debug!("All subsystems are ready; starting sidecars ...");
+        self.started_sidecars = sidecars
+            .into_iter()
+            .map(|sidecar| sidecar(&self.root_handle))
+            .collect();
+    }
+
+    /// Reports the results of subsystems that got aborted, as they can't report them themselves.
+    fn report_aborted(&self, aborted: &[(Arc<str>, AbortCause)], shutdown_started: Instant) {
+        let shutdown_statistics = self.root_handle.get_shutdown_statistics();
+        let shutdown_duration = self
+            .root_handle
+            .get_clock()
+            .now()
+            .saturating_duration_since(shutdown_started);
+        for (name, cause) in aborted {
+            shutdown_statistics.record_result(
+                SubsystemResult {
+                    name: Arc::clone(name),
+                    outcome: SubsystemOutcome::Aborted,
+                    exit_status: None,
+                    shutdown_duration: Some(shutdown_duration),
+                    abort_cause: Some(cause.clone()),
+                },
+                None,
+            );
+        }
+    }
+
+    /// Waits for all subsystems to finish.
+    ///
+    /// Errors get collected as they arrive, and the progress gets logged
+    /// whenever the number of remaining subsystems changes.
+    async fn wait_for_subsystems(&mut self) {
+        let mut children = self.root_handle.watch_children();
+        let mut previous_remaining = None;
+
+        loop {
+            let remaining = children.borrow_and_update().1;
+            if remaining == 0 {
+                break;
+            }
+            if previous_remaining != Some(remaining) {
+                tracing::debug!("Waiting for {remaining} subsystem(s) to finish ...");
+                previous_remaining = Some(remaining);
+            }
+
+            tokio::select! {
+                Some(e) = self.errors.recv() => self.received_errors.push(e),
+                changed = children.changed() => {
+                    // An error would mean that the root got dropped,
+                    // so no subsystems can exist any more.
+                    if changed.is_err() {
+                        break;
+                    }
+                }
             }
         }
     }
 
+    fn collect_errors(mut self) -> Box<[SubsystemError<ErrType>]> {
+        self.shutdown_handled = true;
+        let mut errors = std::mem::take(&mut self.received_errors);
+        self.errors.close();
+        while let Ok(e) = self.errors.try_recv() {
+            errors.push(e);
+        }
+        if self.sorted_errors {
+            errors.sort_by(|a, b| a.name().cmp(b.name()));
+        }
+        errors.into_boxed_slice()
+    }
+
+    /// Passes a snapshot of the subsystem tree to the hook of
+    /// [`ToplevelBuilder::on_shutdown_triggered`].
+    fn run_shutdown_hook(&self, hook: ShutdownHook) {
+        let shutdown_statistics = self.root_handle.get_shutdown_statistics();
+
+        let mut running_subsystems = self.root_handle.unfinished_children();
+        running_subsystems.sort();
+
+        let snapshot = ShutdownSnapshot {
+            // No reason gets recorded if the shutdown comes from outside of the tree.
+            reason: shutdown_statistics
+                .shutdown_reason()
+                .cloned()
+                .unwrap_or(ShutdownReason::External),
+            running_subsystems,
+            statistics: shutdown_statistics.snapshot(true),
+        };
+
+        if catch_unwind(AssertUnwindSafe(|| hook(&snapshot))).is_err() {
+            tracing::error!("The shutdown hook panicked.");
+        }
+    }
+
+    /// Reports subsystems that are still running when the Toplevel gets dropped,
+    /// for example because `handle_shutdown_requests` never got awaited.
+    ///
+    /// The subsystems get cancelled once the Toplevel is gone; requesting a shutdown
+    /// beforehand lets them observe the shutdown through their handles and tokens.
+    fn report_unhandled_shutdown(&self) {
+        let running = self
+            .root_handle
+            .unfinished_children()
+            .iter()
+            .map(|name| format!("'{name}'"))
+            .collect::<Vec<_>>();
+        if running.is_empty() {
+            return;
+        }
+
+        tracing::warn!(
+            "Toplevel got dropped without handling its shutdown! Cancelling the remaining subsystems: {}",
+            running.join(", ")
+        );
+        self.root_handle.request_shutdown();
+    }
+
     #[doc(hidden)]
     // Only for unit tests; not intended for public use
     pub fn _get_shutdown_token(&self) -> &CancellationToken {
         self.root_handle.get_cancellation_token()
     }
 }
+
+impl<ErrType: ErrTypeTraits> Drop for Toplevel<ErrType> {
+    fn drop(&mut self) {
+        // Only reached with errors left if the errors did not get collected,
+        // for example because the shutdown future got dropped.
+        self.errors.close();
+        let pending_errors = std::iter::from_fn(|| self.errors.try_recv().ok());
+        for e in self.received_errors.drain(..).chain(pending_errors) {
+            tracing::warn!("An error got dropped: {e:?}");
+        }
+
+        if !self.shutdown_handled {
+            self.report_unhandled_shutdown();
+            self.last_words.run();
+        }
+    }
+}
+
+/// Passes the outcome of a finished shutdown to the given report sinks.
+fn persist_report<ErrType: ErrTypeTraits>(
+    report_sinks: &[BoxedReportSink],
+    root_handle: &SubsystemHandle<ErrType>,
+    result: &Result<(), GracefulShutdownError<ErrType>>,
+) {
+    let shutdown_statistics = root_handle.get_shutdown_statistics();
+    let statistics = shutdown_statistics.snapshot(true);
+    let shutdown_duration = statistics
+        .shutdown_requested_at
+        .map(|requested_at| {
+            root_handle
+                .get_clock()
+                .now()
+                .saturating_duration_since(requested_at)
+        })
+        .unwrap_or_default();
+
+    let record = ShutdownRecord {
+        finished_at: SystemTime::now(),
+        // No reason gets recorded if the shutdown comes from outside of the tree.
+        reason: shutdown_statistics
+            .shutdown_reason()
+            .cloned()
+            .unwrap_or(ShutdownReason::External),
+        succeeded: result.is_ok(),
+        timed_out: matches!(result, Err(GracefulShutdownError::ShutdownTimeout(_))),
+        shutdown_duration,
+        report: shutdown_statistics.report(),
+        statistics,
+    };
+
+    for sink in report_sinks {
+        if catch_unwind(AssertUnwindSafe(|| sink.persist(&record))).is_err() {
+            tracing::error!("A report sink panicked.");
+        }
+    }
+}
+
+/// Logs the results of all subsystems, after a shutdown that did not go cleanly.
+fn log_report(shutdown_statistics: &ShutdownStatisticsCollector) {
+    let report = shutdown_statistics.report();
+    tracing::warn!("Shutdown report:\n{report}");
+}
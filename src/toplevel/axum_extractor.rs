@@ -0,0 +1,42 @@
+use axum::{
+    extract::{rejection::ExtensionRejection, FromRequestParts},
+    http::request::Parts,
+    Extension,
+};
+
+use crate::{ErrTypeTraits, ToplevelHandle};
+
+/// Extracts the [`ToplevelHandle`] from the request extensions.
+///
+/// Requires the handle to be added as an [`Extension`] layer to the router.
+///
+/// # Examples
+///
+/// ```
+/// use axum::{http::StatusCode, routing::post, Extension, Router};
+/// use tokio_graceful_shutdown::ToplevelHandle;
+///
+/// async fn admin_shutdown(toplevel: ToplevelHandle) -> StatusCode {
+///     toplevel.request_shutdown();
+///     StatusCode::ACCEPTED
+/// }
+///
+/// fn router(toplevel: ToplevelHandle) -> Router {
+///     Router::new()
+///         .route("/admin/shutdown", post(admin_shutdown))
+///         .layer(Extension(toplevel))
+/// }
+/// ```
+#[async_trait::async_trait]
+impl<S, ErrType> FromRequestParts<S> for ToplevelHandle<ErrType>
+where
+    S: Send + Sync,
+    ErrType: ErrTypeTraits,
+{
+    type Rejection = ExtensionRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Extension(handle) = Extension::<Self>::from_request_parts(parts, state).await?;
+        Ok(handle)
+    }
+}
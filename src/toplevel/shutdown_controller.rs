@@ -0,0 +1,108 @@
+use std::future::Future;
+
+use crate::{
+    errors::GracefulShutdownError, utils::Mutex, BoxedError, ErrTypeTraits, SubsystemHandle,
+    ToplevelBuilder, ToplevelHandle,
+};
+
+type Configure<ErrType> =
+    Box<dyn Fn(ToplevelBuilder<ErrType>) -> ToplevelBuilder<ErrType> + Send + Sync>;
+
+/// Runs subsystem trees repeatedly with the same configuration.
+///
+/// Every call to [`run`](Self::run) creates a fresh [`Toplevel`](crate::Toplevel)
+/// and performs a full shutdown before it returns, so no state carries over between runs.
+/// Designed for long-lived test harnesses and REPL-like hosts.
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{ShutdownController, SubsystemBuilder, SubsystemHandle};
+///
+/// async fn job(subsys: SubsystemHandle) -> Result<()> {
+///     tracing::info!("Running job ...");
+///     subsys.request_shutdown();
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let controller = ShutdownController::new(|toplevel| {
+///         toplevel.shutdown_timeout(Duration::from_millis(500))
+///     });
+///
+///     for _ in 0..3 {
+///         controller
+///             .run(|s: SubsystemHandle| async move {
+///                 s.start(SubsystemBuilder::new("job", job));
+///             })
+///             .await?;
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub struct ShutdownController<ErrType: ErrTypeTraits = BoxedError> {
+    configure: Configure<ErrType>,
+    current_run: Mutex<Option<ToplevelHandle<ErrType>>>,
+}
+
+impl<ErrType: ErrTypeTraits> ShutdownController<ErrType> {
+    /// Creates a new controller.
+    ///
+    /// # Arguments
+    ///
+    /// * `configure` - Configures the [`ToplevelBuilder`] of every run.
+    pub fn new(
+        configure: impl Fn(ToplevelBuilder<ErrType>) -> ToplevelBuilder<ErrType> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            configure: Box::new(configure),
+            current_run: Mutex::new(None),
+        }
+    }
+
+    /// Runs a fresh subsystem tree until it is shut down.
+    ///
+    /// Behaves like [`Toplevel::run`](crate::Toplevel::run).
+    ///
+    /// # Arguments
+    ///
+    /// * `subsystem` - The subsystem that should be spawned as the root node.
+    ///
+    /// # Returns
+    ///
+    /// An error of type [`GracefulShutdownError`] if an error occurred.
+    pub async fn run<Fut, Subsys>(
+        &self,
+        subsystem: Subsys,
+    ) -> Result<(), GracefulShutdownError<ErrType>>
+    where
+        Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
+        Fut: 'static + Future<Output = ()> + Send,
+    {
+        let toplevel = (self.configure)(ToplevelBuilder::new()).build(subsystem);
+        *self.current_run.lock() = Some(toplevel.handle());
+
+        let result = toplevel.run().await;
+
+        *self.current_run.lock() = None;
+        result
+    }
+
+    /// Returns a [`ToplevelHandle`] to the current run, if there is one.
+    pub fn handle(&self) -> Option<ToplevelHandle<ErrType>> {
+        self.current_run.lock().clone()
+    }
+
+    /// Triggers a shutdown of the current run.
+    ///
+    /// Does nothing if no run is in progress; later runs are not affected.
+    pub fn request_shutdown(&self) {
+        if let Some(current_run) = self.current_run.lock().as_ref() {
+            current_run.request_shutdown();
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+    time::Duration,
+};
+
+use crate::last_words::LastWords;
+
+/// Runs a callback on a dedicated OS thread if it does not get dropped within a time limit.
+///
+/// Does not depend on the tokio runtime, so it still fires if the runtime is starved
+/// or deadlocked.
+pub(crate) struct ShutdownWatchdog {
+    // Dropping the sender wakes up the watchdog thread.
+    _disarm: mpsc::Sender<()>,
+}
+
+impl ShutdownWatchdog {
+    pub(crate) fn start(limit: Duration, on_expiry: impl FnOnce() + Send + 'static) -> Self {
+        let (disarm, disarmed) = mpsc::channel::<()>();
+
+        let spawned = thread::Builder::new()
+            .name("shutdown-watchdog".into())
+            .spawn(move || {
+                if let Err(mpsc::RecvTimeoutError::Timeout) = disarmed.recv_timeout(limit) {
+                    on_expiry();
+                }
+            });
+        if let Err(e) = spawned {
+            tracing::error!("Failed to start shutdown watchdog: {e}");
+        }
+
+        Self { _disarm: disarm }
+    }
+
+    /// Starts a watchdog that terminates the process with the given exit code,
+    /// after running the last words.
+    ///
+    /// Uses [`std::process::exit`], so no destructors of other threads run.
+    /// The stacks of the stuck threads can't be captured from the watchdog
+    /// thread; use a task dump or a debugger for those.
+    pub(crate) fn exit_process(
+        limit: Duration,
+        exit_code: i32,
+        last_words: Arc<LastWords>,
+    ) -> Self {
+        Self::start(limit, move || {
+            tracing::error!(
+                "Shutdown did not finish within {limit:?}; terminating the process with exit code {exit_code}."
+            );
+            last_words.run();
+            std::process::exit(exit_code);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests;
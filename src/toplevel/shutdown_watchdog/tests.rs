@@ -0,0 +1,33 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use super::*;
+
+#[test]
+fn fires_after_limit() {
+    let fired = Arc::new(AtomicBool::new(false));
+
+    let _watchdog = ShutdownWatchdog::start(Duration::from_millis(50), {
+        let fired = Arc::clone(&fired);
+        move || fired.store(true, Ordering::Relaxed)
+    });
+
+    thread::sleep(Duration::from_millis(200));
+    assert!(fired.load(Ordering::Relaxed));
+}
+
+#[test]
+fn does_not_fire_when_dropped() {
+    let fired = Arc::new(AtomicBool::new(false));
+
+    let watchdog = ShutdownWatchdog::start(Duration::from_millis(100), {
+        let fired = Arc::clone(&fired);
+        move || fired.store(true, Ordering::Relaxed)
+    });
+    drop(watchdog);
+
+    thread::sleep(Duration::from_millis(200));
+    assert!(!fired.load(Ordering::Relaxed));
+}
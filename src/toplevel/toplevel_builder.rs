@@ -0,0 +1,1207 @@
+#[cfg(not(madsim))]
+use std::time::Instant;
+use std::{future::Future, marker::PhantomData, sync::Arc, time::Duration};
+
+use tokio_util::sync::CancellationToken;
+
+#[cfg(not(madsim))]
+use crate::errors::{GracefulShutdownError, InternalError, SubsystemError};
+#[cfg(feature = "global")]
+use crate::global::GlobalGuard;
+#[cfg(feature = "fault-injection")]
+use crate::testing::FaultInjection;
+use crate::{
+    clock::SharedClock,
+    emergency_shutdown::{CriticalFinalizer, EmergencyShutdown},
+    last_words::{LastWords, LastWordsCallback, DEFAULT_LAST_WORDS_TIMEOUT},
+    middleware::BoxedMiddleware,
+    report_sink::BoxedReportSink,
+    shared_resources::{SharedResourceConfig, SharedResources},
+    shutdown_groups::ShutdownGroups,
+    shutdown_report::DEFAULT_REPORT_AGGREGATION_THRESHOLD,
+    shutdown_snapshot::ShutdownHook,
+    spawn_hook::BoxedSpawnHook,
+    subsystem::TreeConfig,
+    testing::Instrumentation,
+    AsyncClose, BoxedError, Clock, ErrTypeTraits, Profile, ReportSink, ShutdownSnapshot, SpawnHook,
+    StartupRacePolicy, SubsystemBuilder, SubsystemHandle, SubsystemMiddleware, TokioClock,
+    Toplevel,
+};
+
+use super::{Sidecar, SignalHandling};
+
+/// Configures a [`Toplevel`] object before it gets created.
+///
+/// Collects all the settings of the Toplevel in one place.
+/// For simple use cases, [`Toplevel::new`] is a shortcut for
+/// a builder with default settings.
+///
+/// # Examples
+///
+/// ```
+/// use miette::Result;
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+///
+/// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+///     subsys.request_shutdown();
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     Toplevel::builder()
+///         .catch_signals()
+///         .shutdown_timeout(Duration::from_millis(1000))
+///         .build(|s| async move {
+///             s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+///         })
+///         .run()
+///         .await
+///         .map_err(Into::into)
+/// }
+/// ```
+#[must_use = "This builder must be consumed by calling `build` on it."]
+pub struct ToplevelBuilder<ErrType: ErrTypeTraits = BoxedError> {
+    catch_signals: bool,
+    shutdown_on_panic: bool,
+    #[cfg(feature = "global")]
+    install_global: bool,
+    signal_handling: SignalHandling,
+    shutdown_timeout: Option<Duration>,
+    shutdown_on_idle: bool,
+    shutdown_watchdog: Option<(Duration, i32)>,
+    #[cfg(feature = "task-dump")]
+    task_dump_on_stall: Option<Duration>,
+    emergency_exit_code: i32,
+    critical_finalizers: Vec<(Arc<str>, CriticalFinalizer)>,
+    last_words: Vec<(Arc<str>, LastWordsCallback)>,
+    last_words_timeout: Duration,
+    on_shutdown_triggered: Option<ShutdownHook>,
+    report_sinks: Vec<BoxedReportSink>,
+    shutdown_groups: Vec<(Arc<str>, Duration)>,
+    shared_resources: Vec<SharedResourceConfig>,
+    startup_race_policy: StartupRacePolicy,
+    name_separator: Arc<str>,
+    max_depth: Option<usize>,
+    strict_exit_statuses: bool,
+    middlewares: Vec<BoxedMiddleware<ErrType>>,
+    spawn_hooks: Vec<BoxedSpawnHook>,
+    sidecars: Vec<Sidecar<ErrType>>,
+    blocking_threshold: Option<Duration>,
+    sorted_errors: bool,
+    deterministic_shutdown: Option<u64>,
+    cancellation_token: Option<CancellationToken>,
+    #[cfg_attr(madsim, allow(dead_code))]
+    runtime_shutdown_timeout: Duration,
+    clock: SharedClock,
+    report_aggregation_threshold: usize,
+    #[cfg(feature = "fault-injection")]
+    fault_injection: Option<FaultInjection>,
+    _phantom: PhantomData<fn() -> ErrType>,
+}
+
+impl<ErrType: ErrTypeTraits> ToplevelBuilder<ErrType> {
+    /// Creates a new ToplevelBuilder with default settings.
+    ///
+    /// Also available as [`Toplevel::builder`].
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            catch_signals: false,
+            shutdown_on_panic: false,
+            #[cfg(feature = "global")]
+            install_global: false,
+            signal_handling: SignalHandling::default(),
+            shutdown_timeout: None,
+            shutdown_on_idle: true,
+            shutdown_watchdog: None,
+            #[cfg(feature = "task-dump")]
+            task_dump_on_stall: None,
+            emergency_exit_code: 1,
+            critical_finalizers: Vec::new(),
+            last_words: Vec::new(),
+            last_words_timeout: DEFAULT_LAST_WORDS_TIMEOUT,
+            on_shutdown_triggered: None,
+            report_sinks: Vec::new(),
+            shutdown_groups: Vec::new(),
+            shared_resources: Vec::new(),
+            startup_race_policy: StartupRacePolicy::default(),
+            name_separator: Arc::from("/"),
+            max_depth: None,
+            strict_exit_statuses: false,
+            middlewares: Vec::new(),
+            spawn_hooks: Vec::new(),
+            sidecars: Vec::new(),
+            blocking_threshold: None,
+            sorted_errors: false,
+            deterministic_shutdown: None,
+            cancellation_token: None,
+            runtime_shutdown_timeout: Duration::from_secs(1),
+            clock: Arc::new(TokioClock),
+            report_aggregation_threshold: DEFAULT_REPORT_AGGREGATION_THRESHOLD,
+            #[cfg(feature = "fault-injection")]
+            fault_injection: None,
+            _phantom: Default::default(),
+        }
+    }
+
+    /// Applies the settings of a [`Profile`] for a typical environment.
+    ///
+    /// Settings that get configured afterwards override the ones of the profile.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - The environment that the program runs in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{Profile, SubsystemBuilder, SubsystemHandle, Toplevel};
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.request_shutdown();
+    ///     Ok(())
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     Toplevel::builder()
+    ///         .profile(Profile::Kubernetes)
+    ///         // Our load balancer takes a little longer to notice.
+    ///         .drain_delay(Duration::from_millis(10))
+    ///         .build(|s| async move {
+    ///             s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+    ///         })
+    ///         .run()
+    ///         .await
+    ///         .map_err(Into::into)
+    /// }
+    /// ```
+    pub fn profile(self, profile: Profile) -> Self {
+        match profile {
+            Profile::Kubernetes => self
+                .catch_signals()
+                .drain_delay(Duration::from_secs(5))
+                .shutdown_timeout(Duration::from_secs(20))
+                .shutdown_watchdog(Duration::from_secs(25), 1),
+            Profile::Systemd => self
+                .catch_signals()
+                .shutdown_timeout(Duration::from_secs(60))
+                .shutdown_watchdog(Duration::from_secs(80), 1),
+            Profile::Cli => self
+                .catch_signals()
+                .catch_hangup(true)
+                .shutdown_timeout(Duration::from_secs(5))
+                .force_exit_on_repeated_signal(130),
+            Profile::Desktop => self
+                .catch_signals()
+                .catch_hangup(true)
+                .shutdown_timeout(Duration::from_secs(10))
+                .force_exit_on_repeated_signal(130),
+        }
+    }
+
+    /// Registers signal handlers that initiate a shutdown.
+    ///
+    /// For more information, see [`Toplevel::catch_signals`].
+    pub fn catch_signals(mut self) -> Self {
+        self.catch_signals = true;
+        self
+    }
+
+    /// Installs a panic hook that initiates a shutdown when any thread panics.
+    ///
+    /// For more information, see [`Toplevel::shutdown_on_panic`].
+    pub fn shutdown_on_panic(mut self) -> Self {
+        self.shutdown_on_panic = true;
+        self
+    }
+
+    /// Makes the Toplevel reachable from anywhere in the process, through [`global()`](crate::global()).
+    ///
+    /// The Toplevel stays installed until it gets dropped. Only one Toplevel can be
+    /// installed at a time; installing another one replaces it.
+    #[cfg(feature = "global")]
+    pub fn install_global(mut self) -> Self {
+        self.install_global = true;
+        self
+    }
+
+    /// Sets the time between receiving a signal and initiating the shutdown.
+    ///
+    /// During this time, subsystems keep running normally. This is useful
+    /// in environments like Kubernetes, where requests might still be routed
+    /// to the service for a short while after the termination signal.
+    /// Subsystems can still prepare their clients for the upcoming shutdown
+    /// through [`SubsystemHandle::on_shutdown_imminent`].
+    ///
+    /// Has no effect unless [`catch_signals`](ToplevelBuilder::catch_signals) is set.
+    ///
+    /// The default is no delay.
+    pub fn drain_delay(mut self, drain_delay: Duration) -> Self {
+        self.signal_handling.drain_delay = drain_delay;
+        self
+    }
+
+    /// Registers a callback that gets consulted before a signal initiates a shutdown.
+    ///
+    /// The callback can delay the shutdown by not resolving immediately, or refuse it
+    /// by returning `false`, for example while critical work is in progress.
+    ///
+    /// Only the first signal is subject to confirmation. Any further signal, as well as
+    /// shutdown requests from subsystems or errors, initiate a shutdown right away.
+    ///
+    /// Has no effect unless [`catch_signals`](ToplevelBuilder::catch_signals) is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `shutdown_confirmation` - Returns whether the shutdown may proceed.
+    pub fn shutdown_confirmation<F, Fut>(mut self, shutdown_confirmation: F) -> Self
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = bool> + Send + 'static,
+    {
+        self.signal_handling.shutdown_confirmation =
+            Some(Box::new(move || Box::pin(shutdown_confirmation())));
+        self
+    }
+
+    /// Sets whether `SIGHUP` initiates a shutdown as well.
+    ///
+    /// `SIGHUP` gets sent when the controlling terminal is closed. By default, it
+    /// terminates the process right away, skipping the graceful shutdown.
+    /// Note that catching it replaces this default behavior for the rest
+    /// of the lifetime of the process.
+    ///
+    /// Has no effect on Windows, or unless [`catch_signals`](ToplevelBuilder::catch_signals) is set.
+    ///
+    /// The default is `false`.
+    pub fn catch_hangup(mut self, catch_hangup: bool) -> Self {
+        self.signal_handling.signal_set.catch_hangup = catch_hangup;
+        self
+    }
+
+    /// Sets whether only `Ctrl+C` initiates a shutdown.
+    ///
+    /// Limits the handled signals to `SIGINT` on Unix and `CTRL_C` on Windows,
+    /// so the program behaves the same on every platform. All other signals keep
+    /// their default behavior, which usually terminates the process right away.
+    /// Overrides [`catch_hangup`](ToplevelBuilder::catch_hangup).
+    ///
+    /// Has no effect unless [`catch_signals`](ToplevelBuilder::catch_signals) is set.
+    ///
+    /// The default is `false`.
+    pub fn ctrl_c_only(mut self, ctrl_c_only: bool) -> Self {
+        self.signal_handling.signal_set.ctrl_c_only = ctrl_c_only;
+        self
+    }
+
+    /// Terminates the process if another signal is received while the shutdown is in progress.
+    ///
+    /// This gives users of interactive programs a way out of a shutdown that takes too long,
+    /// by pressing `Ctrl+C` a second time. The exit happens through an
+    /// [emergency shutdown](Toplevel::emergency_handle), so the
+    /// [critical finalizers](ToplevelBuilder::critical_finalizer) still run.
+    ///
+    /// Has no effect unless [`catch_signals`](ToplevelBuilder::catch_signals) is set.
+    ///
+    /// By default, further signals only get logged.
+    ///
+    /// # Arguments
+    ///
+    /// * `exit_code` - The exit code of the process. `130` is customary for `SIGINT`.
+    pub fn force_exit_on_repeated_signal(mut self, exit_code: i32) -> Self {
+        self.signal_handling.repeated_signal_exit_code = Some(exit_code);
+        self
+    }
+
+    /// Sets the maximum time that is allowed to pass after a shutdown was initiated.
+    ///
+    /// Used by [`Toplevel::run`]. By default, there is no timeout.
+    pub fn shutdown_timeout(mut self, shutdown_timeout: Duration) -> Self {
+        self.shutdown_timeout = Some(shutdown_timeout);
+        self
+    }
+
+    /// Sets whether the Toplevel should shut down once all of its
+    /// subsystems have finished.
+    ///
+    /// If disabled, the Toplevel keeps running until a shutdown gets requested.
+    ///
+    /// The default is `true`.
+    pub fn shutdown_on_idle(mut self, shutdown_on_idle: bool) -> Self {
+        self.shutdown_on_idle = shutdown_on_idle;
+        self
+    }
+
+    /// Terminates the process if the shutdown did not finish within a hard time limit.
+    ///
+    /// Unlike the [`shutdown_timeout`](ToplevelBuilder::shutdown_timeout), this is enforced
+    /// by a dedicated OS thread, so it also fires if the tokio runtime is starved or deadlocked.
+    /// Before exiting, it logs an error and runs the [`last_words`](ToplevelBuilder::last_words)
+    /// callbacks. The process then exits through [`std::process::exit`], so destructors
+    /// don't run and buffered output that is not flushed by the last words gets lost.
+    ///
+    /// The limit should be longer than the shutdown timeout, to give the regular
+    /// shutdown a chance to report its errors first.
+    ///
+    /// By default, there is no watchdog.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The maximum time that is allowed to pass after a shutdown was initiated.
+    /// * `exit_code` - The exit code of the process if the limit is exceeded.
+    pub fn shutdown_watchdog(mut self, limit: Duration, exit_code: i32) -> Self {
+        self.shutdown_watchdog = Some((limit, exit_code));
+        self
+    }
+
+    /// Warns about subsystems that block the executor while they are shutting down.
+    ///
+    /// Every poll of a subsystem that takes longer than the given threshold after the
+    /// subsystem received its shutdown request gets logged with the name of the subsystem.
+    /// Such stalls, usually caused by synchronous calls like `JoinHandle::join` or `block_on`,
+    /// are a common reason for shutdowns that hang or exceed their timeout.
+    ///
+    /// Only the subsystems' own tasks are measured, not the tasks they spawn.
+    /// Timing every poll has a cost, so the detection is only active in debug builds;
+    /// in release builds, this setting has no effect.
+    ///
+    /// By default, blocking is not detected.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The duration after which a poll counts as blocking.
+    pub fn detect_blocking(mut self, threshold: Duration) -> Self {
+        self.blocking_threshold = Some(threshold);
+        self
+    }
+
+    /// Captures and logs a task dump of the runtime if the shutdown stalls.
+    ///
+    /// Once the shutdown did not finish within the given time, the subsystems that are
+    /// still running get logged, together with a dump of all tasks of the runtime.
+    /// Tasks that belong to a subsystem are labeled with its name, so it is easy to see
+    /// where each remaining subsystem is stuck.
+    ///
+    /// Task dumps are an unstable feature of tokio. They only get captured if the program
+    /// is built with `--cfg tokio_unstable`, on Linux atop `aarch64`, `x86`, `x86_64`
+    /// or `s390x`; otherwise, only the remaining subsystems get logged.
+    /// For the traces to be meaningful, the program must not use split debug info.
+    /// For more information, see
+    /// [`tokio::runtime::Handle::dump`](https://docs.rs/tokio/latest/tokio/runtime/struct.Handle.html#method.dump).
+    ///
+    /// By default, no task dump gets captured.
+    ///
+    /// # Arguments
+    ///
+    /// * `stall_timeout` - The time after which a shutdown is considered stalled.
+    ///   Should be shorter than the [`shutdown_timeout`](ToplevelBuilder::shutdown_timeout),
+    ///   which aborts the remaining subsystems.
+    #[cfg(feature = "task-dump")]
+    pub fn task_dump_on_stall(mut self, stall_timeout: Duration) -> Self {
+        self.task_dump_on_stall = Some(stall_timeout);
+        self
+    }
+
+    /// Sets the exit code of the process after an emergency shutdown.
+    ///
+    /// For more information, see [`Toplevel::emergency_handle`].
+    ///
+    /// The default is `1`.
+    pub fn emergency_exit_code(mut self, exit_code: i32) -> Self {
+        self.emergency_exit_code = exit_code;
+        self
+    }
+
+    /// Registers a finalizer that runs during an emergency shutdown.
+    ///
+    /// Critical finalizers run synchronously on the thread that triggered the
+    /// emergency shutdown, in the order in which they were registered. They should
+    /// only do the bare minimum, like flushing a log, as the program is in a fatal state.
+    ///
+    /// They do not run during a regular shutdown.
+    /// For more information, see [`Toplevel::emergency_handle`].
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the finalizer, used for logging.
+    /// * `finalizer` - The finalizer to run.
+    pub fn critical_finalizer(
+        mut self,
+        name: &str,
+        finalizer: impl FnOnce() + Send + 'static,
+    ) -> Self {
+        self.critical_finalizers
+            .push((Arc::from(name), Box::new(finalizer)));
+        self
+    }
+
+    /// Registers a callback that runs once at the very end of the shutdown,
+    /// like writing a crash marker file or a final audit record.
+    ///
+    /// Last words run after all subsystems finished or got aborted, no matter how
+    /// the shutdown ends: cleanly, with errors, after a shutdown timeout, when the
+    /// [`shutdown_watchdog`](ToplevelBuilder::shutdown_watchdog) expires, during an
+    /// [emergency shutdown](Toplevel::emergency_handle), or when the [`Toplevel`]
+    /// gets dropped without handling its shutdown.
+    ///
+    /// They run synchronously on a dedicated thread, one after another, in the order
+    /// in which they were registered. All of them together may take at most the
+    /// [`last_words_timeout`](ToplevelBuilder::last_words_timeout); callbacks that did
+    /// not get to finish by then are abandoned. They should therefore only do the
+    /// bare minimum.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the callback, used for logging.
+    /// * `callback` - The callback to run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{SubsystemHandle, Toplevel};
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     Toplevel::builder()
+    ///         .last_words("audit record", || {
+    ///             eprintln!("Service stopped.");
+    ///         })
+    ///         .last_words_timeout(Duration::from_millis(200))
+    ///         .build(|s: SubsystemHandle| async move {
+    ///             s.request_shutdown();
+    ///         })
+    ///         .handle_shutdown_requests(Duration::from_millis(500))
+    ///         .await
+    ///         .map_err(Into::into)
+    /// }
+    /// ```
+    pub fn last_words(mut self, name: &str, callback: impl FnOnce() + Send + 'static) -> Self {
+        self.last_words.push((Arc::from(name), Box::new(callback)));
+        self
+    }
+
+    /// Sets the wall-clock time that all [`last_words`](ToplevelBuilder::last_words)
+    /// together may take.
+    ///
+    /// The cap is measured in real time and does not depend on the configured
+    /// [`clock`](ToplevelBuilder::clock).
+    ///
+    /// The default is one second.
+    pub fn last_words_timeout(mut self, timeout: Duration) -> Self {
+        self.last_words_timeout = timeout;
+        self
+    }
+
+    /// Registers a hook that runs once when the shutdown gets triggered,
+    /// before any subsystem sees the shutdown request.
+    ///
+    /// The hook receives a [`ShutdownSnapshot`] with the reason of the shutdown and
+    /// the subsystems that were running, so that it can be persisted for a later
+    /// analysis of an incident. It runs synchronously and delays the shutdown of all
+    /// subsystems, so it should return quickly. If it panics, the shutdown continues.
+    ///
+    /// The shutdown gets delivered to the subsystems from within
+    /// [`Toplevel::handle_shutdown_requests`], like with [`StartupRacePolicy::Buffer`];
+    /// a shutdown that gets requested before then is held back until it gets called.
+    /// The hook does not run if the subsystems finish on their own.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The hook to run.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     Toplevel::builder()
+    ///         .on_shutdown_triggered(|snapshot| {
+    ///             eprintln!(
+    ///                 "Stopping because of {:?}, while running {:?}.",
+    ///                 snapshot.reason, snapshot.running_subsystems
+    ///             );
+    ///         })
+    ///         .build(|s: SubsystemHandle| async move {
+    ///             s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+    ///             s.request_shutdown();
+    ///         })
+    ///         .handle_shutdown_requests(Duration::from_millis(500))
+    ///         .await
+    ///         .map_err(Into::into)
+    /// }
+    /// ```
+    pub fn on_shutdown_triggered(
+        mut self,
+        hook: impl FnOnce(&ShutdownSnapshot) + Send + 'static,
+    ) -> Self {
+        self.on_shutdown_triggered = Some(Box::new(hook));
+        self
+    }
+
+    /// Adds a sink that receives the outcome of the shutdown once it finished,
+    /// for example to archive the outcome of every shutdown.
+    ///
+    /// The sinks get called with a [`ShutdownRecord`](crate::ShutdownRecord) at the end of
+    /// [`Toplevel::handle_shutdown_requests`], whether the shutdown succeeded or not,
+    /// in the order in which they were added. Closures can be used as sinks; to append the
+    /// records to a file, use a [`FileReportSink`](crate::FileReportSink).
+    ///
+    /// # Arguments
+    ///
+    /// * `sink` - The sink to add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{
+    ///     FileReportSink, ShutdownRecord, SubsystemBuilder, SubsystemHandle, Toplevel,
+    /// };
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let archive = std::env::temp_dir().join("shutdowns.log");
+    ///
+    ///     Toplevel::builder()
+    ///         .report_sink(FileReportSink::new(archive))
+    ///         .report_sink(|record: &ShutdownRecord| {
+    ///             if !record.succeeded {
+    ///                 eprintln!("Unclean shutdown:\n{record}");
+    ///             }
+    ///         })
+    ///         .build(|s: SubsystemHandle| async move {
+    ///             s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+    ///             s.request_shutdown();
+    ///         })
+    ///         .handle_shutdown_requests(Duration::from_millis(500))
+    ///         .await
+    ///         .map_err(Into::into)
+    /// }
+    /// ```
+    pub fn report_sink(mut self, sink: impl ReportSink) -> Self {
+        self.report_sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Adds a sidecar, a subsystem that only runs while the rest of the program is ready.
+    ///
+    /// Designed for subsystems like readiness endpoints or the announcement to service
+    /// discovery. The sidecar starts once the root subsystem returned and all subsystems
+    /// started with [`SubsystemBuilder::reports_readiness`](crate::SubsystemBuilder::reports_readiness)
+    /// called [`SubsystemHandle::mark_ready`]. If a shutdown begins before then, it does
+    /// not start at all.
+    ///
+    /// Once a shutdown begins, the sidecars shut down first; all other subsystems only
+    /// receive the shutdown request after all sidecars finished. Like with
+    /// [`StartupRacePolicy::Buffer`], both the start of the sidecars and the delivery of
+    /// the shutdown happen from within [`Toplevel::handle_shutdown_requests`].
+    ///
+    /// # Arguments
+    ///
+    /// * `builder` - The sidecar subsystem, started as a child of the root subsystem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+    ///
+    /// async fn database(subsys: SubsystemHandle) -> Result<()> {
+    ///     tracing::info!("Connecting ...");
+    ///     subsys.mark_ready();
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// async fn announcement(subsys: SubsystemHandle) -> Result<()> {
+    ///     tracing::info!("Registering at service discovery ...");
+    ///     subsys.on_shutdown_requested().await;
+    ///     tracing::info!("Deregistering from service discovery ...");
+    ///     Ok(())
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     Toplevel::builder()
+    ///         .sidecar(SubsystemBuilder::new("announcement", announcement))
+    ///         .build(|s: SubsystemHandle| async move {
+    ///             s.start(SubsystemBuilder::new("database", database).reports_readiness());
+    ///             s.request_shutdown();
+    ///         })
+    ///         .handle_shutdown_requests(Duration::from_millis(500))
+    ///         .await
+    ///         .map_err(Into::into)
+    /// }
+    /// ```
+    pub fn sidecar<Err, Fut, Subsys>(
+        mut self,
+        builder: SubsystemBuilder<'_, ErrType, Err, Fut, Subsys>,
+    ) -> Self
+    where
+        Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
+        Fut: 'static + Future<Output = Result<(), Err>> + Send,
+        Err: 'static + Into<ErrType>,
+    {
+        let mut builder = builder.map_subsystem(|subsystem| subsystem);
+        builder.sidecar = true;
+        self.sidecars
+            .push(Box::new(move |root: &SubsystemHandle<ErrType>| {
+                root.start(builder)
+            }));
+        self
+    }
+
+    /// Adds a shutdown group with its own time budget.
+    ///
+    /// During shutdown, the groups get shut down one after another, in the order
+    /// in which they were added. Subsystems that are not part of any group receive
+    /// the shutdown request right away, together with the first group.
+    /// Every further group gets signaled once the previous one finished; if a group
+    /// does not finish within its budget, its remaining subsystems get aborted
+    /// and reported as [`SubsystemError::Aborted`](crate::errors::SubsystemError::Aborted)
+    /// in a [`ShutdownTimeout`](GracefulShutdownError::ShutdownTimeout) error,
+    /// and the next group gets signaled.
+    ///
+    /// This keeps the overall shutdown time bounded, even if one layer misbehaves.
+    /// The [`shutdown_timeout`](ToplevelBuilder::shutdown_timeout) still applies to the
+    /// shutdown as a whole.
+    ///
+    /// Subsystems get assigned to a group through
+    /// [`SubsystemBuilder::shutdown_group`](crate::SubsystemBuilder::shutdown_group).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the group.
+    /// * `budget` - The maximum time the group is allowed to take for its shutdown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     Toplevel::builder()
+    ///         .shutdown_group("http", Duration::from_secs(10))
+    ///         .shutdown_group("workers", Duration::from_secs(15))
+    ///         .shutdown_group("storage", Duration::from_secs(5))
+    ///         .build(|s| async move {
+    ///             s.start(SubsystemBuilder::new("Http", my_subsystem).shutdown_group("http"));
+    ///             s.start(SubsystemBuilder::new("Worker", my_subsystem).shutdown_group("workers"));
+    ///             s.start(SubsystemBuilder::new("Database", my_subsystem).shutdown_group("storage"));
+    ///             s.request_shutdown();
+    ///         })
+    ///         .run()
+    ///         .await
+    ///         .map_err(Into::into)
+    /// }
+    /// ```
+    pub fn shutdown_group(mut self, name: &str, budget: Duration) -> Self {
+        self.shutdown_groups.push((Arc::from(name), budget));
+        self
+    }
+
+    /// Registers a resource that is shared between multiple subsystems, like a database pool.
+    ///
+    /// During shutdown, the resource gets closed as soon as all of its users are finished,
+    /// while the rest of the subsystem tree might still be shutting down.
+    /// Users are given by their full subsystem names, like `"/api/worker"`; subsystems
+    /// that were not started before the shutdown are not waited for.
+    /// If all subsystems finish on their own, the resources get closed afterwards.
+    ///
+    /// Closing the resources counts towards the
+    /// [`shutdown_timeout`](ToplevelBuilder::shutdown_timeout).
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the resource, used for logging.
+    /// * `resource` - The resource to close.
+    /// * `used_by` - The names of the subsystems that use the resource.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{AsyncClose, SubsystemBuilder, SubsystemHandle, Toplevel};
+    ///
+    /// struct DatabasePool;
+    ///
+    /// #[async_trait::async_trait]
+    /// impl AsyncClose for DatabasePool {
+    ///     async fn close(&self) {
+    ///         tracing::info!("Closing database connections ...");
+    ///     }
+    /// }
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.on_shutdown_requested().await;
+    ///     Ok(())
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     Toplevel::builder()
+    ///         .shared_resource("database", DatabasePool, ["/api", "/jobs/worker"])
+    ///         .build(|s| async move {
+    ///             s.start(SubsystemBuilder::new("api", my_subsystem));
+    ///             s.start(SubsystemBuilder::new("jobs", |s: SubsystemHandle| async move {
+    ///                 s.start(SubsystemBuilder::new("worker", my_subsystem));
+    ///                 my_subsystem(s).await
+    ///             }));
+    ///             s.request_shutdown();
+    ///         })
+    ///         .handle_shutdown_requests(Duration::from_millis(1000))
+    ///         .await
+    ///         .map_err(Into::into)
+    /// }
+    /// ```
+    pub fn shared_resource<I>(mut self, name: &str, resource: impl AsyncClose, used_by: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.shared_resources.push((
+            Arc::from(name),
+            Box::new(resource),
+            used_by
+                .into_iter()
+                .map(|user| Arc::from(user.as_ref()))
+                .collect(),
+        ));
+        self
+    }
+
+    /// Sets how shutdown requests get handled that arrive while the program is still starting.
+    ///
+    /// For more information, see [`StartupRacePolicy`].
+    ///
+    /// The default is [`StartupRacePolicy::StartThenShutdown`].
+    pub fn startup_race_policy(mut self, startup_race_policy: StartupRacePolicy) -> Self {
+        self.startup_race_policy = startup_race_policy;
+        self
+    }
+
+    /// Sets the separator between the names of parents and children
+    /// in the absolute names of subsystems, like `/parent/child`.
+    ///
+    /// The default is `/`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name_separator` - The separator, like `.` or `::`.
+    pub fn name_separator(mut self, name_separator: impl Into<Arc<str>>) -> Self {
+        self.name_separator = name_separator.into();
+        self
+    }
+
+    /// Limits the depth of the subsystem tree.
+    ///
+    /// Subsystems that would exceed it are not started; instead, they fail right away
+    /// with [`InternalError::MaxDepthExceeded`](crate::errors::InternalError::MaxDepthExceeded).
+    /// This turns an accidental unbounded recursion of [`SubsystemHandle::start`] calls
+    /// into an error instead of exhausting the available resources.
+    ///
+    /// The children of the root subsystem have a depth of one.
+    /// By default, the depth is unlimited.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_depth` - The maximum depth of a subsystem.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Sets whether subsystems that return successfully, but with an
+    /// [`ExitStatus`](crate::ExitStatus) that is not [clean](crate::ExitStatus::is_clean),
+    /// count as failed.
+    ///
+    /// In strict mode, those subsystems raise a
+    /// [`SubsystemError::UncleanExit`](crate::errors::SubsystemError::UncleanExit), which
+    /// gets handled like any other error of the subsystem; if it reaches the [`Toplevel`],
+    /// the shutdown returns an error and the program exits with a nonzero exit code.
+    /// For example, a subsystem that drained its queue and dropped 12 items fails in strict mode,
+    /// while it only shows up in the [`ShutdownReport`](crate::ShutdownReport) otherwise.
+    ///
+    /// The default is `false`.
+    pub fn strict_exit_statuses(mut self, strict_exit_statuses: bool) -> Self {
+        self.strict_exit_statuses = strict_exit_statuses;
+        self
+    }
+
+    /// Wraps the execution of all subsystems in a middleware.
+    ///
+    /// Middlewares apply in the order in which they get added;
+    /// the first one is the outermost and sees the subsystem last.
+    ///
+    /// For more information, see [`SubsystemMiddleware`].
+    ///
+    /// # Arguments
+    ///
+    /// * `middleware` - The middleware to add.
+    pub fn middleware(mut self, middleware: impl SubsystemMiddleware<ErrType>) -> Self {
+        self.middlewares.push(Box::new(middleware));
+        self
+    }
+
+    /// Adds a hook that gets invoked whenever the task of a subsystem gets spawned.
+    ///
+    /// Hooks apply in the order in which they get added;
+    /// the first one is the outermost and sees the task last.
+    ///
+    /// For more information, see [`SpawnHook`].
+    ///
+    /// # Arguments
+    ///
+    /// * `hook` - The hook to add.
+    pub fn spawn_hook(mut self, hook: impl SpawnHook) -> Self {
+        self.spawn_hooks.push(Box::new(hook));
+        self
+    }
+
+    /// Sets whether the errors of the shutdown result should be sorted by subsystem name.
+    ///
+    /// By default, errors are reported in the order in which they occurred.
+    /// As subsystems run concurrently, this order can differ between runs,
+    /// which makes tests that inspect the errors flaky.
+    ///
+    /// This only orders the reported errors; subsystems are still
+    /// joined and cancelled concurrently.
+    /// To also make the order in which subsystems shut down reproducible,
+    /// see [`deterministic_shutdown`](Self::deterministic_shutdown).
+    ///
+    /// The default is `false`.
+    pub fn sorted_errors(mut self, sorted_errors: bool) -> Self {
+        self.sorted_errors = sorted_errors;
+        self
+    }
+
+    /// Shuts down the children of every subsystem one at a time, in an order derived from `seed`.
+    ///
+    /// Once a subsystem shuts down, its children get sorted by name and shuffled with the given
+    /// seed. Then, each child gets cancelled and joined, together with all of its own children,
+    /// before its next sibling sees the shutdown request.
+    /// The same seed always results in the same order, independent of the
+    /// runtime and the timing of the subsystems, so the shutdown can be replayed.
+    ///
+    /// Subsystems that are detached, transferable, sidecars or part of a shutdown group
+    /// are not affected, as they get shut down through their own mechanisms.
+    ///
+    /// Implies [`sorted_errors`](Self::sorted_errors).
+    ///
+    /// As siblings no longer shut down concurrently, the shutdown can take considerably longer;
+    /// it is mostly meant for tests and debugging.
+    ///
+    /// # Arguments
+    ///
+    /// * `seed` - Selects the order in which siblings shut down.
+    pub fn deterministic_shutdown(mut self, seed: u64) -> Self {
+        self.deterministic_shutdown = Some(seed);
+        self
+    }
+
+    /// Sets an external token that initiates a shutdown when cancelled.
+    ///
+    /// For more information, see [`Toplevel::new_with_cancellation_token`].
+    pub fn cancellation_token(mut self, cancellation_token: CancellationToken) -> Self {
+        self.cancellation_token = Some(cancellation_token);
+        self
+    }
+
+    /// Sets the maximum time to wait for remaining blocking tasks when
+    /// shutting down the runtime in [`block_on_runtime`](ToplevelBuilder::block_on_runtime).
+    ///
+    /// The default is one second.
+    pub fn runtime_shutdown_timeout(mut self, runtime_shutdown_timeout: Duration) -> Self {
+        self.runtime_shutdown_timeout = runtime_shutdown_timeout;
+        self
+    }
+
+    /// Sets the source of time for all timeouts, delays and timestamps of the subsystem tree.
+    ///
+    /// For more information, see [`Clock`].
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The clock to use. Defaults to [`TokioClock`].
+    pub fn clock(mut self, clock: impl Clock) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Sets the number of identically-named subsystems from which on they get
+    /// rolled up into a single entry of the [`ShutdownReport`](crate::ShutdownReport).
+    ///
+    /// Keeps the report readable if there are many instances of the same subsystem,
+    /// like one per connection: `connection: 4312 ok, 3 failed, 1 timed out`,
+    /// followed by a few samples of the failures.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The minimum number of subsystems with the same name that
+    ///   get aggregated. Defaults to 10.
+    pub fn report_aggregation_threshold(mut self, threshold: usize) -> Self {
+        self.report_aggregation_threshold = threshold;
+        self
+    }
+
+    /// Injects faults into chosen subsystems during shutdown.
+    ///
+    /// Only intended for testing. For more information, see [`FaultInjection`].
+    #[cfg(feature = "fault-injection")]
+    pub fn fault_injection(mut self, fault_injection: FaultInjection) -> Self {
+        self.fault_injection = Some(fault_injection);
+        self
+    }
+
+    /// Creates the [`Toplevel`] object and spawns the given root subsystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `subsystem` - The subsystem that should be spawned as the root node.
+    ///   Usually the job of this subsystem is to spawn further subsystems.
+    pub fn build<Fut, Subsys>(self, subsystem: Subsys) -> Toplevel<ErrType>
+    where
+        Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
+        Fut: 'static + Future<Output = ()> + Send,
+    {
+        let cancellation_token = self.cancellation_token.unwrap_or_default();
+
+        let instrumentation = Instrumentation {
+            #[cfg(feature = "fault-injection")]
+            fault_injection: self.fault_injection,
+            ..Default::default()
+        };
+
+        let mut toplevel = Toplevel::new_impl(
+            cancellation_token,
+            ShutdownGroups::new(self.shutdown_groups),
+            SharedResources::new(self.shared_resources),
+            TreeConfig {
+                startup_race_policy: self.startup_race_policy,
+                name_separator: self.name_separator,
+                max_depth: self.max_depth,
+                strict_exit_statuses: self.strict_exit_statuses,
+                has_shutdown_hook: self.on_shutdown_triggered.is_some(),
+                middlewares: self.middlewares,
+                has_sidecars: !self.sidecars.is_empty(),
+                shutdown_on_panic: self.shutdown_on_panic,
+                blocking_threshold: self.blocking_threshold,
+                spawn_hooks: self.spawn_hooks,
+                deterministic_shutdown: self.deterministic_shutdown,
+            },
+            instrumentation,
+            self.clock,
+            subsystem,
+        );
+        toplevel.shutdown_timeout = self.shutdown_timeout;
+        toplevel.shutdown_on_idle = self.shutdown_on_idle;
+        toplevel.shutdown_watchdog = self.shutdown_watchdog;
+        #[cfg(feature = "task-dump")]
+        {
+            toplevel.task_dump_on_stall = self.task_dump_on_stall;
+        }
+        toplevel.last_words = Arc::new(LastWords::new(self.last_words_timeout, self.last_words));
+        toplevel.on_shutdown_triggered = self.on_shutdown_triggered;
+        toplevel.report_sinks = self.report_sinks;
+        toplevel.sidecars = self.sidecars;
+        toplevel.emergency_shutdown = Arc::new(EmergencyShutdown::new(
+            self.emergency_exit_code,
+            self.critical_finalizers,
+            Arc::clone(&toplevel.last_words),
+        ));
+        toplevel.sorted_errors = self.sorted_errors || self.deterministic_shutdown.is_some();
+        toplevel
+            .root_handle
+            .get_shutdown_statistics()
+            .set_report_aggregation_threshold(self.report_aggregation_threshold);
+
+        if self.catch_signals {
+            toplevel = toplevel.catch_signals_impl(self.signal_handling);
+        }
+        #[cfg(feature = "global")]
+        if self.install_global {
+            toplevel.global_guard = Some(GlobalGuard::install(toplevel.handle()));
+        }
+
+        toplevel
+    }
+
+    /// Runs the subsystem tree on the given runtime and shuts down the runtime
+    /// afterwards.
+    ///
+    /// Blocks the current thread until the subsystem tree is shut down.
+    /// After that, the runtime gets shut down as well, waiting at most
+    /// [`runtime_shutdown_timeout`](ToplevelBuilder::runtime_shutdown_timeout)
+    /// for remaining blocking tasks. Blocking tasks that do not finish in time get leaked
+    /// and reported in the logs.
+    ///
+    /// This must not be called from within an asynchronous context.
+    ///
+    /// # Arguments
+    ///
+    /// * `runtime` - The runtime to run the subsystem tree on.
+    /// * `subsystem` - The subsystem that should be spawned as the root node.
+    ///
+    /// # Returns
+    ///
+    /// An error of type [`GracefulShutdownError`] if an error occurred.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.request_shutdown();
+    ///     Ok(())
+    /// }
+    ///
+    /// fn main() -> Result<()> {
+    ///     let runtime = tokio::runtime::Runtime::new().unwrap();
+    ///
+    ///     Toplevel::builder()
+    ///         .catch_signals()
+    ///         .shutdown_timeout(Duration::from_millis(1000))
+    ///         .runtime_shutdown_timeout(Duration::from_millis(500))
+    ///         .block_on_runtime(runtime, |s| async move {
+    ///             s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+    ///         })
+    ///         .map_err(Into::into)
+    /// }
+    /// ```
+    ///
+    /// Not available within a `madsim` simulation, which does not allow blocking.
+    #[cfg(not(madsim))]
+    pub fn block_on_runtime<Fut, Subsys>(
+        self,
+        runtime: tokio::runtime::Runtime,
+        subsystem: Subsys,
+    ) -> Result<(), GracefulShutdownError<ErrType>>
+    where
+        Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
+        Fut: 'static + Future<Output = ()> + Send,
+    {
+        let runtime_shutdown_timeout = self.runtime_shutdown_timeout;
+
+        let result = {
+            let _runtime_guard = runtime.enter();
+            let toplevel = self.build(subsystem);
+            runtime.block_on(toplevel.run())
+        };
+
+        let runtime_shutdown_start = Instant::now();
+        runtime.shutdown_timeout(runtime_shutdown_timeout);
+        if runtime_shutdown_start.elapsed() >= runtime_shutdown_timeout {
+            tracing::warn!("Runtime shutdown timed out; remaining blocking tasks got leaked.");
+        }
+
+        result
+    }
+
+    /// Runs the subsystem tree on an internally created runtime.
+    ///
+    /// This is a convenience entry point for synchronous `main()` functions;
+    /// it creates a current-thread runtime, catches signals and performs
+    /// the shutdown handling, similar to [`block_on_runtime`](ToplevelBuilder::block_on_runtime).
+    ///
+    /// Signals will be caught regardless of whether
+    /// [`catch_signals`](ToplevelBuilder::catch_signals) was set.
+    ///
+    /// This must not be called from within an asynchronous context.
+    ///
+    /// # Arguments
+    ///
+    /// * `subsystem` - The subsystem that should be spawned as the root node.
+    ///
+    /// # Returns
+    ///
+    /// An error of type [`GracefulShutdownError`] if an error occurred.
+    /// If the runtime could not be created, this is an
+    /// [`InternalError::RuntimeCreationFailed`](crate::errors::InternalError::RuntimeCreationFailed)
+    /// of the unnamed root subsystem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use miette::Result;
+    /// use tokio::time::Duration;
+    /// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.request_shutdown();
+    ///     Ok(())
+    /// }
+    ///
+    /// fn main() -> Result<()> {
+    ///     Toplevel::builder()
+    ///         .shutdown_timeout(Duration::from_millis(1000))
+    ///         .run_blocking(|s| async move {
+    ///             s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+    ///         })
+    ///         .map_err(Into::into)
+    /// }
+    /// ```
+    ///
+    /// Not available within a `madsim` simulation, which does not allow blocking.
+    #[cfg(not(madsim))]
+    pub fn run_blocking<Fut, Subsys>(
+        self,
+        subsystem: Subsys,
+    ) -> Result<(), GracefulShutdownError<ErrType>>
+    where
+        Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
+        Fut: 'static + Future<Output = ()> + Send,
+    {
+        let runtime = match tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+        {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                tracing::error!("Failed to create the tokio runtime: {e}");
+                return Err(GracefulShutdownError::SubsystemsFailed(Box::new([
+                    SubsystemError::Internal(
+                        Arc::from(""),
+                        InternalError::RuntimeCreationFailed(e),
+                    ),
+                ])));
+            }
+        };
+
+        self.catch_signals().block_on_runtime(runtime, subsystem)
+    }
+}
@@ -0,0 +1,224 @@
+use std::{
+    future::Future,
+    sync::{Arc, Weak},
+};
+
+use tokio::sync::watch;
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    errors::ToplevelGone, shutdown_statistics::ShutdownStatisticsCollector, BoxedError,
+    ErrTypeTraits, NestedSubsystem, ShutdownReport, ShutdownState, ShutdownStatistics,
+    SubsystemBuilder, SubsystemHandle,
+};
+
+/// A cloneable handle to a [`Toplevel`](crate::Toplevel) object.
+///
+/// Allows code that is not managed by this crate, like an admin API task,
+/// to start additional root-level subsystems and to request a shutdown.
+///
+/// Created through [`Toplevel::handle`](crate::Toplevel::handle).
+pub struct ToplevelHandle<ErrType: ErrTypeTraits = BoxedError> {
+    root_handle: Weak<SubsystemHandle<ErrType>>,
+    cancellation_token: CancellationToken,
+    shutdown_statistics: Arc<ShutdownStatisticsCollector>,
+    // The `(alive, children)` state of the root subsystem.
+    root_state: watch::Receiver<(bool, u32)>,
+}
+
+impl<ErrType: ErrTypeTraits> Clone for ToplevelHandle<ErrType> {
+    fn clone(&self) -> Self {
+        Self {
+            root_handle: Weak::clone(&self.root_handle),
+            cancellation_token: self.cancellation_token.clone(),
+            shutdown_statistics: Arc::clone(&self.shutdown_statistics),
+            root_state: self.root_state.clone(),
+        }
+    }
+}
+
+impl<ErrType: ErrTypeTraits> ToplevelHandle<ErrType> {
+    pub(crate) fn new(root_handle: &Arc<SubsystemHandle<ErrType>>) -> Self {
+        Self {
+            root_handle: Arc::downgrade(root_handle),
+            cancellation_token: root_handle.get_cancellation_token().clone(),
+            shutdown_statistics: Arc::clone(root_handle.get_shutdown_statistics()),
+            root_state: root_handle.watch_children(),
+        }
+    }
+
+    /// Starts a subsystem at the root level of the subsystem tree.
+    ///
+    /// Behaves like [`SubsystemHandle::start`]. The started subsystem
+    /// will be waited for by the [`Toplevel`](crate::Toplevel) during shutdown.
+    ///
+    /// # Arguments
+    ///
+    /// * `builder` - The [`SubsystemBuilder`] that contains all the information
+    ///   about the subsystem that should be spawned.
+    ///
+    /// # Returns
+    ///
+    /// A [`NestedSubsystem`] that can be used to control or join the subsystem,
+    /// or [`ToplevelGone`] if the Toplevel object no longer exists.
+    pub fn start<Err, Fut, Subsys>(
+        &self,
+        builder: SubsystemBuilder<ErrType, Err, Fut, Subsys>,
+    ) -> Result<NestedSubsystem<ErrType>, ToplevelGone>
+    where
+        Subsys: 'static + FnOnce(SubsystemHandle<ErrType>) -> Fut + Send,
+        Fut: 'static + Future<Output = Result<(), Err>> + Send,
+        Err: Into<ErrType>,
+    {
+        let root_handle = self.root_handle.upgrade().ok_or(ToplevelGone)?;
+        Ok(root_handle.start(builder))
+    }
+
+    /// Returns the names of all subsystems that are still running.
+    #[cfg(feature = "status")]
+    pub(crate) fn running_subsystems(&self) -> Vec<Arc<str>> {
+        let Some(root_handle) = self.root_handle.upgrade() else {
+            return Vec::new();
+        };
+
+        root_handle.unfinished_children()
+    }
+
+    /// Returns the names and restart counts of all running subsystems that restarted at least once.
+    #[cfg(feature = "status")]
+    pub(crate) fn restarted_subsystems(&self) -> Vec<(Arc<str>, u32)> {
+        let Some(root_handle) = self.root_handle.upgrade() else {
+            return Vec::new();
+        };
+
+        root_handle.restarted_children()
+    }
+
+    #[cfg(feature = "status")]
+    pub(crate) fn created_at(&self) -> tokio::time::Instant {
+        self.shutdown_statistics.created_at()
+    }
+
+    #[cfg(any(feature = "ffi", feature = "global"))]
+    pub(crate) fn get_cancellation_token(&self) -> &CancellationToken {
+        &self.cancellation_token
+    }
+
+    /// Triggers a shutdown of the entire subsystem tree.
+    pub fn request_shutdown(&self) {
+        self.shutdown_statistics.record_request();
+        self.cancellation_token.cancel();
+    }
+
+    /// Returns whether a shutdown was requested.
+    pub fn is_shutdown_requested(&self) -> bool {
+        self.cancellation_token.is_cancelled()
+    }
+
+    /// Waits until a shutdown was requested.
+    pub async fn on_shutdown_requested(&self) {
+        let _waiter = self.shutdown_statistics.register_waiter();
+        self.cancellation_token.cancelled().await
+    }
+
+    /// Queries statistics about the shutdown state of the subsystem tree.
+    ///
+    /// Intended for debugging and metrics. For more information, see [`ShutdownStatistics`].
+    pub fn shutdown_statistics(&self) -> ShutdownStatistics {
+        self.shutdown_statistics
+            .snapshot(self.cancellation_token.is_cancelled())
+    }
+
+    /// Returns the results of all subsystems that finished so far.
+    ///
+    /// Identically-named subsystems get rolled up; for more information, see [`ShutdownReport`].
+    pub fn shutdown_report(&self) -> ShutdownReport {
+        self.shutdown_statistics.report()
+    }
+
+    /// Returns the current lifecycle state of the subsystem tree.
+    pub fn shutdown_state(&self) -> ShutdownState {
+        if self.is_finished() {
+            ShutdownState::Finished
+        } else if self.is_shutdown_requested() {
+            ShutdownState::ShuttingDown
+        } else {
+            ShutdownState::Running
+        }
+    }
+
+    /// Creates a stream of the lifecycle states of the subsystem tree.
+    ///
+    /// Yields the state at the time it is first polled, followed by every transition
+    /// (`Running` → `ShuttingDown` → `Finished`). Ends after `Finished`.
+    ///
+    /// Useful for reactive components, like status endpoints or user interfaces,
+    /// that want to reflect the state of the application without polling.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use futures_util::StreamExt;
+    /// use miette::Result;
+    /// use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+    ///
+    /// async fn my_subsystem(subsys: SubsystemHandle) -> Result<()> {
+    ///     subsys.request_shutdown();
+    ///     Ok(())
+    /// }
+    ///
+    /// #[tokio::main]
+    /// async fn main() -> Result<()> {
+    ///     let toplevel = Toplevel::new(|s| async move {
+    ///         s.start(SubsystemBuilder::new("MySubsystem", my_subsystem));
+    ///     });
+    ///
+    ///     let states = toplevel.handle().state_stream();
+    ///     tokio::spawn(async move {
+    ///         let mut states = std::pin::pin!(states);
+    ///         while let Some(state) = states.next().await {
+    ///             tracing::info!("Application state: {state:?}");
+    ///         }
+    ///     });
+    ///
+    ///     toplevel.run().await.map_err(Into::into)
+    /// }
+    /// ```
+    #[cfg(feature = "futures")]
+    pub fn state_stream(&self) -> impl futures_util::Stream<Item = ShutdownState> + Send + 'static {
+        futures_util::stream::unfold(
+            (self.clone(), None),
+            |(handle, previous_state)| async move {
+                let state = match previous_state {
+                    None => handle.shutdown_state(),
+                    Some(ShutdownState::Running) => tokio::select! {
+                        biased;
+                        _ = handle.cancellation_token.cancelled() => ShutdownState::ShuttingDown,
+                        _ = handle.wait_until_finished() => ShutdownState::Finished,
+                    },
+                    Some(ShutdownState::ShuttingDown) => {
+                        handle.wait_until_finished().await;
+                        ShutdownState::Finished
+                    }
+                    Some(ShutdownState::Finished) => return None,
+                };
+                Some((state, (handle, Some(state))))
+            },
+        )
+    }
+
+    fn is_finished(&self) -> bool {
+        let (alive, _children) = *self.root_state.borrow();
+        !alive || self.root_state.has_changed().is_err()
+    }
+
+    #[cfg(feature = "futures")]
+    async fn wait_until_finished(&self) {
+        // Ignore errors; if the channel got closed, the root got dropped.
+        let _ = self
+            .root_state
+            .clone()
+            .wait_for(|&(alive, _children)| !alive)
+            .await;
+    }
+}
@@ -0,0 +1,224 @@
+use std::{future::Future, io, net::SocketAddr, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use tokio::{net::UdpSocket, sync::mpsc};
+
+use crate::{
+    errors::CancelledByShutdown, ErrTypeTraits, ExitStatus, IntoSubsystem, SubsystemHandle,
+};
+
+/// The largest possible UDP payload.
+const MAX_DATAGRAM_SIZE: usize = 65_536;
+
+type Goodbye = Box<dyn FnOnce() -> Vec<(Vec<u8>, SocketAddr)> + Send>;
+
+/// Queues datagrams that get sent by a [`UdpSubsystem`].
+///
+/// Passed to the datagram handler, and available through [`UdpSubsystem::sender`]
+/// for code outside of the handler.
+#[derive(Clone, Debug)]
+pub struct UdpSender {
+    queue: mpsc::UnboundedSender<(Vec<u8>, SocketAddr)>,
+}
+
+impl UdpSender {
+    /// Queues a datagram for sending.
+    ///
+    /// The queue is unbounded, so this never waits.
+    ///
+    /// # Arguments
+    ///
+    /// * `datagram` - The payload of the datagram.
+    /// * `target` - The address to send the datagram to.
+    ///
+    /// # Returns
+    ///
+    /// [`CancelledByShutdown`] if the subsystem does not accept datagrams any more,
+    /// because it is flushing its queue or finished already.
+    pub fn send_to(
+        &self,
+        datagram: impl Into<Vec<u8>>,
+        target: SocketAddr,
+    ) -> Result<(), CancelledByShutdown> {
+        self.queue
+            .send((datagram.into(), target))
+            .map_err(|_| CancelledByShutdown)
+    }
+}
+
+/// A subsystem that serves a [`UdpSocket`] and winds it down gracefully.
+///
+/// Every received datagram gets passed to the handler, one at a time. Datagrams that are
+/// queued through a [`UdpSender`] get sent in between.
+///
+/// When a shutdown is requested, the subsystem stops reading from the socket,
+/// sends the [`goodbye`](Self::goodbye) datagrams, if configured, and then sends the
+/// datagrams that are still queued, limited by the [`flush_timeout`](Self::flush_timeout).
+/// It ends with [`ExitStatus::Drained`], counting the queued datagrams that could not be
+/// sent in time as dropped.
+///
+/// The subsystem also finishes if receiving or sending fails, or if the handler
+/// returns an error.
+///
+/// # Examples
+///
+/// ```
+/// use std::net::SocketAddr;
+///
+/// use miette::{IntoDiagnostic, Result};
+/// use tokio::{net::UdpSocket, time::Duration};
+/// use tokio_graceful_shutdown::{
+///     IntoSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel, UdpSender, UdpSubsystem,
+/// };
+///
+/// async fn echo(datagram: Vec<u8>, peer: SocketAddr, sender: UdpSender) -> std::io::Result<()> {
+///     // Fails only while shutting down; the reply is not needed then.
+///     let _ = sender.send_to(datagram, peer);
+///     Ok(())
+/// }
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let socket = UdpSocket::bind("127.0.0.1:0").await.into_diagnostic()?;
+///     let peers: Vec<SocketAddr> = Vec::new();
+///
+///     let server = UdpSubsystem::new(socket, echo)
+///         .goodbye(move || peers.iter().map(|peer| (b"BYE".to_vec(), *peer)).collect())
+///         .flush_timeout(Duration::from_millis(100));
+///
+///     Toplevel::new(|s: SubsystemHandle| async move {
+///         s.start(SubsystemBuilder::new("udp", server.into_subsystem()));
+///         s.request_shutdown();
+///     })
+///     .handle_shutdown_requests(Duration::from_millis(500))
+///     .await
+///     .map_err(Into::into)
+/// }
+/// ```
+pub struct UdpSubsystem<H> {
+    socket: Arc<UdpSocket>,
+    handler: H,
+    goodbye: Option<Goodbye>,
+    flush_timeout: Duration,
+    sender: UdpSender,
+    queue: mpsc::UnboundedReceiver<(Vec<u8>, SocketAddr)>,
+}
+
+impl<H> UdpSubsystem<H> {
+    /// Creates a new UDP subsystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `socket` - The socket to serve.
+    /// * `handler` - Handles a received datagram, given its payload, its sender and
+    ///   a [`UdpSender`] for replies. Returning an error stops the subsystem and makes it fail.
+    pub fn new(socket: UdpSocket, handler: H) -> Self {
+        let (sender, queue) = mpsc::unbounded_channel();
+        Self {
+            socket: Arc::new(socket),
+            handler,
+            goodbye: None,
+            flush_timeout: Duration::from_secs(1),
+            sender: UdpSender { queue: sender },
+            queue,
+        }
+    }
+
+    /// Returns a [`UdpSender`] through which datagrams can be sent from outside of the handler.
+    pub fn sender(&self) -> UdpSender {
+        self.sender.clone()
+    }
+
+    /// Sets the datagrams that get sent once a shutdown is requested, like a
+    /// protocol-specific goodbye message to all known peers.
+    ///
+    /// They get sent before the datagrams that are still queued.
+    ///
+    /// # Arguments
+    ///
+    /// * `goodbye` - Creates the goodbye datagrams, with the addresses to send them to.
+    pub fn goodbye(
+        mut self,
+        goodbye: impl FnOnce() -> Vec<(Vec<u8>, SocketAddr)> + Send + 'static,
+    ) -> Self {
+        self.goodbye = Some(Box::new(goodbye));
+        self
+    }
+
+    /// How long sending the goodbye datagrams and the queued datagrams
+    /// may take after a shutdown was requested.
+    ///
+    /// The default is one second.
+    pub fn flush_timeout(mut self, flush_timeout: Duration) -> Self {
+        self.flush_timeout = flush_timeout;
+        self
+    }
+}
+
+#[async_trait]
+impl<H, Fut, Err, ErrWrapper> IntoSubsystem<Err, ErrWrapper> for UdpSubsystem<H>
+where
+    H: FnMut(Vec<u8>, SocketAddr, UdpSender) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<(), Err>> + Send,
+    Err: From<io::Error> + Into<ErrWrapper> + Send + 'static,
+    ErrWrapper: ErrTypeTraits,
+{
+    async fn run(self, subsys: SubsystemHandle<ErrWrapper>) -> Result<(), Err> {
+        let Self {
+            socket,
+            mut handler,
+            goodbye,
+            flush_timeout,
+            sender,
+            mut queue,
+        } = self;
+
+        let mut buffer = vec![0; MAX_DATAGRAM_SIZE];
+        loop {
+            tokio::select! {
+                biased;
+                _ = subsys.on_shutdown_requested() => break,
+                // Can not end, as `sender` is still alive.
+                Some((datagram, target)) = queue.recv() => {
+                    socket.send_to(&datagram, target).await?;
+                },
+                received = socket.recv_from(&mut buffer) => {
+                    let (len, peer) = received?;
+                    handler(buffer[..len].to_vec(), peer, sender.clone()).await?;
+                },
+            }
+        }
+
+        queue.close();
+        let goodbye = goodbye.map(|goodbye| goodbye()).unwrap_or_default();
+        let flush = async {
+            for (datagram, target) in goodbye {
+                socket.send_to(&datagram, target).await?;
+            }
+            while let Some((datagram, target)) = queue.recv().await {
+                socket.send_to(&datagram, target).await?;
+            }
+            Ok::<_, io::Error>(())
+        };
+
+        let dropped = match subsys.get_clock().timeout(flush_timeout, flush).await {
+            Some(result) => {
+                result?;
+                0
+            }
+            None => {
+                let mut dropped = 0;
+                while queue.try_recv().is_ok() {
+                    dropped += 1;
+                }
+                tracing::warn!(
+                    "Sending queued datagrams did not finish within {flush_timeout:?}; dropping {dropped} of them."
+                );
+                dropped
+            }
+        };
+
+        subsys.set_exit_status(ExitStatus::Drained { dropped });
+        Ok(())
+    }
+}
@@ -1,4 +1,7 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{
+    fmt::Debug,
+    sync::{Arc, Weak},
+};
 
 use tokio::sync::watch;
 
@@ -7,12 +10,38 @@ use crate::{
     ErrTypeTraits,
 };
 
+use super::Mutex;
+
 struct Inner<ErrType: ErrTypeTraits> {
     counter: watch::Sender<(bool, u32)>,
-    parent: Option<Arc<Inner<ErrType>>>,
+    parent: Parent<ErrType>,
+    // Shared by the entire tree; serializes transfers, so that two of them
+    // can't pass each other's cycle check and link up two tokens crosswise.
+    transfers: Arc<Mutex<()>>,
     on_error: Box<dyn Fn(SubsystemError<ErrType>) -> Option<SubsystemError<ErrType>> + Sync + Send>,
 }
 
+enum Parent<ErrType: ErrTypeTraits> {
+    Fixed(Option<Arc<Inner<ErrType>>>),
+    // Every walk that passes through a transferable token holds this lock
+    // until it reached the root, so a transfer never races with a walk.
+    Transferable(Mutex<Option<Arc<Inner<ErrType>>>>),
+}
+
+/// Why a [`WeakJoinerToken`] could not be moved to a new parent.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ReparentError {
+    /// The token and all of its children are gone.
+    Finished,
+    /// The new parent is the token itself or one of its descendants.
+    Cycle,
+}
+
+/// Refers to a transferable [`JoinerToken`] without keeping it alive.
+pub(crate) struct WeakJoinerToken<ErrType: ErrTypeTraits> {
+    inner: Weak<Inner<ErrType>>,
+}
+
 /// A token that keeps reference of its existance and its children.
 pub(crate) struct JoinerToken<ErrType: ErrTypeTraits> {
     inner: Arc<Inner<ErrType>>,
@@ -60,7 +89,8 @@ impl<ErrType: ErrTypeTraits> JoinerToken<ErrType> {
     ) -> (Self, JoinerTokenRef) {
         let inner = Arc::new(Inner {
             counter: watch::channel((true, 0)).0,
-            parent: None,
+            parent: Parent::Fixed(None),
+            transfers: Arc::new(Mutex::new(())),
             on_error: Box::new(on_error),
         });
 
@@ -88,17 +118,44 @@ impl<ErrType: ErrTypeTraits> JoinerToken<ErrType> {
             + Send
             + 'static,
     ) -> (Self, JoinerTokenRef) {
-        let mut maybe_parent = Some(&self.inner);
-        while let Some(parent) = maybe_parent {
+        self.create_child(Parent::Fixed(Some(Arc::clone(&self.inner))), on_error)
+    }
+
+    /// Creates a child token that can be moved to a different parent later on.
+    ///
+    /// For more information, see [`WeakJoinerToken::reparent`].
+    pub(crate) fn transferable_child_token(
+        &self,
+        on_error: impl Fn(SubsystemError<ErrType>) -> Option<SubsystemError<ErrType>>
+            + Sync
+            + Send
+            + 'static,
+    ) -> (Self, JoinerTokenRef) {
+        self.create_child(
+            Parent::Transferable(Mutex::new(Some(Arc::clone(&self.inner)))),
+            on_error,
+        )
+    }
+
+    fn create_child(
+        &self,
+        parent: Parent<ErrType>,
+        on_error: impl Fn(SubsystemError<ErrType>) -> Option<SubsystemError<ErrType>>
+            + Sync
+            + Send
+            + 'static,
+    ) -> (Self, JoinerTokenRef) {
+        walk_up(&self.inner, &mut |parent| {
             parent
                 .counter
                 .send_modify(|(_alive, children)| *children += 1);
-            maybe_parent = parent.parent.as_ref();
-        }
+            true
+        });
 
         let inner = Arc::new(Inner {
             counter: watch::channel((true, 0)).0,
-            parent: Some(Arc::clone(&self.inner)),
+            parent,
+            transfers: Arc::clone(&self.inner.transfers),
             on_error: Box::new(on_error),
         });
 
@@ -109,6 +166,11 @@ impl<ErrType: ErrTypeTraits> JoinerToken<ErrType> {
         (Self { inner }, weak_ref)
     }
 
+    /// Subscribes to the `(alive, children)` state of this token.
+    pub(crate) fn watch(&self) -> watch::Receiver<(bool, u32)> {
+        self.inner.counter.subscribe()
+    }
+
     #[cfg(test)]
     pub(crate) fn count(&self) -> u32 {
         self.inner.counter.borrow().1
@@ -117,27 +179,123 @@ impl<ErrType: ErrTypeTraits> JoinerToken<ErrType> {
     pub(crate) fn raise_failure(&self, stop_reason: SubsystemError<ErrType>) {
         let mut maybe_stop_reason = Some(stop_reason);
 
-        let mut maybe_parent = Some(&self.inner);
-        while let Some(parent) = maybe_parent {
-            if let Some(stop_reason) = maybe_stop_reason {
+        walk_up(&self.inner, &mut |parent| match maybe_stop_reason.take() {
+            Some(stop_reason) => {
                 maybe_stop_reason = (parent.on_error)(stop_reason);
-            } else {
-                break;
+                true
             }
-
-            maybe_parent = parent.parent.as_ref();
-        }
+            None => false,
+        });
 
         handle_unhandled_stopreason(maybe_stop_reason);
     }
 
-    pub(crate) fn downgrade(self) -> JoinerTokenRef {
+    /// Creates a [`WeakJoinerToken`] that allows moving this token to a new parent,
+    /// if it was created through [`transferable_child_token`](Self::transferable_child_token).
+    pub(crate) fn downgrade(&self) -> WeakJoinerToken<ErrType> {
+        WeakJoinerToken {
+            inner: Arc::downgrade(&self.inner),
+        }
+    }
+
+    pub(crate) fn get_ref(&self) -> JoinerTokenRef {
         JoinerTokenRef {
             counter: self.inner.counter.subscribe(),
         }
     }
 }
 
+impl<ErrType: ErrTypeTraits> WeakJoinerToken<ErrType> {
+    /// Moves the token, together with all of its children, to a new parent.
+    ///
+    /// From then on, the new parent waits for it instead of the old one,
+    /// and its errors get propagated to the new parent.
+    ///
+    /// Both tokens have to be part of the same tree.
+    pub(crate) fn reparent(&self, new_parent: &JoinerToken<ErrType>) -> Result<(), ReparentError> {
+        let inner = self.inner.upgrade().ok_or(ReparentError::Finished)?;
+        let Parent::Transferable(parent) = &inner.parent else {
+            unreachable!("Only transferable tokens can be downgraded");
+        };
+        debug_assert!(Arc::ptr_eq(&inner.transfers, &new_parent.inner.transfers));
+
+        // Keeps the result of the cycle check valid until the token got relinked.
+        let _transfer = inner.transfers.lock();
+
+        // Has to happen before locking, as walking through this token would lock it as well.
+        let mut is_descendant = false;
+        walk_up(&new_parent.inner, &mut |ancestor| {
+            is_descendant = std::ptr::eq(ancestor, &*inner);
+            !is_descendant
+        });
+        if is_descendant {
+            return Err(ReparentError::Cycle);
+        }
+
+        let mut parent = parent.lock();
+        let Some(old_parent) = parent.clone() else {
+            return Err(ReparentError::Finished);
+        };
+
+        // Holding the lock guarantees that nobody is currently walking through this token.
+        let (alive, children) = *inner.counter.borrow();
+        let moved = children + u32::from(alive);
+        if moved == 0 {
+            return Err(ReparentError::Finished);
+        }
+
+        walk_up(&old_parent, &mut |ancestor| {
+            ancestor
+                .counter
+                .send_modify(|(_alive, children)| *children -= moved);
+            true
+        });
+        walk_up(&new_parent.inner, &mut |ancestor| {
+            ancestor
+                .counter
+                .send_modify(|(_alive, children)| *children += moved);
+            true
+        });
+        *parent = Some(Arc::clone(&new_parent.inner));
+
+        Ok(())
+    }
+}
+
+/// Calls `f` on the given token and then on all of its ancestors, until it returns `false`.
+///
+/// Holds the locks of all transferable tokens on the way.
+/// Only recurses at transferable tokens, so deeply nested trees can not overflow the stack.
+fn walk_up<ErrType: ErrTypeTraits>(
+    start: &Arc<Inner<ErrType>>,
+    f: &mut impl FnMut(&Inner<ErrType>) -> bool,
+) {
+    let mut node = start;
+    loop {
+        let parent = match &node.parent {
+            Parent::Fixed(parent) => {
+                if !f(node) {
+                    return;
+                }
+                parent
+            }
+            Parent::Transferable(parent) => {
+                let parent = parent.lock();
+                if f(node) {
+                    if let Some(parent) = parent.as_ref() {
+                        walk_up(parent, f);
+                    }
+                }
+                return;
+            }
+        };
+        match parent {
+            Some(parent) => node = parent,
+            None => return,
+        }
+    }
+}
+
 impl JoinerTokenRef {
     pub(crate) async fn join(&self) {
         // Ignore errors; if the channel got closed, that definitely means
@@ -149,6 +307,12 @@ impl JoinerTokenRef {
             .await;
     }
 
+    /// Whether [`join`](Self::join) would return immediately.
+    pub(crate) fn is_finished(&self) -> bool {
+        let (alive, children) = *self.counter.borrow();
+        !alive && children == 0
+    }
+
     #[cfg(test)]
     pub(crate) fn count(&self) -> u32 {
         self.counter.borrow().1
@@ -160,21 +324,49 @@ impl JoinerTokenRef {
     }
 }
 
-impl<ErrType: ErrTypeTraits> Drop for JoinerToken<ErrType> {
+impl<ErrType: ErrTypeTraits> Drop for Inner<ErrType> {
     fn drop(&mut self) {
-        self.inner
-            .counter
-            .send_modify(|(alive, _children)| *alive = false);
-
-        let mut maybe_parent = self.inner.parent.as_ref();
+        // Unlink the chain of parents iteratively.
+        // Dropping it recursively could overflow the stack
+        // for deeply nested subsystem trees.
+        let mut maybe_parent = self.parent.take();
         while let Some(parent) = maybe_parent {
-            parent
-                .counter
-                .send_modify(|(_alive, children)| *children -= 1);
-            maybe_parent = parent.parent.as_ref();
+            maybe_parent = match Arc::try_unwrap(parent) {
+                Ok(mut parent) => parent.parent.take(),
+                Err(_) => None,
+            };
         }
     }
 }
 
+impl<ErrType: ErrTypeTraits> Parent<ErrType> {
+    fn take(&mut self) -> Option<Arc<Inner<ErrType>>> {
+        match self {
+            Parent::Fixed(parent) => parent.take(),
+            Parent::Transferable(parent) => parent.lock().take(),
+        }
+    }
+}
+
+impl<ErrType: ErrTypeTraits> Drop for JoinerToken<ErrType> {
+    fn drop(&mut self) {
+        // Walk through the token itself, so a transfer never observes
+        // a token that is dead, but not yet deregistered from its parents.
+        let mut is_self = true;
+        walk_up(&self.inner, &mut |token| {
+            if std::mem::take(&mut is_self) {
+                token
+                    .counter
+                    .send_modify(|(alive, _children)| *alive = false);
+            } else {
+                token
+                    .counter
+                    .send_modify(|(_alive, children)| *children -= 1);
+            }
+            true
+        });
+    }
+}
+
 #[cfg(test)]
 mod tests;
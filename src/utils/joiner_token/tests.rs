@@ -229,3 +229,149 @@ fn debug_print_ref() {
         "JoinerTokenRef(alive = false, children = 0)"
     );
 }
+
+#[test]
+fn deeply_nested_tokens_drop_without_stack_overflow() {
+    // Run with a small stack, so that a recursive drop would overflow it.
+    std::thread::Builder::new()
+        .stack_size(64 * 1024)
+        .spawn(|| {
+            let (root, _) = JoinerToken::<BoxedError>::new(|_| None);
+
+            let mut tokens = vec![root];
+            for _ in 0..2_000 {
+                let child = tokens.last().unwrap().child_token(|_| None).0;
+                tokens.push(child);
+            }
+            assert_eq!(2_000, tokens[0].count());
+
+            // Drop from the root to the leaf; the leaf then holds the last
+            // reference to the entire chain of its parents.
+            for token in tokens {
+                drop(token);
+            }
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+#[test]
+#[traced_test]
+fn reparent_moves_counters() {
+    let (root, _) = JoinerToken::<BoxedError>::new(|_| None);
+    let (old_parent, old_parent_ref) = root.child_token(|_| None);
+    let (new_parent, _) = root.child_token(|_| None);
+
+    let (child, _) = old_parent.transferable_child_token(|_| None);
+    let (grandchild, _) = child.child_token(|_| None);
+    assert_eq!(4, root.count());
+    assert_eq!(2, old_parent.count());
+    assert_eq!(0, new_parent.count());
+
+    child.downgrade().reparent(&new_parent).unwrap();
+    assert_eq!(4, root.count());
+    assert_eq!(0, old_parent.count());
+    assert_eq!(2, new_parent.count());
+
+    // The old parent no longer waits for the child.
+    drop(old_parent);
+    assert!(!old_parent_ref.alive());
+    assert_eq!(0, old_parent_ref.count());
+    assert_eq!(3, root.count());
+
+    drop(grandchild);
+    assert_eq!(1, new_parent.count());
+    assert_eq!(2, root.count());
+
+    drop(child);
+    assert_eq!(0, new_parent.count());
+    assert_eq!(1, root.count());
+}
+
+#[test]
+#[traced_test]
+fn reparent_forwards_errors_to_new_parent() {
+    let (root, _) = JoinerToken::<BoxedError>::new(|_| None);
+    let (old_parent, _) = root.child_token(|e| panic!("Unexpected error: {e:?}"));
+    let errors = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let (new_parent, _) = root.child_token({
+        let errors = Arc::clone(&errors);
+        move |e| {
+            errors.lock().unwrap().push(e.name().to_string());
+            None
+        }
+    });
+
+    let (child, _) = old_parent.transferable_child_token(Some);
+    let (grandchild, _) = child.child_token(Some);
+    child.downgrade().reparent(&new_parent).unwrap();
+
+    grandchild.raise_failure(SubsystemError::Panicked("/grandchild".into()));
+    assert_eq!(*errors.lock().unwrap(), ["/grandchild"]);
+}
+
+#[test]
+#[traced_test]
+fn reparent_fails_for_cycles_and_finished_tokens() {
+    let (root, _) = JoinerToken::<BoxedError>::new(|_| None);
+    let (child, _) = root.transferable_child_token(|_| None);
+    let (grandchild, _) = child.child_token(|_| None);
+
+    let weak_child = child.downgrade();
+    assert_eq!(
+        weak_child.reparent(&child).unwrap_err(),
+        ReparentError::Cycle
+    );
+    assert_eq!(
+        weak_child.reparent(&grandchild).unwrap_err(),
+        ReparentError::Cycle
+    );
+
+    drop(grandchild);
+    drop(child);
+    assert_eq!(
+        weak_child.reparent(&root).unwrap_err(),
+        ReparentError::Finished
+    );
+    assert_eq!(0, root.count());
+}
+
+#[test]
+#[traced_test]
+fn concurrent_crossing_reparents_never_create_cycles() {
+    let (root, _) = JoinerToken::<BoxedError>::new(|_| None);
+    let (a, _) = root.transferable_child_token(|_| None);
+    let (a_child, _) = a.child_token(|_| None);
+    let (b, _) = root.transferable_child_token(|_| None);
+    let (b_child, _) = b.child_token(|_| None);
+    let (weak_a, weak_b) = (a.downgrade(), b.downgrade());
+
+    for _ in 0..1000 {
+        let barrier = std::sync::Barrier::new(2);
+        let (moved_a, moved_b) = std::thread::scope(|s| {
+            let moved_a = s.spawn(|| {
+                barrier.wait();
+                weak_a.reparent(&b_child)
+            });
+            let moved_b = s.spawn(|| {
+                barrier.wait();
+                weak_b.reparent(&a_child)
+            });
+            (moved_a.join().unwrap(), moved_b.join().unwrap())
+        });
+
+        // Exactly one of them wins; the other one would close a cycle.
+        match (moved_a, moved_b) {
+            (Ok(()), Err(ReparentError::Cycle)) | (Err(ReparentError::Cycle), Ok(())) => (),
+            other => panic!("Unexpected result: {other:?}"),
+        }
+        assert_eq!(4, root.count());
+
+        weak_a.reparent(&root).unwrap();
+        weak_b.reparent(&root).unwrap();
+        assert_eq!(1, a.count());
+        assert_eq!(1, b.count());
+        assert_eq!(4, root.count());
+    }
+}
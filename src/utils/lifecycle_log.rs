@@ -0,0 +1,22 @@
+use tracing::level_filters::LevelFilter;
+
+/// The level of the lifecycle logs of subsystems that did not configure one.
+pub(crate) const DEFAULT_LIFECYCLE_LOG_LEVEL: LevelFilter = LevelFilter::DEBUG;
+
+/// Emits a lifecycle log message of a subsystem at the given [`LevelFilter`].
+///
+/// The `tracing` macros require their level to be known at compile time,
+/// so the configured level has to be dispatched manually.
+macro_rules! log_lifecycle {
+    ($level:expr, $($arg:tt)+) => {
+        match $level.into_level() {
+            Some(::tracing::Level::ERROR) => ::tracing::error!($($arg)+),
+            Some(::tracing::Level::WARN) => ::tracing::warn!($($arg)+),
+            Some(::tracing::Level::INFO) => ::tracing::info!($($arg)+),
+            Some(::tracing::Level::DEBUG) => ::tracing::debug!($($arg)+),
+            Some(::tracing::Level::TRACE) => ::tracing::trace!($($arg)+),
+            None => (),
+        }
+    };
+}
+pub(crate) use log_lifecycle;
@@ -1,5 +1,16 @@
 mod joiner_token;
 pub(crate) use joiner_token::JoinerToken;
 pub(crate) use joiner_token::JoinerTokenRef;
+pub(crate) use joiner_token::ReparentError;
+pub(crate) use joiner_token::WeakJoinerToken;
+
+mod lifecycle_log;
+pub(crate) use lifecycle_log::{log_lifecycle, DEFAULT_LIFECYCLE_LOG_LEVEL};
+
+mod mutex;
+pub(crate) use mutex::Mutex;
 
 pub(crate) mod remote_drop_collection;
+
+mod task;
+pub(crate) use task::{resume_panic, JoinSet};
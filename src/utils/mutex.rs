@@ -0,0 +1,50 @@
+//! A mutex that does not propagate panics through lock poisoning.
+//!
+//! With the `parking_lot` feature, this is backed by [`parking_lot::Mutex`],
+//! otherwise by [`std::sync::Mutex`], ignoring its poison flag.
+//! When compiled with `--cfg graceful_shutdown_loom`, it is backed by loom's mock mutex instead.
+//!
+//! None of the critical sections in this crate can leave their data in an
+//! inconsistent state when interrupted by a panic, so ignoring poisoning is sound.
+
+#[cfg(graceful_shutdown_loom)]
+use loom::sync as std_sync;
+#[cfg(all(not(graceful_shutdown_loom), not(feature = "parking_lot")))]
+use std::sync as std_sync;
+
+#[cfg(any(graceful_shutdown_loom, not(feature = "parking_lot")))]
+pub(crate) type MutexGuard<'a, T> = std_sync::MutexGuard<'a, T>;
+#[cfg(all(not(graceful_shutdown_loom), feature = "parking_lot"))]
+pub(crate) type MutexGuard<'a, T> = parking_lot::MutexGuard<'a, T>;
+
+#[derive(Default)]
+pub(crate) struct Mutex<T> {
+    #[cfg(any(graceful_shutdown_loom, not(feature = "parking_lot")))]
+    inner: std_sync::Mutex<T>,
+    #[cfg(all(not(graceful_shutdown_loom), feature = "parking_lot"))]
+    inner: parking_lot::Mutex<T>,
+}
+
+impl<T> Mutex<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self {
+            inner: value.into(),
+        }
+    }
+
+    pub(crate) fn lock(&self) -> MutexGuard<'_, T> {
+        #[cfg(any(graceful_shutdown_loom, not(feature = "parking_lot")))]
+        {
+            self.inner
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+        }
+        #[cfg(all(not(graceful_shutdown_loom), feature = "parking_lot"))]
+        {
+            self.inner.lock()
+        }
+    }
+}
+
+#[cfg(all(test, not(graceful_shutdown_loom)))]
+mod tests;
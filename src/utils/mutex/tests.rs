@@ -0,0 +1,20 @@
+use std::sync::Arc;
+
+use super::*;
+
+#[test]
+fn lock_after_panic_while_locked() {
+    let mutex = Arc::new(Mutex::new(42));
+
+    let result = std::thread::spawn({
+        let mutex = Arc::clone(&mutex);
+        move || {
+            let _guard = mutex.lock();
+            panic!("Panic while locked");
+        }
+    })
+    .join();
+    assert!(result.is_err());
+
+    assert_eq!(42, *mutex.lock());
+}
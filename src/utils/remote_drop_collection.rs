@@ -1,10 +1,18 @@
 use std::sync::{
     atomic::{AtomicUsize, Ordering},
-    Arc, Mutex, Weak,
+    Arc, Weak,
 };
 
+use smallvec::SmallVec;
+
+use super::Mutex;
+
+/// Most subsystems only have a handful of children,
+/// so those get stored inline without a separate heap allocation.
+type Items<T> = SmallVec<[RemotelyDroppableItem<T>; 4]>;
+
 struct RemotelyDroppableItem<T> {
-    _item: T,
+    item: T,
     offset: Arc<AtomicUsize>,
 }
 
@@ -18,7 +26,16 @@ struct RemotelyDroppableItem<T> {
 /// The important part here is that the token is sendable to other context/threads,
 /// so it's basically a 'remote drop guard' concept.
 pub(crate) struct RemotelyDroppableItems<T> {
-    items: Arc<Mutex<Vec<RemotelyDroppableItem<T>>>>,
+    items: Arc<Mutex<Items<T>>>,
+}
+
+// Clones share the same items; the items get dropped once all clones are dropped.
+impl<T> Clone for RemotelyDroppableItems<T> {
+    fn clone(&self) -> Self {
+        Self {
+            items: Arc::clone(&self.items),
+        }
+    }
 }
 
 impl<T> RemotelyDroppableItems<T> {
@@ -29,57 +46,92 @@ impl<T> RemotelyDroppableItems<T> {
     }
 
     pub(crate) fn insert(&self, item: T) -> RemoteDrop<T> {
-        let mut items = self.items.lock().unwrap();
+        let mut items = self.items.lock();
 
         let offset = Arc::new(AtomicUsize::new(items.len()));
         let weak_offset = Arc::downgrade(&offset);
 
-        items.push(RemotelyDroppableItem {
-            _item: item,
-            offset,
-        });
+        items.push(RemotelyDroppableItem { item, offset });
 
         RemoteDrop {
             data: Arc::downgrade(&self.items),
             offset: weak_offset,
         }
     }
+
+    /// Maps all currently stored items, while holding the lock.
+    pub(crate) fn map_items<R>(&self, f: impl FnMut(&T) -> R) -> Vec<R> {
+        self.items
+            .lock()
+            .iter()
+            .map(|item| &item.item)
+            .map(f)
+            .collect()
+    }
 }
 
 /// Drops its referenced item when dropped
 pub(crate) struct RemoteDrop<T> {
     // Both weak.
     // If data is gone, then our item collection dropped.
-    data: Weak<Mutex<Vec<RemotelyDroppableItem<T>>>>,
+    data: Weak<Mutex<Items<T>>>,
     // If offset is gone, then the item itself got removed
     // while the dropguard still exists.
     offset: Weak<AtomicUsize>,
 }
 
+impl<T> RemoteDrop<T> {
+    /// Moves the referenced item into another collection.
+    ///
+    /// Returns `None` if the item no longer exists.
+    pub(crate) fn move_to(mut self, target: &RemotelyDroppableItems<T>) -> Option<RemoteDrop<T>> {
+        let item = self.remove()?;
+        Some(target.insert(item))
+    }
+
+    /// Removes the referenced item from its collection.
+    ///
+    /// Only succeeds once; afterwards, the item is no longer referenced.
+    fn remove(&mut self) -> Option<T> {
+        let data = std::mem::take(&mut self.data).upgrade()?;
+
+        // Important: lock first, then read the offset.
+        let mut data = data.lock();
+
+        let Some(offset) = self.offset.upgrade() else {
+            tracing::error!("Trying to delete non-existent item! Please report this.");
+            return None;
+        };
+        let offset = offset.load(Ordering::Acquire);
+
+        let Some(last_item) = data.pop() else {
+            tracing::error!("Trying to delete non-existent item! Please report this.");
+            return None;
+        };
+
+        let removed_item = if offset != data.len() {
+            // There must have been at least two items, and we are not at the end.
+            // So swap first before removing.
+
+            last_item.offset.store(offset, Ordering::Release);
+            std::mem::replace(&mut data[offset], last_item)
+        } else {
+            last_item
+        };
+
+        // Release the memory of long-lived parents after a burst of children,
+        // like an acceptor after a spike of connections.
+        if data.capacity() > 4 * data.len() {
+            data.shrink_to_fit();
+        }
+
+        Some(removed_item.item)
+    }
+}
+
 impl<T> Drop for RemoteDrop<T> {
     fn drop(&mut self) {
-        if let Some(data) = self.data.upgrade() {
-            // Important: lock first, then read the offset.
-            let mut data = data.lock().unwrap();
-
-            let offset = self
-                .offset
-                .upgrade()
-                .expect("Trying to delete non-existent item! Please report this.")
-                .load(Ordering::Acquire);
-
-            let last_item = data
-                .pop()
-                .expect("Trying to delete non-existent item! Please report this.");
-
-            if offset != data.len() {
-                // There must have been at least two items, and we are not at the end.
-                // So swap first before dropping.
-
-                last_item.offset.store(offset, Ordering::Release);
-                data[offset] = last_item;
-            }
-        }
+        drop(self.remove());
     }
 }
 
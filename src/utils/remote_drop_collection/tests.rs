@@ -84,3 +84,54 @@ fn drop_token() {
     assert_eq!(0, count3.count());
     assert_eq!(0, count4.count());
 }
+
+#[test]
+fn storage_shrinks_after_items_got_dropped() {
+    let items = RemotelyDroppableItems::new();
+
+    let tokens = (0..1000).map(|_| items.insert(())).collect::<Vec<_>>();
+    assert_eq!(1000, items.items.lock().len());
+
+    drop(tokens);
+    let data = items.items.lock();
+    assert_eq!(0, data.len());
+    assert!(!data.spilled());
+}
+
+#[test]
+fn move_to_other_collection() {
+    let items1 = RemotelyDroppableItems::new();
+    let items2 = RemotelyDroppableItems::new();
+
+    let (count1, _) = JoinerToken::<BoxedError>::new(|_| None);
+    let (count2, _) = JoinerToken::<BoxedError>::new(|_| None);
+
+    let token1 = items1.insert(count1.child_token(|_| None));
+    let _token2 = items1.insert(count2.child_token(|_| None));
+
+    let token1 = token1.move_to(&items2).unwrap();
+    assert_eq!(1, items1.items.lock().len());
+    assert_eq!(1, items2.items.lock().len());
+    assert_eq!(1, count1.count());
+
+    // The moved item no longer depends on its original collection.
+    drop(items1);
+    assert_eq!(1, count1.count());
+    assert_eq!(0, count2.count());
+
+    drop(token1);
+    assert_eq!(0, count1.count());
+    assert_eq!(0, items2.items.lock().len());
+}
+
+#[test]
+fn move_of_dropped_collection_fails() {
+    let items1 = RemotelyDroppableItems::new();
+    let items2 = RemotelyDroppableItems::new();
+
+    let token = items1.insert(());
+    drop(items1);
+
+    assert!(token.move_to(&items2).is_none());
+    assert_eq!(0, items2.items.lock().len());
+}
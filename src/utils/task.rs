@@ -0,0 +1,70 @@
+//! Task utilities that differ between `tokio` and the simulated runtime of `madsim`.
+//!
+//! When compiled with `--cfg madsim`, tasks are simulated by `madsim`, which neither
+//! provides a `JoinSet` nor catches panics of tasks; a panic aborts the simulation instead.
+
+#[cfg(not(madsim))]
+pub(crate) use tokio::task::JoinSet;
+
+/// Resumes the panic of a task that panicked.
+pub(crate) fn resume_panic(join_error: tokio::task::JoinError) -> ! {
+    #[cfg(not(madsim))]
+    {
+        std::panic::resume_unwind(join_error.into_panic())
+    }
+    #[cfg(madsim)]
+    {
+        unreachable!("Panics of simulated tasks are not caught: {join_error}")
+    }
+}
+
+/// A minimal replacement of [`tokio::task::JoinSet`] for simulated tasks.
+///
+/// Aborts all of its tasks when dropped.
+#[cfg(madsim)]
+pub(crate) struct JoinSet<T> {
+    tasks: Vec<tokio::task::JoinHandle<T>>,
+}
+
+#[cfg(madsim)]
+impl<T: Send + 'static> JoinSet<T> {
+    pub(crate) fn new() -> Self {
+        Self { tasks: Vec::new() }
+    }
+
+    pub(crate) fn spawn<F>(&mut self, future: F)
+    where
+        F: std::future::Future<Output = T> + Send + 'static,
+    {
+        self.tasks.push(tokio::spawn(future));
+    }
+
+    /// Waits for the next task to finish, in the order in which they finish.
+    pub(crate) async fn join_next(&mut self) -> Option<Result<T, tokio::task::JoinError>> {
+        use std::{future::Future, pin::Pin, task::Poll};
+
+        if self.tasks.is_empty() {
+            return None;
+        }
+
+        std::future::poll_fn(|cx| {
+            for position in 0..self.tasks.len() {
+                if let Poll::Ready(result) = Pin::new(&mut self.tasks[position]).poll(cx) {
+                    self.tasks.swap_remove(position);
+                    return Poll::Ready(Some(result));
+                }
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+#[cfg(madsim)]
+impl<T> Drop for JoinSet<T> {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
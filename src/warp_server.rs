@@ -0,0 +1,133 @@
+use std::{future::Future, net::SocketAddr, pin::Pin, time::Duration};
+
+use async_trait::async_trait;
+use tokio_util::sync::CancellationToken;
+use warp::{Filter, Reply};
+
+use crate::{ErrTypeTraits, IntoSubsystem, SubsystemHandle};
+
+/// Runs a [`warp`](https://docs.rs/warp) server as a subsystem.
+///
+/// When a shutdown is requested, the server stops accepting new connections
+/// and waits for the open connections to close. Warp does not close idle connections
+/// by itself, so the subsystem stops waiting for them after the
+/// [`drain_timeout`](Self::drain_timeout) elapsed.
+///
+/// # Examples
+///
+/// ```no_run
+/// use miette::{IntoDiagnostic, Result};
+/// use tokio::time::Duration;
+/// use tokio_graceful_shutdown::{
+///     IntoSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel, WarpServer,
+/// };
+/// use warp::Filter;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<()> {
+///     let routes = warp::any().map(|| "Hello, World!");
+///     let server = WarpServer::bind(routes, ([127, 0, 0, 1], 12345))
+///         .into_diagnostic()?
+///         .drain_timeout(Duration::from_secs(10));
+///     tracing::info!("Listening on http://{}", server.local_addr());
+///
+///     Toplevel::new(|s: SubsystemHandle| async move {
+///         s.start(SubsystemBuilder::new("warp", server.into_subsystem()));
+///     })
+///     .catch_signals()
+///     .handle_shutdown_requests(Duration::from_secs(30))
+///     .await?;
+///
+///     Ok(())
+/// }
+/// ```
+pub struct WarpServer {
+    addr: SocketAddr,
+    server: Pin<Box<dyn Future<Output = ()> + Send>>,
+    shutdown_token: CancellationToken,
+    drain_timeout: Duration,
+}
+
+impl WarpServer {
+    /// Binds a server for the given filter to the given address.
+    ///
+    /// The server does not serve any requests before it is started as a subsystem.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - The filter that handles the requests.
+    /// * `addr` - The address to bind to.
+    ///
+    /// # Returns
+    ///
+    /// An error if the address could not be bound.
+    pub fn bind<F>(filter: F, addr: impl Into<SocketAddr>) -> Result<Self, warp::Error>
+    where
+        F: Filter + Clone + Send + Sync + 'static,
+        F::Extract: Reply,
+    {
+        let addr: SocketAddr = addr.into();
+        let shutdown_token = CancellationToken::new();
+        let (addr, server) = warp::serve(filter)
+            .try_bind_with_graceful_shutdown(addr, shutdown_token.clone().cancelled_owned())?;
+
+        Ok(Self {
+            addr,
+            server: Box::pin(server),
+            shutdown_token,
+            drain_timeout: Duration::from_secs(30),
+        })
+    }
+
+    /// How long open connections may stay open after a shutdown was requested.
+    /// Defaults to 30 seconds.
+    ///
+    /// Should be shorter than the shutdown timeout of the [`Toplevel`](crate::Toplevel).
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+
+    /// The address the server is listening on.
+    ///
+    /// Useful when binding to port `0`.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+#[async_trait]
+impl<ErrWrapper> IntoSubsystem<warp::Error, ErrWrapper> for WarpServer
+where
+    warp::Error: Into<ErrWrapper>,
+    ErrWrapper: ErrTypeTraits,
+{
+    async fn run(self, subsys: SubsystemHandle<ErrWrapper>) -> Result<(), warp::Error> {
+        let Self {
+            addr,
+            mut server,
+            shutdown_token,
+            drain_timeout,
+        } = self;
+
+        tokio::select! {
+            _ = &mut server => return Ok(()),
+            _ = subsys.on_shutdown_requested() => {}
+        }
+
+        tracing::debug!("Stopping warp server on {addr} ...");
+        shutdown_token.cancel();
+        if subsys
+            .get_clock()
+            .timeout(drain_timeout, server)
+            .await
+            .is_none()
+        {
+            tracing::warn!(
+                "Connections of warp server on {addr} did not close within {drain_timeout:?}; no longer waiting for them."
+            );
+        }
+
+        Ok(())
+    }
+}
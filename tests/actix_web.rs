@@ -0,0 +1,64 @@
+#![cfg(feature = "actix-web")]
+
+use actix_web::{web, App, HttpServer};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::{sleep, Duration},
+};
+use tokio_graceful_shutdown::{
+    ActixWebServer, IntoSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+#[tokio::test]
+#[traced_test]
+async fn server_finishes_requests_on_shutdown() {
+    let server = HttpServer::new(|| {
+        App::new().route(
+            "/",
+            web::get().to(|| async {
+                sleep(Duration::from_millis(200)).await;
+                "Hello!"
+            }),
+        )
+    })
+    .workers(1)
+    .disable_signals()
+    .bind("127.0.0.1:0")
+    .unwrap();
+    let addr = server.addrs()[0];
+    let server = server.run();
+
+    let (response_sender, response_receiver) = tokio::sync::oneshot::channel();
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new(
+            "http",
+            ActixWebServer::new(server).into_subsystem(),
+        ));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(50)).await;
+        s.request_shutdown();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response_sender.send(response).unwrap();
+    })
+    .handle_shutdown_requests(Duration::from_secs(5))
+    .await;
+
+    assert!(result.is_ok());
+    let response = response_receiver.await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("Hello!"));
+
+    assert!(TcpStream::connect(addr).await.is_err());
+}
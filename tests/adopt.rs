@@ -0,0 +1,113 @@
+use anyhow::anyhow;
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{
+    errors::{GracefulShutdownError, SubsystemError},
+    SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+use crate::common::Event;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn adopted_task_gets_joined_on_shutdown() {
+    let (task_finished, set_task_finished) = Event::create();
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        let join_handle = tokio::spawn(async move {
+            sleep(Duration::from_millis(300)).await;
+            set_task_finished();
+            BoxedResult::Ok(())
+        });
+        subsys.adopt("task", join_handle, false);
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let start = Instant::now();
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert!(task_finished.get());
+    assert_eq!(start.elapsed(), Duration::from_millis(300));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn adopted_task_gets_aborted_on_shutdown() {
+    let (task_finished, set_task_finished) = Event::create();
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        let join_handle = tokio::spawn(async move {
+            sleep(Duration::from_millis(300)).await;
+            set_task_finished();
+            BoxedResult::Ok(())
+        });
+        subsys.adopt("task", join_handle, true);
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let start = Instant::now();
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert!(!task_finished.get());
+    assert_eq!(start.elapsed(), Duration::from_millis(100));
+}
+
+async fn panicking_task() -> BoxedResult {
+    panic!("Panic in adopted task")
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn adopted_task_errors_get_reported() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.adopt(
+            "failing",
+            tokio::spawn(async { BoxedResult::Err(anyhow!("failed").into()) }),
+            false,
+        );
+        subsys.adopt("panicking", tokio::spawn(panicking_task()), false);
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    let Err(GracefulShutdownError::SubsystemsFailed(errors)) = result else {
+        panic!("Expected the shutdown to fail, got {result:?}");
+    };
+    assert_eq!(errors.len(), 2);
+    assert!(errors.iter().any(
+        |e| matches!(e, SubsystemError::Failed(name, _) if name.as_ref() == "/subsys/failing")
+    ));
+    assert!(errors.iter().any(
+        |e| matches!(e, SubsystemError::Panicked(name) if name.as_ref() == "/subsys/panicking")
+    ));
+}
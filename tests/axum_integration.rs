@@ -0,0 +1,66 @@
+#![cfg(feature = "axum")]
+
+use axum::{
+    body::Body,
+    http::{Request, StatusCode},
+    routing::post,
+    Extension, Router,
+};
+use tokio::time::Duration;
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel, ToplevelHandle};
+use tower::Service;
+use tracing_test::traced_test;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+async fn admin_shutdown(toplevel: ToplevelHandle) -> StatusCode {
+    toplevel.request_shutdown();
+    StatusCode::ACCEPTED
+}
+
+fn shutdown_request() -> Request<Body> {
+    Request::post("/admin/shutdown")
+        .body(Body::empty())
+        .unwrap()
+}
+
+#[tokio::test]
+#[traced_test]
+async fn handler_can_request_shutdown() {
+    let toplevel = Toplevel::new(|s| async move {
+        s.start(SubsystemBuilder::new(
+            "subsys",
+            |s: SubsystemHandle| async move {
+                s.on_shutdown_requested().await;
+                BoxedResult::Ok(())
+            },
+        ));
+    });
+
+    let mut router = Router::new()
+        .route("/admin/shutdown", post(admin_shutdown))
+        .layer(Extension(toplevel.handle()));
+
+    let response = router.call(shutdown_request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+    let result = tokio::time::timeout(
+        Duration::from_millis(500),
+        toplevel.handle_shutdown_requests(Duration::from_millis(500)),
+    )
+    .await;
+    assert!(matches!(result, Ok(Ok(()))));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn missing_extension_gets_rejected() {
+    let mut router = Router::new().route("/admin/shutdown", post(admin_shutdown));
+
+    let response = router.call(shutdown_request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+}
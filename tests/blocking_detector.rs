@@ -0,0 +1,101 @@
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn blocking_during_shutdown_gets_reported() {
+    let toplevel = Toplevel::builder()
+        .detect_blocking(Duration::from_millis(50))
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new(
+                "blocking",
+                |s: SubsystemHandle| async move {
+                    s.on_shutdown_requested().await;
+                    std::thread::sleep(Duration::from_millis(100));
+                    BoxedResult::Ok(())
+                },
+            ));
+            s.start(SubsystemBuilder::new(
+                "well_behaved",
+                |s: SubsystemHandle| async move {
+                    s.on_shutdown_requested().await;
+                    sleep(Duration::from_millis(100)).await;
+                    BoxedResult::Ok(())
+                },
+            ));
+            sleep(Duration::from_millis(100)).await;
+            s.request_shutdown();
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    if cfg!(debug_assertions) {
+        assert!(logs_contain(
+            "Subsystem '/blocking' blocked the executor for"
+        ));
+    }
+    assert!(!logs_contain(
+        "Subsystem '/well_behaved' blocked the executor"
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn blocking_before_shutdown_is_ignored() {
+    let toplevel = Toplevel::builder()
+        .detect_blocking(Duration::from_millis(50))
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new(
+                "startup",
+                |s: SubsystemHandle| async move {
+                    std::thread::sleep(Duration::from_millis(100));
+                    s.on_shutdown_requested().await;
+                    BoxedResult::Ok(())
+                },
+            ));
+            sleep(Duration::from_millis(100)).await;
+            s.request_shutdown();
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert!(!logs_contain("blocked the executor"));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn not_detected_by_default() {
+    let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new(
+            "blocking",
+            |s: SubsystemHandle| async move {
+                s.on_shutdown_requested().await;
+                std::thread::sleep(Duration::from_millis(100));
+                BoxedResult::Ok(())
+            },
+        ));
+        s.request_shutdown();
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert!(!logs_contain("blocked the executor"));
+}
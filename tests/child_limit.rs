@@ -0,0 +1,209 @@
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{
+    errors::{ChildLimitReached, InternalError, SubsystemError, SubsystemJoinError},
+    SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+async fn child(_subsys: SubsystemHandle) -> BoxedResult {
+    sleep(Duration::from_millis(100)).await;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn try_start_fails_beyond_limit() {
+    let parent = |subsys: SubsystemHandle| async move {
+        assert!(subsys.try_start(SubsystemBuilder::new("a", child)).is_ok());
+        assert!(subsys.try_start(SubsystemBuilder::new("b", child)).is_ok());
+        assert!(matches!(
+            subsys.try_start(SubsystemBuilder::new("c", child)),
+            Err(ChildLimitReached)
+        ));
+
+        subsys.wait_for_children().await;
+        assert!(subsys.try_start(SubsystemBuilder::new("d", child)).is_ok());
+        subsys.wait_for_children().await;
+
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("parent", parent).max_children(2));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn start_when_available_waits_for_free_slot() {
+    let parent = |subsys: SubsystemHandle| async move {
+        let start = Instant::now();
+        for i in 0..4 {
+            subsys
+                .start_when_available(SubsystemBuilder::new(format!("child{i}"), child))
+                .await;
+        }
+        assert_eq!(start.elapsed(), Duration::from_millis(100));
+
+        subsys.wait_for_children().await;
+        assert_eq!(start.elapsed(), Duration::from_millis(200));
+
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("parent", parent).max_children(2));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn start_fails_beyond_limit() {
+    let parent = |subsys: SubsystemHandle| async move {
+        subsys.start(SubsystemBuilder::new("a", child));
+        let rejected = subsys.start(SubsystemBuilder::new("b", child));
+
+        let Err(SubsystemJoinError::SubsystemsFailed(errors)) = rejected.join().await else {
+            panic!("Expected the child to fail");
+        };
+        assert!(matches!(
+            errors.as_ref(),
+            [SubsystemError::Internal(name, InternalError::ChildLimitReached)]
+                if name.as_ref() == "/parent/b"
+        ));
+
+        subsys.wait_for_children().await;
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("parent", parent).max_children(1));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert!(logs_contain(
+        "Not starting subsystem '/parent/b', as its parent already has the maximum number of children."
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn adopt_aborts_task_beyond_limit() {
+    let parent = |subsys: SubsystemHandle| async move {
+        subsys.start(SubsystemBuilder::new("a", child));
+
+        let join_handle = tokio::spawn(std::future::pending::<BoxedResult>());
+        let abort_handle = join_handle.abort_handle();
+        let rejected = subsys.adopt("b", join_handle, false);
+
+        sleep(Duration::from_millis(1)).await;
+        assert!(abort_handle.is_finished());
+
+        let Err(SubsystemJoinError::SubsystemsFailed(errors)) = rejected.join().await else {
+            panic!("Expected the adopted task to fail");
+        };
+        assert!(matches!(
+            errors.as_ref(),
+            [SubsystemError::Internal(name, InternalError::ChildLimitReached)]
+                if name.as_ref() == "/parent/b"
+        ));
+
+        subsys.wait_for_children().await;
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("parent", parent).max_children(1));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn parent_survives_hitting_the_limit() {
+    let acceptor = |subsys: SubsystemHandle| async move {
+        // More connections than the limit; the excess ones get rejected.
+        for i in 0..5 {
+            subsys.start(SubsystemBuilder::new(format!("connection{i}"), child));
+        }
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("acceptor", acceptor).max_children(2));
+
+        sleep(Duration::from_millis(1000)).await;
+        assert!(!s.is_shutdown_requested());
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn children_are_unlimited_by_default() {
+    let parent = |subsys: SubsystemHandle| async move {
+        for i in 0..100 {
+            assert!(subsys
+                .try_start(SubsystemBuilder::new(format!("child{i}"), child))
+                .is_ok());
+        }
+        subsys.wait_for_children().await;
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("parent", parent));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[test]
+#[should_panic(expected = "max_children must be at least 1")]
+fn zero_children_are_rejected() {
+    let _ = SubsystemBuilder::new("parent", child).max_children(0);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn limits_beyond_semaphore_capacity_are_clamped() {
+    let parent = |subsys: SubsystemHandle| async move {
+        assert!(subsys.try_start(SubsystemBuilder::new("a", child)).is_ok());
+        subsys.wait_for_children().await;
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("parent", parent).max_children(usize::MAX));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
@@ -0,0 +1,277 @@
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use tokio::{
+    sync::watch,
+    time::{Duration, Instant},
+};
+use tokio_graceful_shutdown::{
+    errors::{GracefulShutdownError, SubsystemError},
+    Clock, Flusher, IntervalSubsystem, IntoSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+/// A clock that only advances when told to.
+#[derive(Clone)]
+struct ManualClock {
+    now: Arc<watch::Sender<Instant>>,
+}
+
+impl ManualClock {
+    fn new() -> Self {
+        Self {
+            now: Arc::new(watch::channel(Instant::now()).0),
+        }
+    }
+
+    fn advance(&self, duration: Duration) {
+        self.now.send_modify(|now| *now += duration);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.borrow()
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        let mut now = self.now.subscribe();
+        Box::pin(async move {
+            let _ = now.wait_for(|now| *now >= deadline).await;
+        })
+    }
+}
+
+async fn stuck_subsystem(subsys: SubsystemHandle) -> BoxedResult {
+    subsys.on_shutdown_requested().await;
+    std::future::pending().await
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn custom_clock_drives_shutdown_timeouts() {
+    let clock = ManualClock::new();
+    let start = clock.now();
+
+    let toplevel = Toplevel::builder()
+        .clock(clock.clone())
+        .shutdown_timeout(Duration::from_secs(10))
+        .build(|s| async move {
+            s.start(
+                SubsystemBuilder::new("stuck", stuck_subsystem)
+                    .shutdown_timeout(Duration::from_secs(5)),
+            );
+            s.start(SubsystemBuilder::new("ignoring", stuck_subsystem));
+            s.request_shutdown();
+        });
+    let handle = toplevel.handle();
+    let shutdown = tokio::spawn(toplevel.run());
+
+    // The tokio time does not affect the shutdown.
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert!(!shutdown.is_finished());
+    assert_eq!(
+        handle.shutdown_statistics().shutdown_requested_at,
+        Some(start)
+    );
+
+    clock.advance(Duration::from_secs(5));
+    tokio::time::sleep(Duration::from_millis(1)).await;
+    assert!(!shutdown.is_finished());
+    assert!(logs_contain(
+        "Subsystem '/stuck' exceeded its shutdown timeout of 5s, aborting it ..."
+    ));
+
+    clock.advance(Duration::from_secs(5));
+    let result = shutdown.await.unwrap();
+
+    let Err(GracefulShutdownError::ShutdownTimeout(errors)) = result else {
+        panic!("Incorrect return value!");
+    };
+    assert_eq!(errors.len(), 2);
+    assert!(errors
+        .iter()
+        .any(|e| matches!(e, SubsystemError::Aborted(name) if name.as_ref() == "/ignoring")));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn custom_clock_drives_shutdown_requested_timeout() {
+    let clock = ManualClock::new();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let result = subsys
+            .on_shutdown_requested_timeout(Duration::from_secs(1))
+            .await;
+        assert!(result.is_err());
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::builder()
+        .clock(clock.clone())
+        .build(move |s| async move {
+            s.start(SubsystemBuilder::new("subsys", subsystem));
+        });
+    let shutdown = tokio::spawn(toplevel.run());
+
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert!(!shutdown.is_finished());
+
+    clock.advance(Duration::from_secs(1));
+    let result = shutdown.await.unwrap();
+    assert!(result.is_ok());
+}
+
+#[test]
+#[traced_test]
+fn custom_clock_does_not_require_tokio_time() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let clock = ManualClock::new();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let result = subsys
+            .on_shutdown_requested_timeout(Duration::from_secs(1))
+            .await;
+        assert!(result.is_err());
+        subsys.request_shutdown();
+        BoxedResult::Ok(())
+    };
+
+    runtime.block_on(async {
+        let toplevel = Toplevel::builder()
+            .clock(clock.clone())
+            .build(move |s| async move {
+                s.start(SubsystemBuilder::new("subsys", subsystem));
+            });
+        let shutdown = tokio::spawn(toplevel.run());
+
+        while !shutdown.is_finished() {
+            clock.advance(Duration::from_secs(1));
+            tokio::task::yield_now().await;
+        }
+        assert!(shutdown.await.unwrap().is_ok());
+    });
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn custom_clock_drives_flusher() {
+    let clock = ManualClock::new();
+    let (flushed_sender, mut flushed) = tokio::sync::mpsc::unbounded_channel();
+
+    let flusher = Flusher::new(move |batch: Vec<u32>| {
+        flushed_sender.send(batch).unwrap();
+        async { BoxedResult::Ok(()) }
+    })
+    .interval(Duration::from_secs(10));
+    let sender = flusher.sender();
+
+    let toplevel =
+        Toplevel::builder()
+            .clock(clock.clone())
+            .build(|s: SubsystemHandle| async move {
+                s.start(SubsystemBuilder::new("flusher", flusher.into_subsystem()));
+            });
+    let handle = toplevel.handle();
+    let shutdown = tokio::spawn(toplevel.run());
+
+    sender.push(1);
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert!(flushed.try_recv().is_err());
+
+    clock.advance(Duration::from_secs(10));
+    assert_eq!(flushed.recv().await.unwrap(), [1]);
+
+    handle.request_shutdown();
+    assert!(shutdown.await.unwrap().is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn custom_clock_drives_interval_subsystem() {
+    let clock = ManualClock::new();
+    let (ticked_sender, mut ticked) = tokio::sync::mpsc::unbounded_channel();
+
+    let ticker = IntervalSubsystem::new(Duration::from_secs(10), move || {
+        ticked_sender.send(()).unwrap();
+        async { BoxedResult::Ok(()) }
+    });
+
+    let toplevel =
+        Toplevel::builder()
+            .clock(clock.clone())
+            .build(|s: SubsystemHandle| async move {
+                s.start(SubsystemBuilder::new("ticker", ticker.into_subsystem()));
+            });
+    let handle = toplevel.handle();
+    let shutdown = tokio::spawn(toplevel.run());
+
+    // The first tick happens right away.
+    ticked.recv().await.unwrap();
+    tokio::time::sleep(Duration::from_secs(60)).await;
+    assert!(ticked.try_recv().is_err());
+
+    clock.advance(Duration::from_secs(10));
+    ticked.recv().await.unwrap();
+
+    handle.request_shutdown();
+    assert!(shutdown.await.unwrap().is_ok());
+}
+
+#[cfg(feature = "coordination")]
+#[tokio::test]
+#[traced_test]
+async fn custom_clock_drives_coordination_receive_timeout() {
+    use tokio::io::AsyncWriteExt;
+    use tokio_graceful_shutdown::coordination::{
+        CoordinationMessage, CoordinationTransport, TcpTransport,
+    };
+
+    let clock = ManualClock::new();
+    let transport = TcpTransport::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .clock(clock.clone());
+    let addr = transport.local_addr().unwrap();
+
+    // Connect, but never send anything; more than can be read at the same time
+    let mut silent_peers = Vec::new();
+    for _ in 0..32 {
+        silent_peers.push(tokio::net::TcpStream::connect(addr).await.unwrap());
+    }
+    let mut peer = tokio::net::TcpStream::connect(addr).await.unwrap();
+    peer.write_all(b"shutdown_requested peer\n").await.unwrap();
+    peer.shutdown().await.unwrap();
+
+    // The silent peers block the slots until the clock frees them
+    assert!(
+        tokio::time::timeout(Duration::from_millis(50), transport.receive())
+            .await
+            .is_err()
+    );
+
+    let receive = transport.receive();
+    tokio::pin!(receive);
+    let message = tokio::time::timeout(Duration::from_secs(2), async {
+        loop {
+            tokio::select! {
+                result = &mut receive => break result.unwrap(),
+                _ = tokio::time::sleep(Duration::from_millis(10)) => clock.advance(Duration::from_secs(1)),
+            }
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        message,
+        CoordinationMessage::ShutdownRequested("peer".to_string())
+    );
+    assert!(logs_contain("peer did not send a message"));
+}
@@ -0,0 +1,214 @@
+#![cfg(feature = "coordination")]
+
+use async_trait::async_trait;
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    coordination::{CoordinationMessage, CoordinationTransport, ShutdownCoordinator, TcpTransport},
+    SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::{
+    error::Error,
+    io,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+async fn connected_transports() -> (TcpTransport, TcpTransport) {
+    let a = TcpTransport::bind("127.0.0.1:0").await.unwrap();
+    let b = TcpTransport::bind("127.0.0.1:0").await.unwrap();
+    let (addr_a, addr_b) = (a.local_addr().unwrap(), b.local_addr().unwrap());
+    (a.peer(addr_b), b.peer(addr_a))
+}
+
+fn idle_toplevel() -> Toplevel {
+    Toplevel::new(|s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new(
+            "subsys",
+            |subsys: SubsystemHandle| async move {
+                subsys.on_shutdown_requested().await;
+                BoxedResult::Ok(())
+            },
+        ));
+    })
+}
+
+#[tokio::test]
+#[traced_test]
+async fn shutdown_propagates_to_peers() {
+    let (transport_a, transport_b) = connected_transports().await;
+    let coordinator_a = ShutdownCoordinator::new("a", transport_a);
+    let coordinator_b = ShutdownCoordinator::new("b", transport_b);
+
+    let toplevel_a = coordinator_a.attach(Toplevel::new(|s: SubsystemHandle| async move {
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    }));
+    let toplevel_b = coordinator_b.attach(idle_toplevel());
+
+    let (result_a, result_b) = tokio::join!(
+        async {
+            let result = toplevel_a
+                .handle_shutdown_requests(Duration::from_millis(1000))
+                .await;
+            tokio::time::timeout(Duration::from_secs(5), coordinator_a.wait_for_peers(["b"]))
+                .await
+                .unwrap();
+            result
+        },
+        async {
+            let result = toplevel_b
+                .handle_shutdown_requests(Duration::from_millis(1000))
+                .await;
+            coordinator_b.report_completion().await;
+            result
+        },
+    );
+
+    assert!(result_a.is_ok());
+    assert!(result_b.is_ok());
+    assert!(!coordinator_a.is_shutdown_requested_by_peer());
+    assert!(coordinator_b.is_shutdown_requested_by_peer());
+    assert!(logs_contain("Peer 'a' requested a shutdown."));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn unreachable_peers_do_not_prevent_shutdown() {
+    let unreachable = TcpTransport::bind("127.0.0.1:0").await.unwrap();
+    let unreachable_addr = unreachable.local_addr().unwrap();
+    drop(unreachable);
+
+    let transport = TcpTransport::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .peer(unreachable_addr);
+    let coordinator = ShutdownCoordinator::new("a", transport);
+
+    let result = coordinator
+        .attach(Toplevel::new(|s: SubsystemHandle| async move {
+            s.request_shutdown();
+        }))
+        .handle_shutdown_requests(Duration::from_millis(1000))
+        .await;
+    coordinator.report_completion().await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn silent_connections_do_not_block_other_peers() {
+    let (transport_a, transport_b) = connected_transports().await;
+    let addr_b = transport_b.local_addr().unwrap();
+    let coordinator_a = ShutdownCoordinator::new("a", transport_a);
+    let coordinator_b = ShutdownCoordinator::new("b", transport_b);
+
+    // Connects, but never sends anything
+    let _silent_peer = tokio::net::TcpStream::connect(addr_b).await.unwrap();
+    sleep(Duration::from_millis(10)).await;
+
+    coordinator_a.report_completion().await;
+    tokio::time::timeout(Duration::from_secs(1), coordinator_b.wait_for_peers(["a"]))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+#[traced_test]
+async fn invalid_messages_do_not_delay_other_peers() {
+    use tokio::io::AsyncWriteExt;
+
+    let (transport_a, transport_b) = connected_transports().await;
+    let addr_b = transport_b.local_addr().unwrap();
+    let coordinator_a = ShutdownCoordinator::new("a", transport_a);
+    let coordinator_b = ShutdownCoordinator::new("b", transport_b);
+
+    // More broken connections than can be read at the same time
+    for _ in 0..32 {
+        let mut broken_peer = tokio::net::TcpStream::connect(addr_b).await.unwrap();
+        broken_peer.write_all(b"garbage\n").await.unwrap();
+    }
+    sleep(Duration::from_millis(10)).await;
+
+    coordinator_a.report_completion().await;
+    tokio::time::timeout(
+        Duration::from_millis(500),
+        coordinator_b.wait_for_peers(["a"]),
+    )
+    .await
+    .unwrap();
+    assert!(!logs_contain("Failed to receive coordination message"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+#[traced_test]
+async fn unix_transport_propagates_shutdown() {
+    use tokio_graceful_shutdown::coordination::UnixTransport;
+
+    let dir = std::env::temp_dir().join(format!("tgs-coordination-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (path_a, path_b) = (dir.join("a.sock"), dir.join("b.sock"));
+
+    let transport_a = UnixTransport::bind(&path_a).unwrap().peer(&path_b);
+    let transport_b = UnixTransport::bind(&path_b).unwrap().peer(&path_a);
+    let coordinator_a = ShutdownCoordinator::new("a", transport_a);
+    let coordinator_b = ShutdownCoordinator::new("b", transport_b);
+
+    let toplevel_a = coordinator_a.attach(idle_toplevel());
+    let toplevel_b = coordinator_b.attach(Toplevel::new(|s: SubsystemHandle| async move {
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    }));
+
+    let (result_a, result_b) = tokio::join!(
+        toplevel_a.handle_shutdown_requests(Duration::from_millis(1000)),
+        toplevel_b.handle_shutdown_requests(Duration::from_millis(1000)),
+    );
+
+    assert!(result_a.is_ok());
+    assert!(result_b.is_ok());
+    assert!(coordinator_a.is_shutdown_requested_by_peer());
+
+    drop(coordinator_a);
+    drop(coordinator_b);
+    sleep(Duration::from_millis(10)).await;
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn failing_transport_gets_retried_with_backoff() {
+    struct ClosedTransport(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl CoordinationTransport for ClosedTransport {
+        async fn broadcast(&self, _message: &CoordinationMessage) -> io::Result<()> {
+            Ok(())
+        }
+
+        async fn receive(&self) -> io::Result<CoordinationMessage> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            Err(io::ErrorKind::NotConnected.into())
+        }
+    }
+
+    let attempts = Arc::new(AtomicUsize::new(0));
+    let coordinator = ShutdownCoordinator::new("a", ClosedTransport(Arc::clone(&attempts)));
+
+    // Backs off by 10, 20, 40, ..., 640 milliseconds, then by one second each.
+    sleep(Duration::from_millis(3000)).await;
+    assert_eq!(attempts.load(Ordering::Relaxed), 9);
+
+    drop(coordinator);
+}
@@ -0,0 +1,52 @@
+#![cfg(unix)]
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    ShutdownReason, ShutdownSignal, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test]
+#[traced_test]
+async fn ctrl_c_initiates_shutdown() {
+    use nix::sys::signal::{self, Signal};
+    use nix::unistd::Pid;
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        assert_eq!(
+            subsys.shutdown_reason(),
+            Some(ShutdownReason::Signal(ShutdownSignal::Interrupt))
+        );
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::builder().catch_signals().ctrl_c_only(true).build(
+        move |s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("subsys", subsystem));
+        },
+    );
+
+    tokio::join!(
+        async {
+            sleep(Duration::from_millis(100)).await;
+            signal::kill(Pid::this(), Signal::SIGINT).unwrap();
+        },
+        async {
+            let result = toplevel
+                .handle_shutdown_requests(Duration::from_millis(400))
+                .await;
+            assert!(result.is_ok());
+        },
+    );
+
+    assert!(logs_contain("Received SIGINT."));
+}
@@ -0,0 +1,107 @@
+use std::{
+    error::Error,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[derive(Default)]
+struct Recorder {
+    completed: Mutex<Vec<String>>,
+    shutting_down: AtomicUsize,
+    max_shutting_down: AtomicUsize,
+}
+
+async fn leaf(subsys: SubsystemHandle, recorder: Arc<Recorder>, delay: u64) -> BoxedResult {
+    subsys.on_shutdown_requested().await;
+
+    let shutting_down = recorder.shutting_down.fetch_add(1, Ordering::SeqCst) + 1;
+    recorder
+        .max_shutting_down
+        .fetch_max(shutting_down, Ordering::SeqCst);
+
+    // Without a deterministic shutdown, siblings with a shorter delay would finish first.
+    sleep(Duration::from_millis(delay)).await;
+
+    recorder.shutting_down.fetch_sub(1, Ordering::SeqCst);
+    recorder
+        .completed
+        .lock()
+        .unwrap()
+        .push(subsys.name().to_string());
+    Ok(())
+}
+
+async fn run_once(seed: u64) -> (Vec<String>, usize) {
+    let recorder = Arc::new(Recorder::default());
+
+    let result = Toplevel::builder()
+        .deterministic_shutdown(seed)
+        .build({
+            let recorder = Arc::clone(&recorder);
+            move |s: SubsystemHandle| async move {
+                for i in 0..6 {
+                    let recorder = Arc::clone(&recorder);
+                    s.start(SubsystemBuilder::new(format!("leaf{i}"), move |s| {
+                        leaf(s, recorder, 6 - i)
+                    }));
+                }
+
+                let recorder = Arc::clone(&recorder);
+                s.start(SubsystemBuilder::new(
+                    "nested",
+                    move |s: SubsystemHandle| async move {
+                        for i in 0..3 {
+                            let recorder = Arc::clone(&recorder);
+                            s.start(SubsystemBuilder::new(format!("leaf{i}"), move |s| {
+                                leaf(s, recorder, 3 - i)
+                            }));
+                        }
+                        s.on_shutdown_requested().await;
+                        BoxedResult::Ok(())
+                    },
+                ));
+
+                sleep(Duration::from_millis(20)).await;
+                s.request_shutdown();
+            }
+        })
+        .handle_shutdown_requests(Duration::from_secs(5))
+        .await;
+    assert!(result.is_ok());
+
+    let completed = recorder.completed.lock().unwrap().clone();
+    (completed, recorder.max_shutting_down.load(Ordering::SeqCst))
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn same_seed_results_in_same_completion_order() {
+    let (expected, max_shutting_down) = run_once(42).await;
+    assert_eq!(expected.len(), 9);
+    assert_eq!(max_shutting_down, 1);
+
+    for _ in 0..10 {
+        assert_eq!(run_once(42).await, (expected.clone(), 1));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn children_of_a_sibling_finish_before_the_next_sibling() {
+    let (completed, _) = run_once(7).await;
+
+    let nested = completed
+        .iter()
+        .position(|name| name.starts_with("/nested/"))
+        .unwrap();
+    assert!(completed[nested..nested + 3]
+        .iter()
+        .all(|name| name.starts_with("/nested/")));
+}
@@ -0,0 +1,155 @@
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{sleep, Duration},
+};
+use tokio_graceful_shutdown::{
+    DrainSummary, ExitStatus, ShutdownReportEntry, SubsystemBuilder, SubsystemHandle, Toplevel,
+    ToplevelHandle,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+/// Runs a worker that handles every job in 10ms, while the given
+/// number of jobs is queued and a shutdown is requested right away.
+async fn drain_jobs(jobs: u32, deadline: Duration) -> (DrainSummary, ToplevelHandle) {
+    let (job_sender, job_receiver) = mpsc::channel(100);
+    for job in 0..jobs {
+        job_sender.try_send(job).unwrap();
+    }
+    let (summary_sender, summary_receiver) = oneshot::channel();
+
+    let worker = move |subsys: SubsystemHandle| async move {
+        let summary = subsys
+            .drain(
+                job_receiver,
+                |_job| async {
+                    sleep(Duration::from_millis(10)).await;
+                    BoxedResult::Ok(())
+                },
+                deadline,
+            )
+            .await?;
+        summary_sender.send(summary).unwrap();
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("worker", worker));
+        s.request_shutdown();
+    });
+    let handle = toplevel.handle();
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    // New jobs get refused once the queue is drained.
+    assert!(job_sender.try_send(jobs).is_err());
+
+    (summary_receiver.await.unwrap(), handle)
+}
+
+fn worker_exit_status(handle: &ToplevelHandle) -> Option<ExitStatus> {
+    match &handle.shutdown_report().entries[0] {
+        ShutdownReportEntry::Single { result, .. } => result.exit_status.clone(),
+        ShutdownReportEntry::Aggregated(_) => panic!("Expected a single entry"),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn drains_buffered_jobs() {
+    let (summary, handle) = drain_jobs(5, Duration::from_millis(100)).await;
+
+    assert_eq!(summary.processed, 5);
+    assert_eq!(summary.dropped, 0);
+    assert_eq!(
+        worker_exit_status(&handle),
+        Some(ExitStatus::Drained { dropped: 0 })
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn drops_jobs_after_deadline() {
+    let (summary, handle) = drain_jobs(20, Duration::from_millis(55)).await;
+
+    // The job that was started before the deadline still gets finished.
+    assert_eq!(summary.processed, 6);
+    assert_eq!(summary.dropped, 14);
+    assert_eq!(
+        worker_exit_status(&handle),
+        Some(ExitStatus::Drained { dropped: 14 })
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn completes_when_senders_are_gone() {
+    let (job_sender, job_receiver) = mpsc::channel(100);
+
+    let worker = move |subsys: SubsystemHandle| async move {
+        let summary = subsys
+            .drain(
+                job_receiver,
+                |_job: u32| async { BoxedResult::Ok(()) },
+                Duration::from_millis(100),
+            )
+            .await?;
+        assert_eq!(summary.processed, 3);
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("worker", worker));
+        for job in 0..3 {
+            job_sender.send(job).await.unwrap();
+        }
+    });
+    let handle = toplevel.handle();
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert_eq!(worker_exit_status(&handle), Some(ExitStatus::Completed));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn handler_errors_stop_draining() {
+    let (job_sender, job_receiver) = mpsc::channel(100);
+    job_sender.try_send(1).unwrap();
+
+    let worker = move |subsys: SubsystemHandle| async move {
+        subsys
+            .drain(
+                job_receiver,
+                |job: u32| async move { Err(BoxedError::from(format!("Job {job} failed"))) },
+                Duration::from_millis(100),
+            )
+            .await?;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("worker", worker));
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    let errors = result.unwrap_err().into_subsystem_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].name(), "/worker");
+    drop(job_sender);
+}
@@ -0,0 +1,69 @@
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    errors::{SubsystemError, SubsystemJoinError},
+    SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+use crate::common::Event;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn errors_in_isolated_subtree_only_shut_down_the_subtree() {
+    let (sibling_stopped, set_sibling_stopped) = Event::create();
+    let (other_tenant_stopped, set_other_tenant_stopped) = Event::create();
+
+    let failing_pipeline = |_: SubsystemHandle| async {
+        sleep(Duration::from_millis(100)).await;
+        BoxedResult::Err("Pipeline failed".into())
+    };
+    let sibling_pipeline = move |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        set_sibling_stopped();
+        BoxedResult::Ok(())
+    };
+    let failing_tenant = move |subsys: SubsystemHandle| async move {
+        subsys.start(SubsystemBuilder::new("failing", failing_pipeline));
+        subsys.start(SubsystemBuilder::new("sibling", sibling_pipeline));
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+    let other_tenant = move |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        set_other_tenant_stopped();
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        let tenant = s.start(SubsystemBuilder::new("tenant_a", failing_tenant).isolate_errors());
+        s.start(SubsystemBuilder::new("tenant_b", other_tenant).isolate_errors());
+
+        let Err(SubsystemJoinError::SubsystemsFailed(errors)) = tenant.join().await else {
+            panic!("Expected the tenant to fail");
+        };
+        assert!(matches!(
+            errors.as_ref(),
+            [SubsystemError::Failed(name, _)] if name.as_ref() == "/tenant_a/failing"
+        ));
+        assert!(sibling_stopped.get());
+
+        sleep(Duration::from_millis(100)).await;
+        assert!(!s.is_shutdown_requested());
+        assert!(!other_tenant_stopped.get());
+
+        s.request_shutdown();
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+    assert!(result.is_ok());
+}
@@ -0,0 +1,134 @@
+use tokio::time::Duration;
+use tokio_graceful_shutdown::{
+    errors::{GracefulShutdownError, SubsystemError},
+    ExitStatus, ShutdownReportEntry, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+fn exiting_with(
+    exit_status: ExitStatus,
+) -> impl FnOnce(SubsystemHandle) -> std::future::Ready<BoxedResult> {
+    move |subsys: SubsystemHandle| {
+        subsys.set_exit_status(exit_status);
+        std::future::ready(Ok(()))
+    }
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn exit_statuses_show_up_in_report() {
+    let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("plain", |_| async {
+            BoxedResult::Ok(())
+        }));
+        s.start(SubsystemBuilder::new(
+            "queue",
+            exiting_with(ExitStatus::Drained { dropped: 12 }),
+        ));
+    });
+    let handle = toplevel.handle();
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    let exit_statuses = handle
+        .shutdown_report()
+        .entries
+        .into_iter()
+        .map(|entry| match entry {
+            ShutdownReportEntry::Single { result, .. } => {
+                (result.name.to_string(), result.exit_status)
+            }
+            ShutdownReportEntry::Aggregated(_) => panic!("Expected single entries"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        exit_statuses,
+        [
+            ("/plain".to_string(), Some(ExitStatus::Completed)),
+            (
+                "/queue".to_string(),
+                Some(ExitStatus::Drained { dropped: 12 })
+            ),
+        ]
+    );
+    assert_eq!(
+        handle.shutdown_report().to_string(),
+        "/plain: ok\n/queue: drained with 12 items dropped"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn strict_mode_fails_on_unclean_exit_statuses() {
+    let toplevel =
+        Toplevel::builder()
+            .strict_exit_statuses(true)
+            .build(|s: SubsystemHandle| async move {
+                s.start(SubsystemBuilder::new(
+                    "empty",
+                    exiting_with(ExitStatus::Drained { dropped: 0 }),
+                ));
+                s.start(SubsystemBuilder::new(
+                    "idle",
+                    exiting_with(ExitStatus::Skipped {
+                        reason: "nothing to do".into(),
+                    }),
+                ));
+                s.start(SubsystemBuilder::new(
+                    "queue",
+                    exiting_with(ExitStatus::Drained { dropped: 12 }),
+                ));
+            });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+
+    let Err(GracefulShutdownError::SubsystemsFailed(errors)) = result else {
+        panic!("Expected the unclean exit to fail the shutdown");
+    };
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        &errors[0],
+        SubsystemError::UncleanExit(name, ExitStatus::Drained { dropped: 12 })
+            if name.as_ref() == "/queue"
+    ));
+    assert!(logs_contain(
+        "Subsystem '/queue' drained with 12 items dropped."
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn unclean_exit_statuses_only_get_reported_by_default() {
+    let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new(
+            "cache",
+            exiting_with(ExitStatus::Degraded {
+                reason: "backend unreachable".into(),
+            }),
+        ));
+    });
+    let handle = toplevel.handle();
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        handle.shutdown_report().to_string(),
+        "/cache: degraded (backend unreachable)"
+    );
+}
@@ -0,0 +1,125 @@
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{
+    ErrorAction, IntoSubsystem, RetryingSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn retries_are_counted_as_restarts() {
+    let start = Instant::now();
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        let mut attempt = 0;
+        let flaky = s.start(
+            SubsystemBuilder::new(
+                "flaky",
+                RetryingSubsystem::new(move |_: &SubsystemHandle| {
+                    attempt += 1;
+                    let error = format!("Attempt {attempt} failed");
+                    async move { Err::<(), _>(error) }
+                })
+                .initial_backoff(Duration::from_millis(100))
+                .max_retries(2)
+                .into_subsystem(),
+            )
+            .on_failure(ErrorAction::CatchAndLocalShutdown),
+        );
+
+        assert!(flaky.join().await.is_err());
+        assert_eq!(flaky.restart_count(), 2);
+
+        let history = flaky
+            .failure_history()
+            .into_iter()
+            .map(|failure| (failure.summary, failure.at - start))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            history,
+            [
+                ("Attempt 1 failed".to_string(), Duration::ZERO),
+                ("Attempt 2 failed".to_string(), Duration::from_millis(100)),
+                ("Attempt 3 failed".to_string(), Duration::from_millis(300)),
+            ]
+        );
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn history_is_bounded() {
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        let nested = s.start(SubsystemBuilder::new(
+            "nested",
+            |subsys: SubsystemHandle| async move {
+                for i in 0..100 {
+                    subsys.record_restart(format!("Failure {i}"));
+                }
+                assert_eq!(subsys.restart_count(), 100);
+                BoxedResult::Ok(())
+            },
+        ));
+
+        nested.join().await.unwrap();
+        assert_eq!(nested.restart_count(), 100);
+
+        let history = nested.failure_history();
+        assert!(history.len() < 100);
+        assert_eq!(history.last().unwrap().summary, "Failure 99");
+        assert_eq!(
+            history.first().unwrap().summary,
+            format!("Failure {}", 100 - history.len())
+        );
+
+        s.request_shutdown();
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn panics_are_recorded() {
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        let nested = s.start(
+            SubsystemBuilder::new("nested", |_: SubsystemHandle| async move {
+                sleep(Duration::from_millis(100)).await;
+                panic!("Boom!");
+                #[allow(unreachable_code)]
+                BoxedResult::Ok(())
+            })
+            .on_panic(ErrorAction::CatchAndLocalShutdown),
+        );
+
+        assert!(nested.failure_history().is_empty());
+        assert!(nested.join().await.is_err());
+        assert_eq!(nested.restart_count(), 0);
+
+        let history = nested.failure_history();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].summary, "Subsystem '/nested' panicked");
+
+        s.request_shutdown();
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+}
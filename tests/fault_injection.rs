@@ -0,0 +1,113 @@
+#![cfg(feature = "fault-injection")]
+
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{
+    errors::{GracefulShutdownError, SubsystemError},
+    testing::{Fault, FaultInjection},
+    SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+async fn subsystem(subsys: SubsystemHandle) -> BoxedResult {
+    subsys.on_shutdown_requested().await;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn delay_fault_slows_down_shutdown() {
+    let start = Instant::now();
+
+    let result = Toplevel::builder()
+        .fault_injection(FaultInjection::new(|name| {
+            (name == "/slow").then_some(Fault::Delay(Duration::from_millis(200)))
+        }))
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("slow", subsystem));
+            s.start(SubsystemBuilder::new("fast", subsystem));
+            sleep(Duration::from_millis(100)).await;
+            s.request_shutdown();
+        })
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+
+    assert!(result.is_ok());
+    assert_eq!(start.elapsed(), Duration::from_millis(300));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn panic_fault_gets_reported() {
+    let result = Toplevel::builder()
+        .fault_injection(FaultInjection::new(|name| {
+            (name == "/crashing").then_some(Fault::Panic)
+        }))
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("crashing", subsystem));
+            s.start(SubsystemBuilder::new("healthy", subsystem));
+            s.request_shutdown();
+        })
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+
+    let Err(GracefulShutdownError::SubsystemsFailed(errors)) = result else {
+        panic!("Expected the shutdown to fail, got {result:?}");
+    };
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(&errors[0], SubsystemError::Panicked(name) if name.as_ref() == "/crashing"));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn hang_fault_causes_timeout() {
+    let result = Toplevel::builder()
+        .fault_injection(FaultInjection::new(|name| {
+            (name == "/stuck").then_some(Fault::Hang)
+        }))
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("stuck", subsystem));
+            s.request_shutdown();
+        })
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(GracefulShutdownError::ShutdownTimeout(_))
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn faults_only_get_injected_during_shutdown() {
+    let result = Toplevel::builder()
+        .fault_injection(FaultInjection::new(|_| Some(Fault::Hang)))
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("short_lived", |_| async {
+                BoxedResult::Ok(())
+            }))
+            .join()
+            .await
+            .unwrap();
+            s.request_shutdown();
+        })
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+
+    // The root subsystem itself finishes during shutdown and therefore hangs;
+    // the child must not have hung, otherwise joining it would not have finished.
+    assert!(matches!(
+        result,
+        Err(GracefulShutdownError::ShutdownTimeout(_))
+    ));
+    assert!(logs_contain("Injecting fault into subsystem '': Hang"));
+    assert!(!logs_contain(
+        "Injecting fault into subsystem '/short_lived'"
+    ));
+}
@@ -0,0 +1,99 @@
+#![cfg(feature = "ffi")]
+
+use std::{
+    ffi::c_void,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    ffi::{
+        tgs_handle_free, tgs_is_shutting_down, tgs_on_shutdown, tgs_request_shutdown, TgsHandle,
+    },
+    SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+extern "C" fn count_call(user_data: *mut c_void) {
+    let calls = unsafe { &*(user_data as *const AtomicU32) };
+    calls.fetch_add(1, Ordering::SeqCst);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn c_code_requests_and_observes_shutdown() {
+    static CALLS: AtomicU32 = AtomicU32::new(0);
+
+    let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+        s.on_shutdown_requested().await;
+    });
+
+    let handle = TgsHandle::new(&toplevel.handle()).into_raw();
+    let user_data = &CALLS as *const AtomicU32 as *mut c_void;
+
+    unsafe {
+        tgs_on_shutdown(handle, Some(count_call), user_data);
+        assert!(!tgs_is_shutting_down(handle));
+    }
+
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(CALLS.load(Ordering::SeqCst), 0);
+
+    unsafe {
+        tgs_request_shutdown(handle);
+        assert!(tgs_is_shutting_down(handle));
+        // Registered after the shutdown got requested
+        tgs_on_shutdown(handle, Some(count_call), user_data);
+        tgs_handle_free(handle);
+    }
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+    assert!(result.is_ok());
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn null_pointers_are_ignored() {
+    unsafe {
+        tgs_request_shutdown(std::ptr::null());
+        assert!(!tgs_is_shutting_down(std::ptr::null()));
+        tgs_on_shutdown(std::ptr::null(), Some(count_call), std::ptr::null_mut());
+        tgs_handle_free(std::ptr::null_mut());
+    }
+}
+
+#[tokio::test]
+async fn missing_callback_is_ignored() {
+    let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+        s.request_shutdown();
+    });
+
+    let handle = TgsHandle::new(&toplevel.handle()).into_raw();
+    unsafe {
+        tgs_on_shutdown(handle, None, std::ptr::null_mut());
+        tgs_handle_free(handle);
+    }
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+    assert!(result.is_ok());
+}
+
+#[test]
+fn header_declares_all_functions() {
+    let header = include_str!("../include/tokio_graceful_shutdown.h");
+
+    for declaration in [
+        "void tgs_request_shutdown(const TgsHandle *handle);",
+        "bool tgs_is_shutting_down(const TgsHandle *handle);",
+        "void tgs_on_shutdown(const TgsHandle *handle, void (*callback)(void *), void *user_data);",
+        "void tgs_handle_free(TgsHandle *handle);",
+    ] {
+        assert!(header.contains(declaration), "missing: {declaration}");
+    }
+}
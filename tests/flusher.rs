@@ -0,0 +1,134 @@
+use anyhow::anyhow;
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    Flusher, IntoSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::{
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+fn recording_flusher(
+    batches: &Arc<Mutex<Vec<Vec<u32>>>>,
+) -> Flusher<u32, impl FnMut(Vec<u32>) -> std::future::Ready<BoxedResult> + Send + 'static> {
+    let batches = Arc::clone(batches);
+    Flusher::new(move |batch| {
+        batches.lock().unwrap().push(batch);
+        std::future::ready(BoxedResult::Ok(()))
+    })
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn flushes_on_interval_and_size() {
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let flusher = recording_flusher(&batches)
+        .interval(Duration::from_millis(100))
+        .max_batch_size(3);
+    let sender = flusher.sender();
+
+    let verify_batches = Arc::clone(&batches);
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("flusher", flusher.into_subsystem()));
+
+        sender.push(1);
+        sender.push(2);
+        sleep(Duration::from_millis(150)).await;
+        assert_eq!(*verify_batches.lock().unwrap(), vec![vec![1, 2]]);
+
+        sender.push(3);
+        sender.push(4);
+        sender.push(5);
+        sleep(Duration::from_millis(10)).await;
+        assert_eq!(
+            *verify_batches.lock().unwrap(),
+            vec![vec![1, 2], vec![3, 4, 5]]
+        );
+
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn flushes_remaining_items_on_shutdown() {
+    let batches = Arc::new(Mutex::new(Vec::new()));
+    let flusher = recording_flusher(&batches).interval(Duration::from_secs(10));
+    let sender = flusher.sender();
+    let dropped_items = flusher.dropped_items();
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("flusher", flusher.into_subsystem()));
+        sender.push(1);
+        sender.push(2);
+        s.request_shutdown();
+        sleep(Duration::from_millis(10)).await;
+        assert!(!sender.push(3));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(*batches.lock().unwrap(), vec![vec![1, 2]]);
+    assert_eq!(dropped_items.get(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn final_flush_times_out() {
+    let flusher = Flusher::new(|_: Vec<u32>| async {
+        sleep(Duration::from_secs(10)).await;
+        BoxedResult::Ok(())
+    })
+    .final_flush_timeout(Duration::from_millis(200));
+    let sender = flusher.sender();
+    let dropped_items = flusher.dropped_items();
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("flusher", flusher.into_subsystem()));
+        sender.push(1);
+        sender.push(2);
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(dropped_items.get(), 2);
+    assert!(logs_contain(
+        "Final flush did not finish within 200ms; dropping 2 items."
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn failed_flush_fails_subsystem() {
+    let flusher =
+        Flusher::new(|_: Vec<u32>| async { BoxedResult::Err(anyhow!("Disk full").into()) })
+            .max_batch_size(2);
+    let sender = flusher.sender();
+    let dropped_items = flusher.dropped_items();
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("flusher", flusher.into_subsystem()));
+        sender.push(1);
+        sender.push(2);
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(dropped_items.get(), 2);
+}
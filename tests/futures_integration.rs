@@ -0,0 +1,296 @@
+#![cfg(feature = "futures")]
+
+use anyhow::anyhow;
+use futures_util::{future::Aborted, StreamExt};
+use tokio::{
+    sync::mpsc,
+    time::{sleep, Duration},
+};
+use tokio_graceful_shutdown::{
+    errors::GracefulShutdownError, IntoSubsystem, ShutdownState, StreamProcessor, SubsystemBuilder,
+    SubsystemHandle, SubsystemOutcome, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+use crate::common::Event;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn abort_handle_gets_aborted_on_shutdown() {
+    let (aborted, set_aborted) = Event::create();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let (_abort_handle, abortable) = subsys.abortable(sleep(Duration::from_secs(10)));
+        let result = abortable.await;
+        assert_eq!(result, Err(Aborted));
+        set_aborted();
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert!(aborted.get());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn abort_handle_can_still_be_aborted_manually() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let (abort_handle, abortable) = subsys.abortable(std::future::pending::<()>());
+        abort_handle.abort();
+        let result = abortable.await;
+        assert_eq!(result, Err(Aborted));
+        assert!(!subsys.is_shutdown_requested());
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn state_stream_yields_transitions() {
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new(
+            "subsys",
+            |s: SubsystemHandle| async move {
+                s.on_shutdown_requested().await;
+                sleep(Duration::from_millis(100)).await;
+                BoxedResult::Ok(())
+            },
+        ));
+    });
+    let handle = toplevel.handle();
+
+    let states = tokio::spawn(handle.state_stream().collect::<Vec<_>>());
+    tokio::task::yield_now().await;
+
+    let shutdown = tokio::spawn(toplevel.handle_shutdown_requests(Duration::from_millis(500)));
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(handle.shutdown_state(), ShutdownState::Running);
+
+    handle.request_shutdown();
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(handle.shutdown_state(), ShutdownState::ShuttingDown);
+
+    assert!(shutdown.await.unwrap().is_ok());
+    assert_eq!(handle.shutdown_state(), ShutdownState::Finished);
+
+    assert_eq!(
+        states.await.unwrap(),
+        [
+            ShutdownState::Running,
+            ShutdownState::ShuttingDown,
+            ShutdownState::Finished
+        ]
+    );
+}
+
+/// Provides a stream whose sender stays alive, so that it never ends by itself.
+fn channel_stream(
+    items: &[u32],
+) -> (
+    mpsc::UnboundedSender<u32>,
+    impl futures_util::Stream<Item = u32>,
+) {
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    for &item in items {
+        sender.send(item).unwrap();
+    }
+    let stream = futures_util::stream::poll_fn(move |cx| receiver.poll_recv(cx));
+    (sender, stream)
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn stream_processor_handles_all_items() {
+    let processor = StreamProcessor::new(futures_util::stream::iter(0..5), |_| async {
+        sleep(Duration::from_millis(10)).await;
+        BoxedResult::Ok(())
+    });
+    let handled_items = processor.handled_items();
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("stream", processor.into_subsystem()));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(handled_items.get(), 5);
+    assert!(logs_contain(
+        "Stream processing finished after handling 5 items."
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn stream_processor_stops_pulling_on_shutdown() {
+    let (_sender, stream) = channel_stream(&[1, 2, 3, 4, 5]);
+    let processor = StreamProcessor::new(stream, |_| async {
+        sleep(Duration::from_millis(100)).await;
+        BoxedResult::Ok(())
+    });
+    let handled_items = processor.handled_items();
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("stream", processor.into_subsystem()));
+        sleep(Duration::from_millis(150)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    // The second item was in progress during the shutdown and got finished.
+    assert!(result.is_ok());
+    assert_eq!(handled_items.get(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn stream_processor_drains_buffered_items() {
+    let (_sender, stream) = channel_stream(&[1, 2, 3, 4, 5]);
+    let processor = StreamProcessor::new(stream, |_| async {
+        sleep(Duration::from_millis(100)).await;
+        BoxedResult::Ok(())
+    })
+    .drain_on_shutdown(true);
+    let handled_items = processor.handled_items();
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("stream", processor.into_subsystem()));
+        sleep(Duration::from_millis(150)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(1000))
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(handled_items.get(), 5);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn stream_processor_fails_on_handler_error() {
+    let processor = StreamProcessor::new(futures_util::stream::iter(0..5), |item| async move {
+        if item == 2 {
+            BoxedResult::Err(anyhow!("Item {item} failed").into())
+        } else {
+            BoxedResult::Ok(())
+        }
+    });
+    let handled_items = processor.handled_items();
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("stream", processor.into_subsystem()));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(matches!(
+        result,
+        Err(GracefulShutdownError::SubsystemsFailed(_))
+    ));
+    assert_eq!(handled_items.get(), 2);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn shutdown_results_stream_yields_results_as_they_arrive() {
+    fn shut_down_after(
+        millis: u64,
+    ) -> impl Fn(SubsystemHandle) -> futures_util::future::BoxFuture<'static, BoxedResult> {
+        move |s: SubsystemHandle| {
+            Box::pin(async move {
+                s.on_shutdown_requested().await;
+                sleep(Duration::from_millis(millis)).await;
+                BoxedResult::Ok(())
+            })
+        }
+    }
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("slow", shut_down_after(200)));
+        s.start(SubsystemBuilder::new("fast", shut_down_after(100)));
+        s.start(SubsystemBuilder::new(
+            "failing",
+            |s: SubsystemHandle| async move {
+                s.on_shutdown_requested().await;
+                sleep(Duration::from_millis(150)).await;
+                BoxedResult::Err("Failed".into())
+            },
+        ));
+        s.start(SubsystemBuilder::new("stuck", shut_down_after(1000)));
+    });
+    let handle = toplevel.handle();
+
+    let results = tokio::spawn(toplevel.shutdown_results_stream().collect::<Vec<_>>());
+    let shutdown = tokio::spawn(toplevel.handle_shutdown_requests(Duration::from_millis(500)));
+
+    sleep(Duration::from_millis(100)).await;
+    handle.request_shutdown();
+
+    assert!(matches!(
+        shutdown.await.unwrap(),
+        Err(GracefulShutdownError::ShutdownTimeout(_))
+    ));
+
+    let results = results
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|result| {
+            (
+                result.name.to_string(),
+                result.outcome,
+                result.shutdown_duration,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        results,
+        [
+            (
+                "/fast".to_string(),
+                SubsystemOutcome::Succeeded,
+                Some(Duration::from_millis(100))
+            ),
+            (
+                "/failing".to_string(),
+                SubsystemOutcome::Failed,
+                Some(Duration::from_millis(150))
+            ),
+            (
+                "/slow".to_string(),
+                SubsystemOutcome::Succeeded,
+                Some(Duration::from_millis(200))
+            ),
+            (
+                "/stuck".to_string(),
+                SubsystemOutcome::Aborted,
+                Some(Duration::from_millis(500))
+            ),
+        ]
+    );
+}
@@ -0,0 +1,71 @@
+#![cfg(feature = "global")]
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+// The global handle is shared by the entire process, so everything
+// that touches it has to happen within a single test.
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn global_handle_follows_installed_toplevel() {
+    assert!(tokio_graceful_shutdown::global().is_none());
+
+    // Not installed without opting in.
+    Toplevel::new(|s: SubsystemHandle| async move {
+        assert!(tokio_graceful_shutdown::global().is_none());
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(400))
+    .await
+    .unwrap();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let global = tokio_graceful_shutdown::global().unwrap();
+        assert!(!global.is_shutdown_requested());
+
+        let token = global.shutdown_token();
+        assert!(global.toplevel_handle::<String>().is_none());
+        let toplevel_handle = global.toplevel_handle::<BoxedError>().unwrap();
+        toplevel_handle
+            .start(SubsystemBuilder::new("started_globally", |s| async move {
+                s.on_shutdown_requested().await;
+                BoxedResult::Ok(())
+            }))
+            .unwrap();
+
+        sleep(Duration::from_millis(100)).await;
+        global.request_shutdown();
+        global.on_shutdown_requested().await;
+        assert!(global.is_shutdown_requested());
+        assert!(token.is_shutdown_requested());
+        assert!(subsys.is_shutdown_requested());
+
+        BoxedResult::Ok(())
+    };
+
+    let toplevel =
+        Toplevel::builder()
+            .install_global()
+            .build(move |s: SubsystemHandle| async move {
+                s.start(SubsystemBuilder::new("subsys", subsystem));
+            });
+    let handle = toplevel.handle();
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert_eq!(handle.shutdown_report().entries.len(), 2);
+    // Uninstalled once the Toplevel is gone.
+    assert!(tokio_graceful_shutdown::global().is_none());
+}
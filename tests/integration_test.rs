@@ -9,7 +9,7 @@ use tracing_test::traced_test;
 pub mod common;
 use common::Event;
 
-use std::error::Error;
+use std::{error::Error, future::Future, pin::Pin};
 
 /// Wrapper function to simplify lambdas
 type BoxedError = Box<dyn Error + Sync + Send>;
@@ -67,6 +67,52 @@ async fn use_subsystem_struct() {
     assert!(result.is_ok());
 }
 
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn use_boxed_future_as_subsystem() {
+    let (finished, set_finished) = Event::create();
+
+    let future: Pin<Box<dyn Future<Output = BoxedResult> + Send>> = Box::pin(async move {
+        sleep(Duration::from_millis(100)).await;
+        set_finished();
+        Ok(())
+    });
+
+    let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("subsys", future.into_subsystem()));
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    assert!(result.is_ok());
+    assert!(finished.get());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn use_function_without_handle_as_subsystem() {
+    async fn failing_task() -> BoxedResult {
+        sleep(Duration::from_millis(100)).await;
+        Err(anyhow!("failed").into())
+    }
+
+    let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new(
+            "subsys",
+            failing_task.into_subsystem(),
+        ));
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    assert!(matches!(
+        result,
+        Err(GracefulShutdownError::SubsystemsFailed(_))
+    ));
+}
+
 #[tokio::test]
 #[traced_test]
 async fn shutdown_timeout_causes_error() {
@@ -839,12 +885,16 @@ async fn subsystem_errors_get_propagated_to_user_when_timeout() {
         .await;
 
     if let Err(GracefulShutdownError::ShutdownTimeout(mut errors)) = result {
-        assert_eq!(2, errors.len());
+        assert_eq!(4, errors.len());
 
         errors.sort_by_key(|el| el.name().to_string());
 
         let mut iter = errors.iter();
 
+        let el = iter.next().unwrap();
+        assert!(matches!(el, SubsystemError::Aborted(_)));
+        assert_eq!("/subsys", el.name());
+
         let el = iter.next().unwrap();
         assert!(matches!(el, SubsystemError::Panicked(_)));
         assert_eq!("/subsys/nested1", el.name());
@@ -859,6 +909,10 @@ async fn subsystem_errors_get_propagated_to_user_when_timeout() {
         assert!(matches!(el, SubsystemError::Failed(_, _)));
         assert_eq!("/subsys/nested2", el.name());
 
+        let el = iter.next().unwrap();
+        assert!(matches!(el, SubsystemError::Aborted(_)));
+        assert_eq!("/subsys/nested3", el.name());
+
         assert!(iter.next().is_none());
     } else {
         panic!("Incorrect return value!");
@@ -920,3 +974,72 @@ async fn shutdown_through_signal() {
         },
     );
 }
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn unfinished_subsystems_get_aborted_and_reported_on_timeout() {
+    let (finished, set_finished) = Event::create();
+
+    let hanging = |_: SubsystemHandle| std::future::pending::<BoxedResult>();
+    let well_behaved = move |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        set_finished();
+        BoxedResult::Ok(())
+    };
+    let parent = move |subsys: SubsystemHandle| async move {
+        subsys.start(SubsystemBuilder::new("hanging", hanging));
+        subsys.start(SubsystemBuilder::new("well_behaved", well_behaved));
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("parent", parent));
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(200))
+    .await;
+
+    let Err(GracefulShutdownError::ShutdownTimeout(errors)) = result else {
+        panic!("Incorrect return value!");
+    };
+    assert!(finished.get());
+
+    let aborted = errors
+        .iter()
+        .map(|e| {
+            assert!(matches!(e, SubsystemError::Aborted(_)));
+            e.name()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(aborted, ["/parent/hanging", "/parent"]);
+    assert!(logs_contain("Aborting subsystem '/parent/hanging' ..."));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn on_shutdown_requested_timeout_returns_early() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let mut ticks = 0;
+        while subsys
+            .on_shutdown_requested_timeout(Duration::from_millis(100))
+            .await
+            .is_err()
+        {
+            ticks += 1;
+        }
+        assert_eq!(ticks, 2);
+        assert!(subsys.is_shutdown_requested());
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(250)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
@@ -48,6 +48,50 @@ async fn leak_subsystem_handle() {
     ));
 }
 
+#[tokio::test]
+#[traced_test]
+async fn leaked_subsystem_handle_gets_reported_right_away() {
+    let subsys_ext: Arc<Mutex<Option<SubsystemHandle>>> = Default::default();
+    let subsys_ext2 = Arc::clone(&subsys_ext);
+    let subsys_ext3 = Arc::clone(&subsys_ext);
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        *subsys_ext2.lock().unwrap() = Some(subsys);
+
+        BoxedResult::Ok(())
+    };
+
+    let reported_while_leaked = Arc::new(AtomicBool::new(false));
+    let reported_while_leaked2 = Arc::clone(&reported_while_leaked);
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+
+        // The leak itself initiates the shutdown.
+        s.on_shutdown_requested().await;
+        reported_while_leaked2.store(subsys_ext3.lock().unwrap().is_some(), Ordering::SeqCst);
+    });
+
+    let (result, ()) = tokio::join!(
+        toplevel.handle_shutdown_requests(Duration::from_millis(400)),
+        async {
+            sleep(Duration::from_millis(200)).await;
+            drop(subsys_ext.lock().unwrap().take());
+        }
+    );
+    assert!(reported_while_leaked.load(Ordering::SeqCst));
+
+    let errors = result.unwrap_err().into_subsystem_errors();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        &errors[0],
+        tokio_graceful_shutdown::errors::SubsystemError::Internal(
+            name,
+            tokio_graceful_shutdown::errors::InternalError::SubsystemHandleLeaked
+        ) if name.as_ref() == "/subsys"
+    ));
+}
+
 #[tokio::test]
 #[traced_test]
 async fn wait_for_children() {
@@ -329,3 +373,302 @@ async fn shutdown_does_not_propagate_to_detached_subsystem() {
         .await;
     assert!(result.is_ok());
 }
+
+#[tokio::test]
+#[traced_test]
+async fn subsystem_gets_spawned_on_given_runtime() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("dedicated-runtime")
+        .enable_all()
+        .build()
+        .unwrap();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        assert_eq!(std::thread::current().name(), Some("dedicated-runtime"));
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let runtime_handle = runtime.handle().clone();
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem).runtime(runtime_handle));
+
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    assert!(result.is_ok());
+
+    runtime.shutdown_background();
+}
+
+#[tokio::test]
+#[traced_test]
+async fn defer_shutdown_delays_shutdown_request_until_released() {
+    let (shutdown_received, set_shutdown_received) = Event::create();
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        let guard = subsys.defer_shutdown(Duration::from_millis(1000));
+
+        tokio::join!(
+            async {
+                subsys.on_shutdown_requested().await;
+                set_shutdown_received();
+            },
+            async {
+                sleep(Duration::from_millis(200)).await;
+                assert!(!subsys.is_shutdown_requested());
+                assert!(!shutdown_received.get());
+                drop(guard);
+                sleep(Duration::from_millis(20)).await;
+                assert!(subsys.is_shutdown_requested());
+                assert!(shutdown_received.get());
+            }
+        );
+
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn defer_shutdown_expires_after_max_defer_time() {
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        let _guard = subsys.defer_shutdown(Duration::from_millis(200));
+
+        tokio::time::timeout(Duration::from_millis(300), subsys.on_shutdown_requested())
+            .await
+            .unwrap();
+        assert!(subsys.is_shutdown_requested());
+
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        s.request_shutdown();
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn shutdown_statistics_track_waiters_and_requests() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys1", subsystem));
+        s.start(SubsystemBuilder::new("subsys2", subsystem));
+        sleep(Duration::from_millis(100)).await;
+
+        let statistics = s.shutdown_statistics();
+        assert!(!statistics.is_shutdown_requested);
+        assert!(statistics.shutdown_requested_at.is_none());
+        assert_eq!(statistics.request_count, 0);
+        assert_eq!(statistics.waiter_count, 2);
+
+        s.request_shutdown();
+        s.request_shutdown();
+
+        let statistics = s.shutdown_statistics();
+        assert!(statistics.is_shutdown_requested);
+        assert!(statistics.shutdown_requested_at.is_some());
+        assert_eq!(statistics.request_count, 2);
+
+        sleep(Duration::from_millis(20)).await;
+        assert_eq!(s.shutdown_statistics().waiter_count, 0);
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn on_shutdown_requested_does_not_miss_wakeups() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        // Create the future before the shutdown gets triggered,
+        // but only poll it afterwards.
+        let shutdown_requested = subsys.on_shutdown_requested();
+        subsys.request_shutdown();
+        tokio::time::timeout(Duration::from_millis(100), shutdown_requested)
+            .await
+            .unwrap();
+
+        // Waiting after the shutdown was triggered resolves immediately.
+        tokio::time::timeout(Duration::from_millis(100), subsys.on_shutdown_requested())
+            .await
+            .unwrap();
+
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn shutdown_progress_gets_reported() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Err("failed".into())
+    };
+
+    let slow_subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        sleep(Duration::from_millis(100)).await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        s.start(SubsystemBuilder::new("slow", slow_subsystem));
+        s.request_shutdown();
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+
+    let errors = result.unwrap_err().into_subsystem_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].name(), "/subsys");
+    assert!(logs_contain("Waiting for 1 subsystem(s) to finish ..."));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn finished_subsystems_still_get_reported() {
+    let acceptor = move |subsys: SubsystemHandle| async move {
+        for i in 0..1000 {
+            let connection = move |_: SubsystemHandle| async move {
+                if i == 999 {
+                    BoxedResult::Err("failed".into())
+                } else {
+                    BoxedResult::Ok(())
+                }
+            };
+            let conn = subsys.start(SubsystemBuilder::new(i.to_string(), connection));
+            let _ = conn.join().await;
+        }
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("acceptor", acceptor));
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+
+    let errors = result.unwrap_err().into_subsystem_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].name(), "/acceptor/999");
+}
+
+#[tokio::test]
+#[traced_test]
+async fn weak_subsystem_handle() {
+    let (weak_sender, weak_receiver) = tokio::sync::oneshot::channel();
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        weak_sender.send(subsys.downgrade()).ok().unwrap();
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        let nested = s.start(SubsystemBuilder::new("subsys", subsystem));
+
+        let weak = weak_receiver.await.unwrap();
+        let upgraded = weak.upgrade().unwrap();
+        upgraded.request_local_shutdown();
+
+        // The upgraded handle keeps the subsystem alive
+        assert!(
+            tokio::time::timeout(Duration::from_millis(100), nested.finished())
+                .await
+                .is_err()
+        );
+
+        drop(upgraded);
+        nested.join().await.unwrap();
+        assert!(weak.upgrade().is_none());
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn idle_until_shutdown_keeps_resource_alive() {
+    let (resource_dropped, set_resource_dropped) = Event::create();
+
+    struct Resource<F: FnOnce()>(Option<F>);
+    impl<F: FnOnce()> Drop for Resource<F> {
+        fn drop(&mut self) {
+            if let Some(on_drop) = self.0.take() {
+                on_drop();
+            }
+        }
+    }
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        subsys
+            .idle_until_shutdown(Resource(Some(set_resource_dropped)))
+            .await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+
+        sleep(Duration::from_millis(100)).await;
+        assert!(!resource_dropped.get());
+
+        s.request_shutdown();
+        resource_dropped.wait().await;
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    assert!(result.is_ok());
+}
@@ -0,0 +1,156 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{
+    ExitStatus, InFlightTick, IntervalSubsystem, IntoSubsystem, ShutdownReportEntry,
+    SubsystemBuilder, SubsystemHandle, Toplevel, ToplevelHandle,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+/// Runs ticks of 50ms every 100ms and requests a shutdown after the given delay.
+///
+/// Returns the number of started and finished ticks, the shutdown duration and the
+/// toplevel handle.
+async fn run_ticks(
+    in_flight_tick: InFlightTick,
+    shutdown_after: Duration,
+) -> (u32, u32, Duration, ToplevelHandle) {
+    let started = Arc::new(AtomicU32::new(0));
+    let finished = Arc::new(AtomicU32::new(0));
+
+    let ticker = IntervalSubsystem::new(Duration::from_millis(100), {
+        let started = Arc::clone(&started);
+        let finished = Arc::clone(&finished);
+        move || {
+            let started = Arc::clone(&started);
+            let finished = Arc::clone(&finished);
+            async move {
+                started.fetch_add(1, Ordering::SeqCst);
+                sleep(Duration::from_millis(50)).await;
+                finished.fetch_add(1, Ordering::SeqCst);
+                BoxedResult::Ok(())
+            }
+        }
+    })
+    .in_flight_tick(in_flight_tick);
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("ticker", ticker.into_subsystem()));
+        sleep(shutdown_after).await;
+        s.request_shutdown();
+    });
+    let handle = toplevel.handle();
+
+    let start = Instant::now();
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    (
+        started.load(Ordering::SeqCst),
+        finished.load(Ordering::SeqCst),
+        Instant::now() - start,
+        handle,
+    )
+}
+
+fn ticker_exit_status(handle: &ToplevelHandle) -> Option<ExitStatus> {
+    match &handle.shutdown_report().entries[0] {
+        ShutdownReportEntry::Single { result, .. } => result.exit_status.clone(),
+        ShutdownReportEntry::Aggregated(_) => panic!("Expected a single entry"),
+    }
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn finishes_in_flight_tick() {
+    let (started, finished, duration, handle) =
+        run_ticks(InFlightTick::Finish, Duration::from_millis(220)).await;
+
+    // Ticks at 0ms, 100ms and 200ms; the last one finishes at 250ms.
+    assert_eq!(started, 3);
+    assert_eq!(finished, 3);
+    assert_eq!(duration, Duration::from_millis(250));
+    assert_eq!(
+        ticker_exit_status(&handle),
+        Some(ExitStatus::Drained { dropped: 0 })
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn cancels_in_flight_tick() {
+    let (started, finished, duration, handle) =
+        run_ticks(InFlightTick::Cancel, Duration::from_millis(220)).await;
+
+    assert_eq!(started, 3);
+    assert_eq!(finished, 2);
+    assert_eq!(duration, Duration::from_millis(220));
+    assert_eq!(
+        ticker_exit_status(&handle),
+        Some(ExitStatus::Drained { dropped: 1 })
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn stops_between_ticks() {
+    for in_flight_tick in [InFlightTick::Finish, InFlightTick::Cancel] {
+        let (started, finished, duration, handle) =
+            run_ticks(in_flight_tick, Duration::from_millis(270)).await;
+
+        assert_eq!(started, 3);
+        assert_eq!(finished, 3);
+        assert_eq!(duration, Duration::from_millis(270));
+        assert_eq!(ticker_exit_status(&handle), Some(ExitStatus::Completed));
+    }
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn tick_error_stops_subsystem() {
+    let ticks = Arc::new(AtomicU32::new(0));
+
+    let ticker = IntervalSubsystem::new(Duration::from_millis(100), {
+        let ticks = Arc::clone(&ticks);
+        move || {
+            let tick = ticks.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if tick == 1 {
+                    BoxedResult::Err("tick failed".into())
+                } else {
+                    BoxedResult::Ok(())
+                }
+            }
+        }
+    });
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("ticker", ticker.into_subsystem()));
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(ticks.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+#[should_panic(expected = "The period of an interval must not be zero")]
+fn rejects_zero_period() {
+    IntervalSubsystem::new(Duration::ZERO, || async { BoxedResult::Ok(()) });
+}
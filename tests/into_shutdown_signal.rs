@@ -0,0 +1,105 @@
+use tokio::{
+    sync::{oneshot, watch},
+    time::{sleep, Duration, Instant},
+};
+use tokio_graceful_shutdown::{
+    IntoShutdownSignal, ShutdownToken, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tokio_util::sync::CancellationToken;
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+/// Stands in for library code that accepts any shutdown signal.
+async fn library_worker(shutdown: impl IntoShutdownSignal) -> Instant {
+    shutdown.into_shutdown_signal().await;
+    Instant::now()
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn accepts_tokens() {
+    let start = Instant::now();
+
+    let shutdown_token = ShutdownToken::new();
+    let worker = tokio::spawn(library_worker(shutdown_token.clone()));
+    sleep(Duration::from_millis(100)).await;
+    shutdown_token.request_shutdown();
+    assert_eq!(worker.await.unwrap() - start, Duration::from_millis(100));
+
+    let cancellation_token = CancellationToken::new();
+    let worker = tokio::spawn(library_worker(cancellation_token.clone()));
+    sleep(Duration::from_millis(100)).await;
+    cancellation_token.cancel();
+    assert_eq!(worker.await.unwrap() - start, Duration::from_millis(200));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn accepts_watch_receiver() {
+    let start = Instant::now();
+
+    let (sender, receiver) = watch::channel(false);
+    let worker = tokio::spawn(library_worker(receiver));
+    sleep(Duration::from_millis(100)).await;
+    sender.send(true).unwrap();
+    assert_eq!(worker.await.unwrap() - start, Duration::from_millis(100));
+
+    // A dropped sender does not signal a shutdown.
+    let (sender, receiver) = watch::channel(false);
+    let worker = tokio::spawn(library_worker(receiver));
+    drop(sender);
+    sleep(Duration::from_millis(100)).await;
+    assert!(!worker.is_finished());
+    worker.abort();
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn accepts_boxed_future() {
+    let start = Instant::now();
+
+    let worker = library_worker(Box::pin(sleep(Duration::from_millis(100))));
+    assert_eq!(worker.await - start, Duration::from_millis(100));
+
+    let (sender, receiver) = oneshot::channel::<()>();
+    let worker = tokio::spawn(library_worker(Box::pin(async {
+        receiver.await.ok();
+    })));
+    sleep(Duration::from_millis(100)).await;
+    sender.send(()).unwrap();
+    assert_eq!(worker.await.unwrap() - start, Duration::from_millis(200));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn accepts_subsystem_handle() {
+    let (stopped_sender, stopped_receiver) = oneshot::channel();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        stopped_sender.send(library_worker(&subsys).await).unwrap();
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    });
+
+    let start = Instant::now();
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+    assert_eq!(
+        stopped_receiver.await.unwrap() - start,
+        Duration::from_millis(100)
+    );
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "io")]
+
+use tokio::{
+    io::{duplex, AsyncReadExt, AsyncWriteExt},
+    time::{sleep, Duration},
+};
+use tokio_graceful_shutdown::{
+    io::copy_bidirectional, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn proxies_until_shutdown() {
+    let (mut client, mut proxy_downstream) = duplex(1024);
+    let (mut proxy_upstream, mut upstream) = duplex(1024);
+
+    let (counts_sender, counts_receiver) = tokio::sync::oneshot::channel();
+
+    let proxy = move |subsys: SubsystemHandle| async move {
+        let counts = copy_bidirectional(
+            &subsys,
+            &mut proxy_downstream,
+            &mut proxy_upstream,
+            Duration::from_millis(100),
+        )
+        .await?;
+        counts_sender.send(counts).unwrap();
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("proxy", proxy));
+
+        client.write_all(b"ping").await.unwrap();
+        let mut buffer = [0; 4];
+        upstream.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"ping");
+
+        upstream.write_all(b"pong!").await.unwrap();
+        let mut buffer = [0; 5];
+        client.read_exact(&mut buffer).await.unwrap();
+        assert_eq!(&buffer, b"pong!");
+
+        s.request_shutdown();
+
+        // Both write halves get shut down
+        assert_eq!(client.read(&mut buffer).await.unwrap(), 0);
+        assert_eq!(upstream.read(&mut buffer).await.unwrap(), 0);
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(counts_receiver.await.unwrap(), (4, 5));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn hung_peer_times_out() {
+    let (mut client, mut proxy_downstream) = duplex(1024);
+    let (mut proxy_upstream, _upstream) = duplex(4);
+
+    let proxy = move |subsys: SubsystemHandle| async move {
+        let result = copy_bidirectional(
+            &subsys,
+            &mut proxy_downstream,
+            &mut proxy_upstream,
+            Duration::from_millis(100),
+        )
+        .await;
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::TimedOut);
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("proxy", proxy));
+
+        client.write_all(b"more than four bytes").await.unwrap();
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert!(logs_contain(
+        "Peer did not finish the connection within 100ms; aborting copy."
+    ));
+}
@@ -0,0 +1,98 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    errors::GracefulShutdownError, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+type Journal = Arc<Mutex<Vec<&'static str>>>;
+
+fn record(journal: &Journal, entry: &'static str) -> impl FnOnce() + Send + 'static {
+    let journal = Arc::clone(journal);
+    move || journal.lock().unwrap().push(entry)
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn last_words_run_after_clean_shutdown() {
+    let journal = Journal::default();
+
+    let subsystem = {
+        let journal = Arc::clone(&journal);
+        move |subsys: SubsystemHandle| async move {
+            subsys.on_shutdown_requested().await;
+            journal.lock().unwrap().push("subsystem");
+            BoxedResult::Ok(())
+        }
+    };
+
+    let toplevel = Toplevel::builder()
+        .last_words("first", record(&journal, "first"))
+        .last_words("second", record(&journal, "second"))
+        .build(move |s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("subsys", subsystem));
+            sleep(Duration::from_millis(100)).await;
+            s.request_shutdown();
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert_eq!(*journal.lock().unwrap(), ["subsystem", "first", "second"]);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn last_words_run_after_shutdown_timeout() {
+    let journal = Journal::default();
+
+    let toplevel = Toplevel::builder()
+        .last_words("crash marker", record(&journal, "crash marker"))
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("stuck", |_| async {
+                sleep(Duration::from_secs(10)).await;
+                BoxedResult::Ok(())
+            }));
+            s.request_shutdown();
+        });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(GracefulShutdownError::ShutdownTimeout(_))
+    ));
+    assert_eq!(*journal.lock().unwrap(), ["crash marker"]);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn last_words_run_when_toplevel_gets_dropped() {
+    let journal = Journal::default();
+
+    let toplevel = Toplevel::<BoxedError>::builder()
+        .last_words("crash marker", record(&journal, "crash marker"))
+        .build(|s: SubsystemHandle| async move {
+            s.on_shutdown_requested().await;
+        });
+
+    let shutdown = toplevel.handle_shutdown_requests(Duration::from_millis(400));
+    assert!(tokio::time::timeout(Duration::from_millis(100), shutdown)
+        .await
+        .is_err());
+
+    assert_eq!(*journal.lock().unwrap(), ["crash marker"]);
+}
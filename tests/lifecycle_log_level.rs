@@ -0,0 +1,81 @@
+use tokio::time::Duration;
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing::level_filters::LevelFilter;
+use tracing_test::traced_test;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn lifecycle_logs_use_debug_level_by_default() {
+    let subsystem = |_: SubsystemHandle| async move { BoxedResult::Ok(()) };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("worker", subsystem));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    logs_assert(|lines: &[&str]| {
+        for expected in [
+            "Subsystem '/worker' started.",
+            "Subsystem '/worker' finished.",
+        ] {
+            let line = lines
+                .iter()
+                .find(|line| line.contains(expected))
+                .ok_or_else(|| format!("Missing log line: {expected}"))?;
+            if !line.contains("DEBUG") {
+                return Err(format!("Log line with wrong level: {line}"));
+            }
+        }
+        Ok(())
+    });
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn lifecycle_log_level_gets_inherited_by_children() {
+    let child = |_: SubsystemHandle| async move { BoxedResult::Ok(()) };
+    let noisy = move |subsys: SubsystemHandle| async move {
+        subsys.start(SubsystemBuilder::new("child", child));
+        BoxedResult::Ok(())
+    };
+    let flaky = |_: SubsystemHandle| async move {
+        tracing::info!("Connecting ...");
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("noisy", noisy).lifecycle_log_level(LevelFilter::OFF));
+        s.start(SubsystemBuilder::new("flaky", flaky).lifecycle_log_level(tracing::Level::INFO));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert!(!logs_contain("Subsystem '/noisy' started."));
+    assert!(!logs_contain("Subsystem '/noisy/child' started."));
+    assert!(!logs_contain("Subsystem '/noisy/child' finished."));
+    logs_assert(|lines: &[&str]| {
+        for expected in [
+            "Subsystem '/flaky' started.",
+            "Connecting ...",
+            "Subsystem '/flaky' finished.",
+        ] {
+            let line = lines
+                .iter()
+                .find(|line| line.contains(expected))
+                .ok_or_else(|| format!("Missing log line: {expected}"))?;
+            if !line.contains("INFO") || !line.contains("subsystem{name=/flaky}") {
+                return Err(format!("Log line with wrong level or span: {line}"));
+            }
+        }
+        Ok(())
+    });
+}
@@ -0,0 +1,149 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{
+    errors::{GracefulShutdownError, SubsystemError},
+    LightweightChildCounts, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tokio_util::sync::CancellationToken;
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+use crate::common::Event;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn shutdown_waits_for_lightweight_children() {
+    let finished = Arc::new(AtomicUsize::new(0));
+
+    let subsystem = {
+        let finished = Arc::clone(&finished);
+        move |subsys: SubsystemHandle| async move {
+            for _ in 0..100 {
+                let finished = Arc::clone(&finished);
+                subsys.start_lightweight(|token: CancellationToken| async move {
+                    token.cancelled().await;
+                    sleep(Duration::from_millis(200)).await;
+                    finished.fetch_add(1, Ordering::Relaxed);
+                    BoxedResult::Ok(())
+                });
+            }
+            assert_eq!(subsys.lightweight_child_counts().running, 100);
+
+            subsys.on_shutdown_requested().await;
+            BoxedResult::Ok(())
+        }
+    };
+
+    let start = Instant::now();
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("acceptor", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(finished.load(Ordering::Relaxed), 100);
+    assert_eq!(start.elapsed(), Duration::from_millis(300));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn lightweight_children_are_counted() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.start_lightweight(|_| async { BoxedResult::Ok(()) });
+        subsys.start_lightweight(|_| async { BoxedResult::Err("failed".into()) });
+        subsys.start_lightweight(|_| async {
+            panic!("lightweight child panicked");
+            #[allow(unreachable_code)]
+            BoxedResult::Ok(())
+        });
+        subsys.start_lightweight(|token: CancellationToken| async move {
+            token.cancelled().await;
+            BoxedResult::Ok(())
+        });
+
+        subsys.wait_for_children().await;
+        assert_eq!(
+            subsys.lightweight_child_counts(),
+            LightweightChildCounts {
+                running: 0,
+                started: 4,
+                failed: 1,
+                panicked: 1,
+                aborted: 0,
+            }
+        );
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("acceptor", subsystem));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    // The first failure shuts down the tree, which releases the last child.
+    let Err(GracefulShutdownError::SubsystemsFailed(errors)) = result else {
+        panic!("Incorrect return value!");
+    };
+    assert!(errors.iter().all(|e| e.name() == "/acceptor"));
+    assert!(errors
+        .iter()
+        .any(|e| matches!(e, SubsystemError::Failed(_, _))));
+    assert!(errors
+        .iter()
+        .any(|e| matches!(e, SubsystemError::Panicked(_))));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn lightweight_children_get_aborted_with_their_parent() {
+    let (child_dropped, set_child_dropped) = Event::create();
+
+    struct DropNotifier<F: FnOnce()>(Option<F>);
+    impl<F: FnOnce()> Drop for DropNotifier<F> {
+        fn drop(&mut self) {
+            if let Some(f) = self.0.take() {
+                f();
+            }
+        }
+    }
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        subsys.start_lightweight(move |_| async move {
+            let _notifier = DropNotifier(Some(set_child_dropped));
+            std::future::pending::<()>().await;
+            BoxedResult::Ok(())
+        });
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("acceptor", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(matches!(
+        result,
+        Err(GracefulShutdownError::ShutdownTimeout(_))
+    ));
+    sleep(Duration::from_millis(1)).await;
+    assert!(child_dropped.get());
+}
@@ -0,0 +1,69 @@
+//! Run with `RUSTFLAGS="--cfg madsim" cargo test --features madsim --test madsim`.
+#![cfg(all(madsim, feature = "madsim"))]
+
+use std::time::Duration;
+
+use madsim::runtime::Handle;
+use tokio_graceful_shutdown::{
+    errors::GracefulShutdownError, ShutdownReason, ShutdownSignal, SubsystemBuilder,
+    SubsystemHandle, Toplevel,
+};
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[madsim::test]
+async fn simulated_ctrl_c_triggers_shutdown() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        assert_eq!(
+            subsys.shutdown_reason(),
+            Some(ShutdownReason::Signal(ShutdownSignal::Interrupt))
+        );
+        BoxedResult::Ok(())
+    };
+
+    let handle = Handle::current();
+    let node = handle.create_node().name("service").build();
+    let shutdown = node.spawn(async move {
+        Toplevel::new(move |s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("subsys", subsystem));
+        })
+        .catch_signals()
+        .handle_shutdown_requests(Duration::from_secs(1))
+        .await
+    });
+
+    madsim::time::sleep(Duration::from_secs(10)).await;
+    assert!(!shutdown.is_finished());
+
+    handle.send_ctrl_c(node.id());
+    let result = shutdown.await.unwrap();
+    assert!(result.is_ok());
+}
+
+#[madsim::test]
+async fn shutdown_timeout_uses_simulated_time() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        madsim::time::sleep(Duration::from_secs(60)).await;
+        BoxedResult::Ok(())
+    };
+
+    let start = madsim::time::Instant::now();
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_secs(30))
+    .await;
+
+    assert!(matches!(
+        result,
+        Err(GracefulShutdownError::ShutdownTimeout(_))
+    ));
+    assert_eq!(start.elapsed().as_secs(), 30);
+}
@@ -0,0 +1,156 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    errors::GracefulShutdownError, SubsystemBuilder, SubsystemFuture, SubsystemHandle,
+    SubsystemMiddleware, SubsystemNode, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+type Events = Arc<Mutex<Vec<String>>>;
+
+/// Records when subsystems start and finish.
+struct Recording {
+    label: &'static str,
+    events: Events,
+}
+
+impl SubsystemMiddleware for Recording {
+    fn wrap(&self, node: &SubsystemNode, subsystem: SubsystemFuture) -> SubsystemFuture {
+        let label = self.label;
+        let name = node.name().to_string();
+        let events = Arc::clone(&self.events);
+        Box::pin(async move {
+            events.lock().unwrap().push(format!("{label} enter {name}"));
+            let result = subsystem.await;
+            events
+                .lock()
+                .unwrap()
+                .push(format!("{label} exit {name} ok={}", result.is_ok()));
+            result
+        })
+    }
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn wraps_all_subsystems_in_order() {
+    let events = Events::default();
+
+    let toplevel = Toplevel::with_middleware(Recording {
+        label: "outer",
+        events: Arc::clone(&events),
+    })
+    .middleware(Recording {
+        label: "inner",
+        events: Arc::clone(&events),
+    })
+    .build({
+        let events = Arc::clone(&events);
+        move |s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("parent", move |s| async move {
+                s.start(SubsystemBuilder::new("child", move |_| async move {
+                    events.lock().unwrap().push("child runs".into());
+                    BoxedResult::Ok(())
+                }))
+                .join()
+                .await
+                .unwrap();
+                BoxedResult::Ok(())
+            }));
+        }
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(100))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "outer enter /parent",
+            "inner enter /parent",
+            "outer enter /parent/child",
+            "inner enter /parent/child",
+            "child runs",
+            "inner exit /parent/child ok=true",
+            "outer exit /parent/child ok=true",
+            "inner exit /parent ok=true",
+            "outer exit /parent ok=true",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn sees_errors_of_subsystems() {
+    let events = Events::default();
+
+    let toplevel = Toplevel::with_middleware(Recording {
+        label: "recording",
+        events: Arc::clone(&events),
+    })
+    .build(|s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("failing", |_| async {
+            BoxedResult::Err("failed".into())
+        }));
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(100))
+        .await;
+    assert!(matches!(
+        result,
+        Err(GracefulShutdownError::SubsystemsFailed(_))
+    ));
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "recording enter /failing",
+            "recording exit /failing ok=false",
+        ]
+    );
+}
+
+/// Turns errors of subsystems into successes.
+struct IgnoreErrors;
+
+impl SubsystemMiddleware for IgnoreErrors {
+    fn wrap(&self, node: &SubsystemNode, subsystem: SubsystemFuture) -> SubsystemFuture {
+        let name = node.name().to_string();
+        Box::pin(async move {
+            if let Err(e) = subsystem.await {
+                tracing::warn!("Ignoring error of '{name}': {e}");
+            }
+            Ok(())
+        })
+    }
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn replaces_result_of_subsystems() {
+    let toplevel = Toplevel::with_middleware(IgnoreErrors).build(|s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("failing", |_| async {
+            sleep(Duration::from_millis(10)).await;
+            BoxedResult::Err("failed".into())
+        }));
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(100))
+        .await
+        .unwrap();
+
+    assert!(logs_contain("Ignoring error of '/failing': failed"));
+}
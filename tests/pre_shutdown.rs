@@ -0,0 +1,111 @@
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+use crate::common::Event;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn children_get_signaled_after_pre_shutdown_hook() {
+    let (hook_finished, set_hook_finished) = Event::create();
+    let (worker_signaled, set_worker_signaled) = Event::create();
+
+    let worker = move |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        set_worker_signaled();
+        BoxedResult::Ok(())
+    };
+
+    let node = move |subsys: SubsystemHandle| async move {
+        subsys.start(SubsystemBuilder::new("worker", worker));
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let hook = move || async move {
+        sleep(Duration::from_millis(200)).await;
+        set_hook_finished();
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("node", node).pre_shutdown(Duration::from_millis(500), hook));
+
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+
+        sleep(Duration::from_millis(100)).await;
+        assert!(!hook_finished.get());
+        assert!(!worker_signaled.get());
+
+        worker_signaled.wait().await;
+        assert!(hook_finished.get());
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(1000))
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn pre_shutdown_hook_times_out() {
+    let worker = move |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let node = move |subsys: SubsystemHandle| async move {
+        subsys.start(SubsystemBuilder::new("worker", worker));
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(
+            SubsystemBuilder::new("node", node)
+                .pre_shutdown(Duration::from_millis(200), std::future::pending),
+        );
+        s.request_shutdown();
+    });
+
+    let start = Instant::now();
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(1000))
+        .await;
+    assert!(result.is_ok());
+    assert!(start.elapsed() >= Duration::from_millis(200));
+    assert!(start.elapsed() < Duration::from_millis(300));
+    assert!(logs_contain(
+        "Pre-shutdown hook of subsystem '/node' did not finish within 200ms; cancelling it."
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn pre_shutdown_hook_does_not_run_without_shutdown() {
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(
+            SubsystemBuilder::new("node", |_| async { BoxedResult::Ok(()) }).pre_shutdown(
+                Duration::from_millis(200),
+                || async {
+                    panic!("Hook should not run");
+                },
+            ),
+        );
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(1000))
+        .await;
+    assert!(result.is_ok());
+    assert!(!logs_contain("Running pre-shutdown hook"));
+}
@@ -0,0 +1,74 @@
+#![cfg(unix)]
+
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{
+    errors::GracefulShutdownError, Profile, ShutdownReason, ShutdownSignal, SubsystemBuilder,
+    SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test]
+#[traced_test]
+async fn cli_profile_catches_hangup() {
+    use nix::sys::signal::{self, Signal};
+    use nix::unistd::Pid;
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        assert_eq!(
+            subsys.shutdown_reason(),
+            Some(ShutdownReason::Signal(ShutdownSignal::Hangup))
+        );
+        BoxedResult::Ok(())
+    };
+
+    let toplevel =
+        Toplevel::with_profile(Profile::Cli).build(move |s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("subsys", subsystem));
+        });
+
+    tokio::join!(
+        async {
+            sleep(Duration::from_millis(100)).await;
+            signal::kill(Pid::this(), Signal::SIGHUP).unwrap();
+        },
+        async {
+            let result = toplevel.run().await;
+            assert!(result.is_ok());
+        },
+    );
+
+    assert!(logs_contain("Received SIGHUP."));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn settings_override_profile() {
+    let toplevel = Toplevel::builder()
+        .profile(Profile::Systemd)
+        .shutdown_timeout(Duration::from_millis(200))
+        .build(move |s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("stuck", |_| async {
+                sleep(Duration::from_secs(1000)).await;
+                BoxedResult::Ok(())
+            }));
+            s.request_shutdown();
+        });
+
+    let start = Instant::now();
+    let result = toplevel.run().await;
+
+    assert!(matches!(
+        result,
+        Err(GracefulShutdownError::ShutdownTimeout(_))
+    ));
+    assert_eq!(start.elapsed(), Duration::from_millis(200));
+}
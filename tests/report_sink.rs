@@ -0,0 +1,177 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    FileReportSink, ShutdownReason, ShutdownRecord, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+type Records = Arc<Mutex<Vec<ShutdownRecord>>>;
+
+fn collect_into(records: &Records) -> impl Fn(&ShutdownRecord) + Send + Sync + 'static {
+    let records = Arc::clone(records);
+    move |record: &ShutdownRecord| records.lock().unwrap().push(record.clone())
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn receives_successful_shutdown() {
+    let records = Records::default();
+
+    let toplevel = Toplevel::builder()
+        .report_sink(collect_into(&records))
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new(
+                "subsys",
+                |s: SubsystemHandle| async move {
+                    s.on_shutdown_requested().await;
+                    sleep(Duration::from_millis(100)).await;
+                    BoxedResult::Ok(())
+                },
+            ));
+            sleep(Duration::from_millis(200)).await;
+            s.request_shutdown();
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    let records = records.lock().unwrap();
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert!(record.succeeded);
+    assert!(!record.timed_out);
+    assert_eq!(record.reason, ShutdownReason::Requested);
+    assert_eq!(record.shutdown_duration, Duration::from_millis(100));
+    assert_eq!(record.report.to_string(), "/subsys: ok");
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn receives_failed_and_timed_out_shutdowns() {
+    let records = Records::default();
+
+    let toplevel = Toplevel::builder()
+        .report_sink(collect_into(&records))
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("failing", |_| async move {
+                sleep(Duration::from_millis(100)).await;
+                BoxedResult::Err("Connection lost".into())
+            }));
+        });
+    assert!(toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .is_err());
+
+    let toplevel = Toplevel::builder()
+        .report_sink(collect_into(&records))
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("stuck", |_| async move {
+                sleep(Duration::from_millis(1000)).await;
+                BoxedResult::Ok(())
+            }));
+            s.request_shutdown();
+        });
+    assert!(toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .is_err());
+
+    let records = records.lock().unwrap();
+    assert_eq!(records.len(), 2);
+
+    assert!(!records[0].succeeded);
+    assert!(!records[0].timed_out);
+    assert_eq!(
+        records[0].reason,
+        ShutdownReason::SubsystemFailed(Arc::from("/failing"))
+    );
+    assert_eq!(
+        records[0].report.to_string(),
+        "/failing: failed (Connection lost)"
+    );
+
+    assert!(!records[1].succeeded);
+    assert!(records[1].timed_out);
+    assert_eq!(records[1].shutdown_duration, Duration::from_millis(400));
+    assert_eq!(
+        records[1].report.to_string(),
+        "/stuck: timed out (shutdown exceeded its timeout of 400ms)"
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn panicking_sink_does_not_stop_others() {
+    let records = Records::default();
+
+    let toplevel = Toplevel::builder()
+        .report_sink(|_: &ShutdownRecord| panic!("Sink failed"))
+        .report_sink(collect_into(&records))
+        .build(|s: SubsystemHandle| async move {
+            s.request_shutdown();
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert!(logs_contain("A report sink panicked."));
+    assert_eq!(records.lock().unwrap().len(), 1);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn file_sink_appends_records() {
+    let path = std::env::temp_dir().join(format!("tgs-report-sink-{}.log", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    for _ in 0..2 {
+        let toplevel = Toplevel::builder()
+            .report_sink(FileReportSink::new(&path))
+            .build(|s: SubsystemHandle| async move {
+                s.start(SubsystemBuilder::new(
+                    "subsys",
+                    |s: SubsystemHandle| async move {
+                        s.on_shutdown_requested().await;
+                        BoxedResult::Ok(())
+                    },
+                ));
+                s.request_shutdown();
+            });
+        toplevel
+            .handle_shutdown_requests(Duration::from_millis(400))
+            .await
+            .unwrap();
+    }
+
+    let contents = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let records = contents
+        .split("\n\n")
+        .filter(|r| !r.is_empty())
+        .collect::<Vec<_>>();
+    assert_eq!(records.len(), 2);
+    for record in records {
+        let lines = record.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with("shutdown finished at "));
+        assert!(lines[0].ends_with(" (unix time): succeeded"));
+        assert_eq!(
+            lines[1..],
+            ["reason: Requested", "duration: 0.00ns", "  /subsys: ok"]
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    AsyncClose, IntoSubsystem, ResourceSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::{
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[derive(Clone, Default)]
+struct Resource {
+    events: Arc<Mutex<Vec<&'static str>>>,
+    close_duration: Duration,
+}
+
+impl Resource {
+    fn record(&self, event: &'static str) {
+        self.events.lock().unwrap().push(event);
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncClose for Resource {
+    async fn close(&self) {
+        sleep(self.close_duration).await;
+        self.record("closed");
+    }
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn resource_gets_closed_after_users() {
+    let resource = Resource::default();
+    let events = Arc::clone(&resource.events);
+
+    let subsystem = ResourceSubsystem::new(resource, |s: &SubsystemHandle, resource: &Resource| {
+        let resource = resource.clone();
+        s.start(SubsystemBuilder::new(
+            "user",
+            move |subsys: SubsystemHandle| async move {
+                subsys.on_shutdown_requested().await;
+                sleep(Duration::from_millis(200)).await;
+                resource.record("user finished");
+                BoxedResult::Ok(())
+            },
+        ));
+    });
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new(
+            "resource",
+            subsystem.into_subsystem(),
+        ));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(*events.lock().unwrap(), vec!["user finished", "closed"]);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn closing_times_out() {
+    let resource = Resource {
+        close_duration: Duration::from_secs(10),
+        ..Default::default()
+    };
+    let events = Arc::clone(&resource.events);
+
+    let subsystem = ResourceSubsystem::new(resource, |_: &SubsystemHandle, _: &Resource| {})
+        .close_timeout(Duration::from_millis(200));
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new(
+            "resource",
+            subsystem.into_subsystem(),
+        ));
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert!(events.lock().unwrap().is_empty());
+    assert!(logs_contain("Resource did not close within 200ms."));
+}
+
+#[cfg(feature = "deadpool")]
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn deadpool_pool_gets_closed() {
+    use deadpool::managed::{Manager, Metrics, Pool, RecycleResult};
+
+    struct Connections;
+
+    impl Manager for Connections {
+        type Type = ();
+        type Error = std::convert::Infallible;
+
+        async fn create(&self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        async fn recycle(&self, _: &mut (), _: &Metrics) -> RecycleResult<Self::Error> {
+            Ok(())
+        }
+    }
+
+    let pool: Pool<Connections> = Pool::builder(Connections).build().unwrap();
+
+    let subsystem = ResourceSubsystem::new(
+        pool.clone(),
+        |_: &SubsystemHandle, _: &Pool<Connections>| {},
+    );
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("pool", subsystem.into_subsystem()));
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert!(pool.is_closed());
+}
@@ -0,0 +1,121 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{
+    errors::{GracefulShutdownError, SubsystemError},
+    IntoSubsystem, RetryingSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+/// Creates a factory whose attempts fail until the given number of attempts was made.
+fn failing_attempts(
+    failures: u32,
+    attempts: Arc<AtomicU32>,
+) -> impl FnMut(&SubsystemHandle) -> std::future::Ready<BoxedResult> {
+    move |_: &SubsystemHandle| {
+        let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        std::future::ready(if attempt <= failures {
+            Err(format!("Attempt {attempt} failed").into())
+        } else {
+            Ok(())
+        })
+    }
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn retries_with_exponential_backoff() {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let retrying = RetryingSubsystem::new(failing_attempts(3, Arc::clone(&attempts)))
+        .initial_backoff(Duration::from_millis(100))
+        .max_backoff(Duration::from_millis(300));
+
+    let start = Instant::now();
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("retrying", retrying.into_subsystem()));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(attempts.load(Ordering::SeqCst), 4);
+    // 100ms + 200ms + 300ms
+    assert_eq!(start.elapsed(), Duration::from_millis(600));
+    assert!(logs_contain("retrying in 100ms (1/5): Attempt 1 failed"));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn gives_up_after_budget() {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let retrying =
+        RetryingSubsystem::new(failing_attempts(u32::MAX, Arc::clone(&attempts))).max_retries(2);
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("retrying", retrying.into_subsystem()));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    let Err(GracefulShutdownError::SubsystemsFailed(errors)) = result else {
+        panic!("Expected the subsystem to fail, got {result:?}");
+    };
+    assert!(matches!(
+        errors.as_ref(),
+        [SubsystemError::Failed(name, e)] if name.as_ref() == "/retrying" && e.to_string() == "Attempt 3 failed"
+    ));
+    assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    assert!(logs_contain("Giving up after 2 retries."));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn does_not_retry_excluded_errors() {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let retrying = RetryingSubsystem::new(failing_attempts(u32::MAX, Arc::clone(&attempts)))
+        .retry_if(|e: &BoxedError| e.to_string().contains("timeout"));
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("retrying", retrying.into_subsystem()));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(matches!(
+        result,
+        Err(GracefulShutdownError::SubsystemsFailed(_))
+    ));
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn shutdown_interrupts_backoff() {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let retrying = RetryingSubsystem::new(failing_attempts(u32::MAX, Arc::clone(&attempts)))
+        .initial_backoff(Duration::from_secs(10));
+
+    let start = Instant::now();
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("retrying", retrying.into_subsystem()));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    assert_eq!(start.elapsed(), Duration::from_millis(100));
+}
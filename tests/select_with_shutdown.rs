@@ -0,0 +1,90 @@
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{select_with_shutdown, SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+use crate::common::Event;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn shutdown_branch_gets_taken_on_shutdown() {
+    let (shutdown_handled, set_shutdown_handled) = Event::create();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        select_with_shutdown! { subsys,
+            shutdown => set_shutdown_handled(),
+            _ = sleep(Duration::from_secs(10)) => panic!("Sleep should have been cancelled"),
+        }
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert!(shutdown_handled.get());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn other_branches_get_taken_while_running() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let mut ticks = 0;
+        let value = loop {
+            select_with_shutdown! { subsys,
+                shutdown => {
+                    break ticks;
+                }
+                _ = sleep(Duration::from_millis(100)) => {
+                    ticks += 1;
+                }
+            }
+        };
+        assert_eq!(value, 3);
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(350)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn shutdown_branch_is_preferred() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.request_shutdown();
+        let result = select_with_shutdown! { subsys,
+            shutdown => "shutdown",
+            _ = std::future::ready(()) => "ready",
+        };
+        assert_eq!(result, "shutdown");
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
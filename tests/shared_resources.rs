@@ -0,0 +1,121 @@
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{AsyncClose, SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::{
+    error::Error,
+    sync::{Arc, Mutex},
+};
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+/// Records the time at which it got closed.
+#[derive(Clone, Default)]
+struct Resource(Arc<Mutex<Option<Instant>>>);
+
+impl Resource {
+    fn closed_at(&self) -> Option<Instant> {
+        *self.0.lock().unwrap()
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncClose for Resource {
+    async fn close(&self) {
+        *self.0.lock().unwrap() = Some(Instant::now());
+    }
+}
+
+async fn slow_shutdown(subsys: SubsystemHandle, duration: Duration) -> BoxedResult {
+    subsys.on_shutdown_requested().await;
+    sleep(duration).await;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn resource_gets_closed_after_its_users() {
+    let database = Resource::default();
+    let cache = Resource::default();
+
+    let start = Instant::now();
+    let result = Toplevel::builder()
+        .shared_resource("database", database.clone(), ["/api", "/jobs/worker"])
+        .shared_resource("cache", cache.clone(), ["/api"])
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("api", move |s| {
+                slow_shutdown(s, Duration::from_millis(100))
+            }));
+            s.start(SubsystemBuilder::new(
+                "jobs",
+                |s: SubsystemHandle| async move {
+                    s.start(SubsystemBuilder::new("worker", move |s| {
+                        slow_shutdown(s, Duration::from_millis(200))
+                    }));
+                    BoxedResult::Ok(())
+                },
+            ));
+            s.start(SubsystemBuilder::new("unrelated", move |s| {
+                slow_shutdown(s, Duration::from_millis(400))
+            }));
+            s.request_shutdown();
+        })
+        .handle_shutdown_requests(Duration::from_millis(1000))
+        .await;
+
+    assert!(result.is_ok());
+    assert_eq!(start.elapsed(), Duration::from_millis(400));
+    assert_eq!(
+        cache.closed_at().unwrap() - start,
+        Duration::from_millis(100)
+    );
+    assert_eq!(
+        database.closed_at().unwrap() - start,
+        Duration::from_millis(200)
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn resource_gets_closed_when_all_subsystems_finished() {
+    let database = Resource::default();
+
+    let result = Toplevel::builder()
+        .shared_resource("database", database.clone(), ["/api"])
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("api", |_| async {
+                sleep(Duration::from_millis(100)).await;
+                BoxedResult::Ok(())
+            }));
+        })
+        .handle_shutdown_requests(Duration::from_millis(1000))
+        .await;
+
+    assert!(result.is_ok());
+    assert!(database.closed_at().is_some());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn unused_resource_gets_closed_immediately() {
+    let database = Resource::default();
+
+    let start = Instant::now();
+    let result = Toplevel::builder()
+        .shared_resource("database", database.clone(), ["/not_started"])
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("api", move |s| {
+                slow_shutdown(s, Duration::from_millis(100))
+            }));
+            s.request_shutdown();
+        })
+        .handle_shutdown_requests(Duration::from_millis(1000))
+        .await;
+
+    assert!(result.is_ok());
+    assert_eq!(database.closed_at().unwrap(), start);
+}
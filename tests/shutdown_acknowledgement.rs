@@ -0,0 +1,90 @@
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing_test::traced_test;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn acknowledgements_show_up_in_shutdown_statistics() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let statistics = subsys.shutdown_statistics();
+        assert_eq!(statistics.acknowledged_count, 0);
+        assert_eq!(statistics.draining_count, 0);
+
+        let acknowledgement = subsys.acknowledge_shutdown().await;
+        let statistics = subsys.shutdown_statistics();
+        assert_eq!(statistics.acknowledged_count, 1);
+        assert_eq!(statistics.draining_count, 1);
+
+        sleep(Duration::from_millis(100)).await;
+        acknowledgement.done();
+        let statistics = subsys.shutdown_statistics();
+        assert_eq!(statistics.acknowledged_count, 1);
+        assert_eq!(statistics.draining_count, 0);
+
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(50)).await;
+        s.request_shutdown();
+    });
+    let handle = toplevel.handle();
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+
+    assert!(result.is_ok());
+    assert_eq!(handle.shutdown_statistics().acknowledged_count, 1);
+    assert_eq!(handle.shutdown_statistics().draining_count, 0);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn acknowledgements_distinguish_draining_from_ignoring_subsystems() {
+    let draining = |subsys: SubsystemHandle| async move {
+        let _acknowledgement = subsys.acknowledge_shutdown().await;
+        sleep(Duration::from_secs(10)).await;
+        BoxedResult::Ok(())
+    };
+    let ignoring = |_: SubsystemHandle| async move {
+        sleep(Duration::from_secs(10)).await;
+        BoxedResult::Ok(())
+    };
+    let cleaned_up = |subsys: SubsystemHandle| async move {
+        subsys.acknowledge_shutdown().await.done();
+        sleep(Duration::from_secs(10)).await;
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(
+            SubsystemBuilder::new("draining", draining)
+                .shutdown_timeout(Duration::from_millis(100)),
+        );
+        s.start(SubsystemBuilder::new("ignoring", ignoring));
+        s.start(SubsystemBuilder::new("cleaned_up", cleaned_up));
+        sleep(Duration::from_millis(50)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_err());
+    assert!(logs_contain(
+        "Subsystem '/draining' exceeded its shutdown timeout of 100ms, aborting it ... (it is still draining)"
+    ));
+    assert!(logs_contain(
+        "Aborting subsystem '/ignoring' ... (it did not acknowledge the shutdown)"
+    ));
+    assert!(logs_contain(
+        "Aborting subsystem '/cleaned_up' ... (it finished its cleanup, but did not return)"
+    ));
+}
@@ -0,0 +1,89 @@
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing_test::traced_test;
+
+pub mod common;
+use common::Event;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[cfg(unix)]
+#[tokio::test]
+#[traced_test]
+async fn announced_during_drain_delay() {
+    use nix::sys::signal::{self, Signal};
+    use nix::unistd::Pid;
+
+    let (announced, set_announced) = Event::create();
+    let (shutdown_requested, set_shutdown_requested) = Event::create();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        assert_eq!(subsys.shutdown_imminent(), None);
+
+        let remaining = subsys.on_shutdown_imminent().await;
+        assert!(remaining > Duration::from_millis(100));
+        assert!(remaining <= Duration::from_millis(200));
+        assert!(subsys.shutdown_imminent().is_some());
+        assert!(!subsys.is_shutdown_requested());
+        set_announced();
+
+        subsys.on_shutdown_requested().await;
+        set_shutdown_requested();
+        BoxedResult::Ok(())
+    };
+
+    tokio::join!(
+        async {
+            sleep(Duration::from_millis(100)).await;
+
+            // Send SIGTERM to ourselves.
+            signal::kill(Pid::this(), Signal::SIGTERM).unwrap();
+
+            sleep(Duration::from_millis(100)).await;
+            assert!(announced.get());
+            assert!(!shutdown_requested.get());
+            sleep(Duration::from_millis(200)).await;
+            assert!(shutdown_requested.get());
+        },
+        async {
+            let result = Toplevel::builder()
+                .catch_signals()
+                .drain_delay(Duration::from_millis(200))
+                .shutdown_timeout(Duration::from_millis(400))
+                .build(move |s| async move {
+                    s.start(SubsystemBuilder::new("subsys", subsystem));
+                })
+                .run()
+                .await;
+            assert!(result.is_ok());
+        },
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn unannounced_shutdown_is_imminent_right_away() {
+    let start = Instant::now();
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        assert_eq!(subsys.on_shutdown_imminent().await, Duration::ZERO);
+        assert_eq!(start.elapsed(), Duration::from_millis(100));
+        assert_eq!(subsys.shutdown_imminent(), Some(Duration::ZERO));
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,66 @@
+#![cfg(unix)]
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+use crate::common::Event;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test]
+#[traced_test]
+async fn refused_shutdown_gets_bypassed_by_second_signal() {
+    use nix::sys::signal::{self, Signal};
+    use nix::unistd::Pid;
+
+    let (confirmation_requested, set_confirmation_requested) = Event::create();
+    let (shutdown_requested, set_shutdown_requested) = Event::create();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        set_shutdown_requested();
+        BoxedResult::Ok(())
+    };
+
+    tokio::join!(
+        async {
+            sleep(Duration::from_millis(100)).await;
+
+            // Send SIGTERM to ourselves.
+            signal::kill(Pid::this(), Signal::SIGTERM).unwrap();
+
+            sleep(Duration::from_millis(100)).await;
+            assert!(confirmation_requested.get());
+            assert!(!shutdown_requested.get());
+
+            signal::kill(Pid::this(), Signal::SIGTERM).unwrap();
+
+            sleep(Duration::from_millis(100)).await;
+            assert!(shutdown_requested.get());
+        },
+        async {
+            let result = Toplevel::builder()
+                .catch_signals()
+                .shutdown_confirmation(move || async move {
+                    set_confirmation_requested();
+                    false
+                })
+                .shutdown_timeout(Duration::from_millis(400))
+                .build(move |s| async move {
+                    s.start(SubsystemBuilder::new("subsys", subsystem));
+                })
+                .run()
+                .await;
+            assert!(result.is_ok());
+        },
+    );
+
+    assert!(logs_contain("Shutdown request was refused."));
+}
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{
+    errors::GracefulShutdownError, ShutdownController, SubsystemBuilder, SubsystemHandle,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn runs_are_independent() {
+    let controller =
+        ShutdownController::new(|toplevel| toplevel.shutdown_timeout(Duration::from_millis(500)));
+
+    let result = controller
+        .run(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("failing", |_| async {
+                BoxedResult::Err("Failed".into())
+            }));
+        })
+        .await;
+    assert!(matches!(
+        result,
+        Err(GracefulShutdownError::SubsystemsFailed(_))
+    ));
+
+    let result = controller
+        .run(|s: SubsystemHandle| async move {
+            assert!(!s.is_shutdown_requested());
+            s.start(SubsystemBuilder::new("subsys", |_| async {
+                sleep(Duration::from_millis(100)).await;
+                BoxedResult::Ok(())
+            }));
+        })
+        .await;
+    assert!(result.is_ok());
+    assert!(controller.handle().is_none());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn request_shutdown_affects_current_run() {
+    let controller = Arc::new(ShutdownController::new(|toplevel| {
+        toplevel.shutdown_timeout(Duration::from_millis(500))
+    }));
+
+    // Without a run in progress, this has no effect.
+    controller.request_shutdown();
+
+    tokio::spawn({
+        let controller = Arc::clone(&controller);
+        async move {
+            sleep(Duration::from_millis(100)).await;
+            controller.request_shutdown();
+        }
+    });
+
+    let start = Instant::now();
+    let result = controller
+        .run(|s: SubsystemHandle| async move {
+            s.on_shutdown_requested().await;
+        })
+        .await;
+    assert!(result.is_ok());
+    assert_eq!(start.elapsed(), Duration::from_millis(100));
+}
@@ -0,0 +1,166 @@
+use tokio::{
+    sync::oneshot,
+    time::{sleep, Duration, Instant},
+};
+use tokio_graceful_shutdown::{
+    errors::{GracefulShutdownError, SubsystemError},
+    SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+use crate::common::Event;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+/// Reports when the shutdown request arrived, and finishes after the given shutdown duration.
+async fn subsystem(
+    subsys: SubsystemHandle,
+    shutdown_duration: Duration,
+    on_shutdown_requested: oneshot::Sender<Instant>,
+) -> BoxedResult {
+    subsys.on_shutdown_requested().await;
+    on_shutdown_requested.send(Instant::now()).unwrap();
+    sleep(shutdown_duration).await;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn groups_get_shut_down_in_order() {
+    let (ungrouped_sender, ungrouped) = oneshot::channel();
+    let (http_sender, http) = oneshot::channel();
+    let (storage_sender, storage) = oneshot::channel();
+
+    let start = Instant::now();
+
+    let result = Toplevel::builder()
+        .shutdown_group("http", Duration::from_millis(500))
+        .shutdown_group("storage", Duration::from_millis(500))
+        .build(move |s| async move {
+            s.start(
+                SubsystemBuilder::new("storage", move |s| {
+                    subsystem(s, Duration::from_millis(100), storage_sender)
+                })
+                .shutdown_group("storage"),
+            );
+            s.start(
+                SubsystemBuilder::new("http", move |s| {
+                    subsystem(s, Duration::from_millis(200), http_sender)
+                })
+                .shutdown_group("http"),
+            );
+            s.start(SubsystemBuilder::new("ungrouped", move |s| {
+                subsystem(s, Duration::from_millis(300), ungrouped_sender)
+            }));
+            s.request_shutdown();
+        })
+        .handle_shutdown_requests(Duration::from_secs(1))
+        .await;
+
+    assert!(result.is_ok());
+    assert_eq!(ungrouped.await.unwrap() - start, Duration::ZERO);
+    assert_eq!(http.await.unwrap() - start, Duration::ZERO);
+    assert_eq!(storage.await.unwrap() - start, Duration::from_millis(200));
+    assert_eq!(start.elapsed(), Duration::from_millis(300));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn group_gets_aborted_when_exceeding_its_budget() {
+    let (http_sender, _http) = oneshot::channel();
+    let (storage_sender, storage) = oneshot::channel();
+
+    let start = Instant::now();
+
+    let result = Toplevel::builder()
+        .shutdown_group("http", Duration::from_millis(100))
+        .shutdown_group("storage", Duration::from_millis(500))
+        .build(move |s| async move {
+            s.start(
+                SubsystemBuilder::new("http", move |s| {
+                    subsystem(s, Duration::from_secs(10), http_sender)
+                })
+                .shutdown_group("http"),
+            );
+            s.start(
+                SubsystemBuilder::new("storage", move |s| {
+                    subsystem(s, Duration::from_millis(50), storage_sender)
+                })
+                .shutdown_group("storage"),
+            );
+            s.request_shutdown();
+        })
+        .handle_shutdown_requests(Duration::from_secs(1))
+        .await;
+
+    assert_eq!(storage.await.unwrap() - start, Duration::from_millis(100));
+    assert_eq!(start.elapsed(), Duration::from_millis(150));
+
+    let Err(GracefulShutdownError::ShutdownTimeout(errors)) = result else {
+        panic!("Incorrect return value!");
+    };
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(&errors[0], SubsystemError::Aborted(name) if name.as_ref() == "/http"));
+    assert!(logs_contain(
+        "Group 'http' exceeded its shutdown budget of 100ms, aborting it ..."
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn local_shutdown_reaches_grouped_subsystems() {
+    let (finished, set_finished) = Event::create();
+
+    let grouped = move |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        set_finished();
+        BoxedResult::Ok(())
+    };
+
+    let parent = move |subsys: SubsystemHandle| async move {
+        let nested = subsys.start(SubsystemBuilder::new("grouped", grouped).shutdown_group("late"));
+        sleep(Duration::from_millis(100)).await;
+        subsys.request_local_shutdown();
+        nested.join().await?;
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::builder()
+        .shutdown_group("late", Duration::from_millis(500))
+        .build(move |s| async move {
+            s.start(SubsystemBuilder::new("parent", parent));
+        })
+        .handle_shutdown_requests(Duration::from_secs(1))
+        .await;
+
+    assert!(result.is_ok());
+    assert!(finished.get());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn unknown_group_gets_ignored() {
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(
+            SubsystemBuilder::new("subsys", |s: SubsystemHandle| async move {
+                s.on_shutdown_requested().await;
+                BoxedResult::Ok(())
+            })
+            .shutdown_group("unknown"),
+        );
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(100))
+    .await;
+
+    assert!(result.is_ok());
+    assert!(logs_contain(
+        "Subsystem '/subsys' is in unknown shutdown group 'unknown'; ignoring it."
+    ));
+}
@@ -0,0 +1,145 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    ShutdownReason, ShutdownSnapshot, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn hook_runs_before_subsystems_react() {
+    let hook_ran = Arc::new(AtomicBool::new(false));
+    let snapshots = Arc::new(Mutex::new(Vec::<ShutdownSnapshot>::new()));
+
+    let subsystem = {
+        let hook_ran = Arc::clone(&hook_ran);
+        move |subsys: SubsystemHandle| async move {
+            subsys.on_shutdown_requested().await;
+            assert!(hook_ran.load(Ordering::SeqCst));
+            BoxedResult::Ok(())
+        }
+    };
+
+    let toplevel = Toplevel::builder()
+        .on_shutdown_triggered({
+            let snapshots = Arc::clone(&snapshots);
+            move |snapshot| {
+                hook_ran.store(true, Ordering::SeqCst);
+                snapshots.lock().unwrap().push(snapshot.clone());
+            }
+        })
+        .build(move |s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("b", subsystem.clone()));
+            s.start(SubsystemBuilder::new("a", subsystem));
+            sleep(Duration::from_millis(100)).await;
+            s.request_shutdown();
+            s.request_shutdown();
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    let snapshots = snapshots.lock().unwrap();
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].reason, ShutdownReason::Requested);
+    assert_eq!(
+        snapshots[0].running_subsystems,
+        vec![Arc::from("/a"), Arc::from("/b")]
+    );
+    assert!(snapshots[0].statistics.is_shutdown_requested);
+    assert_eq!(snapshots[0].statistics.request_count, 2);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn snapshot_contains_failed_subsystem() {
+    let snapshot = Arc::new(Mutex::new(None));
+
+    let toplevel = Toplevel::builder()
+        .on_shutdown_triggered({
+            let snapshot = Arc::clone(&snapshot);
+            move |s| *snapshot.lock().unwrap() = Some(s.clone())
+        })
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("worker", |s| async move {
+                s.on_shutdown_requested().await;
+                BoxedResult::Ok(())
+            }));
+            s.start(SubsystemBuilder::new("failing", |_| async {
+                sleep(Duration::from_millis(100)).await;
+                BoxedResult::Err("broken".into())
+            }));
+        });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    assert!(result.is_err());
+
+    let snapshot = snapshot.lock().unwrap().take().unwrap();
+    assert_eq!(
+        snapshot.reason,
+        ShutdownReason::SubsystemFailed(Arc::from("/failing"))
+    );
+    assert_eq!(snapshot.running_subsystems, vec![Arc::from("/worker")]);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn hook_does_not_run_without_shutdown() {
+    let hook_ran = Arc::new(AtomicBool::new(false));
+
+    let toplevel = Toplevel::builder()
+        .on_shutdown_triggered({
+            let hook_ran = Arc::clone(&hook_ran);
+            move |_| hook_ran.store(true, Ordering::SeqCst)
+        })
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("finite", |_| async {
+                sleep(Duration::from_millis(100)).await;
+                BoxedResult::Ok(())
+            }));
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert!(!hook_ran.load(Ordering::SeqCst));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn panicking_hook_does_not_stop_shutdown() {
+    let toplevel = Toplevel::builder()
+        .on_shutdown_triggered(|_| panic!("Hook failed"))
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("worker", |s| async move {
+                s.on_shutdown_requested().await;
+                BoxedResult::Ok(())
+            }));
+            s.request_shutdown();
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert!(logs_contain("The shutdown hook panicked."));
+}
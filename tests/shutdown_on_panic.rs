@@ -0,0 +1,159 @@
+//! The panic hook is global, so the tests in this file run one after another,
+//! to not interfere with the panics of each other.
+
+use tokio::{sync::Mutex, time::Duration};
+use tokio_graceful_shutdown::{
+    ErrorAction, ShutdownReason, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::{error::Error, sync::Arc};
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+static SERIAL: Mutex<()> = Mutex::const_new(());
+
+#[tokio::test]
+#[traced_test]
+async fn panic_in_unmanaged_thread_initiates_shutdown() {
+    let _serial = SERIAL.lock().await;
+    let (reason_sender, reason_receiver) = tokio::sync::oneshot::channel();
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new(
+            "observer",
+            move |subsys: SubsystemHandle| async move {
+                let reason = subsys.on_shutdown_requested_with_reason().await;
+                reason_sender.send(reason).unwrap();
+                BoxedResult::Ok(())
+            },
+        ));
+
+        let worker = std::thread::Builder::new()
+            .name("worker".into())
+            .spawn(|| panic!("Boom"))
+            .unwrap();
+        assert!(worker.join().is_err());
+    })
+    .shutdown_on_panic()
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+
+    let ShutdownReason::Panic {
+        thread,
+        message,
+        location,
+    } = reason_receiver.await.unwrap()
+    else {
+        panic!("Unexpected shutdown reason");
+    };
+    assert_eq!(thread, Some(Arc::from("worker")));
+    assert_eq!(message, Arc::from("Boom"));
+    assert!(location.unwrap().contains("shutdown_on_panic.rs"));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn caught_subsystem_panic_does_not_initiate_shutdown() {
+    let _serial = SERIAL.lock().await;
+
+    let result = Toplevel::new(|s: SubsystemHandle| async move {
+        let nested = s.start(
+            SubsystemBuilder::new("nested", |_: SubsystemHandle| async move {
+                panic!("Boom");
+                #[allow(unreachable_code)]
+                BoxedResult::Ok(())
+            })
+            .on_panic(ErrorAction::CatchAndLocalShutdown),
+        );
+        assert!(nested.join().await.is_err());
+
+        assert!(!s.is_shutdown_requested());
+        s.request_shutdown();
+    })
+    .shutdown_on_panic()
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn dropped_toplevel_ignores_panics() {
+    let _serial = SERIAL.lock().await;
+
+    let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+        s.request_shutdown();
+    })
+    .shutdown_on_panic();
+    let shutdown_token = toplevel.create_cancellation_token();
+    drop(toplevel);
+
+    let worker = std::thread::spawn(|| panic!("Boom"));
+    assert!(worker.join().is_err());
+
+    assert!(!shutdown_token.is_cancelled());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn caught_scope_task_panic_does_not_initiate_shutdown() {
+    let _serial = SERIAL.lock().await;
+
+    let result = Toplevel::new(|s: SubsystemHandle| async move {
+        let nested = s.start(
+            SubsystemBuilder::new("nested", |subsys: SubsystemHandle| async move {
+                let mut scope = subsys.scope::<BoxedError>();
+                scope.spawn(async {
+                    panic!("Boom");
+                    #[allow(unreachable_code)]
+                    Ok(())
+                });
+                scope.join().await
+            })
+            .on_panic(ErrorAction::CatchAndLocalShutdown),
+        );
+        assert!(nested.join().await.is_err());
+
+        assert!(!s.is_shutdown_requested());
+        s.request_shutdown();
+    })
+    .shutdown_on_panic()
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+#[traced_test]
+async fn builder_covers_panics_right_after_start() {
+    let _serial = SERIAL.lock().await;
+
+    // The root subsystem already runs while the builder still returns,
+    // so the hook has to be installed before it starts.
+    let result = Toplevel::builder()
+        .shutdown_on_panic()
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new(
+                "observer",
+                |subsys: SubsystemHandle| async move {
+                    subsys.on_shutdown_requested().await;
+                    BoxedResult::Ok(())
+                },
+            ));
+
+            let _ = tokio::spawn(async { panic!("Boom") }).await;
+        })
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+
+    assert!(result.is_ok());
+}
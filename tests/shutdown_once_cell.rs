@@ -0,0 +1,132 @@
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{
+    errors::NeverInitialized, ShutdownOnceCell, ShutdownToken, SubsystemBuilder, SubsystemHandle,
+    Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn shutdown_cancels_pending_initialization() {
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        let cell = Arc::new(ShutdownOnceCell::<u32>::new(s.shutdown_token()));
+
+        let initializer = s.start(SubsystemBuilder::new("initializer", {
+            let cell = Arc::clone(&cell);
+            |_| async move {
+                let result = cell
+                    .get_or_init(|| async {
+                        sleep(Duration::from_secs(10)).await;
+                        42
+                    })
+                    .await;
+                assert!(matches!(result, Err(NeverInitialized)));
+                BoxedResult::Ok(())
+            }
+        }));
+        let waiter = s.start(SubsystemBuilder::new("waiter", {
+            let cell = Arc::clone(&cell);
+            |_| async move {
+                let result = cell.wait().await;
+                assert!(matches!(result, Err(NeverInitialized)));
+                BoxedResult::Ok(())
+            }
+        }));
+
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+
+        initializer.join().await.unwrap();
+        waiter.join().await.unwrap();
+        assert!(!cell.is_initialized());
+    });
+
+    let start = Instant::now();
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+    assert_eq!(Instant::now() - start, Duration::from_millis(100));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn initialized_value_survives_shutdown() {
+    let shutdown = ShutdownToken::new();
+    let cell = ShutdownOnceCell::new(shutdown.clone());
+    let inits = AtomicU32::new(0);
+
+    let init = || async {
+        inits.fetch_add(1, Ordering::SeqCst);
+        42
+    };
+    assert_eq!(*cell.get_or_init(init).await.unwrap(), 42);
+    assert_eq!(*cell.get_or_init(init).await.unwrap(), 42);
+
+    shutdown.request_shutdown();
+
+    assert_eq!(*cell.get_or_init(init).await.unwrap(), 42);
+    assert_eq!(*cell.wait().await.unwrap(), 42);
+    assert_eq!(cell.get(), Some(&42));
+    assert_eq!(inits.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn no_initialization_after_shutdown() {
+    let shutdown = ShutdownToken::new();
+    let cell = ShutdownOnceCell::new(shutdown.clone());
+    shutdown.request_shutdown();
+
+    let result = cell
+        .get_or_init(|| async { panic!("Initialization must not start after the shutdown") })
+        .await;
+    assert!(matches!(result, Err::<&u32, _>(NeverInitialized)));
+    assert!(matches!(cell.wait().await, Err(NeverInitialized)));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn failed_initialization_can_be_retried() {
+    let cell = ShutdownOnceCell::new(ShutdownToken::new());
+
+    let result = cell
+        .get_or_try_init(|| async { Err::<u32, BoxedError>("connection refused".into()) })
+        .await;
+    assert_eq!(result.unwrap_err().to_string(), "connection refused");
+    assert!(!cell.is_initialized());
+
+    let result = cell
+        .get_or_try_init(|| async { Ok::<u32, BoxedError>(42) })
+        .await;
+    assert_eq!(*result.unwrap(), 42);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn wait_returns_value_once_initialized() {
+    let cell = Arc::new(ShutdownOnceCell::new(ShutdownToken::new()));
+
+    let waiter = tokio::spawn({
+        let cell = Arc::clone(&cell);
+        async move { *cell.wait().await.unwrap() }
+    });
+
+    sleep(Duration::from_millis(100)).await;
+    cell.get_or_init(|| async { 42 }).await.unwrap();
+
+    assert_eq!(waiter.await.unwrap(), 42);
+}
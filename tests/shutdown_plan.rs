@@ -0,0 +1,127 @@
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, SubsystemTree, Toplevel};
+use tracing_test::traced_test;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+async fn subsystem(subsys: SubsystemHandle) -> BoxedResult {
+    subsys.on_shutdown_requested().await;
+    Ok(())
+}
+
+fn names(subsystems: &[tokio_graceful_shutdown::PlannedSubsystem]) -> Vec<&str> {
+    subsystems
+        .iter()
+        .map(|subsystem| subsystem.name.as_ref())
+        .collect()
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn dry_run_reports_order_and_timeouts_without_shutting_down() {
+    let toplevel = Toplevel::builder()
+        .shutdown_group("http", Duration::from_millis(500))
+        .shutdown_group("storage", Duration::from_millis(300))
+        .shutdown_timeout(Duration::from_secs(1))
+        .build(|_| async {});
+
+    toplevel.start(
+        SubsystemTree::new(SubsystemBuilder::new("api", subsystem))
+            .child(SubsystemTree::new(
+                SubsystemBuilder::new("http", subsystem)
+                    .shutdown_group("http")
+                    .shutdown_timeout(Duration::from_millis(200)),
+            ))
+            .child(SubsystemTree::new(
+                SubsystemBuilder::new("worker", subsystem)
+                    .pre_shutdown(Duration::from_millis(50), || async {}),
+            )),
+    );
+    toplevel.start(SubsystemTree::new(
+        SubsystemBuilder::new("database", subsystem).shutdown_group("storage"),
+    ));
+
+    // Give the subsystems time to start their children.
+    sleep(Duration::from_millis(10)).await;
+    let plan = toplevel.dry_run_shutdown();
+
+    assert!(plan.is_valid());
+    assert_eq!(plan.shutdown_timeout, Some(Duration::from_secs(1)));
+    assert_eq!(names(&plan.ungrouped), ["/api/worker", "/api"]);
+    assert_eq!(
+        plan.ungrouped[0].pre_shutdown_timeout,
+        Some(Duration::from_millis(50))
+    );
+
+    assert_eq!(plan.stages.len(), 2);
+    assert_eq!(plan.stages[0].group.as_ref(), "http");
+    assert_eq!(plan.stages[0].budget, Duration::from_millis(500));
+    assert_eq!(names(&plan.stages[0].subsystems), ["/api/http"]);
+    assert_eq!(
+        plan.stages[0].subsystems[0].shutdown_timeout,
+        Some(Duration::from_millis(200))
+    );
+    assert_eq!(plan.stages[1].group.as_ref(), "storage");
+    assert_eq!(names(&plan.stages[1].subsystems), ["/database"]);
+
+    assert_eq!(
+        plan.to_string(),
+        "shutdown timeout: 1s\n\
+         ungrouped:\n  /api/worker (pre-shutdown hook: 50ms)\n  /api\n\
+         group 1 'http' (budget: 500ms):\n  /api/http (timeout: 200ms)\n\
+         group 2 'storage' (budget: 300ms):\n  /database\n"
+    );
+
+    // Nothing got cancelled.
+    assert!(!toplevel.handle().is_shutdown_requested());
+
+    toplevel.handle().request_shutdown();
+    let result = toplevel.run().await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn dry_run_detects_parents_waiting_for_later_groups() {
+    let toplevel = Toplevel::builder()
+        .shutdown_group("http", Duration::from_millis(500))
+        .shutdown_group("storage", Duration::from_millis(300))
+        .build(|_| async {});
+
+    toplevel.start(
+        SubsystemTree::new(SubsystemBuilder::new("http", subsystem).shutdown_group("http"))
+            .child(SubsystemTree::new(
+                SubsystemBuilder::new("cache", subsystem).shutdown_group("storage"),
+            ))
+            .child(SubsystemTree::new(SubsystemBuilder::new(
+                "session", subsystem,
+            ))),
+    );
+    toplevel.start(
+        SubsystemTree::new(SubsystemBuilder::new("database", subsystem).shutdown_group("storage"))
+            .child(SubsystemTree::new(
+                SubsystemBuilder::new("listener", subsystem).shutdown_group("http"),
+            )),
+    );
+
+    // Give the subsystems time to start their children.
+    sleep(Duration::from_millis(10)).await;
+    let plan = toplevel.dry_run_shutdown();
+
+    assert!(!plan.is_valid());
+    assert_eq!(plan.cycles.len(), 1);
+    assert_eq!(plan.cycles[0].subsystem.as_ref(), "/http");
+    assert_eq!(plan.cycles[0].group.as_ref(), "http");
+    assert_eq!(plan.cycles[0].descendant.as_ref(), "/http/cache");
+    assert_eq!(plan.cycles[0].descendant_group.as_ref(), "storage");
+    assert!(plan.to_string().contains(
+        "cycle: '/http' in group 'http' waits for '/http/cache' in later group 'storage'"
+    ));
+
+    toplevel.handle().request_shutdown();
+    toplevel.run().await.ok();
+}
@@ -0,0 +1,71 @@
+use tokio::time::Duration;
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn staleness_is_bounded_by_check_interval() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let mut poller = subsys.shutdown_poller(4);
+        assert!(!poller.is_shutdown_requested_fast());
+
+        subsys.request_local_shutdown();
+
+        // The remaining calls of the current interval answer from the local copy.
+        for _ in 0..3 {
+            assert!(!poller.is_shutdown_requested_fast());
+        }
+        assert!(poller.is_shutdown_requested_fast());
+        assert!(poller.is_shutdown_requested_fast());
+
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn first_call_and_refresh_check_right_away() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let mut poller = subsys.shutdown_poller(1000);
+        assert!(!poller.is_shutdown_requested_fast());
+        // An interval of zero checks on every call.
+        let mut every_call = subsys.shutdown_poller(0);
+        assert!(!every_call.is_shutdown_requested_fast());
+
+        subsys.request_local_shutdown();
+        assert!(every_call.is_shutdown_requested_fast());
+        assert!(!poller.is_shutdown_requested_fast());
+        assert!(poller.refresh());
+
+        let mut poller = subsys.shutdown_poller(1000);
+        assert!(poller.is_shutdown_requested_fast());
+
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,166 @@
+use anyhow::anyhow;
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{ShutdownReason, SubsystemBuilder, SubsystemHandle, Toplevel};
+use tokio_util::sync::CancellationToken;
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::{error::Error, sync::Arc};
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+/// Starts a subsystem that reports the reason of its shutdown.
+fn start_observer(s: &SubsystemHandle) -> tokio::sync::oneshot::Receiver<ShutdownReason> {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    s.start(SubsystemBuilder::new(
+        "observer",
+        move |subsys: SubsystemHandle| async move {
+            assert_eq!(subsys.shutdown_reason(), None);
+            let reason = subsys.on_shutdown_requested_with_reason().await;
+            assert_eq!(subsys.shutdown_reason(), Some(reason.clone()));
+            sender.send(reason).unwrap();
+            BoxedResult::Ok(())
+        },
+    ));
+    receiver
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn reason_of_requested_shutdown() {
+    let (reason_sender, reason_receiver) = tokio::sync::oneshot::channel();
+
+    let result = Toplevel::new(move |s| async move {
+        let reason = start_observer(&s);
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+        reason_sender.send(reason.await.unwrap()).unwrap();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(reason_receiver.await.unwrap(), ShutdownReason::Requested);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn reason_of_failed_subsystem() {
+    let (reason_sender, reason_receiver) = tokio::sync::oneshot::channel();
+
+    let result = Toplevel::new(move |s| async move {
+        let reason = start_observer(&s);
+        s.start(SubsystemBuilder::new("database", |_| async {
+            sleep(Duration::from_millis(100)).await;
+            BoxedResult::Err(anyhow!("Connection lost").into())
+        }));
+        reason_sender.send(reason.await.unwrap()).unwrap();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(
+        reason_receiver.await.unwrap(),
+        ShutdownReason::SubsystemFailed(Arc::from("/database"))
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn reason_of_panicked_subsystem() {
+    let (reason_sender, reason_receiver) = tokio::sync::oneshot::channel();
+
+    let result = Toplevel::new(move |s| async move {
+        let reason = start_observer(&s);
+        s.start(SubsystemBuilder::new("database", |_| async {
+            sleep(Duration::from_millis(100)).await;
+            panic!("Connection lost");
+            #[allow(unreachable_code)]
+            BoxedResult::Ok(())
+        }));
+        reason_sender.send(reason.await.unwrap()).unwrap();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(
+        reason_receiver.await.unwrap(),
+        ShutdownReason::SubsystemPanicked(Arc::from("/database"))
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn reason_of_external_shutdown() {
+    let (reason_sender, reason_receiver) = tokio::sync::oneshot::channel();
+    let cancellation_token = CancellationToken::new();
+
+    let toplevel =
+        Toplevel::new_with_cancellation_token(cancellation_token.clone(), move |s| async move {
+            let reason = start_observer(&s);
+            reason_sender.send(reason.await.unwrap()).unwrap();
+        });
+
+    let result = tokio::join!(
+        toplevel.handle_shutdown_requests(Duration::from_millis(500)),
+        async {
+            sleep(Duration::from_millis(100)).await;
+            cancellation_token.cancel();
+        }
+    )
+    .0;
+
+    assert!(result.is_ok());
+    assert_eq!(reason_receiver.await.unwrap(), ShutdownReason::External);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn reason_of_local_shutdown() {
+    let (reason_sender, reason_receiver) = tokio::sync::oneshot::channel();
+
+    let result = Toplevel::new(move |s| async move {
+        let nested = s.start(SubsystemBuilder::new(
+            "nested",
+            move |subsys: SubsystemHandle| async move {
+                let reason = start_observer(&subsys);
+                reason_sender.send(reason.await.unwrap()).unwrap();
+                BoxedResult::Ok(())
+            },
+        ));
+        sleep(Duration::from_millis(100)).await;
+        nested.initiate_shutdown();
+        nested.join().await.unwrap();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(reason_receiver.await.unwrap(), ShutdownReason::Local);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn first_reason_wins() {
+    let (reason_sender, reason_receiver) = tokio::sync::oneshot::channel();
+
+    let result = Toplevel::new(move |s| async move {
+        let reason = start_observer(&s);
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+        s.start(SubsystemBuilder::new("failing", |_| async {
+            BoxedResult::Err(anyhow!("Failed").into())
+        }));
+        reason_sender.send(reason.await.unwrap()).unwrap();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_err());
+    assert_eq!(reason_receiver.await.unwrap(), ShutdownReason::Requested);
+}
@@ -0,0 +1,171 @@
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    AbortCause, AggregatedSubsystems, ShutdownReportEntry, SubsystemBuilder, SubsystemHandle,
+    SubsystemOutcome, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn identically_named_subsystems_get_aggregated() {
+    let connection = |id: u32| {
+        move |subsys: SubsystemHandle| async move {
+            subsys.on_shutdown_requested().await;
+            match id % 50 {
+                0 => BoxedResult::Err(format!("connection {id} reset").into()),
+                1 => {
+                    sleep(Duration::from_secs(10)).await;
+                    Ok(())
+                }
+                _ => Ok(()),
+            }
+        }
+    };
+
+    let toplevel = Toplevel::builder()
+        .shutdown_timeout(Duration::from_secs(1))
+        .report_aggregation_threshold(5)
+        .build(move |s| async move {
+            s.start(SubsystemBuilder::new("listener", |subsys| async move {
+                subsys.on_shutdown_requested().await;
+                BoxedResult::Ok(())
+            }));
+            for id in 0..200 {
+                s.start(SubsystemBuilder::new("connection", connection(id)));
+            }
+            sleep(Duration::from_millis(100)).await;
+            s.request_shutdown();
+        });
+    let handle = toplevel.handle();
+
+    let result = toplevel.run().await;
+    assert!(result.is_err());
+
+    let report = handle.shutdown_report();
+    assert_eq!(report.entries.len(), 2);
+    assert!(report.entries.iter().any(|entry| matches!(
+        entry,
+        ShutdownReportEntry::Single { result, failure: None } if &*result.name == "/listener"
+    )));
+
+    let aggregated = report
+        .entries
+        .iter()
+        .find_map(|entry| match entry {
+            ShutdownReportEntry::Aggregated(aggregated) => Some(aggregated),
+            ShutdownReportEntry::Single { .. } => None,
+        })
+        .unwrap();
+    let AggregatedSubsystems {
+        name,
+        succeeded,
+        failed,
+        panicked,
+        aborted,
+        failure_samples,
+        ..
+    } = aggregated;
+    assert_eq!(&**name, "/connection");
+    assert_eq!((*succeeded, *failed, *panicked, *aborted), (192, 4, 0, 4));
+    assert_eq!(failure_samples.len(), 3);
+    assert!(failure_samples
+        .iter()
+        .all(|sample| sample.starts_with("connection ") && sample.ends_with(" reset")));
+
+    assert!(report
+        .to_string()
+        .contains("/connection: 192 ok, 4 failed, 4 timed out\n  e.g. connection "));
+    assert!(logs_contain("Shutdown report:"));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn no_report_gets_logged_on_clean_shutdown() {
+    let toplevel = Toplevel::<BoxedError>::builder().build(|s| async move {
+        for _ in 0..20 {
+            s.start(SubsystemBuilder::new("worker", |_| async {
+                BoxedResult::Ok(())
+            }));
+        }
+    });
+    let handle = toplevel.handle();
+
+    toplevel.run().await.unwrap();
+
+    assert_eq!(handle.shutdown_report().to_string(), "/worker: 20 ok");
+    assert!(!logs_contain("Shutdown report:"));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn aborted_subsystems_report_their_abort_cause() {
+    let stuck = |_: SubsystemHandle| async move {
+        sleep(Duration::from_secs(10)).await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::builder()
+        .shutdown_group("database", Duration::from_millis(200))
+        .build(move |s| async move {
+            s.start(
+                SubsystemBuilder::new("own_timeout", stuck)
+                    .shutdown_timeout(Duration::from_millis(100)),
+            );
+            s.start(SubsystemBuilder::new("grouped", stuck).shutdown_group("database"));
+            s.start(SubsystemBuilder::new("ungrouped", stuck));
+            s.request_shutdown();
+        });
+    let handle = toplevel.handle();
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+    assert!(result.is_err());
+
+    let mut abort_causes = handle
+        .shutdown_report()
+        .entries
+        .into_iter()
+        .map(|entry| match entry {
+            ShutdownReportEntry::Single { result, .. } => {
+                assert_eq!(result.outcome, SubsystemOutcome::Aborted);
+                (result.name, result.abort_cause)
+            }
+            ShutdownReportEntry::Aggregated(_) => panic!("Unexpected aggregation"),
+        })
+        .collect::<Vec<_>>();
+    abort_causes.sort_by(|a, b| a.0.cmp(&b.0));
+
+    assert_eq!(
+        abort_causes,
+        [
+            (
+                "/grouped".into(),
+                Some(AbortCause::ShutdownGroupTimeout {
+                    group: "database".into(),
+                    budget: Duration::from_millis(200),
+                })
+            ),
+            (
+                "/own_timeout".into(),
+                Some(AbortCause::SubsystemTimeout(Duration::from_millis(100)))
+            ),
+            (
+                "/ungrouped".into(),
+                Some(AbortCause::ShutdownTimeout(Duration::from_millis(500)))
+            ),
+        ]
+    );
+    assert!(handle
+        .shutdown_report()
+        .to_string()
+        .contains("/own_timeout: timed out (exceeded its shutdown timeout of 100ms)"));
+}
@@ -0,0 +1,85 @@
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{ShutdownToken, SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+/// Stands in for library code that only knows about the token.
+async fn library_worker(shutdown: ShutdownToken) -> &'static str {
+    shutdown.on_shutdown_requested().await;
+    "stopped"
+}
+
+#[test]
+fn works_without_runtime() {
+    let token = ShutdownToken::new();
+    let clone = token.clone();
+    let child = token.child_token();
+
+    child.request_shutdown();
+    assert!(child.is_shutdown_requested());
+    assert!(!token.is_shutdown_requested());
+
+    clone.request_shutdown();
+    assert!(token.is_shutdown_requested());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn standalone_token_stops_library() {
+    let token = ShutdownToken::new();
+    let worker = tokio::spawn(library_worker(token.clone()));
+
+    sleep(Duration::from_millis(100)).await;
+    assert!(!worker.is_finished());
+
+    token.request_shutdown();
+    assert_eq!(worker.await.unwrap(), "stopped");
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn subsystem_token_stops_library() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let result = library_worker(subsys.shutdown_token()).await;
+        assert_eq!(result, "stopped");
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn standalone_token_shuts_down_tree() {
+    let token = ShutdownToken::new();
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("subsys", |s| async move {
+            s.on_shutdown_requested().await;
+            BoxedResult::Ok(())
+        }));
+    })
+    .shutdown_on(token.clone().into());
+
+    token.request_shutdown();
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,150 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+type Events = Arc<Mutex<Vec<(&'static str, Duration)>>>;
+
+fn record(events: &Events, start: Instant, event: &'static str) {
+    events.lock().unwrap().push((event, start.elapsed()));
+}
+
+async fn stopper(subsys: SubsystemHandle) -> BoxedResult {
+    sleep(Duration::from_millis(200)).await;
+    subsys.request_shutdown();
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn starts_after_ready_and_stops_first() {
+    let events = Events::default();
+    let start = Instant::now();
+
+    let database = {
+        let events = Arc::clone(&events);
+        move |subsys: SubsystemHandle| async move {
+            sleep(Duration::from_millis(100)).await;
+            subsys.mark_ready();
+            subsys.on_shutdown_requested().await;
+            record(&events, start, "database stopping");
+            BoxedResult::Ok(())
+        }
+    };
+
+    let announcement = {
+        let events = Arc::clone(&events);
+        move |subsys: SubsystemHandle| async move {
+            record(&events, start, "announcement started");
+            subsys.on_shutdown_requested().await;
+            sleep(Duration::from_millis(50)).await;
+            record(&events, start, "announcement stopped");
+            BoxedResult::Ok(())
+        }
+    };
+
+    let toplevel = Toplevel::builder()
+        .sidecar(SubsystemBuilder::new("announcement", announcement))
+        .build(move |s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("database", database).reports_readiness());
+            s.start(SubsystemBuilder::new("stopper", stopper));
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            ("announcement started", Duration::from_millis(100)),
+            ("announcement stopped", Duration::from_millis(250)),
+            ("database stopping", Duration::from_millis(250)),
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn finished_subsystems_count_as_ready() {
+    let events = Events::default();
+    let start = Instant::now();
+
+    let sidecar = {
+        let events = Arc::clone(&events);
+        move |subsys: SubsystemHandle| async move {
+            record(&events, start, "sidecar started");
+            subsys.on_shutdown_requested().await;
+            BoxedResult::Ok(())
+        }
+    };
+
+    let toplevel = Toplevel::builder()
+        .sidecar(SubsystemBuilder::new("sidecar", sidecar))
+        .build(move |s: SubsystemHandle| async move {
+            s.start(
+                SubsystemBuilder::new("oneshot", |_| async {
+                    sleep(Duration::from_millis(100)).await;
+                    BoxedResult::Ok(())
+                })
+                .reports_readiness(),
+            );
+            s.start(SubsystemBuilder::new("stopper", stopper));
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [("sidecar started", Duration::from_millis(100))]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn not_started_if_shutdown_comes_first() {
+    let events = Events::default();
+    let start = Instant::now();
+
+    let sidecar = {
+        let events = Arc::clone(&events);
+        move |_: SubsystemHandle| async move {
+            record(&events, start, "sidecar started");
+            BoxedResult::Ok(())
+        }
+    };
+
+    let toplevel = Toplevel::builder()
+        .sidecar(SubsystemBuilder::new("sidecar", sidecar))
+        .build(move |s: SubsystemHandle| async move {
+            s.start(
+                SubsystemBuilder::new("never_ready", |s: SubsystemHandle| async move {
+                    s.on_shutdown_requested().await;
+                    BoxedResult::Ok(())
+                })
+                .reports_readiness(),
+            );
+            sleep(Duration::from_millis(100)).await;
+            s.request_shutdown();
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert!(events.lock().unwrap().is_empty());
+}
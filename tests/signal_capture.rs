@@ -0,0 +1,67 @@
+#![cfg(unix)]
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    ShutdownReason, ShutdownSignal, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test]
+#[traced_test]
+async fn received_signals_get_recorded() {
+    use nix::sys::signal::{self, Signal};
+    use nix::unistd::Pid;
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        assert_eq!(
+            subsys.shutdown_reason(),
+            Some(ShutdownReason::Signal(ShutdownSignal::Terminate))
+        );
+        // Keep the shutdown in progress for the second signal.
+        sleep(Duration::from_millis(200)).await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+    })
+    .catch_signals();
+    let handle = toplevel.handle();
+
+    tokio::join!(
+        async {
+            sleep(Duration::from_millis(100)).await;
+            signal::kill(Pid::this(), Signal::SIGTERM).unwrap();
+
+            sleep(Duration::from_millis(100)).await;
+            signal::kill(Pid::this(), Signal::SIGINT).unwrap();
+        },
+        async {
+            let result = toplevel
+                .handle_shutdown_requests(Duration::from_millis(400))
+                .await;
+            assert!(result.is_ok());
+        },
+    );
+
+    let received_signals = handle.shutdown_statistics().received_signals;
+    let signals = received_signals
+        .iter()
+        .map(|received| received.signal)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        signals,
+        [ShutdownSignal::Terminate, ShutdownSignal::Interrupt]
+    );
+    assert!(received_signals[0].received_at < received_signals[1].received_at);
+    assert!(logs_contain("Received SIGINT while shutting down."));
+}
@@ -0,0 +1,342 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::time::Duration;
+use tokio_graceful_shutdown::{
+    errors::{GracefulShutdownError, InternalError, SubsystemError},
+    SpawnHook, SpawnedTask, SubsystemBuilder, SubsystemHandle, SubsystemNode, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+tokio::task_local! {
+    static REQUEST_ID: u32;
+}
+
+struct PropagateRequestId;
+
+impl SpawnHook for PropagateRequestId {
+    fn wrap(&self, _node: &SubsystemNode, task: SpawnedTask) -> SpawnedTask {
+        match REQUEST_ID.try_with(|id| *id) {
+            Ok(id) => Box::pin(REQUEST_ID.scope(id, task)),
+            Err(_) => task,
+        }
+    }
+}
+
+type Events = Arc<Mutex<Vec<String>>>;
+
+struct Recorder {
+    hook: &'static str,
+    events: Events,
+}
+
+impl SpawnHook for Recorder {
+    fn wrap(&self, node: &SubsystemNode, task: SpawnedTask) -> SpawnedTask {
+        let hook = self.hook;
+        let name = node.name().to_string();
+        let events = Arc::clone(&self.events);
+        events
+            .lock()
+            .unwrap()
+            .push(format!("{hook} wraps '{name}'"));
+        Box::pin(async move {
+            events.lock().unwrap().push(format!("{hook} runs '{name}'"));
+            task.await;
+        })
+    }
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn task_locals_reach_all_subsystems() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let nested = {
+        let seen = Arc::clone(&seen);
+        move |subsys: SubsystemHandle| async move {
+            seen.lock()
+                .unwrap()
+                .push(REQUEST_ID.try_with(|id| *id).ok());
+            subsys.request_shutdown();
+            BoxedResult::Ok(())
+        }
+    };
+
+    let toplevel = REQUEST_ID.sync_scope(42, || {
+        Toplevel::builder().spawn_hook(PropagateRequestId).build(
+            move |s: SubsystemHandle| async move {
+                s.start(SubsystemBuilder::new(
+                    "parent",
+                    move |s: SubsystemHandle| async move {
+                        s.start(SubsystemBuilder::new("child", nested));
+                        s.on_shutdown_requested().await;
+                        BoxedResult::Ok(())
+                    },
+                ));
+            },
+        )
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert_eq!(*seen.lock().unwrap(), [Some(42)]);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn first_hook_is_outermost() {
+    let events = Events::default();
+
+    let toplevel = Toplevel::builder()
+        .spawn_hook(Recorder {
+            hook: "first",
+            events: Arc::clone(&events),
+        })
+        .spawn_hook(Recorder {
+            hook: "second",
+            events: Arc::clone(&events),
+        })
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new(
+                "subsys",
+                |s: SubsystemHandle| async move {
+                    s.request_shutdown();
+                    BoxedResult::Ok(())
+                },
+            ));
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            "second wraps ''",
+            "first wraps ''",
+            "first runs ''",
+            "second runs ''",
+            "second wraps '/subsys'",
+            "first wraps '/subsys'",
+            "first runs '/subsys'",
+            "second runs '/subsys'",
+        ]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn dropped_task_fails_the_subsystem() {
+    struct DropSubsystems;
+
+    impl SpawnHook for DropSubsystems {
+        fn wrap(&self, node: &SubsystemNode, task: SpawnedTask) -> SpawnedTask {
+            if node.name().is_empty() {
+                task
+            } else {
+                Box::pin(async {})
+            }
+        }
+    }
+
+    let toplevel =
+        Toplevel::builder()
+            .spawn_hook(DropSubsystems)
+            .build(|s: SubsystemHandle| async move {
+                s.start(SubsystemBuilder::new(
+                    "subsys",
+                    |s: SubsystemHandle| async move {
+                        s.on_shutdown_requested().await;
+                        BoxedResult::Ok(())
+                    },
+                ));
+            });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+
+    let Err(GracefulShutdownError::SubsystemsFailed(errors)) = result else {
+        panic!("Unexpected result: {result:?}");
+    };
+    assert!(matches!(
+        &errors[..],
+        [SubsystemError::Internal(name, InternalError::SubsystemNotRun)] if &**name == "/subsys"
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn helper_tasks_are_wrapped_and_end_with_their_subsystem() {
+    struct TrackRunning {
+        started: Arc<Mutex<Vec<String>>>,
+        running: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl SpawnHook for TrackRunning {
+        fn wrap(&self, node: &SubsystemNode, task: SpawnedTask) -> SpawnedTask {
+            let started = Arc::clone(&self.started);
+            let running = Arc::clone(&self.running);
+            let name = node.name().to_string();
+            Box::pin(async move {
+                started.lock().unwrap().push(name.clone());
+                running.lock().unwrap().push(name.clone());
+                task.await;
+                let mut running = running.lock().unwrap();
+                let position = running.iter().position(|n| *n == name).unwrap();
+                running.remove(position);
+            })
+        }
+    }
+
+    let started = Arc::new(Mutex::new(Vec::new()));
+    let running = Arc::new(Mutex::new(Vec::new()));
+
+    let toplevel = Toplevel::builder()
+        .spawn_hook(TrackRunning {
+            started: Arc::clone(&started),
+            running: Arc::clone(&running),
+        })
+        .shutdown_group("group", Duration::from_millis(500))
+        .build({
+            let running = Arc::clone(&running);
+            move |s: SubsystemHandle| async move {
+                let transferable = s.start(
+                    SubsystemBuilder::new("transferable", |_: SubsystemHandle| async {
+                        BoxedResult::Ok(())
+                    })
+                    .transferable(),
+                );
+                let grouped = s.start(
+                    SubsystemBuilder::new("grouped", |_: SubsystemHandle| async {
+                        BoxedResult::Ok(())
+                    })
+                    .shutdown_group("group"),
+                );
+                transferable.join().await.unwrap();
+                grouped.join().await.unwrap();
+                tokio::time::sleep(Duration::from_millis(100)).await;
+
+                // Only the root subsystem is left.
+                assert_eq!(*running.lock().unwrap(), [""]);
+                s.request_shutdown();
+            }
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    // Both subsystems and their forwarding tasks.
+    let mut started = started.lock().unwrap().clone();
+    started.sort();
+    assert_eq!(
+        started,
+        ["", "/grouped", "/grouped", "/transferable", "/transferable"]
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn task_locals_reach_pre_shutdown_hooks() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let hook = {
+        let seen = Arc::clone(&seen);
+        move || async move {
+            seen.lock()
+                .unwrap()
+                .push(REQUEST_ID.try_with(|id| *id).ok());
+        }
+    };
+
+    let toplevel = REQUEST_ID.sync_scope(42, || {
+        Toplevel::builder().spawn_hook(PropagateRequestId).build(
+            move |s: SubsystemHandle| async move {
+                s.start(
+                    SubsystemBuilder::new("subsys", |s: SubsystemHandle| async move {
+                        s.on_shutdown_requested().await;
+                        BoxedResult::Ok(())
+                    })
+                    .pre_shutdown(Duration::from_millis(100), hook),
+                );
+                s.request_shutdown();
+            },
+        )
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert_eq!(*seen.lock().unwrap(), [Some(42)]);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn task_locals_reach_lightweight_children_and_scopes() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let subsys = {
+        let seen = Arc::clone(&seen);
+        move |s: SubsystemHandle| async move {
+            s.start_lightweight({
+                let seen = Arc::clone(&seen);
+                |_| async move {
+                    seen.lock()
+                        .unwrap()
+                        .push(("lightweight", REQUEST_ID.try_with(|id| *id).ok()));
+                    BoxedResult::Ok(())
+                }
+            });
+            s.wait_for_children().await;
+
+            let mut scope = s.scope::<BoxedError>();
+            scope.spawn({
+                let seen = Arc::clone(&seen);
+                async move {
+                    seen.lock()
+                        .unwrap()
+                        .push(("scope", REQUEST_ID.try_with(|id| *id).ok()));
+                    Ok(())
+                }
+            });
+            scope.join().await?;
+
+            s.request_shutdown();
+            BoxedResult::Ok(())
+        }
+    };
+
+    let toplevel = REQUEST_ID.sync_scope(42, || {
+        Toplevel::builder().spawn_hook(PropagateRequestId).build(
+            move |s: SubsystemHandle| async move {
+                s.start(SubsystemBuilder::new("subsys", subsys));
+            },
+        )
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [("lightweight", Some(42)), ("scope", Some(42))]
+    );
+}
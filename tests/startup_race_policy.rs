@@ -0,0 +1,99 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    StartupRacePolicy, SubsystemBuilder, SubsystemHandle, SubsystemState, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn start_then_shutdown_starts_remaining_subsystems() {
+    let started = Arc::new(AtomicBool::new(false));
+
+    let toplevel = Toplevel::new({
+        let started = Arc::clone(&started);
+        move |s: SubsystemHandle| async move {
+            s.request_shutdown();
+            s.start(SubsystemBuilder::new("subsys", move |s| async move {
+                assert!(s.is_shutdown_requested());
+                started.store(true, Ordering::SeqCst);
+                BoxedResult::Ok(())
+            }));
+        }
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+    assert!(result.is_ok());
+    assert!(started.load(Ordering::SeqCst));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn skip_remaining_does_not_start_subsystems() {
+    let started = Arc::new(AtomicBool::new(false));
+
+    let toplevel = Toplevel::builder()
+        .startup_race_policy(StartupRacePolicy::SkipRemaining)
+        .build({
+            let started = Arc::clone(&started);
+            move |s: SubsystemHandle| async move {
+                s.request_shutdown();
+                let nested = s.start(SubsystemBuilder::new("subsys", move |_| async move {
+                    started.store(true, Ordering::SeqCst);
+                    BoxedResult::Ok(())
+                }));
+
+                assert_eq!(*nested.state().borrow(), SubsystemState::Finished);
+                assert!(nested.join().await.is_ok());
+            }
+        });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+    assert!(result.is_ok());
+    assert!(!started.load(Ordering::SeqCst));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn buffer_holds_back_shutdown_requests() {
+    let saw_shutdown_during_startup = Arc::new(AtomicBool::new(true));
+
+    let toplevel = Toplevel::builder()
+        .startup_race_policy(StartupRacePolicy::Buffer)
+        .build({
+            let saw_shutdown_during_startup = Arc::clone(&saw_shutdown_during_startup);
+            move |s: SubsystemHandle| async move {
+                s.start(SubsystemBuilder::new("subsys", move |s| async move {
+                    sleep(Duration::from_millis(100)).await;
+                    saw_shutdown_during_startup.store(s.is_shutdown_requested(), Ordering::SeqCst);
+                    s.on_shutdown_requested().await;
+                    BoxedResult::Ok(())
+                }));
+            }
+        });
+
+    toplevel.handle().request_shutdown();
+    sleep(Duration::from_millis(200)).await;
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+    assert!(result.is_ok());
+    assert!(!saw_shutdown_during_startup.load(Ordering::SeqCst));
+}
@@ -0,0 +1,118 @@
+#![cfg(feature = "status")]
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    status::StatusReport, ShutdownState, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn status_report_reflects_shutdown_progress() {
+    let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new(
+            "web",
+            |s: SubsystemHandle| async move {
+                s.start(SubsystemBuilder::new(
+                    "worker \"1\"",
+                    |s: SubsystemHandle| async move {
+                        s.on_shutdown_requested().await;
+                        BoxedResult::Ok(())
+                    },
+                ));
+                s.on_shutdown_requested().await;
+                sleep(Duration::from_millis(300)).await;
+                BoxedResult::Ok(())
+            },
+        ));
+    });
+    let handle = toplevel.handle();
+    let shutdown = tokio::spawn(toplevel.handle_shutdown_requests(Duration::from_millis(500)));
+
+    sleep(Duration::from_millis(1000)).await;
+    let report = StatusReport::new(&handle);
+    assert_eq!(report.state, ShutdownState::Running);
+    assert_eq!(
+        report.to_string(),
+        "state: running\n\
+         uptime: 1.00s\n\
+         running subsystems: 2\n  \
+           /web\n  \
+           /web/worker \"1\"\n"
+    );
+    assert_eq!(
+        report.to_json(),
+        r#"{"state":"running","uptime_ms":1000,"shutdown_duration_ms":null,"running_subsystems":["/web","/web/worker \"1\""],"restarts":{}}"#
+    );
+
+    handle.request_shutdown();
+    sleep(Duration::from_millis(100)).await;
+    let report = StatusReport::new(&handle);
+    assert_eq!(
+        report.to_string(),
+        "state: shutting_down\n\
+         uptime: 1.10s\n\
+         shutting down for: 100.00ms\n\
+         running subsystems: 1\n  \
+           /web\n"
+    );
+
+    assert!(shutdown.await.unwrap().is_ok());
+    let report = StatusReport::new(&handle);
+    assert_eq!(report.state, ShutdownState::Finished);
+    assert!(report.running_subsystems.is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn status_report_lists_restarts() {
+    let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new(
+            "broker",
+            |s: SubsystemHandle| async move {
+                s.record_restart("Connection refused");
+                s.record_restart("Connection refused");
+                s.on_shutdown_requested().await;
+                BoxedResult::Ok(())
+            },
+        ));
+        s.start(SubsystemBuilder::new(
+            "web",
+            |s: SubsystemHandle| async move {
+                s.on_shutdown_requested().await;
+                BoxedResult::Ok(())
+            },
+        ));
+    });
+    let handle = toplevel.handle();
+    let shutdown = tokio::spawn(toplevel.handle_shutdown_requests(Duration::from_millis(500)));
+
+    sleep(Duration::from_millis(1000)).await;
+    let report = StatusReport::new(&handle);
+    assert_eq!(
+        report.to_string(),
+        "state: running\n\
+         uptime: 1.00s\n\
+         running subsystems: 2\n  \
+           /broker\n  \
+           /web\n\
+         restarted subsystems:\n  \
+           /broker: 2 restarts\n"
+    );
+    assert_eq!(
+        report.to_json(),
+        r#"{"state":"running","uptime_ms":1000,"shutdown_duration_ms":null,"running_subsystems":["/broker","/web"],"restarts":{"/broker":2}}"#
+    );
+
+    handle.request_shutdown();
+    assert!(shutdown.await.unwrap().is_ok());
+    assert!(StatusReport::new(&handle).restarted_subsystems.is_empty());
+}
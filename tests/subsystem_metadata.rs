@@ -0,0 +1,79 @@
+use tokio::time::Duration;
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing_test::traced_test;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test]
+#[traced_test]
+async fn metadata_gets_inherited_by_children() {
+    let child = |subsys: SubsystemHandle| async move {
+        let metadata = subsys.metadata();
+        assert_eq!(metadata.get("component"), Some("connection"));
+        assert_eq!(metadata.get("tenant"), Some("acme"));
+        assert_eq!(metadata.to_string(), "component=connection tenant=acme");
+        BoxedResult::Ok(())
+    };
+
+    let parent = move |subsys: SubsystemHandle| async move {
+        assert_eq!(
+            subsys.metadata().to_string(),
+            "component=ingest tenant=acme"
+        );
+        subsys
+            .start(SubsystemBuilder::new("child", child).metadata("component", "connection"))
+            .join()
+            .await?;
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        assert!(s.metadata().is_empty());
+        s.start(
+            SubsystemBuilder::new("parent", parent)
+                .metadata("component", "ingest")
+                .metadata("tenant", "acme"),
+        );
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn metadata_gets_attached_to_logs_and_errors() {
+    let subsystem = |_: SubsystemHandle| async move {
+        tracing::info!("Ingesting data ...");
+        BoxedResult::Err("Ingestion failed".into())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("ingest", subsystem).metadata("tenant", "acme"));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_err());
+    assert!(logs_contain("metadata=tenant=acme"));
+    logs_assert(|lines: &[&str]| {
+        for expected in [
+            "Ingesting data ...",
+            "Uncaught error from subsystem '/ingest'",
+        ] {
+            let line = lines
+                .iter()
+                .find(|line| line.contains(expected))
+                .ok_or_else(|| format!("Missing log line: {expected}"))?;
+            if !line.contains("metadata=tenant=acme") {
+                return Err(format!("Log line without metadata: {line}"));
+            }
+        }
+        Ok(())
+    });
+}
@@ -0,0 +1,94 @@
+use tokio::sync::oneshot;
+use tokio_graceful_shutdown::{SubsystemBuilder, SubsystemHandle, Toplevel};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn navigate_upwards() {
+    let (sender, receiver) = oneshot::channel();
+
+    let leaf = move |subsys: SubsystemHandle| async move {
+        let node = subsys.node();
+        let parent = subsys.parent().unwrap();
+        let ancestors = node
+            .ancestors()
+            .map(|ancestor| ancestor.name().to_string())
+            .collect::<Vec<_>>();
+
+        sender
+            .send((
+                subsys.name().to_string(),
+                node.local_name().to_string(),
+                node.depth(),
+                parent.name().to_string(),
+                parent.child_name("sibling"),
+                node.root().name().to_string(),
+                node.root().metadata().get("tenant").map(str::to_string),
+                ancestors,
+            ))
+            .unwrap();
+
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        assert_eq!(s.name(), "");
+        assert_eq!(s.node().depth(), 0);
+        assert!(s.parent().is_none());
+
+        s.start(
+            SubsystemBuilder::new("tenant", |s| async move {
+                assert_eq!(s.node().root().name(), "/tenant");
+                assert_eq!(s.parent().unwrap().name(), "");
+
+                s.start(SubsystemBuilder::new("pipeline", |s| async move {
+                    s.start(SubsystemBuilder::new("leaf", leaf));
+                    BoxedResult::Ok(())
+                }));
+                BoxedResult::Ok(())
+            })
+            .metadata("tenant", "acme"),
+        );
+    });
+
+    toplevel.run().await.unwrap();
+
+    let (name, local_name, depth, parent, sibling, root, root_tenant, ancestors) =
+        receiver.await.unwrap();
+    assert_eq!(name, "/tenant/pipeline/leaf");
+    assert_eq!(local_name, "leaf");
+    assert_eq!(depth, 3);
+    assert_eq!(parent, "/tenant/pipeline");
+    assert_eq!(sibling, "/tenant/pipeline/sibling");
+    assert_eq!(root, "/tenant");
+    assert_eq!(root_tenant.as_deref(), Some("acme"));
+    assert_eq!(ancestors, ["/tenant/pipeline", "/tenant", ""]);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn navigation_respects_name_separator() {
+    let toplevel = Toplevel::builder()
+        .name_separator(".")
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("parent", |s| async move {
+                s.start(SubsystemBuilder::new("child", |s| async move {
+                    assert_eq!(s.name(), ".parent.child");
+                    assert_eq!(s.node().local_name(), "child");
+                    assert_eq!(s.parent().unwrap().child_name("sibling"), ".parent.sibling");
+                    BoxedResult::Ok(())
+                }));
+                BoxedResult::Ok(())
+            }));
+        });
+
+    toplevel.run().await.unwrap();
+}
@@ -0,0 +1,116 @@
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{
+    errors::{GracefulShutdownError, SubsystemError},
+    SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+use crate::common::Event;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn scope_tasks_get_cancelled_on_shutdown() {
+    let (scope_joined, set_scope_joined) = Event::create();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let mut scope = subsys.scope();
+        for _ in 0..3 {
+            scope.spawn(async {
+                sleep(Duration::from_secs(10)).await;
+                BoxedResult::Ok(())
+            });
+        }
+        scope.join().await?;
+        set_scope_joined();
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    });
+
+    let start = Instant::now();
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+    assert!(result.is_ok());
+    assert!(scope_joined.get());
+    assert_eq!(start.elapsed(), Duration::from_millis(100));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn scope_errors_propagate_to_subsystem() {
+    let (task_finished, set_task_finished) = Event::create();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let mut scope = subsys.scope();
+        scope.spawn(async {
+            sleep(Duration::from_millis(100)).await;
+            BoxedResult::Err("Task failed".into())
+        });
+        scope.spawn(async move {
+            sleep(Duration::from_millis(200)).await;
+            set_task_finished();
+            BoxedResult::Ok(())
+        });
+        scope.join().await
+    };
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    let Err(GracefulShutdownError::SubsystemsFailed(errors)) = result else {
+        panic!("Expected the subsystem to fail, got {result:?}");
+    };
+    assert!(matches!(
+        errors.as_ref(),
+        [SubsystemError::Failed(name, e)] if name.as_ref() == "/subsys" && e.to_string() == "Task failed"
+    ));
+
+    // The remaining task got aborted.
+    sleep(Duration::from_millis(500)).await;
+    assert!(!task_finished.get());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn scope_panics_propagate_to_subsystem() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let mut scope = subsys.scope();
+        scope.spawn(async {
+            sleep(Duration::from_millis(100)).await;
+            panic!("Task panicked");
+        });
+        scope.join().await
+    };
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::<_, BoxedError, _, _>::new(
+            "subsys", subsystem,
+        ));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    let Err(GracefulShutdownError::SubsystemsFailed(errors)) = result else {
+        panic!("Expected the subsystem to panic, got {result:?}");
+    };
+    assert!(matches!(
+        errors.as_ref(),
+        [SubsystemError::Panicked(name)] if name.as_ref() == "/subsys"
+    ));
+}
@@ -0,0 +1,93 @@
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{
+    errors::{GracefulShutdownError, SubsystemError, SubsystemJoinError},
+    ErrorAction, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+async fn stuck_subsystem(subsys: SubsystemHandle) -> BoxedResult {
+    subsys.on_shutdown_requested().await;
+    sleep(Duration::from_secs(10)).await;
+    Ok(())
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn aborts_subsystem_after_shutdown_timeout() {
+    let start = Instant::now();
+
+    let result = Toplevel::new(|s: SubsystemHandle| async move {
+        s.start(
+            SubsystemBuilder::new("stuck", stuck_subsystem)
+                .shutdown_timeout(Duration::from_millis(100)),
+        );
+        sleep(Duration::from_millis(50)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_secs(1))
+    .await;
+
+    assert_eq!(start.elapsed(), Duration::from_millis(150));
+
+    let Err(GracefulShutdownError::SubsystemsFailed(errors)) = result else {
+        panic!("Incorrect return value!");
+    };
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(&errors[0], SubsystemError::Aborted(name) if name.as_ref() == "/stuck"));
+    assert!(logs_contain(
+        "Subsystem '/stuck' exceeded its shutdown timeout of 100ms, aborting it ..."
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn shutdown_timeout_is_handled_like_a_failure() {
+    let result = Toplevel::new(|s: SubsystemHandle| async move {
+        let nested = s.start(
+            SubsystemBuilder::new("stuck", stuck_subsystem)
+                .shutdown_timeout(Duration::from_millis(100))
+                .on_failure(ErrorAction::CatchAndLocalShutdown),
+        );
+        sleep(Duration::from_millis(50)).await;
+        nested.initiate_shutdown();
+
+        let SubsystemJoinError::SubsystemsFailed(errors) = nested.join().await.unwrap_err();
+        assert!(matches!(
+            errors.as_ref(),
+            [SubsystemError::Aborted(name)] if name.as_ref() == "/stuck"
+        ));
+        assert!(!s.is_shutdown_requested());
+    })
+    .handle_shutdown_requests(Duration::from_secs(1))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn subsystem_within_shutdown_timeout_is_not_aborted() {
+    let result = Toplevel::new(|s: SubsystemHandle| async move {
+        s.start(
+            SubsystemBuilder::new("subsys", |s: SubsystemHandle| async move {
+                s.on_shutdown_requested().await;
+                sleep(Duration::from_millis(50)).await;
+                BoxedResult::Ok(())
+            })
+            .shutdown_timeout(Duration::from_millis(100)),
+        );
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_secs(1))
+    .await;
+
+    assert!(result.is_ok());
+}
@@ -0,0 +1,75 @@
+use anyhow::anyhow;
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    ErrorAction, SubsystemBuilder, SubsystemHandle, SubsystemState, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn state_follows_lifecycle() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        sleep(Duration::from_millis(100)).await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        let nested = s.start(SubsystemBuilder::new("subsys", subsystem));
+        let mut state = nested.state();
+        assert_eq!(*state.borrow(), SubsystemState::Running);
+
+        sleep(Duration::from_millis(100)).await;
+        nested.initiate_shutdown();
+
+        state.changed().await.unwrap();
+        assert_eq!(*state.borrow_and_update(), SubsystemState::ShuttingDown);
+
+        state.changed().await.unwrap();
+        assert_eq!(*state.borrow_and_update(), SubsystemState::Finished);
+
+        nested.join().await.unwrap();
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn state_reports_failure() {
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        let nested = s.start(
+            SubsystemBuilder::new("subsys", |_| async {
+                sleep(Duration::from_millis(100)).await;
+                BoxedResult::Err(anyhow!("Failed").into())
+            })
+            .on_failure(ErrorAction::CatchAndLocalShutdown),
+        );
+        let mut state = nested.state();
+
+        let failed_state = *state
+            .wait_for(|state| *state != SubsystemState::Running)
+            .await
+            .unwrap();
+        assert_eq!(failed_state, SubsystemState::Failed);
+
+        assert!(nested.join().await.is_err());
+        assert_eq!(*state.borrow(), SubsystemState::Failed);
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+    assert!(result.is_ok());
+}
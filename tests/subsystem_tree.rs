@@ -0,0 +1,107 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    ErrorAction, SubsystemBuilder, SubsystemHandle, SubsystemTree, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+fn recording_node(name: &str, started: &Arc<Mutex<Vec<String>>>) -> SubsystemTree {
+    let started = Arc::clone(started);
+    let recorded_name = name.to_string();
+    SubsystemTree::new(SubsystemBuilder::new(
+        name,
+        move |s: SubsystemHandle| async move {
+            started.lock().unwrap().push(recorded_name);
+            s.on_shutdown_requested().await;
+            BoxedResult::Ok(())
+        },
+    ))
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn starts_entire_tree() {
+    let started = Arc::new(Mutex::new(Vec::new()));
+
+    let tree = recording_node("a", &started)
+        .child(recording_node("b", &started).child(recording_node("c", &started)))
+        .child(
+            SubsystemTree::group("d")
+                .children([recording_node("e", &started), recording_node("f", &started)]),
+        );
+
+    let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    });
+    toplevel.start(tree);
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+    assert!(result.is_ok());
+
+    let mut started = started.lock().unwrap().clone();
+    started.sort();
+    assert_eq!(started, ["a", "b", "c", "e", "f"]);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn group_finishes_with_its_children() {
+    let tree = SubsystemTree::group("group").child(SubsystemTree::new(SubsystemBuilder::new(
+        "child",
+        |_| async {
+            sleep(Duration::from_millis(100)).await;
+            BoxedResult::Ok(())
+        },
+    )));
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        let nested = s.start_tree(tree);
+        nested.join().await.unwrap();
+        assert!(!s.is_shutdown_requested());
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn keeps_subsystem_settings() {
+    let tree = SubsystemTree::new(
+        SubsystemBuilder::new("parent", |s: SubsystemHandle| async move {
+            s.on_shutdown_requested().await;
+            BoxedResult::Ok(())
+        })
+        .on_failure(ErrorAction::CatchAndLocalShutdown),
+    )
+    .child(SubsystemTree::new(SubsystemBuilder::new(
+        "child",
+        |_| async { BoxedResult::Err("Failed".into()) },
+    )));
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        let nested = s.start_tree(tree);
+        let result = nested.join().await;
+        assert!(result.is_err());
+        assert!(!s.is_shutdown_requested());
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(500))
+        .await;
+    assert!(result.is_ok());
+}
@@ -0,0 +1,68 @@
+#![cfg(feature = "task-dump")]
+
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    errors::GracefulShutdownError, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn reports_stalled_shutdown() {
+    let toplevel = Toplevel::builder()
+        .task_dump_on_stall(Duration::from_millis(100))
+        .build(move |s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("quick", |s| async move {
+                s.on_shutdown_requested().await;
+                BoxedResult::Ok(())
+            }));
+            s.start(SubsystemBuilder::new("stuck", |_| async move {
+                sleep(Duration::from_secs(10)).await;
+                BoxedResult::Ok(())
+            }));
+            s.request_shutdown();
+        });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(GracefulShutdownError::ShutdownTimeout(_))
+    ));
+    assert!(logs_contain(
+        "Shutdown stalled for 100ms; still waiting for: '/stuck'"
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn quiet_if_shutdown_finishes_in_time() {
+    let toplevel = Toplevel::builder()
+        .task_dump_on_stall(Duration::from_millis(100))
+        .build(move |s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("subsys", |s| async move {
+                s.on_shutdown_requested().await;
+                sleep(Duration::from_millis(50)).await;
+                BoxedResult::Ok(())
+            }));
+            s.request_shutdown();
+        });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    sleep(Duration::from_millis(200)).await;
+    assert!(!logs_contain("Shutdown stalled"));
+}
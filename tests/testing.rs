@@ -0,0 +1,149 @@
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{
+    errors::SubsystemError,
+    testing::{LifecycleEventKind, MockSubsystemHandle, TestToplevel},
+    SubsystemBuilder, SubsystemHandle,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+use crate::common::Event;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test]
+#[traced_test]
+async fn mock_handle_triggers_shutdown() {
+    let (finished, set_finished) = Event::create();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        set_finished();
+        BoxedResult::Ok(())
+    };
+
+    let mock = MockSubsystemHandle::new();
+    let (result, ()) = tokio::join!(subsystem(mock.handle()), async {
+        sleep(Duration::from_millis(100)).await;
+        assert!(!finished.get());
+        mock.request_shutdown();
+    });
+
+    assert!(result.is_ok());
+    assert!(finished.get());
+    assert!(mock.is_shutdown_requested());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn mock_handle_captures_started_subsystems_and_errors() {
+    let failing = |_: SubsystemHandle| async { BoxedResult::Err("failed".into()) };
+    let waiting = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        subsys.start(SubsystemBuilder::new("waiting", waiting));
+        subsys.start(SubsystemBuilder::new("failing", failing));
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let mock = MockSubsystemHandle::new();
+    let result = tokio::time::timeout(Duration::from_millis(400), subsystem(mock.handle())).await;
+    assert!(result.unwrap().is_ok());
+
+    tokio::time::timeout(Duration::from_millis(400), mock.wait_for_children())
+        .await
+        .unwrap();
+
+    assert_eq!(mock.started_subsystems(), ["/waiting", "/failing"]);
+    assert!(mock.is_shutdown_requested());
+
+    let errors = mock.take_errors();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(&errors[0], SubsystemError::Failed(name, _) if name.as_ref() == "/failing"));
+    assert!(mock.take_errors().is_empty());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn test_toplevel_records_shutdown_in_virtual_time() {
+    let subsystem = |delay: Duration| {
+        move |subsys: SubsystemHandle| async move {
+            subsys.on_shutdown_requested().await;
+            sleep(delay).await;
+            BoxedResult::Ok(())
+        }
+    };
+
+    let toplevel = TestToplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new(
+            "db",
+            subsystem(Duration::from_secs(30)),
+        ));
+        s.start(SubsystemBuilder::new(
+            "workers",
+            subsystem(Duration::from_secs(20)),
+        ));
+        s.start(SubsystemBuilder::new(
+            "web",
+            subsystem(Duration::from_secs(10)),
+        ));
+    });
+    let handle = toplevel.handle();
+
+    let (report, ()) = tokio::join!(toplevel.run(Duration::from_secs(60)), async {
+        sleep(Duration::from_secs(100)).await;
+        handle.request_shutdown();
+    });
+
+    assert!(report.result().is_ok());
+    assert_eq!(report.shutdown_order(), ["/web", "/workers", "/db"]);
+    report.assert_shutdown_order(["/web", "/workers", "/db"]);
+    report.assert_shutdown_order(["/web", "/db"]);
+    report.assert_completed_within(Duration::from_secs(30));
+    assert_eq!(report.shutdown_duration(), Some(Duration::from_secs(30)));
+
+    let started = report
+        .events()
+        .iter()
+        .filter(|event| event.kind == LifecycleEventKind::Started && !event.name.is_empty())
+        .count();
+    assert_eq!(started, 3);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+#[should_panic(expected = "Subsystems finished in unexpected order")]
+async fn test_toplevel_detects_wrong_shutdown_order() {
+    let subsystem = |delay: Duration| {
+        move |subsys: SubsystemHandle| async move {
+            subsys.on_shutdown_requested().await;
+            sleep(delay).await;
+            BoxedResult::Ok(())
+        }
+    };
+
+    let report = TestToplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new(
+            "db",
+            subsystem(Duration::from_secs(1)),
+        ));
+        s.start(SubsystemBuilder::new(
+            "web",
+            subsystem(Duration::from_secs(2)),
+        ));
+        s.request_shutdown();
+    })
+    .run(Duration::from_secs(10))
+    .await;
+
+    report.assert_shutdown_order(["/web", "/db"]);
+}
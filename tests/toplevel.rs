@@ -0,0 +1,520 @@
+use tokio::time::{sleep, Duration};
+use tokio_graceful_shutdown::{ShutdownState, SubsystemBuilder, SubsystemHandle, Toplevel};
+use tokio_util::sync::CancellationToken;
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+use crate::common::Event;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test]
+#[traced_test]
+async fn nested_toplevel_receives_shutdown_from_parent() {
+    let (nested_finished, set_nested_finished) = Event::create();
+
+    let nested_subsystem = move |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        set_nested_finished();
+        BoxedResult::Ok(())
+    };
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        Toplevel::nested(&subsys, move |s| async move {
+            s.start(SubsystemBuilder::new("nested", nested_subsystem));
+        })
+        .handle_shutdown_requests(Duration::from_millis(100))
+        .await
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+
+        sleep(Duration::from_millis(100)).await;
+        assert!(!nested_finished.get());
+        s.request_shutdown();
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn nested_toplevel_does_not_propagate_shutdown_to_parent() {
+    let nested_subsystem = move |subsys: SubsystemHandle| async move {
+        subsys.request_shutdown();
+        BoxedResult::Ok(())
+    };
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        Toplevel::nested(&subsys, move |s| async move {
+            s.start(SubsystemBuilder::new("nested", nested_subsystem));
+        })
+        .handle_shutdown_requests(Duration::from_millis(100))
+        .await?;
+
+        assert!(!subsys.is_shutdown_requested());
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    assert!(result.is_ok());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn toplevel_with_external_cancellation_token() {
+    let external_token = CancellationToken::new();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel =
+        Toplevel::new_with_cancellation_token(external_token.clone(), move |s| async move {
+            s.start(SubsystemBuilder::new("subsys", subsystem));
+        });
+
+    tokio::join!(
+        async {
+            sleep(Duration::from_millis(100)).await;
+            external_token.cancel();
+        },
+        async {
+            let result = toplevel
+                .handle_shutdown_requests(Duration::from_millis(400))
+                .await;
+            assert!(result.is_ok());
+        },
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn independent_toplevels_do_not_affect_each_other() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel1 = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+    });
+    let toplevel2 = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+    });
+    let toplevel2_token = toplevel2.create_cancellation_token();
+
+    let result = tokio::time::timeout(Duration::from_millis(200), async {
+        sleep(Duration::from_millis(100)).await;
+        toplevel1._get_shutdown_token().cancel();
+        toplevel1
+            .handle_shutdown_requests(Duration::from_millis(100))
+            .await
+    })
+    .await
+    .unwrap();
+    assert!(result.is_ok());
+
+    sleep(Duration::from_millis(100)).await;
+    assert!(!toplevel2_token.is_cancelled());
+    drop(toplevel2);
+}
+
+#[tokio::test]
+#[traced_test]
+async fn linked_toplevel_shuts_down_with_other_toplevel() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let control_plane = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("control", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    });
+
+    let data_plane = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("data", subsystem));
+    })
+    .shutdown_on(control_plane.create_cancellation_token());
+
+    let (control_result, data_result) = tokio::join!(
+        control_plane.handle_shutdown_requests(Duration::from_millis(400)),
+        data_plane.handle_shutdown_requests(Duration::from_millis(400)),
+    );
+    assert!(control_result.is_ok());
+    assert!(data_result.is_ok());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn linked_toplevel_shutdown_does_not_propagate_back() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let control_plane = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("control", subsystem));
+    });
+    let control_plane_token = control_plane.create_cancellation_token();
+
+    let data_plane = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("data", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .shutdown_on(control_plane.create_cancellation_token());
+
+    let result = data_plane
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    assert!(result.is_ok());
+    assert!(!control_plane_token.is_cancelled());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn builder_shutdown_timeout_is_used_by_run() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        sleep(Duration::from_millis(400)).await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::builder()
+        .shutdown_timeout(Duration::from_millis(100))
+        .build(move |s| async move {
+            s.start(SubsystemBuilder::new("subsys", subsystem));
+            sleep(Duration::from_millis(100)).await;
+            s.request_shutdown();
+        });
+
+    let result = toplevel.run().await;
+    assert!(matches!(
+        result,
+        Err(tokio_graceful_shutdown::errors::GracefulShutdownError::ShutdownTimeout(_))
+    ));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn builder_without_shutdown_on_idle_waits_for_shutdown_request() {
+    let external_token = CancellationToken::new();
+    let (toplevel_finished, set_toplevel_finished) = Event::create();
+
+    let toplevel = Toplevel::<BoxedError>::builder()
+        .shutdown_on_idle(false)
+        .cancellation_token(external_token.clone())
+        .build(|_| async {});
+
+    tokio::join!(
+        async {
+            sleep(Duration::from_millis(100)).await;
+            assert!(!toplevel_finished.get());
+            external_token.cancel();
+        },
+        async {
+            let result = toplevel
+                .handle_shutdown_requests(Duration::from_millis(100))
+                .await;
+            assert!(result.is_ok());
+            set_toplevel_finished();
+        },
+    );
+    assert!(toplevel_finished.get());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+#[traced_test]
+async fn builder_drain_delay_postpones_shutdown() {
+    use nix::sys::signal::{self, Signal};
+    use nix::unistd::Pid;
+
+    let (shutdown_requested, set_shutdown_requested) = Event::create();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        set_shutdown_requested();
+        BoxedResult::Ok(())
+    };
+
+    tokio::join!(
+        async {
+            sleep(Duration::from_millis(100)).await;
+
+            // Send SIGTERM to ourselves.
+            signal::kill(Pid::this(), Signal::SIGTERM).unwrap();
+
+            sleep(Duration::from_millis(100)).await;
+            assert!(!shutdown_requested.get());
+            sleep(Duration::from_millis(200)).await;
+            assert!(shutdown_requested.get());
+        },
+        async {
+            let result = Toplevel::builder()
+                .catch_signals()
+                .drain_delay(Duration::from_millis(200))
+                .shutdown_timeout(Duration::from_millis(400))
+                .build(move |s| async move {
+                    s.start(SubsystemBuilder::new("subsys", subsystem));
+                })
+                .run()
+                .await;
+            assert!(result.is_ok());
+        },
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn toplevel_handle_starts_subsystems_from_outside() {
+    let (subsys_finished, set_subsys_finished) = Event::create();
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        sleep(Duration::from_millis(100)).await;
+        set_subsys_finished();
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(|s: SubsystemHandle| async move {
+        s.on_shutdown_requested().await;
+    });
+    let handle = toplevel.handle();
+
+    tokio::join!(
+        async {
+            sleep(Duration::from_millis(50)).await;
+            handle
+                .start(SubsystemBuilder::new("subsys", subsystem))
+                .unwrap();
+            sleep(Duration::from_millis(50)).await;
+            assert!(!handle.is_shutdown_requested());
+            handle.request_shutdown();
+        },
+        async {
+            let result = toplevel
+                .handle_shutdown_requests(Duration::from_millis(400))
+                .await;
+            assert!(result.is_ok());
+            assert!(subsys_finished.get());
+        },
+    );
+
+    assert!(handle
+        .start(SubsystemBuilder::new("subsys", |_| async {
+            BoxedResult::Ok(())
+        }))
+        .is_err());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn toplevel_handle_subsystem_errors_get_reported() {
+    let toplevel = Toplevel::<BoxedError>::new(|_| async {});
+    let handle = toplevel.handle();
+
+    handle
+        .start(SubsystemBuilder::new(
+            "subsys",
+            |_: SubsystemHandle| async { BoxedResult::Err("failed".into()) },
+        ))
+        .unwrap();
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+    let errors = result.unwrap_err().into_subsystem_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].name(), "/subsys");
+}
+
+#[test]
+#[traced_test]
+fn block_on_runtime_shuts_down_runtime() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::builder()
+        .shutdown_timeout(Duration::from_millis(400))
+        .runtime_shutdown_timeout(Duration::from_millis(100))
+        .block_on_runtime(runtime, move |s| async move {
+            s.start(SubsystemBuilder::new("subsys", subsystem));
+            tokio::task::spawn_blocking(|| std::thread::sleep(Duration::from_millis(1000)));
+            sleep(Duration::from_millis(100)).await;
+            s.request_shutdown();
+        });
+
+    assert!(result.is_ok());
+    assert!(logs_contain(
+        "Runtime shutdown timed out; remaining blocking tasks got leaked."
+    ));
+}
+
+#[test]
+#[traced_test]
+fn run_blocking_creates_runtime_and_shuts_down() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Err("subsystem failed".into())
+    };
+
+    let result = Toplevel::builder()
+        .shutdown_timeout(Duration::from_millis(400))
+        .run_blocking(move |s| async move {
+            s.start(SubsystemBuilder::new("subsys", subsystem));
+            sleep(Duration::from_millis(100)).await;
+            s.request_shutdown();
+        });
+
+    let errors = result.unwrap_err().into_subsystem_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].name(), "/subsys");
+}
+
+#[tokio::test]
+#[traced_test]
+async fn errors_do_not_get_lost_if_shutdown_future_gets_dropped() {
+    let failing_subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Err("failed".into())
+    };
+
+    let slow_subsystem = |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        sleep(Duration::from_millis(400)).await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("failing", failing_subsystem));
+        s.start(SubsystemBuilder::new("slow", slow_subsystem));
+        s.request_shutdown();
+    });
+
+    let result = tokio::time::timeout(
+        Duration::from_millis(100),
+        toplevel.handle_shutdown_requests(Duration::from_millis(800)),
+    )
+    .await;
+
+    assert!(result.is_err());
+    assert!(logs_contain("An error got dropped: Failed(\"/failing\""));
+}
+
+#[tokio::test]
+#[traced_test]
+async fn sorted_errors_are_ordered_by_name() {
+    let failing = |delay: Duration| {
+        move |subsys: SubsystemHandle| async move {
+            subsys.on_shutdown_requested().await;
+            sleep(delay).await;
+            BoxedResult::Err("failed".into())
+        }
+    };
+
+    let result = Toplevel::builder()
+        .sorted_errors(true)
+        .shutdown_timeout(Duration::from_millis(400))
+        .build(move |s| async move {
+            s.start(SubsystemBuilder::new(
+                "c",
+                failing(Duration::from_millis(0)),
+            ));
+            s.start(SubsystemBuilder::new(
+                "a",
+                failing(Duration::from_millis(100)),
+            ));
+            s.start(SubsystemBuilder::new(
+                "b",
+                failing(Duration::from_millis(50)),
+            ));
+            s.request_shutdown();
+        })
+        .run()
+        .await;
+
+    let errors = result.unwrap_err().into_subsystem_errors();
+    let names = errors.iter().map(|e| e.name()).collect::<Vec<_>>();
+    assert_eq!(names, ["/a", "/b", "/c"]);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn toplevel_handle_reports_shutdown_state() {
+    let toplevel = Toplevel::new(|s| async move {
+        s.start(SubsystemBuilder::new(
+            "subsys",
+            |s: SubsystemHandle| async move {
+                s.on_shutdown_requested().await;
+                sleep(Duration::from_millis(100)).await;
+                BoxedResult::Ok(())
+            },
+        ));
+    });
+    let handle = toplevel.handle();
+    assert_eq!(handle.shutdown_state(), ShutdownState::Running);
+
+    let shutdown = tokio::spawn(toplevel.handle_shutdown_requests(Duration::from_millis(500)));
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(handle.shutdown_state(), ShutdownState::Running);
+
+    handle.request_shutdown();
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(handle.shutdown_state(), ShutdownState::ShuttingDown);
+
+    assert!(shutdown.await.unwrap().is_ok());
+    assert_eq!(handle.shutdown_state(), ShutdownState::Finished);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn dropped_toplevel_reports_running_subsystems() {
+    let (shutdown_requested, set_shutdown_requested) = Event::create();
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        let token = subsys.create_cancellation_token();
+        tokio::spawn(async move {
+            token.cancelled().await;
+            set_shutdown_requested();
+        });
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+    });
+    sleep(Duration::from_millis(50)).await;
+    drop(toplevel);
+
+    assert!(logs_contain(
+        "Toplevel got dropped without handling its shutdown! Cancelling the remaining subsystems: '/subsys'"
+    ));
+
+    sleep(Duration::from_millis(50)).await;
+    assert!(shutdown_requested.get());
+}
@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{sleep, Instant},
+};
+use tokio_graceful_shutdown::{
+    errors::{GracefulShutdownError, TransferError},
+    NestedSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn warmup_hands_off_to_pool() {
+    let (handoff_sender, mut handoff_receiver) = mpsc::channel::<NestedSubsystem<BoxedError>>(1);
+    let (warmup_done_sender, warmup_done_receiver) = oneshot::channel();
+    let (connection_done_sender, connection_done_receiver) = oneshot::channel();
+
+    let connection = move |subsys: SubsystemHandle| async move {
+        subsys.on_shutdown_requested().await;
+        connection_done_sender.send(Instant::now()).unwrap();
+        BoxedResult::Ok(())
+    };
+
+    let warmup = move |subsys: SubsystemHandle| async move {
+        let connection =
+            subsys.start(SubsystemBuilder::new("connection", connection).transferable());
+        sleep(Duration::from_millis(100)).await;
+        handoff_sender.send(connection).await.unwrap();
+        BoxedResult::Ok(())
+    };
+
+    let pool = move |subsys: SubsystemHandle| async move {
+        let connection = handoff_receiver.recv().await.unwrap();
+        connection.transfer_to(&subsys)?;
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        let warmup = s.start(SubsystemBuilder::new("warmup", warmup));
+        s.start(SubsystemBuilder::new("pool", pool));
+
+        // The warmup does not wait for the connection it handed off.
+        warmup.join().await.unwrap();
+        warmup_done_sender.send(Instant::now()).unwrap();
+
+        sleep(Duration::from_millis(200)).await;
+        s.request_shutdown();
+    });
+
+    let start = Instant::now();
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        warmup_done_receiver.await.unwrap() - start,
+        Duration::from_millis(100)
+    );
+    // The connection stayed alive until the pool got shut down.
+    assert_eq!(
+        connection_done_receiver.await.unwrap() - start,
+        Duration::from_millis(300)
+    );
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn errors_propagate_to_new_parent() {
+    let (handoff_sender, mut handoff_receiver) = mpsc::channel::<NestedSubsystem<BoxedError>>(1);
+
+    let warmup = move |subsys: SubsystemHandle| async move {
+        let connection = subsys.start(
+            SubsystemBuilder::new("connection", |_| async {
+                sleep(Duration::from_millis(100)).await;
+                BoxedResult::Err("connection lost".into())
+            })
+            .transferable(),
+        );
+        handoff_sender.send(connection).await.unwrap();
+        BoxedResult::Ok(())
+    };
+
+    let pool = move |subsys: SubsystemHandle| async move {
+        let connection = handoff_receiver.recv().await.unwrap();
+        connection.transfer_to(&subsys)?;
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        // Would catch the error if it still reached the original parent.
+        s.start(SubsystemBuilder::new("warmup", warmup).isolate_errors());
+        s.start(SubsystemBuilder::new("pool", pool));
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await;
+
+    // The connection keeps its original name.
+    let Err(GracefulShutdownError::SubsystemsFailed(errors)) = result else {
+        panic!("Expected the connection to fail");
+    };
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].name(), "/warmup/connection");
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn transfer_errors() {
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        let regular = s.start(SubsystemBuilder::new("regular", |s| async move {
+            s.on_shutdown_requested().await;
+            BoxedResult::Ok(())
+        }));
+        assert!(matches!(
+            regular.transfer_to(&s),
+            Err(TransferError::NotTransferable)
+        ));
+
+        let finished = s.start(
+            SubsystemBuilder::new("finished", |_| async { BoxedResult::Ok(()) }).transferable(),
+        );
+        finished.join().await.unwrap();
+        assert!(matches!(
+            finished.transfer_to(&s),
+            Err(TransferError::Finished)
+        ));
+
+        // Tries to move its own parent below itself.
+        let (outer_sender, outer_receiver) = oneshot::channel::<NestedSubsystem<BoxedError>>();
+        let (result_sender, result_receiver) = oneshot::channel();
+        let outer = s.start(
+            SubsystemBuilder::new("outer", |s| async move {
+                s.start(SubsystemBuilder::new("inner", |s| async move {
+                    let outer = outer_receiver.await.unwrap();
+                    result_sender.send(outer.transfer_to(&s)).unwrap();
+                    s.on_shutdown_requested().await;
+                    BoxedResult::Ok(())
+                }));
+                s.on_shutdown_requested().await;
+                BoxedResult::Ok(())
+            })
+            .transferable(),
+        );
+        outer_sender.send(outer).ok();
+        assert!(matches!(
+            result_receiver.await.unwrap(),
+            Err(TransferError::InvalidParent)
+        ));
+
+        s.request_shutdown();
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,118 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use tokio_graceful_shutdown::{
+    errors::{GracefulShutdownError, InternalError, SubsystemError, SubsystemJoinError},
+    SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn custom_name_separator() {
+    let toplevel =
+        Toplevel::builder()
+            .name_separator("::")
+            .build(|s: SubsystemHandle| async move {
+                s.start(SubsystemBuilder::new("parent", |s| async move {
+                    s.start(SubsystemBuilder::new("child", |_| async {
+                        BoxedResult::Err("broken".into())
+                    }));
+                    BoxedResult::Ok(())
+                }));
+            });
+
+    let result = toplevel.run().await;
+    let Err(GracefulShutdownError::SubsystemsFailed(errors)) = result else {
+        panic!("Expected the child to fail");
+    };
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].name(), "::parent::child");
+}
+
+type BoxedFuture = Pin<Box<dyn Future<Output = BoxedResult> + Send>>;
+
+/// A subsystem that endlessly starts itself as its own child.
+fn recursive(subsys: SubsystemHandle, started: Arc<AtomicUsize>) -> BoxedFuture {
+    Box::pin(async move {
+        started.fetch_add(1, Ordering::Relaxed);
+        subsys.start(SubsystemBuilder::new("nested", move |s| {
+            recursive(s, started)
+        }));
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    })
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn max_depth_stops_unbounded_recursion() {
+    let started = Arc::new(AtomicUsize::new(0));
+
+    let toplevel = Toplevel::builder().max_depth(5).build({
+        let started = Arc::clone(&started);
+        move |s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("nested", move |s| {
+                recursive(s, started)
+            }));
+        }
+    });
+
+    let result = toplevel.run().await;
+    let Err(GracefulShutdownError::SubsystemsFailed(errors)) = result else {
+        panic!("Expected the recursion to fail");
+    };
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].name(),
+        "/nested/nested/nested/nested/nested/nested"
+    );
+    assert!(matches!(
+        errors[0],
+        SubsystemError::Internal(_, InternalError::MaxDepthExceeded { max_depth: 5 })
+    ));
+    assert_eq!(started.load(Ordering::Relaxed), 5);
+    assert!(logs_contain("exceeds the maximum depth of 5"));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn max_depth_error_respects_error_actions() {
+    let toplevel = Toplevel::builder()
+        .max_depth(1)
+        .build(|s: SubsystemHandle| async move {
+            s.start(SubsystemBuilder::new("parent", |s| async move {
+                let child = s.start(
+                    SubsystemBuilder::new("child", |_| async { BoxedResult::Ok(()) })
+                        .isolate_errors(),
+                );
+
+                let Err(SubsystemJoinError::SubsystemsFailed(errors)) = child.join().await else {
+                    panic!("Expected the child to fail");
+                };
+                assert!(matches!(
+                    errors.as_ref(),
+                    [SubsystemError::Internal(name, InternalError::MaxDepthExceeded { max_depth: 1 })]
+                        if name.as_ref() == "/parent/child"
+                ));
+
+                BoxedResult::Ok(())
+            }));
+        });
+
+    toplevel.run().await.unwrap();
+}
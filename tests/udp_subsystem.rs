@@ -0,0 +1,142 @@
+#![cfg(feature = "udp")]
+
+use std::net::SocketAddr;
+
+use tokio::{
+    net::UdpSocket,
+    time::{sleep, timeout, Duration},
+};
+use tokio_graceful_shutdown::{
+    ExitStatus, IntoSubsystem, ShutdownReportEntry, SubsystemBuilder, SubsystemHandle, Toplevel,
+    ToplevelHandle, UdpSender, UdpSubsystem,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+async fn echo(datagram: Vec<u8>, peer: SocketAddr, sender: UdpSender) -> BoxedResult {
+    sender.send_to(datagram, peer)?;
+    Ok(())
+}
+
+async fn receive(client: &UdpSocket) -> Vec<u8> {
+    let mut buffer = vec![0; 1024];
+    let len = timeout(Duration::from_secs(5), client.recv(&mut buffer))
+        .await
+        .unwrap()
+        .unwrap();
+    buffer.truncate(len);
+    buffer
+}
+
+fn udp_exit_status(handle: &ToplevelHandle) -> Option<ExitStatus> {
+    match &handle.shutdown_report().entries[0] {
+        ShutdownReportEntry::Single { result, .. } => result.exit_status.clone(),
+        ShutdownReportEntry::Aggregated(_) => panic!("Expected a single entry"),
+    }
+}
+
+#[tokio::test]
+#[traced_test]
+async fn echoes_and_says_goodbye() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let server_addr = socket.local_addr().unwrap();
+    let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    client.connect(server_addr).await.unwrap();
+    let client_addr = client.local_addr().unwrap();
+
+    let server = UdpSubsystem::new(socket, echo)
+        .goodbye(move || vec![(b"BYE".to_vec(), client_addr)])
+        .flush_timeout(Duration::from_millis(500));
+    let sender = server.sender();
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("udp", server.into_subsystem()));
+    });
+    let handle = toplevel.handle();
+
+    let client_side = async {
+        client.send(b"hello").await.unwrap();
+        assert_eq!(receive(&client).await, b"hello");
+
+        sender
+            .send_to(b"unsolicited".to_vec(), client_addr)
+            .unwrap();
+        assert_eq!(receive(&client).await, b"unsolicited");
+
+        handle.request_shutdown();
+        assert_eq!(receive(&client).await, b"BYE");
+    };
+
+    let (result, ()) = tokio::join!(
+        toplevel.handle_shutdown_requests(Duration::from_millis(1000)),
+        client_side,
+    );
+    result.unwrap();
+
+    assert!(sender.send_to(b"late".to_vec(), client_addr).is_err());
+    assert_eq!(
+        udp_exit_status(&handle),
+        Some(ExitStatus::Drained { dropped: 0 })
+    );
+}
+
+#[tokio::test]
+#[traced_test]
+async fn flushes_queued_datagrams_after_goodbye() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let client_addr = client.local_addr().unwrap();
+
+    let server =
+        UdpSubsystem::new(socket, echo).goodbye(move || vec![(b"BYE".to_vec(), client_addr)]);
+    let sender = server.sender();
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("udp", server.into_subsystem()));
+        // Queued while the subsystem is still starting up.
+        for i in 0..3u8 {
+            sender.send_to(vec![i], client_addr).unwrap();
+        }
+        s.request_shutdown();
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(1000))
+        .await
+        .unwrap();
+
+    assert_eq!(receive(&client).await, b"BYE");
+    for i in 0..3u8 {
+        assert_eq!(receive(&client).await, vec![i]);
+    }
+}
+
+#[tokio::test]
+#[traced_test]
+async fn handler_error_stops_subsystem() {
+    let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let server_addr = socket.local_addr().unwrap();
+    let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+    let server = UdpSubsystem::new(socket, |_, _, _| async {
+        BoxedResult::Err("malformed datagram".into())
+    });
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("udp", server.into_subsystem()));
+        sleep(Duration::from_millis(10)).await;
+        client.send_to(b"garbage", server_addr).await.unwrap();
+    });
+
+    let result = toplevel
+        .handle_shutdown_requests(Duration::from_millis(1000))
+        .await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,80 @@
+use tokio::{
+    sync::{mpsc, oneshot},
+    time::{sleep, Duration},
+};
+use tokio_graceful_shutdown::{
+    errors::CancelledByShutdown, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn busy_loop_stops_despite_backlog() {
+    let (job_sender, mut job_receiver) = mpsc::channel(100);
+    for job in 0..100 {
+        job_sender.try_send(job).unwrap();
+    }
+    let (processed_sender, processed_receiver) = oneshot::channel();
+
+    let worker = move |subsys: SubsystemHandle| async move {
+        let mut processed = 0;
+        while let Ok(Some(_job)) = subsys.unless_shutdown_requested(job_receiver.recv()).await {
+            // There is always another job, so the worker never waits for one.
+            sleep(Duration::from_millis(10)).await;
+            processed += 1;
+        }
+        processed_sender.send(processed).unwrap();
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("worker", worker));
+        sleep(Duration::from_millis(55)).await;
+        s.request_shutdown();
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+
+    assert_eq!(processed_receiver.await.unwrap(), 6);
+    drop(job_sender);
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn idle_work_gets_cancelled() {
+    let worker = |subsys: SubsystemHandle| async move {
+        let result = subsys
+            .unless_shutdown_requested(sleep(Duration::from_secs(10)))
+            .await;
+        assert!(matches!(result, Err(CancelledByShutdown)));
+
+        // Does not start any further work.
+        let result = subsys
+            .unless_shutdown_requested(async { panic!("Work should not have started") })
+            .await;
+        assert!(matches!(result, Err::<(), _>(CancelledByShutdown)));
+        BoxedResult::Ok(())
+    };
+
+    let toplevel = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("worker", worker));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    });
+
+    toplevel
+        .handle_shutdown_requests(Duration::from_millis(400))
+        .await
+        .unwrap();
+}
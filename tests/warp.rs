@@ -0,0 +1,86 @@
+#![cfg(feature = "warp")]
+
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::{sleep, Duration, Instant},
+};
+use tokio_graceful_shutdown::{
+    IntoSubsystem, SubsystemBuilder, SubsystemHandle, Toplevel, WarpServer,
+};
+use tracing_test::traced_test;
+use warp::Filter;
+
+pub mod common;
+
+fn hello_server() -> WarpServer {
+    let slow = warp::path("slow").then(|| async {
+        sleep(Duration::from_secs(10)).await;
+        "Slow hello!"
+    });
+    let hello = warp::any().map(|| "Hello!");
+
+    WarpServer::bind(slow.or(hello), ([127, 0, 0, 1], 0))
+        .unwrap()
+        .drain_timeout(Duration::from_millis(200))
+}
+
+#[tokio::test]
+#[traced_test]
+async fn serves_requests_until_shutdown() {
+    let server = hello_server();
+    let addr = server.local_addr();
+
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("warp", server.into_subsystem()));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with("Hello!"));
+
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_secs(5))
+    .await;
+
+    assert!(result.is_ok());
+    assert!(!logs_contain("did not close"));
+    assert!(TcpStream::connect(addr).await.is_err());
+}
+
+#[tokio::test]
+#[traced_test]
+async fn hanging_connections_time_out() {
+    let server = hello_server();
+    let addr = server.local_addr();
+
+    let start = Instant::now();
+    let result = Toplevel::new(move |s: SubsystemHandle| async move {
+        s.start(SubsystemBuilder::new("warp", server.into_subsystem()));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /slow HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        sleep(Duration::from_millis(50)).await;
+
+        s.request_shutdown();
+        sleep(Duration::from_millis(300)).await;
+        drop(stream);
+    })
+    .handle_shutdown_requests(Duration::from_secs(5))
+    .await;
+
+    assert!(result.is_ok());
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(logs_contain(&format!(
+        "Connections of warp server on {addr} did not close within 200ms; no longer waiting for them."
+    )));
+}
@@ -0,0 +1,150 @@
+use tokio::time::{sleep, Duration, Instant};
+use tokio_graceful_shutdown::{
+    errors::CancelledByShutdown, SubsystemBuilder, SubsystemHandle, Toplevel,
+};
+use tracing_test::traced_test;
+
+pub mod common;
+
+use std::error::Error;
+
+use crate::common::Event;
+
+/// Wrapper function to simplify lambdas
+type BoxedError = Box<dyn Error + Sync + Send>;
+type BoxedResult = Result<(), BoxedError>;
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn permits_are_refused_after_shutdown() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        assert!(subsys.acquire_permit().await.is_ok());
+        subsys.on_shutdown_requested().await;
+        assert!(matches!(
+            subsys.acquire_permit().await,
+            Err(CancelledByShutdown)
+        ));
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn shutdown_waits_for_outstanding_permits() {
+    let (job_finished, set_job_finished) = Event::create();
+
+    let subsystem = move |subsys: SubsystemHandle| async move {
+        let permit = subsys.acquire_permit().await.unwrap();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(200)).await;
+            set_job_finished();
+            drop(permit);
+        });
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let start = Instant::now();
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert!(job_finished.get());
+    assert_eq!(start.elapsed(), Duration::from_millis(200));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn outstanding_permits_cause_shutdown_timeout() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let permit = subsys.acquire_permit().await.unwrap();
+        tokio::spawn(async move {
+            std::future::pending::<()>().await;
+            drop(permit);
+        });
+        subsys.on_shutdown_requested().await;
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn max_work_permits_limits_outstanding_permits() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let start = Instant::now();
+        for _ in 0..4 {
+            let permit = subsys.acquire_permit().await.unwrap();
+            tokio::spawn(async move {
+                sleep(Duration::from_millis(100)).await;
+                drop(permit);
+            });
+        }
+        assert_eq!(start.elapsed(), Duration::from_millis(100));
+        BoxedResult::Ok(())
+    };
+
+    let start = Instant::now();
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem).max_work_permits(2));
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+    assert_eq!(start.elapsed(), Duration::from_millis(200));
+}
+
+#[tokio::test(start_paused = true)]
+#[traced_test]
+async fn waiting_for_permit_gets_cancelled_by_shutdown() {
+    let subsystem = |subsys: SubsystemHandle| async move {
+        let _permit = subsys.acquire_permit().await.unwrap();
+        assert!(matches!(
+            subsys.acquire_permit().await,
+            Err(CancelledByShutdown)
+        ));
+        BoxedResult::Ok(())
+    };
+
+    let result = Toplevel::new(move |s| async move {
+        s.start(SubsystemBuilder::new("subsys", subsystem).max_work_permits(1));
+        sleep(Duration::from_millis(100)).await;
+        s.request_shutdown();
+    })
+    .handle_shutdown_requests(Duration::from_millis(500))
+    .await;
+
+    assert!(result.is_ok());
+}
+
+#[test]
+#[should_panic(expected = "max_work_permits must be at least 1")]
+fn zero_work_permits_are_rejected() {
+    let _ = SubsystemBuilder::new("subsys", |_: SubsystemHandle| async { BoxedResult::Ok(()) })
+        .max_work_permits(0);
+}